@@ -0,0 +1,346 @@
+//! In-process embedding backfill.
+//!
+//! With `embeddings.inline` off, saved thoughts and entities are queued in
+//! `{instance}:embeddings:pending` instead of being embedded on the request
+//! path, as are session summary chunks beyond
+//! `embeddings.max_calls_per_request`. When `embeddings.backfill_interval_secs` is non-zero a background
+//! task embeds up to `backfill_batch_size` pending items per run with a single
+//! batch request and writes the same vector hashes as embed-on-save (every
+//! configured `knowledge.entity_vectors` entry for entities).
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::embeddings::{Embedder, pending_key, truncate_for_embedding};
+use crate::error::Result;
+use crate::models::KnowledgeScope;
+use crate::redis::RedisManager;
+use crate::repository_traits::{KnowledgeRepository, ThoughtRepository};
+
+pub fn thought_member(thought_id: &str) -> String {
+    format!("thought:{thought_id}")
+}
+
+pub fn entity_member(scope: &KnowledgeScope, entity_id: &str) -> String {
+    let scope = match scope {
+        KnowledgeScope::Federation => "federation",
+        KnowledgeScope::Personal => "personal",
+    };
+    format!("kg_entity:{scope}:{entity_id}")
+}
+
+/// Queue an item for the backfill scheduler (best-effort)
+pub async fn mark_pending(redis_manager: &RedisManager, instance: &str, member: &str) {
+    if let Err(e) = redis_manager.sadd(&pending_key(instance), member).await {
+        tracing::warn!("Failed to queue {} for embedding backfill: {}", member, e);
+    }
+}
+
+#[derive(Debug)]
+enum PendingItem {
+    Thought(String),
+    Entity(KnowledgeScope, String),
+    /// `{chain}:{offset}` of a stored session summary chunk
+    SummaryChunk(String),
+}
+
+fn parse_member(member: &str) -> Option<PendingItem> {
+    if let Some(id) = member.strip_prefix("thought:") {
+        return Some(PendingItem::Thought(id.to_string()));
+    }
+    if let Some(id) = member.strip_prefix("session-summaries:") {
+        return Some(PendingItem::SummaryChunk(id.to_string()));
+    }
+    let rest = member.strip_prefix("kg_entity:")?;
+    let (scope, id) = rest.split_once(':')?;
+    let scope = match scope {
+        "federation" => KnowledgeScope::Federation,
+        "personal" => KnowledgeScope::Personal,
+        _ => return None,
+    };
+    Some(PendingItem::Entity(scope, id.to_string()))
+}
+
+/// Vector hash fields for one pending item
+struct PendingDoc {
+    /// `thought` or an entity vector kind; selects the index and key prefix
+    kind: &'static str,
+    id: String,
+    content: String,
+    tags: Vec<String>,
+    category: String,
+    importance: String,
+    chain_id: String,
+    thought_id: String,
+    ts: i64,
+    sentiment: Option<f32>,
+}
+
+/// Load the vector documents behind a pending member; empty when it no longer exists
+async fn load_pending<R>(
+    config: &Config,
+    repository: &R,
+    redis_manager: &RedisManager,
+    instance: &str,
+    member: &str,
+) -> Result<Vec<PendingDoc>>
+where
+    R: ThoughtRepository + KnowledgeRepository + ?Sized,
+{
+    match parse_member(member) {
+        Some(PendingItem::Thought(id)) => {
+            let Some(record) = repository.get_thought(instance, &id).await? else {
+                return Ok(Vec::new());
+            };
+            let ts = chrono::DateTime::parse_from_rfc3339(&record.timestamp)
+                .map(|dt| dt.timestamp())
+                .unwrap_or_else(|_| chrono::Utc::now().timestamp());
+            Ok(vec![PendingDoc {
+                kind: "thought",
+                content: record.thought,
+                tags: record.tags.unwrap_or_default(),
+                category: record.category.unwrap_or_default(),
+                importance: record.importance.unwrap_or(5).to_string(),
+                chain_id: record.chain_id.unwrap_or_default(),
+                thought_id: record.id.clone(),
+                id: record.id,
+                ts,
+                sentiment: record.sentiment,
+            }])
+        }
+        Some(PendingItem::Entity(scope, id)) => {
+            // A missing entity surfaces as an error; treat it as gone
+            let Ok(node) = repository.get_entity(&id, &scope).await else {
+                return Ok(Vec::new());
+            };
+            let mut docs = Vec::new();
+            for vector in config.knowledge.entity_vectors() {
+                docs.push(PendingDoc {
+                    kind: vector.kind(),
+                    content: crate::handlers::knowledge::entity_vector_text(
+                        repository, &node, vector,
+                    )
+                    .await,
+                    tags: node.tags.clone(),
+                    category: vector.kind().to_string(),
+                    importance: String::new(),
+                    chain_id: String::new(),
+                    thought_id: String::new(),
+                    id: node.id.clone(),
+                    ts: chrono::Utc::now().timestamp(),
+                    sentiment: None,
+                });
+            }
+            Ok(docs)
+        }
+        Some(PendingItem::SummaryChunk(id)) => {
+            let key = format!("{instance}:embeddings:session-summaries:{id}");
+            let mut con = redis_manager.get_connection().await?;
+            let fields: Vec<Option<String>> = redis::cmd("HMGET")
+                .arg(&key)
+                .arg("content")
+                .arg("tags")
+                .arg("importance")
+                .arg("chain_id")
+                .arg("ts")
+                .query_async(&mut *con)
+                .await?;
+            let [content, tags, importance, chain_id, ts] =
+                <[Option<String>; 5]>::try_from(fields).unwrap_or_default();
+            // Chunk replaced by a later summary update
+            let Some(content) = content else {
+                return Ok(Vec::new());
+            };
+            Ok(vec![PendingDoc {
+                kind: "session-summaries",
+                content,
+                tags: tags
+                    .map(|t| {
+                        t.split(',')
+                            .filter(|t| !t.is_empty())
+                            .map(str::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                category: String::new(),
+                importance: importance.unwrap_or_default(),
+                chain_id: chain_id.unwrap_or_default(),
+                thought_id: String::new(),
+                id,
+                ts: ts
+                    .and_then(|t| t.parse().ok())
+                    .unwrap_or_else(|| chrono::Utc::now().timestamp()),
+                sentiment: None,
+            }])
+        }
+        None => {
+            tracing::warn!("Dropping unrecognized pending embedding member {}", member);
+            Ok(Vec::new())
+        }
+    }
+}
+
+/// Embed one batch of pending items; returns how many vectors were written.
+/// Items are removed from the pending set only once written (or gone), so a
+/// failed provider call is retried on the next run.
+async fn run_batch<R>(
+    config: &Config,
+    repository: &R,
+    redis_manager: &RedisManager,
+    embedder: &dyn Embedder,
+    instance: &str,
+) -> Result<usize>
+where
+    R: ThoughtRepository + KnowledgeRepository + ?Sized,
+{
+    let key = pending_key(instance);
+    let members: Vec<String> = {
+        let mut con = redis_manager.get_connection().await?;
+        redis::cmd("SRANDMEMBER")
+            .arg(&key)
+            .arg(config.embeddings.backfill_batch_size.max(1))
+            .query_async(&mut *con)
+            .await?
+    };
+    if members.is_empty() {
+        return Ok(0);
+    }
+
+    let mut docs = Vec::new();
+    let mut done = Vec::new();
+    for member in members {
+        match load_pending(config, repository, redis_manager, instance, &member).await {
+            Ok(loaded) if loaded.is_empty() => done.push(member),
+            Ok(loaded) => docs.extend(loaded.into_iter().map(|doc| (member.clone(), doc))),
+            Err(e) => tracing::warn!("Embedding backfill: failed to load {}: {}", member, e),
+        }
+    }
+
+    let max_chars = config.openai.embedding_max_input_chars;
+    let texts: Vec<String> = docs
+        .iter()
+        .map(|(_, doc)| truncate_for_embedding(&doc.content, max_chars).to_string())
+        .collect();
+    let vectors = if texts.is_empty() {
+        Vec::new()
+    } else {
+        embedder.embed_batch(&texts).await?
+    };
+
+    let dims = config.openai.embedding_dimensions;
+    let mut kinds: Vec<&str> = docs.iter().map(|(_, doc)| doc.kind).collect();
+    kinds.sort_unstable();
+    kinds.dedup();
+    for kind in kinds {
+        let _ = crate::handlers::thoughts::ensure_index_hash_hnsw(
+            redis_manager,
+            &format!("idx:{instance}:{kind}"),
+            &format!("{instance}:embeddings:{kind}:"),
+            dims,
+            config.redis_search.hnsw.m,
+            config.redis_search.hnsw.ef_construction,
+            config.redis_search.vector_type,
+        )
+        .await;
+    }
+
+    let mut pipe = redis::pipe();
+    let mut embedded = 0;
+    for ((member, doc), vector) in docs.into_iter().zip(vectors) {
+        // Mismatched vectors stay pending until the provider/config is fixed
+        if !matches!(config.openai.check_embedding_dims(vector.len()), Ok(true)) {
+            continue;
+        }
+        let hash_key = format!("{instance}:embeddings:{}:{}", doc.kind, doc.id);
+        if let Some(sentiment) = doc.sentiment {
+            pipe.hset(&hash_key, "sentiment", sentiment);
+        }
+        pipe.hset(&hash_key, "content", &doc.content)
+            .hset(&hash_key, "tags", doc.tags.join(","))
+            .hset(&hash_key, "category", &doc.category)
+            .hset(&hash_key, "importance", &doc.importance)
+            .hset(&hash_key, "chain_id", &doc.chain_id)
+            .hset(&hash_key, "thought_id", &doc.thought_id)
+            .hset(&hash_key, "ts", doc.ts)
+            .hset(
+                &hash_key,
+                "vector",
+                config.redis_search.vector_type.encode(&vector),
+            );
+        done.push(member);
+        embedded += 1;
+    }
+    done.sort_unstable();
+    done.dedup();
+    if !done.is_empty() {
+        pipe.srem(&key, &done);
+        let mut con = redis_manager.get_connection().await?;
+        pipe.query_async::<()>(&mut *con).await?;
+    }
+    Ok(embedded)
+}
+
+/// Start the periodic backfill when `embeddings.backfill_interval_secs` is non-zero
+pub fn spawn<R>(
+    config: Arc<Config>,
+    repository: Arc<R>,
+    redis_manager: Arc<RedisManager>,
+    embedder: Arc<dyn Embedder>,
+    instance: String,
+) where
+    R: ThoughtRepository + KnowledgeRepository,
+{
+    let secs = config.embeddings.backfill_interval_secs;
+    if secs == 0 {
+        return;
+    }
+    tracing::info!(
+        "Embedding backfill every {}s (batch size {})",
+        secs,
+        config.embeddings.backfill_batch_size
+    );
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(secs));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            match run_batch(
+                &config,
+                repository.as_ref(),
+                &redis_manager,
+                embedder.as_ref(),
+                &instance,
+            )
+            .await
+            {
+                Ok(0) => {}
+                Ok(n) => tracing::info!("Embedding backfill: embedded {} pending items", n),
+                Err(e) => tracing::warn!("Embedding backfill run failed: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_member_round_trip() {
+        assert!(matches!(
+            parse_member(&thought_member("t1")),
+            Some(PendingItem::Thought(id)) if id == "t1"
+        ));
+        assert!(matches!(
+            parse_member(&entity_member(&KnowledgeScope::Personal, "e1")),
+            Some(PendingItem::Entity(KnowledgeScope::Personal, id)) if id == "e1"
+        ));
+        assert!(matches!(
+            parse_member(&crate::embeddings::summary_chunk_member("c1", 2000)),
+            Some(PendingItem::SummaryChunk(id)) if id == "c1:2000"
+        ));
+        assert!(parse_member("kg_entity:galaxy:e1").is_none());
+        assert!(parse_member("other:x").is_none());
+    }
+}
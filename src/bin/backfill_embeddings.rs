@@ -1,12 +1,8 @@
 use std::sync::Arc;
 
-use anyhow::{Context, Result, anyhow};
-use async_openai::{
-    Client,
-    config::OpenAIConfig,
-    types::{CreateEmbeddingRequestArgs, EmbeddingInput},
-};
-use bytemuck::cast_slice;
+use anyhow::{Context, Result};
+use unified_intelligence::config::{Config, VectorType};
+use unified_intelligence::embeddings::{Embedder, build_embedder};
 
 // Minimal structs matching stored JSON
 #[derive(serde::Deserialize)]
@@ -45,7 +41,6 @@ struct KnowledgeNode {
 // Lightweight config and Redis manager for this tool
 struct SimpleConfig {
     instance_id: String,
-    dims: usize,
     hnsw_m: u32,
     hnsw_ef: u32,
     redis_url: String,
@@ -54,10 +49,6 @@ struct SimpleConfig {
 impl SimpleConfig {
     fn load() -> Self {
         let instance_id = std::env::var("INSTANCE_ID").unwrap_or_else(|_| "CC".to_string());
-        let dims = std::env::var("OPENAI_EMBED_DIMS")
-            .ok()
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(1536);
         let hnsw_m = std::env::var("REDIS_HNSW_M")
             .ok()
             .and_then(|v| v.parse().ok())
@@ -80,7 +71,6 @@ impl SimpleConfig {
         };
         Self {
             instance_id,
-            dims,
             hnsw_m,
             hnsw_ef,
             redis_url,
@@ -102,23 +92,6 @@ impl RedisManager {
     }
 }
 
-async fn generate_openai_embedding(text: &str, api_key: &str) -> anyhow::Result<Vec<f32>> {
-    let cfg = OpenAIConfig::new().with_api_key(api_key.to_string());
-    let client = Client::with_config(cfg);
-    let req = CreateEmbeddingRequestArgs::default()
-        .model("text-embedding-3-small".to_string())
-        .input(EmbeddingInput::String(text.to_string()))
-        .build()?;
-    let resp = client.embeddings().create(req).await?;
-    let embedding = resp
-        .data
-        .into_iter()
-        .next()
-        .ok_or_else(|| anyhow!("No embeddings returned"))?
-        .embedding;
-    Ok(embedding)
-}
-
 #[tokio::main]
 async fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
@@ -126,11 +99,14 @@ async fn main() -> Result<()> {
     let cfg = Arc::new(SimpleConfig::load());
     let instance_id = cfg.instance_id.clone();
 
-    let openai_key =
-        std::env::var("OPENAI_API_KEY").map_err(|_| anyhow!("OPENAI_API_KEY is not set"))?;
+    // Embed through the provider selected by `embeddings.provider`
+    let config = Config::load();
+    let embedder = build_embedder(&config)?;
+    // Encode vectors as the element type the indexes are declared with
+    let vector_type = config.redis_search.vector_type;
 
     let redis = Arc::new(RedisManager::new(&cfg.redis_url).await?);
-    let dims = cfg.dims;
+    let dims = embedder.dimensions();
 
     // Ensure indices exist for thoughts and KG entities
 let thought_index = format!("idx:{instance_id}:thought");
@@ -142,19 +118,29 @@ let thought_prefix = format!("{instance_id}:embeddings:thought:");
         dims,
         cfg.hnsw_m,
         cfg.hnsw_ef,
+        vector_type,
     )
     .await?;
 
 let kg_index = format!("idx:{instance_id}:kg_entity");
 let kg_prefix = format!("{instance_id}:embeddings:kg_entity:");
-    ensure_index_hash_hnsw(&redis, &kg_index, &kg_prefix, dims, cfg.hnsw_m, cfg.hnsw_ef).await?;
+    ensure_index_hash_hnsw(
+        &redis,
+        &kg_index,
+        &kg_prefix,
+        dims,
+        cfg.hnsw_m,
+        cfg.hnsw_ef,
+        vector_type,
+    )
+    .await?;
 
     // Backfill thoughts -> embeddings
-    let thoughts_count = backfill_thoughts(&redis, &openai_key, &instance_id, dims).await?;
+    let thoughts_count = backfill_thoughts(&redis, embedder.as_ref(), &instance_id, dims, vector_type).await?;
     tracing::info!("Backfilled {} thought embeddings", thoughts_count);
 
     // Backfill KG entities -> embeddings
-    let kg_count = backfill_kg_entities(&redis, &openai_key, &instance_id, dims).await?;
+    let kg_count = backfill_kg_entities(&redis, embedder.as_ref(), &instance_id, dims, vector_type).await?;
     tracing::info!("Backfilled {} KG entity embeddings", kg_count);
 
     println!(
@@ -165,9 +151,10 @@ let kg_prefix = format!("{instance_id}:embeddings:kg_entity:");
 
 async fn backfill_thoughts(
     redis: &RedisManager,
-    openai_key: &str,
+    embedder: &dyn Embedder,
     instance_id: &str,
     dims: usize,
+    vector_type: VectorType,
 ) -> Result<usize> {
     let mut conn = redis.get_connection().await?;
 let pattern = format!("{instance_id}:Thoughts:*");
@@ -210,7 +197,7 @@ let pattern = format!("{instance_id}:Thoughts:*");
             }
 
             // Embed
-let emb = match generate_openai_embedding(&rec.thought, openai_key).await {
+let emb = match embedder.embed(&rec.thought).await {
                 Ok(v) => v,
                 Err(e) => {
                     tracing::warn!("Embedding failed for thought {}: {}", rec.id, e);
@@ -218,10 +205,15 @@ let emb = match generate_openai_embedding(&rec.thought, openai_key).await {
                 }
             };
             if emb.len() != dims {
-                tracing::warn!("Unexpected dims for thought {}", rec.id);
+                tracing::warn!(
+                    "Unexpected dims for thought {} (expected {}, got {})",
+                    rec.id,
+                    dims,
+                    emb.len()
+                );
                 continue;
             }
-            let vec_bytes: Vec<u8> = cast_slice(&emb).to_vec();
+            let vec_bytes: Vec<u8> = vector_type.encode(&emb);
 
             // Store HASH doc for RediSearch
             let doc_key = format!("{}:embeddings:thought:{}", instance_id, rec.id);
@@ -267,9 +259,10 @@ let emb = match generate_openai_embedding(&rec.thought, openai_key).await {
 
 async fn backfill_kg_entities(
     redis: &RedisManager,
-    openai_key: &str,
+    embedder: &dyn Embedder,
     instance_id: &str,
     dims: usize,
+    vector_type: VectorType,
 ) -> Result<usize> {
     let mut conn = redis.get_connection().await?;
 
@@ -281,17 +274,19 @@ let mut total = 0usize;
 total += scan_and_embed_entities(
         &mut conn,
         &personal_pattern,
-        openai_key,
+        embedder,
         instance_id,
         dims,
+        vector_type,
     )
     .await?;
     total += scan_and_embed_entities(
         &mut conn,
         &federation_pattern,
-        openai_key,
+        embedder,
         instance_id,
         dims,
+        vector_type,
     )
     .await?;
     Ok(total)
@@ -300,9 +295,10 @@ total += scan_and_embed_entities(
 async fn scan_and_embed_entities(
     conn: &mut deadpool_redis::Connection,
     pattern: &str,
-    openai_key: &str,
+    embedder: &dyn Embedder,
     instance_id: &str,
     dims: usize,
+    vector_type: VectorType,
 ) -> Result<usize> {
     let mut cursor: u64 = 0;
     let mut processed = 0usize;
@@ -354,7 +350,7 @@ async fn scan_and_embed_entities(
                 continue;
             }
 
-let emb = match generate_openai_embedding(&text, openai_key).await {
+let emb = match embedder.embed(&text).await {
                 Ok(v) => v,
                 Err(e) => {
                     tracing::warn!("Embedding failed for entity {}: {}", node.id, e);
@@ -362,10 +358,15 @@ let emb = match generate_openai_embedding(&text, openai_key).await {
                 }
             };
             if emb.len() != dims {
-                tracing::warn!("Unexpected dims for entity {}", node.id);
+                tracing::warn!(
+                    "Unexpected dims for entity {} (expected {}, got {})",
+                    node.id,
+                    dims,
+                    emb.len()
+                );
                 continue;
             }
-            let vec_bytes: Vec<u8> = cast_slice(&emb).to_vec();
+            let vec_bytes: Vec<u8> = vector_type.encode(&emb);
 
             let doc_key = format!("{}:embeddings:kg_entity:{}", instance_id, node.id);
             let ts = chrono::Utc::now().timestamp();
@@ -408,6 +409,7 @@ async fn ensure_index_hash_hnsw(
     dims: usize,
     m: u32,
     ef_construction: u32,
+    vector_type: VectorType,
 ) -> Result<bool> {
     let mut con = redis_manager.get_connection().await?;
 
@@ -449,7 +451,7 @@ async fn ensure_index_hash_hnsw(
         .arg("HNSW")
         .arg(10)  // number of parameters
         .arg("TYPE")
-        .arg("FLOAT32")
+        .arg(vector_type.index_type())
         .arg("DIM")
         .arg(dims)
         .arg("DISTANCE_METRIC")
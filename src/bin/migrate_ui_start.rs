@@ -1,10 +1,9 @@
 use anyhow::Result;
-use bytemuck::cast_slice;
 use redis::AsyncCommands;
 use serde_json::Value;
 use std::sync::Arc;
 
-use unified_intelligence::config::Config;
+use unified_intelligence::config::{Config, VectorType};
 use unified_intelligence::redis::RedisManager;
 
 #[tokio::main]
@@ -32,6 +31,7 @@ async fn main() -> Result<()> {
         dims,
         config.redis_search.hnsw.m,
         config.redis_search.hnsw.ef_construction,
+        config.redis_search.vector_type,
     )
     .await;
 
@@ -92,7 +92,7 @@ async fn main() -> Result<()> {
                     continue;
                 }
             };
-            let vec_bytes: Vec<u8> = cast_slice(&vec_f32).to_vec();
+            let vec_bytes: Vec<u8> = config.redis_search.vector_type.encode(&vec_f32);
 
             // Load the corresponding summary JSON to reconstruct chunk content
             let sum_key = format!("{instance_id}:ui_start:summary:{chain_id}");
@@ -162,6 +162,7 @@ async fn ensure_index_hash_hnsw(
     dims: usize,
     m: u32,
     ef_construction: u32,
+    vector_type: VectorType,
 ) -> std::result::Result<bool, redis::RedisError> {
     let mut con = redis_manager.get_connection().await.map_err(|e| match e {
         unified_intelligence::error::UnifiedIntelligenceError::Redis(e) => e,
@@ -206,7 +207,7 @@ async fn ensure_index_hash_hnsw(
         .arg("HNSW")
         .arg(10)
         .arg("TYPE")
-        .arg("FLOAT32")
+        .arg(vector_type.index_type())
         .arg("DIM")
         .arg(dims)
         .arg("DISTANCE_METRIC")
@@ -0,0 +1,113 @@
+use std::sync::Arc;
+
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::models::{ChatMessage, GroqRequest};
+use crate::transport::Transport;
+
+/// Documented thought taxonomy accepted by auto-categorization
+pub const CATEGORIES: [&str; 4] = ["technical", "strategic", "operational", "relationship"];
+
+/// Map a raw model answer onto the allowed taxonomy, if it matches
+pub fn normalize_category(raw: &str) -> Option<String> {
+    let cleaned = raw
+        .trim()
+        .trim_matches(|c: char| c == '"' || c == '\'' || c == '.')
+        .to_lowercase();
+    CATEGORIES
+        .iter()
+        .find(|c| **c == cleaned)
+        .map(|c| c.to_string())
+}
+
+/// Classifies thoughts into the taxonomy using a cheap Groq model
+pub struct GroqCategorizer {
+    tx: Arc<dyn Transport>,
+    model: String,
+}
+
+impl GroqCategorizer {
+    pub fn new(tx: Arc<dyn Transport>, model: String) -> Self {
+        Self { tx, model }
+    }
+
+    /// Returns `Ok(None)` when the model answers outside the allowed set
+    pub async fn categorize(&self, thought: &str) -> Result<Option<String>> {
+        let system_message = ChatMessage {
+            role: "system".to_string(),
+            content: format!(
+                "Classify the user's thought into exactly one category: {}. \
+                 Respond with the category name only.",
+                CATEGORIES.join(", ")
+            ),
+        };
+        let user_message = ChatMessage {
+            role: "user".to_string(),
+            content: thought.to_string(),
+        };
+
+        let request = GroqRequest {
+            model: self.model.clone(),
+            messages: vec![system_message, user_message],
+            temperature: 0.0,
+            max_tokens: 10,
+            response_format: None,
+        };
+
+        let response = self.tx.chat(&request).await?;
+        let choice = response.choices.first().ok_or_else(|| {
+            UnifiedIntelligenceError::Internal(
+                "Groq API returned empty choices for categorization".to_string(),
+            )
+        })?;
+        Ok(normalize_category(&choice.message.content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Choice, GroqResponse};
+    use async_trait::async_trait;
+
+    struct FixedTransport(String);
+
+    #[async_trait]
+    impl Transport for FixedTransport {
+        async fn chat(&self, _req: &GroqRequest) -> Result<GroqResponse> {
+            Ok(GroqResponse {
+                choices: vec![Choice {
+                    message: ChatMessage {
+                        role: "assistant".to_string(),
+                        content: self.0.clone(),
+                    },
+                }],
+                usage: None,
+            })
+        }
+    }
+
+    #[test]
+    fn test_normalize_category() {
+        assert_eq!(
+            normalize_category(" Technical.\n"),
+            Some("technical".into())
+        );
+        assert_eq!(
+            normalize_category("\"strategic\""),
+            Some("strategic".into())
+        );
+        assert_eq!(normalize_category("general"), None);
+    }
+
+    #[tokio::test]
+    async fn test_categorize_rejects_unknown_category() {
+        let ok = GroqCategorizer::new(Arc::new(FixedTransport("Operational".into())), "m".into());
+        assert_eq!(
+            ok.categorize("deploy the fix").await.unwrap(),
+            Some("operational".to_string())
+        );
+
+        let bad = GroqCategorizer::new(Arc::new(FixedTransport("misc".into())), "m".into());
+        assert_eq!(bad.categorize("anything").await.unwrap(), None);
+    }
+}
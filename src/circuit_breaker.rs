@@ -1,7 +1,289 @@
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use crate::config::{CircuitBreakerConfig, SynthProviderKind};
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::models::{GroqRequest, GroqResponse};
+use crate::transport::{ChatStream, Transport};
+
 /// Circuit breaker states
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub enum CircuitBreakerState {}
+pub enum CircuitBreakerState {
+    /// Calls go through; failures are counted
+    Closed,
+    /// Calls fail fast until the reset timeout elapses
+    Open,
+    /// One trial call is in flight; its outcome closes or reopens the breaker.
+    /// A trial that reports nothing within the reset timeout (e.g. its future
+    /// was dropped) is abandoned and another caller becomes the trial.
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: CircuitBreakerState,
+    failures: u32,
+    /// When the breaker opened, or when the current half-open trial started
+    opened_at: Option<Instant>,
+}
+
+/// Opens after `failure_threshold` consecutive failures and rejects calls for
+/// `reset_timeout`, then lets a single trial call decide whether to close again
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    service: String,
+    failure_threshold: u32,
+    reset_timeout: Duration,
+    inner: Mutex<Inner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(service: &str, config: &CircuitBreakerConfig) -> Self {
+        Self {
+            service: service.to_string(),
+            failure_threshold: config.failure_threshold,
+            reset_timeout: Duration::from_secs(config.reset_timeout_secs),
+            inner: Mutex::new(Inner {
+                state: CircuitBreakerState::Closed,
+                failures: 0,
+                opened_at: None,
+            }),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().expect("circuit breaker mutex poisoned")
+    }
+
+    #[allow(dead_code)]
+    pub fn state(&self) -> CircuitBreakerState {
+        self.lock().state
+    }
+
+    /// Admit a call, or fail fast with `CircuitOpen` while the breaker is open
+    /// (or while another caller's trial call is in flight)
+    pub fn acquire(&self) -> Result<()> {
+        if self.failure_threshold == 0 {
+            return Ok(());
+        }
+        let mut inner = self.lock();
+        let elapsed = inner.opened_at.map(|t| t.elapsed()).unwrap_or_default();
+        match inner.state {
+            CircuitBreakerState::Closed => Ok(()),
+            CircuitBreakerState::Open | CircuitBreakerState::HalfOpen
+                if elapsed >= self.reset_timeout =>
+            {
+                if inner.state == CircuitBreakerState::HalfOpen {
+                    tracing::warn!(
+                        "{} circuit breaker trial call never reported; admitting another",
+                        self.service
+                    );
+                }
+                inner.state = CircuitBreakerState::HalfOpen;
+                inner.opened_at = Some(Instant::now());
+                Ok(())
+            }
+            CircuitBreakerState::Open | CircuitBreakerState::HalfOpen => {
+                Err(UnifiedIntelligenceError::CircuitOpen {
+                    service: self.service.clone(),
+                    retry_after_secs: self.reset_timeout.saturating_sub(elapsed).as_secs().max(1),
+                })
+            }
+        }
+    }
+
+    pub fn record_success(&self) {
+        let mut inner = self.lock();
+        if inner.state != CircuitBreakerState::Closed {
+            tracing::info!("{} circuit breaker closed", self.service);
+        }
+        inner.state = CircuitBreakerState::Closed;
+        inner.failures = 0;
+        inner.opened_at = None;
+    }
+
+    pub fn record_failure(&self) {
+        if self.failure_threshold == 0 {
+            return;
+        }
+        let mut inner = self.lock();
+        inner.failures = inner.failures.saturating_add(1);
+        let trip = inner.state == CircuitBreakerState::HalfOpen
+            || inner.failures >= self.failure_threshold;
+        if trip {
+            if inner.state != CircuitBreakerState::Open {
+                tracing::warn!(
+                    "{} circuit breaker opened after {} consecutive failures; failing fast for {}s",
+                    self.service,
+                    inner.failures,
+                    self.reset_timeout.as_secs()
+                );
+            }
+            inner.state = CircuitBreakerState::Open;
+            inner.opened_at = Some(Instant::now());
+        }
+    }
+}
+
+/// Transport that routes every call through a circuit breaker
+pub struct BreakerTransport {
+    inner: Arc<dyn Transport>,
+    breaker: Arc<CircuitBreaker>,
+}
+
+impl BreakerTransport {
+    pub fn new(inner: Arc<dyn Transport>, breaker: Arc<CircuitBreaker>) -> Self {
+        Self { inner, breaker }
+    }
+
+    fn record<T>(&self, result: Result<T>) -> Result<T> {
+        match &result {
+            Ok(_) => self.breaker.record_success(),
+            Err(_) => self.breaker.record_failure(),
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl Transport for BreakerTransport {
+    async fn chat(&self, req: &GroqRequest) -> Result<GroqResponse> {
+        self.breaker.acquire()?;
+        self.record(self.inner.chat(req).await)
+    }
+
+    /// Only opening the stream counts; mid-stream errors reach the caller
+    async fn chat_stream(&self, req: &GroqRequest) -> Result<ChatStream> {
+        self.breaker.acquire()?;
+        self.record(self.inner.chat_stream(req).await)
+    }
+}
+
+/// Breaker shared by every Groq call in the process; the config seen on first
+/// use applies for the process lifetime
+pub fn groq_breaker(config: &CircuitBreakerConfig) -> Arc<CircuitBreaker> {
+    static BREAKER: OnceLock<Arc<CircuitBreaker>> = OnceLock::new();
+    BREAKER
+        .get_or_init(|| Arc::new(CircuitBreaker::new("Groq", config)))
+        .clone()
+}
+
+/// Groq transport guarded by the process-wide breaker
+pub fn groq_transport(config: &crate::config::Config) -> Result<Arc<dyn Transport>> {
+    let transport = crate::transport::GroqTransport::new(config.groq.api_key.clone())?;
+    Ok(Arc::new(BreakerTransport::new(
+        Arc::new(transport),
+        groq_breaker(&config.groq.circuit_breaker),
+    )))
+}
+
+/// Breaker shared by every Anthropic call in the process
+fn anthropic_breaker(config: &CircuitBreakerConfig) -> Arc<CircuitBreaker> {
+    static BREAKER: OnceLock<Arc<CircuitBreaker>> = OnceLock::new();
+    BREAKER
+        .get_or_init(|| Arc::new(CircuitBreaker::new("Anthropic", config)))
+        .clone()
+}
+
+/// Transport for synthesis per `synth.provider`, guarded by that provider's breaker
+pub fn synth_transport(config: &crate::config::Config) -> Result<Arc<dyn Transport>> {
+    match config.synth.provider {
+        SynthProviderKind::Groq => groq_transport(config),
+        SynthProviderKind::Anthropic => {
+            let anthropic = &config.synth.anthropic;
+            let transport = crate::transport::AnthropicTransport::new(
+                anthropic.api_key.clone(),
+                anthropic.api_version.clone(),
+            )?;
+            Ok(Arc::new(BreakerTransport::new(
+                Arc::new(transport),
+                anthropic_breaker(&anthropic.circuit_breaker),
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(threshold: u32, reset_secs: u64) -> CircuitBreaker {
+        CircuitBreaker::new(
+            "Groq",
+            &CircuitBreakerConfig {
+                failure_threshold: threshold,
+                reset_timeout_secs: reset_secs,
+            },
+        )
+    }
+
+    #[test]
+    fn test_breaker_opens_after_consecutive_failures() {
+        let b = breaker(3, 60);
+        for _ in 0..2 {
+            assert!(b.acquire().is_ok());
+            b.record_failure();
+        }
+        // A success resets the count
+        b.record_success();
+        for _ in 0..3 {
+            assert!(b.acquire().is_ok());
+            b.record_failure();
+        }
+        assert_eq!(b.state(), CircuitBreakerState::Open);
+        assert!(matches!(
+            b.acquire(),
+            Err(UnifiedIntelligenceError::CircuitOpen { retry_after_secs, .. }) if retry_after_secs <= 60
+        ));
+    }
+
+    // Pretend the reset timeout has elapsed since the breaker opened or the trial began
+    fn backdate(b: &CircuitBreaker) {
+        b.lock().opened_at = Some(Instant::now() - Duration::from_secs(61));
+    }
+
+    #[test]
+    fn test_breaker_half_open_trial_decides() {
+        let b = breaker(1, 60);
+        b.record_failure();
+        assert_eq!(b.state(), CircuitBreakerState::Open);
+        assert!(b.acquire().is_err());
+        // Reset timeout elapsed: one trial call, concurrent callers still rejected
+        backdate(&b);
+        assert!(b.acquire().is_ok());
+        assert_eq!(b.state(), CircuitBreakerState::HalfOpen);
+        assert!(b.acquire().is_err());
+        b.record_failure();
+        assert_eq!(b.state(), CircuitBreakerState::Open);
+        backdate(&b);
+        assert!(b.acquire().is_ok());
+        b.record_success();
+        assert_eq!(b.state(), CircuitBreakerState::Closed);
+    }
+
+    #[test]
+    fn test_breaker_replaces_abandoned_trial() {
+        let b = breaker(1, 60);
+        b.record_failure();
+        backdate(&b);
+        // The trial's future is dropped without reporting
+        assert!(b.acquire().is_ok());
+        assert!(b.acquire().is_err());
+        backdate(&b);
+        assert!(b.acquire().is_ok());
+        assert_eq!(b.state(), CircuitBreakerState::HalfOpen);
+        b.record_success();
+        assert_eq!(b.state(), CircuitBreakerState::Closed);
+    }
 
-/// Circuit breaker configuration
-#[derive(Debug, Clone, Default)]
-pub struct CircuitBreakerConfig {}
+    #[test]
+    fn test_breaker_disabled_with_zero_threshold() {
+        let b = breaker(0, 60);
+        for _ in 0..10 {
+            b.record_failure();
+            assert!(b.acquire().is_ok());
+        }
+    }
+}
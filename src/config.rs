@@ -1,9 +1,12 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::path::Path;
 use std::time::Duration;
 
+use crate::error::UnifiedIntelligenceError;
+
 /// Main configuration structure for UnifiedIntelligence
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -18,6 +21,24 @@ pub struct Config {
     pub openai: OpenAIConfig,
     pub redis_search: RedisSearchConfig,
     pub ui_remember: UiRememberConfig,
+    #[serde(default)]
+    pub knowledge: KnowledgeConfig,
+    #[serde(default)]
+    pub ui_memory: UiMemoryConfig,
+    #[serde(default)]
+    pub stuck_tracker: StuckTrackerConfig,
+    #[serde(default)]
+    pub chains: ChainsConfig,
+    #[serde(default)]
+    pub tools: ToolsConfig,
+    #[serde(default)]
+    pub embeddings: EmbeddingsConfig,
+    #[serde(default)]
+    pub ui_think: UiThinkConfig,
+    #[serde(default)]
+    pub usage: UsageConfig,
+    #[serde(default)]
+    pub synth: SynthConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +46,133 @@ pub struct ServerConfig {
     pub name: String,
     pub version: String,
     pub default_instance_id: String,
+    /// Wrap tool JSON responses in a `{schema_version, tool, data}` envelope
+    #[serde(default)]
+    pub response_envelope: bool,
+    /// Hard cap applied to any requested `limit` on list/search operations
+    #[serde(default = "default_max_page_size")]
+    pub max_page_size: usize,
+    /// Instructions sent to MCP clients in `get_info`
+    #[serde(default)]
+    pub instructions: Option<String>,
+    /// File whose contents override `instructions` (read on each connect)
+    #[serde(default)]
+    pub instructions_file: Option<String>,
+    /// Token required for admin maintenance modes; unset disables them
+    #[serde(default)]
+    pub admin_token: Option<String>,
+    /// Server-side budget applied to every tool without an explicit entry
+    #[serde(default)]
+    pub default_tool_timeout_secs: Option<u64>,
+    /// Per-tool server-side budgets (seconds), keyed by tool name
+    #[serde(default)]
+    pub tool_timeout_secs: HashMap<String, u64>,
+    /// Return large tool results as MCP resource references
+    #[serde(default)]
+    pub resource_results: ResourceResultsConfig,
+    /// Decorative (emoji/colored) stderr output for thoughts and frameworks;
+    /// tracing logs are unaffected
+    #[serde(default = "default_true")]
+    pub visual: bool,
+    /// On SIGTERM/Ctrl-C, seconds to wait for in-flight requests and Redis
+    /// operations before exiting anyway
+    #[serde(default = "default_shutdown_grace_secs")]
+    pub shutdown_grace_secs: u64,
+    /// Serve `/metrics` without bearer auth, for scrapers on a trusted network
+    #[serde(default)]
+    pub metrics_public: bool,
+    /// Largest HTTP request body (bytes) buffered to find the called tool
+    /// for rate limiting; larger requests get 413
+    #[serde(default = "default_max_message_bytes")]
+    pub max_message_bytes: usize,
+}
+
+fn default_shutdown_grace_secs() -> u64 {
+    10
+}
+
+fn default_max_message_bytes() -> usize {
+    4 * 1024 * 1024
+}
+
+/// Large tool results are stored in Redis and returned as a `ui://results/{id}`
+/// reference that clients fetch with `resources/read`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceResultsConfig {
+    pub enabled: bool,
+    /// Encoded results at least this large are returned by reference
+    pub min_bytes: usize,
+    /// Lifetime of the stored result
+    pub ttl_seconds: u64,
+}
+
+impl Default for ResourceResultsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_bytes: 64 * 1024,
+            ttl_seconds: 3600,
+        }
+    }
+}
+
+// Comma-separated env value -> trimmed, non-empty entries
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn default_max_page_size() -> usize {
+    200
+}
+
+pub const DEFAULT_INSTRUCTIONS: &str =
+    "UnifiedIntelligence MCP Server for Redis-backed thought storage";
+
+impl ServerConfig {
+    /// Clamp a requested page size to `max_page_size`.
+    pub fn clamp_limit(&self, requested: usize) -> usize {
+        requested.min(self.max_page_size.max(1))
+    }
+
+    /// Whether `provided` matches the configured admin token (false when none is set)
+    pub fn is_admin(&self, provided: Option<&str>) -> bool {
+        match (self.admin_token.as_deref(), provided) {
+            (Some(expected), Some(given)) => !expected.is_empty() && expected == given,
+            _ => false,
+        }
+    }
+
+    /// Server-side timeout for `tool`, if one is configured (0 disables)
+    pub fn tool_timeout(&self, tool: &str) -> Option<Duration> {
+        self.tool_timeout_secs
+            .get(tool)
+            .copied()
+            .or(self.default_tool_timeout_secs)
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+    }
+
+    /// Resolve client instructions: `instructions_file`, then `instructions`,
+    /// then the built-in default. An unreadable or empty file falls through.
+    pub fn resolve_instructions(&self) -> String {
+        if let Some(path) = &self.instructions_file {
+            match fs::read_to_string(path) {
+                Ok(text) if !text.trim().is_empty() => return text.trim().to_string(),
+                Ok(_) => tracing::warn!("Instructions file {} is empty", path),
+                Err(e) => tracing::warn!("Failed to read instructions file {}: {}", path, e),
+            }
+        }
+        self.instructions
+            .clone()
+            .filter(|s| !s.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_INSTRUCTIONS.to_string())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +182,45 @@ pub struct RedisConfig {
     pub database: u8,
     pub pool: PoolConfig,
     pub default_ttl_seconds: i64,
+    /// COUNT hint passed to each SCAN call
+    #[serde(default = "default_scan_count")]
+    pub scan_count: usize,
+    /// Upper bound on matching keys a single pattern scan may return
+    #[serde(default = "default_scan_max_keys")]
+    pub scan_max_keys: usize,
+    #[serde(default)]
+    pub memory_guard: MemoryGuardConfig,
+}
+
+/// Periodic `INFO memory` check against `maxmemory`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemoryGuardConfig {
+    pub enabled: bool,
+    pub check_interval_secs: u64,
+    /// `used_memory / maxmemory` at which `/health` reports a warning
+    pub warn_ratio: f64,
+    /// Ratio at which writes fail with `StorageFull` (under `noeviction`)
+    pub critical_ratio: f64,
+}
+
+impl Default for MemoryGuardConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            check_interval_secs: 30,
+            warn_ratio: 0.85,
+            critical_ratio: 0.95,
+        }
+    }
+}
+
+fn default_scan_count() -> usize {
+    100
+}
+
+fn default_scan_max_keys() -> usize {
+    10_000
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,18 +235,111 @@ pub struct PoolConfig {
 pub struct RateLimiterConfig {
     pub max_requests: u32,
     pub window_seconds: u32,
+    /// Reject over-limit HTTP requests with 429 and a `Retry-After` header
+    #[serde(default = "default_true")]
+    pub http_retry_after: bool,
+    /// Per-tool overrides keyed by tool name; other tools use the limit above
+    #[serde(default)]
+    pub tools: HashMap<String, ToolRateLimit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolRateLimit {
+    pub max_requests: u32,
+    /// Defaults to `rate_limiter.window_seconds`
+    #[serde(default)]
+    pub window_seconds: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventStreamConfig {
     pub max_length: u64,
     pub approximate_trimming: bool,
+    /// Record thought ids in `{instance}:events:dlq` when `thought_created`
+    /// cannot be published, so a recovery task can replay them
+    #[serde(default = "default_true")]
+    pub dlq_on_publish_failure: bool,
+    /// Add the originating `tool` and `request_id` to every event a tool call writes
+    #[serde(default)]
+    pub include_origin: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BloomFilterConfig {
     pub error_rate: f64,
     pub expected_items: i64,
+    /// How thought content is normalized before computing the dedup hash;
+    /// `off` (default) never rejects repeated content
+    #[serde(default)]
+    pub normalization: DedupNormalization,
+    /// Store the SHA-256 of normalized content as `content_hash` on each thought
+    #[serde(default = "default_true")]
+    pub store_content_hash: bool,
+    /// Also dedup ephemeral saves (ui_remember turns); they are exempt by default
+    #[serde(default)]
+    pub dedup_ephemeral: bool,
+}
+
+/// Normalization level applied to thought content before dedup hashing.
+/// Only the hash input is normalized; stored content is never modified.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DedupNormalization {
+    /// No duplicate rejection; `content_hash` is taken over the exact content
+    #[default]
+    Off,
+    /// Hash content byte-for-byte
+    Exact,
+    /// Trim leading/trailing whitespace
+    Trim,
+    /// Trim and collapse internal whitespace runs to a single space
+    Whitespace,
+    /// Whitespace normalization plus lowercasing
+    CaseInsensitive,
+}
+
+impl DedupNormalization {
+    /// Normalize content for dedup hashing according to this level
+    pub fn apply(&self, content: &str) -> String {
+        match self {
+            DedupNormalization::Off | DedupNormalization::Exact => content.to_string(),
+            DedupNormalization::Trim => content.trim().to_string(),
+            DedupNormalization::Whitespace => {
+                content.split_whitespace().collect::<Vec<_>>().join(" ")
+            }
+            DedupNormalization::CaseInsensitive => content
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_lowercase(),
+        }
+    }
+
+    /// SHA-256 of the normalized content
+    pub fn hash(&self, content: &str) -> String {
+        use sha2::{Digest, Sha256};
+        hex::encode(Sha256::digest(self.apply(content).as_bytes()))
+    }
+
+    /// Whether saves reject content already stored
+    pub fn dedups(&self) -> bool {
+        *self != DedupNormalization::Off
+    }
+}
+
+impl std::str::FromStr for DedupNormalization {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" => Ok(DedupNormalization::Off),
+            "exact" => Ok(DedupNormalization::Exact),
+            "trim" => Ok(DedupNormalization::Trim),
+            "whitespace" => Ok(DedupNormalization::Whitespace),
+            "case_insensitive" => Ok(DedupNormalization::CaseInsensitive),
+            other => Err(format!("Unknown dedup normalization: {other}")),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -85,6 +365,49 @@ pub struct GroqConfig {
     pub intent_model: String,
     pub model_fast: String,
     pub model_deep: String,
+    /// Approximate query + context tokens above which `model_deep` is used
+    /// (0 = disabled; only the `deep` synthesis style picks `model_deep`)
+    #[serde(default)]
+    pub deep_token_threshold: usize,
+    /// Classify uncategorized ui_think thoughts with `model_fast` (one LLM call per thought)
+    #[serde(default)]
+    pub auto_categorize: bool,
+    /// Parsed intents below this confidence fall back to defaults and are flagged
+    #[serde(default = "default_intent_min_confidence")]
+    pub intent_min_confidence: f32,
+    /// Seconds a streamed synthesis may run before it is cut off (0 = no limit)
+    #[serde(default)]
+    pub stream_timeout_secs: u64,
+    /// When a streamed synthesis times out, keep and return the text received
+    /// so far (`partial: true`, `finish_reason: "timeout"`) instead of failing
+    #[serde(default)]
+    pub return_partial_on_timeout: bool,
+    /// Fail Groq calls fast after repeated failures instead of waiting on each
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+}
+
+/// Consecutive-failure circuit breaker around Groq calls
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that open the breaker; 0 disables it
+    pub failure_threshold: u32,
+    /// Seconds the breaker stays open before a single trial call is let through
+    pub reset_timeout_secs: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            reset_timeout_secs: 30,
+        }
+    }
+}
+
+fn default_intent_min_confidence() -> f32 {
+    0.5
 }
 
 impl Config {
@@ -173,6 +496,150 @@ impl Config {
         if let Ok(instance_id) = env::var("INSTANCE_ID") {
             self.server.default_instance_id = instance_id;
         }
+        if let Ok(visual) = env::var("UI_VISUAL") {
+            self.server.visual = !matches!(
+                visual.trim().to_lowercase().as_str(),
+                "0" | "false" | "off" | "no"
+            );
+        }
+        if let Ok(max) = env::var("UI_MAX_PAGE_SIZE")
+            && let Ok(max_size) = max.parse()
+        {
+            self.server.max_page_size = max_size;
+        }
+        if let Ok(instructions) = env::var("UI_INSTRUCTIONS") {
+            self.server.instructions = Some(instructions);
+        }
+        if let Ok(path) = env::var("UI_INSTRUCTIONS_FILE") {
+            self.server.instructions_file = Some(path);
+        }
+        if let Ok(secs) = env::var("UI_TOOL_TIMEOUT_SECS")
+            && let Ok(parsed) = secs.parse()
+        {
+            self.server.default_tool_timeout_secs = Some(parsed);
+        }
+        if let Ok(secs) = env::var("UI_SHUTDOWN_GRACE_SECS")
+            && let Ok(parsed) = secs.parse()
+        {
+            self.server.shutdown_grace_secs = parsed;
+        }
+        if let Ok(public) = env::var("UI_METRICS_PUBLIC")
+            && let Ok(parsed) = public.parse()
+        {
+            self.server.metrics_public = parsed;
+        }
+        if let Ok(bytes) = env::var("UI_MAX_MESSAGE_BYTES")
+            && let Ok(parsed) = bytes.parse()
+        {
+            self.server.max_message_bytes = parsed;
+        }
+        if let Ok(list) = env::var("UI_TOOLS_ENABLED") {
+            self.tools.enabled = Some(split_list(&list));
+        }
+        if let Ok(list) = env::var("UI_TOOLS_DISABLED") {
+            self.tools.disabled = split_list(&list);
+        }
+        if let Ok(mode) = env::var("UI_THINK_SENTIMENT")
+            && let Ok(parsed) = mode.parse()
+        {
+            self.ui_think.sentiment = parsed;
+        }
+        if let Ok(depth) = env::var("UI_KNOWLEDGE_MAX_DEPTH")
+            && let Ok(parsed) = depth.parse()
+        {
+            self.knowledge.max_traversal_depth = parsed;
+        }
+        if let Ok(nodes) = env::var("UI_KNOWLEDGE_MAX_NODES")
+            && let Ok(parsed) = nodes.parse()
+        {
+            self.knowledge.max_traversal_nodes = parsed;
+        }
+        if let Ok(mode) = env::var("UI_KNOWLEDGE_LIMIT_MODE")
+            && let Ok(parsed) = mode.parse()
+        {
+            self.knowledge.traversal_limit_mode = parsed;
+        }
+        if let Ok(enabled) = env::var("UI_KNOWLEDGE_RELATION_ATTRIBUTES")
+            && let Ok(flag) = enabled.parse()
+        {
+            self.knowledge.relation_attributes = flag;
+        }
+        if let Ok(list) = env::var("UI_KNOWLEDGE_ENTITY_VECTORS") {
+            self.knowledge.entity_vectors = split_list(&list)
+                .iter()
+                .filter_map(|name| name.parse().ok())
+                .collect();
+        }
+        if let Ok(search_type) = env::var("UI_KNOWLEDGE_SEARCH_TYPE")
+            && let Ok(parsed) = search_type.parse()
+        {
+            self.knowledge.search_type = parsed;
+        }
+        if let Ok(limit) = env::var("UI_KNOWLEDGE_NEAR_THOUGHT_LIMIT")
+            && let Ok(n) = limit.parse()
+        {
+            self.knowledge.near_thought_limit = n;
+        }
+        if let Ok(distance) = env::var("UI_KNOWLEDGE_NEAR_THOUGHT_MAX_DISTANCE")
+            && let Ok(d) = distance.parse()
+        {
+            self.knowledge.near_thought_max_distance = Some(d);
+        }
+        if let Ok(enabled) = env::var("UI_STORE_CONTENT_HASH")
+            && let Ok(flag) = enabled.parse()
+        {
+            self.bloom_filter.store_content_hash = flag;
+        }
+        if let Ok(provider) = env::var("UI_EMBEDDINGS_PROVIDER")
+            && let Ok(parsed) = provider.parse()
+        {
+            self.embeddings.provider = parsed;
+        }
+        if let Ok(url) = env::var("UI_EMBEDDINGS_URL")
+            && !url.is_empty()
+        {
+            self.embeddings.url = Some(url);
+        }
+        if let Ok(flag) = env::var("UI_EMBEDDINGS_SINGLE_FLIGHT")
+            && let Ok(enabled) = flag.parse()
+        {
+            self.embeddings.single_flight = enabled;
+        }
+        if let Ok(flag) = env::var("UI_EMBEDDINGS_INLINE")
+            && let Ok(enabled) = flag.parse()
+        {
+            self.embeddings.inline = enabled;
+        }
+        if let Ok(secs) = env::var("UI_EMBEDDINGS_BACKFILL_INTERVAL_SECS")
+            && let Ok(parsed) = secs.parse()
+        {
+            self.embeddings.backfill_interval_secs = parsed;
+        }
+        if let Ok(size) = env::var("UI_EMBEDDINGS_BACKFILL_BATCH")
+            && let Ok(parsed) = size.parse()
+        {
+            self.embeddings.backfill_batch_size = parsed;
+        }
+        if let Ok(max) = env::var("UI_EMBEDDINGS_MAX_CALLS_PER_REQUEST")
+            && let Ok(parsed) = max.parse()
+        {
+            self.embeddings.max_calls_per_request = parsed;
+        }
+        if let Ok(flag) = env::var("UI_RESOURCE_RESULTS")
+            && let Ok(enabled) = flag.parse()
+        {
+            self.server.resource_results.enabled = enabled;
+        }
+        if let Ok(token) = env::var("UI_ADMIN_TOKEN")
+            && !token.is_empty()
+        {
+            self.server.admin_token = Some(token);
+        }
+        if let Ok(envelope) = env::var("UI_RESPONSE_ENVELOPE")
+            && let Ok(flag) = envelope.parse()
+        {
+            self.server.response_envelope = flag;
+        }
 
         // Redis overrides
         if let Ok(host) = env::var("REDIS_HOST") {
@@ -196,6 +663,35 @@ impl Config {
             }
         }
 
+        // Scan overrides
+        if let Ok(max_keys) = env::var("UI_REDIS_SCAN_MAX_KEYS")
+            && let Ok(max) = max_keys.parse()
+        {
+            self.redis.scan_max_keys = max;
+        }
+
+        // Memory guard overrides
+        if let Ok(enabled) = env::var("UI_REDIS_MEMORY_GUARD")
+            && let Ok(flag) = enabled.parse()
+        {
+            self.redis.memory_guard.enabled = flag;
+        }
+        if let Ok(secs) = env::var("UI_REDIS_MEMORY_CHECK_INTERVAL_SECS")
+            && let Ok(parsed) = secs.parse()
+        {
+            self.redis.memory_guard.check_interval_secs = parsed;
+        }
+        if let Ok(ratio) = env::var("UI_REDIS_MEMORY_WARN_RATIO")
+            && let Ok(parsed) = ratio.parse()
+        {
+            self.redis.memory_guard.warn_ratio = parsed;
+        }
+        if let Ok(ratio) = env::var("UI_REDIS_MEMORY_CRITICAL_RATIO")
+            && let Ok(parsed) = ratio.parse()
+        {
+            self.redis.memory_guard.critical_ratio = parsed;
+        }
+
         // Rate limiter overrides
         if let Ok(max_requests) = env::var("UI_RATE_LIMIT_MAX_REQUESTS") {
             if let Ok(max) = max_requests.parse() {
@@ -214,6 +710,41 @@ impl Config {
                 self.event_stream.max_length = max;
             }
         }
+        if let Ok(auto) = env::var("UI_CHAIN_AUTO_METADATA")
+            && let Ok(flag) = auto.parse()
+        {
+            self.chains.auto_update_metadata = flag;
+        }
+        if let Ok(verify) = env::var("UI_CHAIN_VERIFY_INSTANCE")
+            && let Ok(flag) = verify.parse()
+        {
+            self.chains.verify_instance = flag;
+        }
+        if let Ok(size) = env::var("UI_CHAIN_EXPORT_PAGE_SIZE")
+            && let Ok(size) = size.parse()
+        {
+            self.chains.export_page_size = size;
+        }
+        if let Ok(bytes) = env::var("UI_CHAIN_EXPORT_MAX_BYTES")
+            && let Ok(bytes) = bytes.parse()
+        {
+            self.chains.export_max_bytes = bytes;
+        }
+        if let Ok(track) = env::var("UI_USAGE_TRACK_ACCESS")
+            && let Ok(flag) = track.parse()
+        {
+            self.usage.track_access = flag;
+        }
+        if let Ok(dlq) = env::var("UI_EVENT_STREAM_DLQ")
+            && let Ok(flag) = dlq.parse()
+        {
+            self.event_stream.dlq_on_publish_failure = flag;
+        }
+        if let Ok(origin) = env::var("UI_EVENT_ORIGIN")
+            && let Ok(flag) = origin.parse()
+        {
+            self.event_stream.include_origin = flag;
+        }
 
         // Retry overrides
         if let Ok(jitter) = env::var("UI_RETRY_JITTER_FACTOR") {
@@ -222,6 +753,49 @@ impl Config {
             }
         }
 
+        if let Ok(level) = env::var("UI_DEDUP_NORMALIZATION")
+            && let Ok(parsed) = level.parse()
+        {
+            self.bloom_filter.normalization = parsed;
+        }
+        if let Ok(enabled) = env::var("UI_DEDUP_EPHEMERAL")
+            && let Ok(flag) = enabled.parse()
+        {
+            self.bloom_filter.dedup_ephemeral = flag;
+        }
+
+        // OpenAI overrides
+        if let Ok(warn) = env::var("UI_EMBEDDING_DIM_MISMATCH_WARN")
+            && let Ok(flag) = warn.parse()
+        {
+            self.openai.warn_on_dim_mismatch = flag;
+        }
+        if let Ok(k) = env::var("UI_KNN_K")
+            && let Ok(parsed) = k.parse()
+        {
+            self.redis_search.knn_k = parsed;
+        }
+        if let Ok(max) = env::var("UI_KNN_MAX_K")
+            && let Ok(parsed) = max.parse()
+        {
+            self.redis_search.knn_max_k = parsed;
+        }
+        if let Ok(check) = env::var("UI_INDEX_DIM_CHECK")
+            && let Ok(parsed) = check.parse()
+        {
+            self.redis_search.index_dim_check = parsed;
+        }
+        if let Ok(vt) = env::var("UI_VECTOR_TYPE")
+            && let Ok(parsed) = vt.parse()
+        {
+            self.redis_search.vector_type = parsed;
+        }
+        if let Ok(max) = env::var("UI_EMBEDDING_MAX_INPUT_CHARS")
+            && let Ok(parsed) = max.parse()
+        {
+            self.openai.embedding_max_input_chars = parsed;
+        }
+
         // Qdrant removed: no overrides
 
         // Groq overrides
@@ -237,6 +811,57 @@ impl Config {
         if let Ok(model_deep) = env::var("GROQ_MODEL_DEEP") {
             self.groq.model_deep = model_deep;
         }
+        if let Ok(enabled) = env::var("UI_AUTO_CATEGORIZE")
+            && let Ok(flag) = enabled.parse()
+        {
+            self.groq.auto_categorize = flag;
+        }
+        if let Ok(min) = env::var("UI_INTENT_MIN_CONFIDENCE")
+            && let Ok(value) = min.parse()
+        {
+            self.groq.intent_min_confidence = value;
+        }
+        if let Ok(threshold) = env::var("UI_GROQ_DEEP_TOKEN_THRESHOLD")
+            && let Ok(parsed) = threshold.parse()
+        {
+            self.groq.deep_token_threshold = parsed;
+        }
+        if let Ok(secs) = env::var("UI_GROQ_STREAM_TIMEOUT_SECS")
+            && let Ok(parsed) = secs.parse()
+        {
+            self.groq.stream_timeout_secs = parsed;
+        }
+        if let Ok(enabled) = env::var("UI_GROQ_RETURN_PARTIAL_ON_TIMEOUT")
+            && let Ok(flag) = enabled.parse()
+        {
+            self.groq.return_partial_on_timeout = flag;
+        }
+        if let Ok(threshold) = env::var("UI_GROQ_BREAKER_THRESHOLD")
+            && let Ok(parsed) = threshold.parse()
+        {
+            self.groq.circuit_breaker.failure_threshold = parsed;
+        }
+        if let Ok(secs) = env::var("UI_GROQ_BREAKER_RESET_SECS")
+            && let Ok(parsed) = secs.parse()
+        {
+            self.groq.circuit_breaker.reset_timeout_secs = parsed;
+        }
+
+        // Synthesis provider overrides
+        if let Ok(provider) = env::var("UI_SYNTH_PROVIDER")
+            && let Ok(parsed) = provider.parse()
+        {
+            self.synth.provider = parsed;
+        }
+        if let Ok(api_key) = env::var("ANTHROPIC_API_KEY") {
+            self.synth.anthropic.api_key = api_key;
+        }
+        if let Ok(model_fast) = env::var("ANTHROPIC_MODEL_FAST") {
+            self.synth.anthropic.model_fast = model_fast;
+        }
+        if let Ok(model_deep) = env::var("ANTHROPIC_MODEL_DEEP") {
+            self.synth.anthropic.model_deep = model_deep;
+        }
 
         // ui_remember hybrid weight overrides
         if let Ok(w) = env::var("UI_REMEMBER_WEIGHT_SEMANTIC") {
@@ -257,6 +882,71 @@ impl Config {
         if let Ok(preset) = env::var("UI_REMEMBER_PRESET") {
             self.ui_remember.preset = Some(preset);
         }
+        if let Ok(enabled) = env::var("UI_REMEMBER_SYNTH_CACHE")
+            && let Ok(flag) = enabled.parse()
+        {
+            self.ui_remember.synthesis_cache_enabled = flag;
+        }
+        if let Ok(enabled) = env::var("UI_REMEMBER_PARSE_INTENT")
+            && let Ok(flag) = enabled.parse()
+        {
+            self.ui_remember.parse_intent = flag;
+        }
+        if let Ok(enabled) = env::var("UI_REMEMBER_LOG_RETRIEVAL")
+            && let Ok(flag) = enabled.parse()
+        {
+            self.ui_remember.log_retrieval = flag;
+        }
+        if let Ok(n) = env::var("UI_REMEMBER_SUMMARY_NEIGHBORS")
+            && let Ok(parsed) = n.parse()
+        {
+            self.ui_remember.summary_chunk_neighbors = parsed;
+        }
+        if let Ok(n) = env::var("UI_REMEMBER_MAX_CONTEXT_ITEMS")
+            && let Ok(parsed) = n.parse()
+        {
+            self.ui_remember.max_context_items = parsed;
+        }
+        if let Ok(n) = env::var("UI_REMEMBER_MAX_CONTEXT_CHARS")
+            && let Ok(parsed) = n.parse()
+        {
+            self.ui_remember.max_context_chars = parsed;
+        }
+        if let Ok(mode) = env::var("UI_REMEMBER_ASSISTANT_CONTEXT")
+            && let Ok(parsed) = mode.parse()
+        {
+            self.ui_remember.assistant_context = parsed;
+        }
+        if let Ok(weight) = env::var("UI_REMEMBER_ASSISTANT_WEIGHT")
+            && let Ok(parsed) = weight.parse()
+        {
+            self.ui_remember.assistant_weight = parsed;
+        }
+        if let Ok(n) = env::var("UI_REMEMBER_HIGHLIGHTS")
+            && let Ok(parsed) = n.parse()
+        {
+            self.ui_remember.highlights = parsed;
+        }
+        if let Ok(enabled) = env::var("UI_REMEMBER_INCLUDE_SOURCES")
+            && let Ok(flag) = enabled.parse()
+        {
+            self.ui_remember.include_sources = flag;
+        }
+        if let Ok(enabled) = env::var("UI_REMEMBER_PERSIST_PROMPTS")
+            && let Ok(flag) = enabled.parse()
+        {
+            self.ui_remember.persist_prompts = flag;
+        }
+        if let Ok(enabled) = env::var("UI_REMEMBER_DEDUP_CANDIDATES")
+            && let Ok(flag) = enabled.parse()
+        {
+            self.ui_remember.dedup_candidates = flag;
+        }
+        if let Ok(secs) = env::var("UI_REMEMBER_FEEDBACK_MERGE_WINDOW_SECS")
+            && let Ok(parsed) = secs.parse()
+        {
+            self.ui_remember.feedback_merge_window_secs = parsed;
+        }
     }
 
     /// Validate configuration
@@ -273,6 +963,14 @@ impl Config {
         if self.rate_limiter.window_seconds == 0 {
             return Err("Rate limiter window_seconds cannot be 0".into());
         }
+        for (tool, limit) in &self.rate_limiter.tools {
+            if limit.max_requests == 0 || limit.window_seconds == Some(0) {
+                return Err(format!(
+                    "Rate limiter override for {tool} needs non-zero max_requests and window_seconds"
+                )
+                .into());
+            }
+        }
 
         // Validate bloom filter
         if self.bloom_filter.error_rate <= 0.0 || self.bloom_filter.error_rate >= 1.0 {
@@ -290,6 +988,11 @@ impl Config {
         if self.groq.api_key == "PLACEHOLDER_GROQ_API_KEY" || self.groq.api_key.is_empty() {
             return Err("GROQ_API_KEY environment variable must be set".into());
         }
+        if self.synth.provider == SynthProviderKind::Anthropic
+            && self.synth.anthropic.api_key.is_empty()
+        {
+            return Err("ANTHROPIC_API_KEY must be set when synth.provider is anthropic".into());
+        }
 
         // Validate ui_remember weights are sane (0..=1)
         let w = self.ui_remember.hybrid_weights;
@@ -354,6 +1057,19 @@ pub struct OpenAIConfig {
     pub embedding_model: String,
     pub embedding_dimensions: usize,
     pub api_key_env: Option<String>,
+    /// Downgrade embedding dimension mismatches to a warning in best-effort
+    /// flows (embed-on-save, KNN retrieval) instead of failing the call.
+    #[serde(default)]
+    pub warn_on_dim_mismatch: bool,
+    /// Maximum characters sent to the embedding API; longer inputs are
+    /// truncated at a word boundary. 0 disables truncation.
+    #[serde(default = "default_embedding_max_input_chars")]
+    pub embedding_max_input_chars: usize,
+}
+
+fn default_embedding_max_input_chars() -> usize {
+    // ~8k tokens for text-embedding-3-small at roughly 4 chars/token, with headroom
+    24_000
 }
 
 impl OpenAIConfig {
@@ -365,11 +1081,208 @@ impl OpenAIConfig {
                 .ok_or_else(|| anyhow::anyhow!("OPENAI_API_KEY not set"))
         })
     }
+
+    /// Check an embedding length against `embedding_dimensions`.
+    ///
+    /// Returns `Ok(true)` when the length matches. A mismatch is logged at error
+    /// level and returned as `EmbeddingDimMismatch`, unless `warn_on_dim_mismatch`
+    /// is set, in which case it is logged at warn level and `Ok(false)` tells the
+    /// caller to skip the write.
+    pub fn check_embedding_dims(&self, got: usize) -> Result<bool, UnifiedIntelligenceError> {
+        let expected = self.embedding_dimensions;
+        if got == expected {
+            return Ok(true);
+        }
+        if self.warn_on_dim_mismatch {
+            tracing::warn!(
+                "Embedding dimension mismatch (expected {}, got {}); skipping",
+                expected,
+                got
+            );
+            return Ok(false);
+        }
+        tracing::error!(
+            "Embedding dimension mismatch: expected {}, got {}",
+            expected,
+            got
+        );
+        Err(UnifiedIntelligenceError::EmbeddingDimMismatch { expected, got })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RedisSearchConfig {
     pub hnsw: HNSWConfig,
+    /// Element type for HNSW vector fields and stored embeddings
+    #[serde(default)]
+    pub vector_type: VectorType,
+    /// Default KNN `$k` for each index queried
+    #[serde(default = "default_knn_k")]
+    pub knn_k: u32,
+    /// Per-index `$k`, keyed by full index name (`idx:CC:thought`) or its
+    /// last segment (`thought`, `kg_entity`)
+    #[serde(default)]
+    pub knn_k_per_index: HashMap<String, u32>,
+    /// Hard ceiling for any configured or client-requested `$k`
+    #[serde(default = "default_knn_max_k")]
+    pub knn_max_k: u32,
+    /// Startup comparison of existing vector index `DIM`s with
+    /// `openai.embedding_dimensions`
+    #[serde(default)]
+    pub index_dim_check: IndexDimCheck,
+}
+
+/// How a vector index created with different dimensions is reported at startup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexDimCheck {
+    /// Skip the check
+    Off,
+    /// Log an error per mismatched index and keep starting
+    #[default]
+    Warn,
+    /// Refuse to start
+    Strict,
+}
+
+impl std::str::FromStr for IndexDimCheck {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" => Ok(IndexDimCheck::Off),
+            "warn" => Ok(IndexDimCheck::Warn),
+            "strict" => Ok(IndexDimCheck::Strict),
+            other => Err(format!("Unknown index dimension check: {other}")),
+        }
+    }
+}
+
+fn default_knn_k() -> u32 {
+    5
+}
+
+fn default_knn_max_k() -> u32 {
+    100
+}
+
+impl RedisSearchConfig {
+    /// KNN `$k` for `index`: the client request, else the per-index setting,
+    /// else `knn_k`; always clamped to `1..=knn_max_k`.
+    pub fn knn_k(&self, index: &str, requested: Option<u32>) -> u32 {
+        let kind = index.rsplit(':').next().unwrap_or(index);
+        let k = requested
+            .or_else(|| self.knn_k_per_index.get(index).copied())
+            .or_else(|| self.knn_k_per_index.get(kind).copied())
+            .unwrap_or(self.knn_k);
+        let max = self.knn_max_k.max(1);
+        if k > max {
+            tracing::warn!(
+                "KNN k={} for {} exceeds knn_max_k; clamped to {}",
+                k,
+                index,
+                max
+            );
+        }
+        k.clamp(1, max)
+    }
+}
+
+/// Vector element type used for HNSW indexes and the bytes written to them.
+/// Half-precision types roughly halve vector memory at a small recall cost;
+/// existing indexes must be recreated (and re-embedded) when this changes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VectorType {
+    #[default]
+    Float32,
+    Float16,
+    Bfloat16,
+}
+
+impl VectorType {
+    /// RediSearch `TYPE` argument for `FT.CREATE ... VECTOR HNSW`
+    pub fn index_type(&self) -> &'static str {
+        match self {
+            VectorType::Float32 => "FLOAT32",
+            VectorType::Float16 => "FLOAT16",
+            VectorType::Bfloat16 => "BFLOAT16",
+        }
+    }
+
+    /// Serialize an embedding as little-endian bytes of this element type,
+    /// matching the `TYPE` of the index it is written to or queried against
+    pub fn encode(&self, vector: &[f32]) -> Vec<u8> {
+        match self {
+            VectorType::Float32 => vector.iter().flat_map(|v| v.to_le_bytes()).collect(),
+            VectorType::Float16 => vector
+                .iter()
+                .flat_map(|v| f32_to_f16_bits(*v).to_le_bytes())
+                .collect(),
+            VectorType::Bfloat16 => vector
+                .iter()
+                .flat_map(|v| f32_to_bf16_bits(*v).to_le_bytes())
+                .collect(),
+        }
+    }
+}
+
+/// IEEE 754 binary16 conversion with round-to-nearest-even
+fn f32_to_f16_bits(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xff) as i32;
+    let mant = bits & 0x007f_ffff;
+
+    if exp == 0xff {
+        // Inf stays Inf; NaN stays a (quiet) NaN
+        return sign | 0x7c00 | if mant != 0 { 0x0200 } else { 0 };
+    }
+    let half_exp = exp - 127 + 15;
+    if half_exp >= 0x1f {
+        return sign | 0x7c00;
+    }
+    if half_exp <= 0 {
+        if half_exp < -10 {
+            return sign;
+        }
+        // Subnormal: shift the full significand (with implicit bit) into 10 bits
+        let full = mant | 0x0080_0000;
+        let shift = (14 - half_exp) as u32;
+        let half_mant = full >> shift;
+        let rem = full & ((1 << shift) - 1);
+        let halfway = 1 << (shift - 1);
+        let round = rem > halfway || (rem == halfway && half_mant & 1 == 1);
+        return sign | (half_mant + round as u32) as u16;
+    }
+    let half_mant = mant >> 13;
+    let rem = mant & 0x1fff;
+    let round = rem > 0x1000 || (rem == 0x1000 && half_mant & 1 == 1);
+    // A rounding carry correctly bumps the exponent (up to Inf)
+    sign | ((((half_exp as u32) << 10) | half_mant) + round as u32) as u16
+}
+
+/// bfloat16 conversion (upper half of the f32) with round-to-nearest-even
+fn f32_to_bf16_bits(value: f32) -> u16 {
+    let bits = value.to_bits();
+    if value.is_nan() {
+        return ((bits >> 16) as u16) | 0x0040;
+    }
+    let rounding = 0x7fff + ((bits >> 16) & 1);
+    (bits.wrapping_add(rounding) >> 16) as u16
+}
+
+impl std::str::FromStr for VectorType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "float32" => Ok(VectorType::Float32),
+            "float16" => Ok(VectorType::Float16),
+            "bfloat16" => Ok(VectorType::Bfloat16),
+            other => Err(format!("Unknown vector type: {other}")),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -385,6 +1298,18 @@ impl Default for Config {
                 name: "unified-intelligence".to_string(),
                 version: "3.0.0".to_string(),
                 default_instance_id: "DT".to_string(),
+                response_envelope: false,
+                max_page_size: default_max_page_size(),
+                instructions: None,
+                instructions_file: None,
+                admin_token: None,
+                default_tool_timeout_secs: None,
+                tool_timeout_secs: HashMap::new(),
+                resource_results: ResourceResultsConfig::default(),
+                visual: true,
+                shutdown_grace_secs: default_shutdown_grace_secs(),
+                metrics_public: false,
+                max_message_bytes: default_max_message_bytes(),
             },
             redis: RedisConfig {
                 host: "localhost".to_string(),
@@ -397,18 +1322,28 @@ impl Default for Config {
                     recycle_timeout_seconds: 5,
                 },
                 default_ttl_seconds: 604800,
+                scan_count: default_scan_count(),
+                scan_max_keys: default_scan_max_keys(),
+                memory_guard: MemoryGuardConfig::default(),
             },
             rate_limiter: RateLimiterConfig {
                 max_requests: 100,
                 window_seconds: 60,
+                http_retry_after: true,
+                tools: HashMap::new(),
             },
             event_stream: EventStreamConfig {
                 max_length: 10000,
                 approximate_trimming: true,
+                dlq_on_publish_failure: true,
+                include_origin: false,
             },
             bloom_filter: BloomFilterConfig {
                 error_rate: 0.01,
                 expected_items: 100000,
+                normalization: DedupNormalization::default(),
+                store_content_hash: true,
+                dedup_ephemeral: false,
             },
             time_series: TimeSeriesConfig {
                 retention_ms: 86400000,
@@ -429,17 +1364,30 @@ impl Default for Config {
                 intent_model: "llama3-8b-8192".to_string(),
                 model_fast: "llama3-8b-8192".to_string(),
                 model_deep: "llama3-70b-8192".to_string(),
+                deep_token_threshold: 0,
+                auto_categorize: false,
+                intent_min_confidence: default_intent_min_confidence(),
+                stream_timeout_secs: 0,
+                return_partial_on_timeout: false,
+                circuit_breaker: CircuitBreakerConfig::default(),
             },
             openai: OpenAIConfig {
                 embedding_model: "text-embedding-3-small".to_string(),
                 embedding_dimensions: 1536,
                 api_key_env: None,
+                warn_on_dim_mismatch: false,
+                embedding_max_input_chars: default_embedding_max_input_chars(),
             },
             redis_search: RedisSearchConfig {
                 hnsw: HNSWConfig {
                     m: 16,
                     ef_construction: 200,
                 },
+                vector_type: VectorType::default(),
+                knn_k: default_knn_k(),
+                knn_k_per_index: HashMap::new(),
+                knn_max_k: default_knn_max_k(),
+                index_dim_check: IndexDimCheck::default(),
             },
             ui_remember: UiRememberConfig {
                 hybrid_weights: HybridWeights {
@@ -448,26 +1396,767 @@ impl Default for Config {
                     recency: 0.15,
                 },
                 preset: None,
+                profile: SynthesisProfile::default(),
+                synthesis_cache_enabled: false,
+                synthesis_cache_ttl_seconds: default_synthesis_cache_ttl_seconds(),
+                ephemeral_thoughts: false,
+                parse_intent: false,
+                aggregate_feedback: false,
+                feedback_merge_window_secs: default_feedback_merge_window_secs(),
+                log_retrieval: false,
+                log_retrieval_max_len: default_log_retrieval_max_len(),
+                summary_chunk_neighbors: 0,
+                persist_prompts: false,
+                dedup_candidates: true,
+                max_context_items: default_max_context_items(),
+                max_context_chars: default_max_context_chars(),
+                assistant_context: AssistantContext::default(),
+                assistant_weight: default_assistant_weight(),
+                highlights: 0,
+                include_sources: false,
             },
+            knowledge: KnowledgeConfig::default(),
+            ui_memory: UiMemoryConfig::default(),
+            stuck_tracker: StuckTrackerConfig::default(),
+            chains: ChainsConfig::default(),
+            tools: ToolsConfig::default(),
+            embeddings: EmbeddingsConfig::default(),
+            ui_think: UiThinkConfig::default(),
+            usage: UsageConfig::default(),
+            synth: SynthConfig::default(),
         }
     }
 }
 
+/// Knowledge graph maintenance settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KnowledgeConfig {
+    /// Maximum entities examined when inferring relations from shared thoughts
+    pub inference_max_entities: usize,
+    /// Minimum number of shared thoughts before a relation is suggested
+    pub inference_min_cooccurrence: usize,
+    /// SCAN list/search reads only the fields a listing needs (name, type,
+    /// tags, timestamps) via `JSON.GET` subpaths and returns those projected
+    /// nodes, without attributes, thought ids or metadata
+    pub projected_search: bool,
+    /// Largest `depth` honoured by graph traversal modes (`subgraph`, `traverse`)
+    pub max_traversal_depth: usize,
+    /// Most entities a traversal returns before it stops and flags truncation
+    pub max_traversal_nodes: usize,
+    /// What to do when a traversal request exceeds the bounds above
+    pub traversal_limit_mode: TraversalLimitMode,
+    /// Return relation `attributes` from relation-returning modes unless the
+    /// request sets `include_attributes`
+    pub relation_attributes: bool,
+    /// Named vectors embedded per entity on create/update; `search` can
+    /// target one with `vector`
+    pub entity_vectors: Vec<EntityVector>,
+    /// Default number of entities returned by `entities_near_thought`
+    pub near_thought_limit: usize,
+    /// Drop `entities_near_thought` matches farther than this vector distance
+    pub near_thought_max_distance: Option<f64>,
+    /// `search` ranking when the request sets no `search_type`
+    pub search_type: EntitySearchType,
+    /// Attribute rules keyed by entity type (`person`, `system`, custom
+    /// types by name), checked on create and on attribute updates; types
+    /// without a schema accept any attributes
+    pub attribute_schemas: HashMap<String, AttributeSchema>,
+}
+
+impl KnowledgeConfig {
+    /// Configured entity vectors without duplicates; `def` when none are set
+    pub fn entity_vectors(&self) -> Vec<EntityVector> {
+        let mut vectors: Vec<EntityVector> = Vec::new();
+        for vector in &self.entity_vectors {
+            if !vectors.contains(vector) {
+                vectors.push(*vector);
+            }
+        }
+        if vectors.is_empty() {
+            vectors.push(EntityVector::Def);
+        }
+        vectors
+    }
+}
+
+/// Named entity embedding, each kept in its own index and key prefix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntityVector {
+    /// Name, tags and attributes ("what it is")
+    Def,
+    /// Name plus neighbouring relations ("how it's used")
+    Context,
+}
+
+impl EntityVector {
+    /// Segment used for `idx:{instance}:{kind}` and `{instance}:embeddings:{kind}:{id}`
+    pub fn kind(self) -> &'static str {
+        match self {
+            EntityVector::Def => "kg_entity",
+            EntityVector::Context => "kg_entity_context",
+        }
+    }
+}
+
+impl std::str::FromStr for EntityVector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "def" => Ok(EntityVector::Def),
+            "context" => Ok(EntityVector::Context),
+            other => Err(format!("Unknown entity vector: {other}")),
+        }
+    }
+}
+
+impl Default for KnowledgeConfig {
+    fn default() -> Self {
+        Self {
+            inference_max_entities: 1000,
+            inference_min_cooccurrence: 1,
+            projected_search: false,
+            max_traversal_depth: 3,
+            max_traversal_nodes: 200,
+            traversal_limit_mode: TraversalLimitMode::Clamp,
+            relation_attributes: true,
+            entity_vectors: vec![EntityVector::Def],
+            near_thought_limit: 5,
+            near_thought_max_distance: None,
+            search_type: EntitySearchType::Text,
+            attribute_schemas: HashMap::new(),
+        }
+    }
+}
+
+/// Allowed shape of `attributes` for one entity type
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AttributeSchema {
+    /// Keys that must be present
+    pub required: Vec<String>,
+    /// Only these keys (plus `required` and `types`) may be present; any key
+    /// when unset
+    pub allowed: Option<Vec<String>>,
+    /// Expected JSON type per key, checked when the key is present
+    pub types: HashMap<String, AttributeType>,
+}
+
+impl AttributeSchema {
+    /// Check attributes against the schema, naming every violation
+    pub fn validate(
+        &self,
+        attributes: &HashMap<String, serde_json::Value>,
+    ) -> std::result::Result<(), String> {
+        let mut problems = Vec::new();
+        for key in &self.required {
+            if !attributes.contains_key(key) {
+                problems.push(format!("missing required key '{key}'"));
+            }
+        }
+        let mut keys: Vec<&String> = attributes.keys().collect();
+        keys.sort();
+        for key in keys {
+            if let Some(allowed) = &self.allowed
+                && !allowed.contains(key)
+                && !self.required.contains(key)
+                && !self.types.contains_key(key)
+            {
+                problems.push(format!("key '{key}' is not allowed"));
+            }
+            if let Some(expected) = self.types.get(key)
+                && !expected.matches(&attributes[key])
+            {
+                problems.push(format!("key '{key}' must be {}", expected.name()));
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems.join("; "))
+        }
+    }
+}
+
+/// JSON value type an attribute must have
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttributeType {
+    String,
+    Number,
+    Integer,
+    Boolean,
+    Array,
+    Object,
+}
+
+impl AttributeType {
+    pub fn matches(self, value: &serde_json::Value) -> bool {
+        match self {
+            AttributeType::String => value.is_string(),
+            AttributeType::Number => value.is_number(),
+            AttributeType::Integer => value.is_i64() || value.is_u64(),
+            AttributeType::Boolean => value.is_boolean(),
+            AttributeType::Array => value.is_array(),
+            AttributeType::Object => value.is_object(),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            AttributeType::String => "a string",
+            AttributeType::Number => "a number",
+            AttributeType::Integer => "an integer",
+            AttributeType::Boolean => "a boolean",
+            AttributeType::Array => "an array",
+            AttributeType::Object => "an object",
+        }
+    }
+}
+
+/// How `ui_knowledge` `search` matches and ranks entities
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntitySearchType {
+    /// Substring match on name/display_name/tags, ranked by match quality
+    #[default]
+    Text,
+    /// KNN over an entity vector index, ranked by similarity
+    Semantic,
+}
+
+impl std::str::FromStr for EntitySearchType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(EntitySearchType::Text),
+            "semantic" => Ok(EntitySearchType::Semantic),
+            other => Err(format!("Unknown search_type: {other} (use text|semantic)")),
+        }
+    }
+}
+
+/// ui_think end-of-chain synthesis (`synthesize_on_complete`) and capture-time
+/// enrichment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiThinkConfig {
+    /// Synthesis profile keyed by the chain's dominant workflow state
+    /// (`conversation`, `debug`, `build`, `stuck`, `review`); unlisted states
+    /// use the default profile
+    pub synthesis_profiles: HashMap<String, SynthesisProfile>,
+    /// Most recent chain thoughts passed as context (a profile's `top_k` wins)
+    pub synthesis_max_thoughts: usize,
+    /// Score each thought's sentiment (-1..1) at capture and index it as
+    /// `sentiment`; off by default
+    pub sentiment: SentimentMode,
+}
+
+impl Default for UiThinkConfig {
+    fn default() -> Self {
+        let synthesis_profiles = HashMap::from([
+            (
+                "debug".to_string(),
+                SynthesisProfile {
+                    style: None,
+                    temperature: Some(0.2),
+                    top_k: None,
+                    model: Some("fast".to_string()),
+                },
+            ),
+            (
+                "review".to_string(),
+                SynthesisProfile {
+                    style: Some("deep".to_string()),
+                    temperature: Some(0.4),
+                    top_k: None,
+                    model: Some("deep".to_string()),
+                },
+            ),
+        ]);
+        Self {
+            synthesis_profiles,
+            synthesis_max_thoughts: 50,
+            sentiment: SentimentMode::Off,
+        }
+    }
+}
+
+impl UiThinkConfig {
+    /// Synthesis profile for a workflow state
+    pub fn profile_for(&self, state: &str) -> SynthesisProfile {
+        self.synthesis_profiles
+            .get(state)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// How ui_think scores thought sentiment
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SentimentMode {
+    /// No sentiment is computed
+    #[default]
+    Off,
+    /// Built-in word lists with simple negation handling (no external calls)
+    Lexicon,
+    /// One `groq.model_fast` call per thought; falls back to the lexicon on failure
+    Llm,
+}
+
+impl std::str::FromStr for SentimentMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" => Ok(SentimentMode::Off),
+            "lexicon" => Ok(SentimentMode::Lexicon),
+            "llm" => Ok(SentimentMode::Llm),
+            other => Err(format!("Unknown sentiment mode: {other}")),
+        }
+    }
+}
+
+/// Whether `ui_remember` retrieval may return its own prior syntheses
+/// (thoughts with category `ui_remember:assistant`) as context
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AssistantContext {
+    /// Rank them like any other memory
+    #[default]
+    Include,
+    /// Multiply their combined score by `assistant_weight`
+    Downweight,
+    /// Drop them from the candidate set
+    Exclude,
+}
+
+impl std::str::FromStr for AssistantContext {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "include" => Ok(AssistantContext::Include),
+            "downweight" => Ok(AssistantContext::Downweight),
+            "exclude" => Ok(AssistantContext::Exclude),
+            other => Err(format!("Unknown assistant context mode: {other}")),
+        }
+    }
+}
+
+/// Handling of traversal requests beyond `max_traversal_depth`/`max_traversal_nodes`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraversalLimitMode {
+    /// Reduce to the configured bound and flag it in the response
+    #[default]
+    Clamp,
+    /// Reject the request with a validation error
+    Error,
+}
+
+impl std::str::FromStr for TraversalLimitMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "clamp" => Ok(TraversalLimitMode::Clamp),
+            "error" => Ok(TraversalLimitMode::Error),
+            other => Err(format!("Unknown traversal limit mode: {other}")),
+        }
+    }
+}
+
+/// ui_memory tool settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiMemoryConfig {
+    /// Re-chunk and re-embed a chain's session summary when its summary key
+    /// (`{instance}:ui_start:summary:{chain}`) is updated; chunk keys re-embed alone
+    pub reembed_summaries_on_update: bool,
+    /// Chunk size in bytes used when embedding session summaries
+    pub summary_chunk_size: usize,
+    /// Default snippet length (chars) for search result content; unset = full content
+    pub snippet_length: Option<usize>,
+}
+
+impl Default for UiMemoryConfig {
+    fn default() -> Self {
+        Self {
+            reembed_summaries_on_update: true,
+            summary_chunk_size: 2000,
+            snippet_length: None,
+        }
+    }
+}
+
+/// Per-chain StuckTracker persistence for `framework_state=stuck`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StuckTrackerConfig {
+    /// Persist the tracker in Redis so modes rotate across calls
+    pub enabled: bool,
+    /// Optimistic-lock retries when concurrent calls race on the same chain
+    pub max_retries: u32,
+}
+
+impl Default for StuckTrackerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_retries: 5,
+        }
+    }
+}
+
+/// Which MCP tools this instance exposes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolsConfig {
+    /// Allowlist; when set, only these tools are exposed
+    pub enabled: Option<Vec<String>>,
+    /// Denylist applied after the allowlist
+    pub disabled: Vec<String>,
+}
+
+impl ToolsConfig {
+    /// Whether `tool` is exposed under the allow/deny lists
+    pub fn is_enabled(&self, tool: &str) -> bool {
+        let allowed = self
+            .enabled
+            .as_ref()
+            .is_none_or(|list| list.iter().any(|t| t == tool));
+        allowed && !self.disabled.iter().any(|t| t == tool)
+    }
+}
+
+/// Backend used to turn text into vectors
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingProviderKind {
+    /// OpenAI embeddings API (`openai.embedding_model`)
+    #[default]
+    OpenAI,
+    /// OpenAI-compatible `/embeddings` endpoint, e.g. a local model server
+    Http,
+}
+
+impl std::str::FromStr for EmbeddingProviderKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "openai" => Ok(EmbeddingProviderKind::OpenAI),
+            "http" => Ok(EmbeddingProviderKind::Http),
+            other => Err(format!("Unknown embedding provider: {other}")),
+        }
+    }
+}
+
+/// LLM backend used for synthesis (`ui_remember`, chain summaries)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SynthProviderKind {
+    /// Groq chat completions (`groq.model_fast` / `groq.model_deep`)
+    #[default]
+    Groq,
+    /// Anthropic Messages API (`synth.anthropic`)
+    Anthropic,
+}
+
+impl std::str::FromStr for SynthProviderKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "groq" => Ok(SynthProviderKind::Groq),
+            "anthropic" => Ok(SynthProviderKind::Anthropic),
+            other => Err(format!("Unknown synthesis provider: {other}")),
+        }
+    }
+}
+
+/// Synthesis provider selection; intent parsing, categorization and
+/// sentiment always use Groq
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SynthConfig {
+    pub provider: SynthProviderKind,
+    pub anthropic: AnthropicConfig,
+}
+
+impl SynthConfig {
+    /// (fast, deep) model names for the configured provider
+    pub fn models<'a>(&'a self, groq: &'a GroqConfig) -> (&'a str, &'a str) {
+        match self.provider {
+            SynthProviderKind::Groq => (&groq.model_fast, &groq.model_deep),
+            SynthProviderKind::Anthropic => {
+                (&self.anthropic.model_fast, &self.anthropic.model_deep)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnthropicConfig {
+    /// Prefer the `ANTHROPIC_API_KEY` environment variable
+    pub api_key: String,
+    pub model_fast: String,
+    pub model_deep: String,
+    /// Sent as the `anthropic-version` header
+    pub api_version: String,
+    pub circuit_breaker: CircuitBreakerConfig,
+}
+
+impl Default for AnthropicConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            model_fast: "claude-3-5-haiku-latest".to_string(),
+            model_deep: "claude-sonnet-4-0".to_string(),
+            api_version: "2023-06-01".to_string(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+        }
+    }
+}
+
+/// Embedding provider selection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingsConfig {
+    pub provider: EmbeddingProviderKind,
+    /// Endpoint for the `http` provider (full URL of its `/embeddings` route)
+    pub url: Option<String>,
+    /// Model name sent to the provider; defaults to `openai.embedding_model`
+    pub model: Option<String>,
+    /// Share one in-flight provider request among concurrent callers
+    /// embedding identical text
+    pub single_flight: bool,
+    /// Embed thoughts and entities while saving them; when off they are
+    /// queued in `{instance}:embeddings:pending` for the backfill scheduler
+    pub inline: bool,
+    /// Seconds between backfill runs over the pending set; 0 disables
+    pub backfill_interval_secs: u64,
+    /// Pending items embedded per backfill run (one batch request)
+    pub backfill_batch_size: usize,
+    /// Most texts embedded by one request (session summary chunks); the rest
+    /// are stored without a vector and queued for the backfill. 0 = unlimited
+    pub max_calls_per_request: usize,
+}
+
+impl Default for EmbeddingsConfig {
+    fn default() -> Self {
+        Self {
+            provider: EmbeddingProviderKind::default(),
+            url: None,
+            model: None,
+            single_flight: true,
+            inline: true,
+            backfill_interval_secs: 0,
+            backfill_batch_size: 32,
+            max_calls_per_request: 64,
+        }
+    }
+}
+
+/// Chain bookkeeping performed by the thought store path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChainsConfig {
+    /// Refresh `thought_count`/`updated_at` in chain metadata inside the store
+    /// script, creating minimal metadata when missing
+    pub auto_update_metadata: bool,
+    /// Reject `ui_recall` chain reads and `ui_remember` feedback for chains
+    /// that do not belong to the calling instance
+    pub verify_instance: bool,
+    /// `ui_memory` `prune_chains`: chains with at most this many thoughts...
+    pub prune_max_thoughts: usize,
+    /// ...and no activity for this many hours are eligible for pruning
+    pub prune_min_age_hours: u64,
+    /// `ui_recall` `export`: thoughts per page...
+    pub export_page_size: usize,
+    /// ...and serialized bytes per page, so a page fits one stdio message
+    pub export_max_bytes: usize,
+}
+
+impl Default for ChainsConfig {
+    fn default() -> Self {
+        Self {
+            auto_update_metadata: true,
+            verify_instance: true,
+            prune_max_thoughts: 2,
+            prune_min_age_hours: 168,
+            export_page_size: 200,
+            export_max_bytes: 256 * 1024,
+        }
+    }
+}
+
+/// Read-path usage signals
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageConfig {
+    /// Bump `access_count`/`last_accessed` in `{instance}:usage:{thought_id}`
+    /// for thoughts returned by recall, search and ui_remember retrieval
+    /// (one batched Lua call per response, off the response path)
+    pub track_access: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UiRememberConfig {
     #[serde(default = "HybridWeights::default")]
     pub hybrid_weights: HybridWeights,
     #[serde(default)]
     pub preset: Option<String>,
+    /// Synthesis defaults applied when the client doesn't override them
+    #[serde(default)]
+    pub profile: SynthesisProfile,
+    /// Reuse synthesized answers for identical query + context
+    #[serde(default)]
+    pub synthesis_cache_enabled: bool,
+    #[serde(default = "default_synthesis_cache_ttl_seconds")]
+    pub synthesis_cache_ttl_seconds: u64,
+    /// Save T1/T2/T3 without the `thought_created` event so they are not embedded
+    #[serde(default)]
+    pub ephemeral_thoughts: bool,
+    /// Parse each query's intent with `groq.intent_model` (one extra Groq
+    /// call) before retrieval, so a temporal filter narrows the candidates
+    #[serde(default)]
+    pub parse_intent: bool,
+    /// Fold scored per-turn feedback hashes into `voice:feedback:chain:{chain_id}`
+    /// and delete them, bounding feedback key growth
+    #[serde(default)]
+    pub aggregate_feedback: bool,
+    /// Follow-ups arriving within this many seconds of the first one on the
+    /// same assistant turn are merged into its feedback; later ones are
+    /// ignored. 0 applies only the first follow-up.
+    #[serde(default = "default_feedback_merge_window_secs")]
+    pub feedback_merge_window_secs: u64,
+    /// Append candidate ids/scores per query to `{instance}:eval:retrieval`
+    #[serde(default)]
+    pub log_retrieval: bool,
+    /// Approximate cap on the retrieval log stream length
+    #[serde(default = "default_log_retrieval_max_len")]
+    pub log_retrieval_max_len: usize,
+    /// For each session summary chunk returned by KNN, also pull this many
+    /// neighbouring chunks of the same chain on each side. Non-zero values
+    /// add `idx:{instance}:session-summaries` to the KNN fan-out; 0 disables.
+    #[serde(default)]
+    pub summary_chunk_neighbors: usize,
+    /// Store the rendered synthesis request for each assistant thought at
+    /// `{instance}:synth_prompt:{thought2_id}` (read back with action `prompt`)
+    #[serde(default)]
+    pub persist_prompts: bool,
+    /// Merge text and KNN candidates with the same (normalized) content before
+    /// ranking, keeping the higher-scored one with the stronger of each signal
+    #[serde(default = "default_true")]
+    pub dedup_candidates: bool,
+    /// Most candidates passed to synthesis regardless of `top_k`
+    #[serde(default = "default_max_context_items")]
+    pub max_context_items: usize,
+    /// Total content characters passed to synthesis; lowest-scored candidates
+    /// are dropped until it fits (the best candidate is always kept)
+    #[serde(default = "default_max_context_chars")]
+    pub max_context_chars: usize,
+    /// Treatment of prior assistant syntheses found by retrieval
+    #[serde(default)]
+    pub assistant_context: AssistantContext,
+    /// Score multiplier for assistant syntheses under `downweight`
+    #[serde(default = "default_assistant_weight")]
+    pub assistant_weight: f64,
+    /// Return up to this many context sentences most similar to the answer
+    /// as `highlights` (one extra embedding batch per query); 0 disables
+    #[serde(default)]
+    pub highlights: usize,
+    /// Return `sources`: each context item's id, origin and the index its
+    /// KNN hit came from
+    #[serde(default)]
+    pub include_sources: bool,
+}
+
+fn default_max_context_items() -> usize {
+    20
+}
+
+fn default_max_context_chars() -> usize {
+    16_000
+}
+
+fn default_log_retrieval_max_len() -> usize {
+    10_000
+}
+
+fn default_assistant_weight() -> f64 {
+    0.5
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Copy)]
+fn default_feedback_merge_window_secs() -> u64 {
+    120
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_synthesis_cache_ttl_seconds() -> u64 {
+    3600
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Copy, schemars::JsonSchema)]
 pub struct HybridWeights {
     pub semantic: f64,
     pub text: f64,
     pub recency: f64,
 }
 
+impl HybridWeights {
+    /// Each weight must lie in `0..=1`
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("semantic", self.semantic),
+            ("text", self.text),
+            ("recency", self.recency),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(format!(
+                    "weights.{name} must be between 0 and 1, got {value}"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Synthesis behavior carried by a ui_remember preset
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SynthesisProfile {
+    /// Default synthesis style (e.g. "deep", "chronological")
+    pub style: Option<String>,
+    /// Sampling temperature for synthesis
+    pub temperature: Option<f32>,
+    /// Default number of context candidates
+    pub top_k: Option<u32>,
+    /// Pin the model tier: "fast" or "deep"; unset selects by style and size
+    pub model: Option<String>,
+}
+
+impl SynthesisProfile {
+    /// Resolve the (fast, deep) model pair, collapsing both to one tier when pinned
+    pub fn model_pair<'a>(&self, model_fast: &'a str, model_deep: &'a str) -> (&'a str, &'a str) {
+        match self.model.as_deref() {
+            Some("fast") => (model_fast, model_fast),
+            Some("deep") => (model_deep, model_deep),
+            _ => (model_fast, model_deep),
+        }
+    }
+}
+
 impl HybridWeights {
     fn default() -> Self {
         Self {
@@ -482,27 +2171,51 @@ impl Config {
     fn apply_ui_remember_preset(&mut self) {
         if let Some(ref preset_raw) = self.ui_remember.preset {
             let preset = preset_raw.to_lowercase();
-            let w = match preset.as_str() {
+            let (w, profile) = match preset.as_str() {
                 // Prioritize speed and exact phrasing
-                "fast-chat" => HybridWeights {
-                    semantic: 0.45,
-                    text: 0.40,
-                    recency: 0.15,
-                },
+                "fast-chat" => (
+                    HybridWeights {
+                        semantic: 0.45,
+                        text: 0.40,
+                        recency: 0.15,
+                    },
+                    SynthesisProfile {
+                        style: None,
+                        temperature: Some(0.3),
+                        top_k: Some(3),
+                        model: Some("fast".to_string()),
+                    },
+                ),
                 // Prioritize semantic depth for synthesis
-                "deep-research" => HybridWeights {
-                    semantic: 0.75,
-                    text: 0.10,
-                    recency: 0.15,
-                },
+                "deep-research" => (
+                    HybridWeights {
+                        semantic: 0.75,
+                        text: 0.10,
+                        recency: 0.15,
+                    },
+                    SynthesisProfile {
+                        style: Some("deep".to_string()),
+                        temperature: Some(0.2),
+                        top_k: Some(8),
+                        model: Some("deep".to_string()),
+                    },
+                ),
                 // Emphasize most recent context
-                "recall-recent" => HybridWeights {
-                    semantic: 0.45,
-                    text: 0.15,
-                    recency: 0.40,
-                },
+                "recall-recent" => (
+                    HybridWeights {
+                        semantic: 0.45,
+                        text: 0.15,
+                        recency: 0.40,
+                    },
+                    SynthesisProfile {
+                        style: Some("chronological".to_string()),
+                        temperature: Some(0.3),
+                        top_k: Some(5),
+                        model: None,
+                    },
+                ),
                 // Balanced default
-                "balanced-default" => HybridWeights::default(),
+                "balanced-default" => (HybridWeights::default(), SynthesisProfile::default()),
                 other => {
                     tracing::warn!(
                         "Unknown ui_remember preset: {}. Using existing weights.",
@@ -512,12 +2225,14 @@ impl Config {
                 }
             };
             self.ui_remember.hybrid_weights = w;
+            self.ui_remember.profile = profile;
             tracing::info!(
-                "Applied ui_remember preset '{}': semantic={}, text={}, recency={}",
+                "Applied ui_remember preset '{}': semantic={}, text={}, recency={}, profile={:?}",
                 preset,
                 w.semantic,
                 w.text,
-                w.recency
+                w.recency,
+                self.ui_remember.profile
             );
         }
     }
@@ -536,6 +2251,10 @@ mod tests {
         assert!((w.semantic - 0.75).abs() < 1e-9);
         assert!((w.text - 0.10).abs() < 1e-9);
         assert!((w.recency - 0.15).abs() < 1e-9);
+        let profile = &cfg.ui_remember.profile;
+        assert_eq!(profile.style.as_deref(), Some("deep"));
+        assert_eq!(profile.top_k, Some(8));
+        assert_eq!(profile.model_pair("fast", "deep"), ("deep", "deep"));
     }
 
     #[test]
@@ -553,4 +2272,217 @@ mod tests {
         assert!((w.text - 0.40).abs() < 1e-9);
         assert!((w.recency - 0.15).abs() < 1e-9);
     }
+
+    #[test]
+    fn test_check_embedding_dims_strict_and_warn() {
+        let mut cfg = Config::default();
+        assert!(cfg.openai.check_embedding_dims(1536).unwrap());
+        assert!(matches!(
+            cfg.openai.check_embedding_dims(768),
+            Err(UnifiedIntelligenceError::EmbeddingDimMismatch {
+                expected: 1536,
+                got: 768
+            })
+        ));
+        cfg.openai.warn_on_dim_mismatch = true;
+        assert!(!cfg.openai.check_embedding_dims(768).unwrap());
+    }
+
+    #[test]
+    fn test_encode_vector_half_precision() {
+        let v = [1.0f32, -2.0, 0.5];
+        assert_eq!(VectorType::Float32.encode(&v).len(), 12);
+
+        let f16 = VectorType::Float16.encode(&v);
+        assert_eq!(f16, [0x00, 0x3c, 0x00, 0xc0, 0x00, 0x38]);
+        assert_eq!(f32_to_f16_bits(65520.0), 0x7c00); // rounds to Inf
+        assert_eq!(f32_to_f16_bits(5.960_464_5e-8), 0x0001); // smallest subnormal
+        assert_eq!(f32_to_f16_bits(1.0e-9), 0x0000);
+
+        let bf16 = VectorType::Bfloat16.encode(&v);
+        assert_eq!(bf16, [0x80, 0x3f, 0x00, 0xc0, 0x00, 0x3f]);
+        assert!(f32_to_bf16_bits(f32::NAN) & 0x7f80 == 0x7f80);
+    }
+
+    #[test]
+    fn test_dedup_normalization_levels() {
+        let content = "  Hello   World\n";
+        assert_eq!(DedupNormalization::default(), DedupNormalization::Off);
+        assert!(!DedupNormalization::Off.dedups());
+        assert!(DedupNormalization::Exact.dedups());
+        assert_eq!(DedupNormalization::Off.apply(content), content);
+        assert_eq!(DedupNormalization::Exact.apply(content), content);
+        assert_eq!(DedupNormalization::Trim.apply(content), "Hello   World");
+        assert_eq!(DedupNormalization::Whitespace.apply(content), "Hello World");
+        assert_eq!(
+            DedupNormalization::CaseInsensitive.apply(content),
+            "hello world"
+        );
+        assert_eq!(
+            "case_insensitive".parse::<DedupNormalization>().unwrap(),
+            DedupNormalization::CaseInsensitive
+        );
+        assert!("bogus".parse::<DedupNormalization>().is_err());
+    }
+
+    #[test]
+    fn test_tools_allow_and_deny_lists() {
+        let mut tools = ToolsConfig::default();
+        assert!(tools.is_enabled("ui_think"));
+        tools.enabled = Some(split_list("ui_recall, ui_help,ui_think"));
+        tools.disabled = vec!["ui_think".to_string()];
+        assert!(tools.is_enabled("ui_recall"));
+        assert!(!tools.is_enabled("ui_think"));
+        assert!(!tools.is_enabled("ui_remember"));
+    }
+
+    #[test]
+    fn test_knn_k_per_index_and_ceiling() {
+        let mut cfg = Config::default();
+        let rs = &mut cfg.redis_search;
+        assert_eq!(rs.knn_k("idx:CC:thought", None), 5);
+        rs.knn_k_per_index.insert("kg_entity".to_string(), 8);
+        rs.knn_k_per_index.insert("idx:CC:thought".to_string(), 12);
+        assert_eq!(rs.knn_k("idx:DT:kg_entity", None), 8);
+        assert_eq!(rs.knn_k("idx:CC:thought", None), 12);
+        assert_eq!(rs.knn_k("idx:DT:thought", None), 5);
+        assert_eq!(rs.knn_k("idx:CC:thought", Some(10_000)), 100);
+        assert_eq!(rs.knn_k("idx:CC:thought", Some(0)), 1);
+    }
+
+    #[test]
+    fn test_entity_vectors_dedup_and_default() {
+        let mut cfg = Config::default();
+        assert_eq!(cfg.knowledge.entity_vectors(), vec![EntityVector::Def]);
+        cfg.knowledge.entity_vectors = vec![
+            EntityVector::Context,
+            EntityVector::Def,
+            EntityVector::Context,
+        ];
+        assert_eq!(
+            cfg.knowledge.entity_vectors(),
+            vec![EntityVector::Context, EntityVector::Def]
+        );
+        cfg.knowledge.entity_vectors.clear();
+        assert_eq!(cfg.knowledge.entity_vectors(), vec![EntityVector::Def]);
+        assert_eq!("Context".parse::<EntityVector>(), Ok(EntityVector::Context));
+        assert_eq!(EntityVector::Context.kind(), "kg_entity_context");
+    }
+
+    #[test]
+    fn test_hybrid_weights_validate_range() {
+        let mut weights = Config::default().ui_remember.hybrid_weights;
+        assert!(weights.validate().is_ok());
+        weights.recency = 1.5;
+        assert!(weights.validate().unwrap_err().contains("recency"));
+        weights.recency = f64::NAN;
+        assert!(weights.validate().is_err());
+    }
+
+    #[test]
+    fn test_attribute_schema_validation() {
+        let schema: AttributeSchema = serde_yaml::from_str(
+            "required: [role]\nallowed: [email]\ntypes: {role: string, level: integer}",
+        )
+        .unwrap();
+        let attrs = |v: serde_json::Value| -> HashMap<String, serde_json::Value> {
+            serde_json::from_value(v).unwrap()
+        };
+        assert!(
+            schema
+                .validate(&attrs(serde_json::json!({"role": "dev", "level": 3})))
+                .is_ok()
+        );
+        let err = schema
+            .validate(&attrs(serde_json::json!({"level": 1.5, "team": "x"})))
+            .unwrap_err();
+        assert!(err.contains("missing required key 'role'"));
+        assert!(err.contains("key 'level' must be an integer"));
+        assert!(err.contains("key 'team' is not allowed"));
+        assert!(
+            AttributeSchema::default()
+                .validate(&attrs(serde_json::json!({"any": 1})))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_assistant_context_default_and_parse() {
+        let cfg = Config::default().ui_remember;
+        assert_eq!(cfg.assistant_context, AssistantContext::Include);
+        assert_eq!(
+            "Exclude".parse::<AssistantContext>(),
+            Ok(AssistantContext::Exclude)
+        );
+        assert!("drop".parse::<AssistantContext>().is_err());
+        let yaml: UiRememberConfig = serde_yaml::from_str("assistant_context: downweight").unwrap();
+        assert_eq!(yaml.assistant_context, AssistantContext::Downweight);
+        assert_eq!(yaml.assistant_weight, 0.5);
+    }
+
+    #[test]
+    fn test_tool_timeout_resolution() {
+        let mut cfg = Config::default();
+        assert_eq!(cfg.server.tool_timeout("ui_remember"), None);
+        cfg.server.default_tool_timeout_secs = Some(30);
+        cfg.server
+            .tool_timeout_secs
+            .insert("ui_remember".to_string(), 120);
+        cfg.server
+            .tool_timeout_secs
+            .insert("ui_help".to_string(), 0);
+        assert_eq!(
+            cfg.server.tool_timeout("ui_remember"),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            cfg.server.tool_timeout("ui_think"),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(cfg.server.tool_timeout("ui_help"), None);
+    }
+
+    #[test]
+    fn test_synth_provider_selects_models() {
+        let mut cfg = Config::default();
+        assert_eq!(
+            cfg.synth.models(&cfg.groq),
+            (cfg.groq.model_fast.as_str(), cfg.groq.model_deep.as_str())
+        );
+        cfg.synth.provider = "Anthropic".parse().expect("known provider");
+        assert_eq!(
+            cfg.synth.models(&cfg.groq),
+            ("claude-3-5-haiku-latest", "claude-sonnet-4-0")
+        );
+        assert!("openai".parse::<SynthProviderKind>().is_err());
+    }
+
+    #[test]
+    fn test_is_admin_requires_configured_token() {
+        let mut cfg = Config::default();
+        assert!(!cfg.server.is_admin(Some("anything")));
+        cfg.server.admin_token = Some("s3cret".to_string());
+        assert!(cfg.server.is_admin(Some("s3cret")));
+        assert!(!cfg.server.is_admin(Some("wrong")));
+        assert!(!cfg.server.is_admin(None));
+    }
+
+    #[test]
+    fn test_resolve_instructions_precedence() {
+        let mut cfg = Config::default();
+        assert_eq!(cfg.server.resolve_instructions(), DEFAULT_INSTRUCTIONS);
+
+        cfg.server.instructions = Some("Use ui_think for notes".to_string());
+        assert_eq!(cfg.server.resolve_instructions(), "Use ui_think for notes");
+
+        // Missing file falls back to the configured string
+        cfg.server.instructions_file = Some("/nonexistent/instructions.md".to_string());
+        assert_eq!(cfg.server.resolve_instructions(), "Use ui_think for notes");
+
+        let path = std::env::temp_dir().join("ui_test_instructions.md");
+        fs::write(&path, "  From file\n").unwrap();
+        cfg.server.instructions_file = Some(path.to_string_lossy().into_owned());
+        assert_eq!(cfg.server.resolve_instructions(), "From file");
+        let _ = fs::remove_file(path);
+    }
 }
@@ -1,48 +1,463 @@
-use anyhow::Result;
 use async_openai::{
     Client,
     config::OpenAIConfig,
     types::{CreateEmbeddingRequestArgs, EmbeddingInput},
 };
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
 use tracing::{info, warn};
 
-use crate::error::UnifiedIntelligenceError;
+use crate::config::{Config, EmbeddingProviderKind};
+use crate::error::{Result, UnifiedIntelligenceError};
 use crate::redis::RedisManager;
 
-#[cfg_attr(not(test), allow(dead_code))]
-pub async fn generate_openai_embedding(
+/// Pluggable text-to-vector backend
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>>;
+
+    /// Vector length this embedder is expected to return
+    /// (`openai.embedding_dimensions` for the built-in providers)
+    fn dimensions(&self) -> usize;
+
+    /// Provider, model and size the vectors come from; scopes the embedding
+    /// cache so switching any of them never serves vectors from another model
+    fn model_id(&self) -> String;
+
+    /// Embed several texts, preserving input order. The default issues one
+    /// request per text; providers with a batch endpoint should override it.
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut out = Vec::with_capacity(texts.len());
+        for text in texts {
+            out.push(self.embed(text).await?);
+        }
+        Ok(out)
+    }
+}
+
+/// Build the embedder selected by `embeddings.provider`.
+///
+/// Fails when the provider is missing its credentials or endpoint; callers
+/// treat that as "embeddings unavailable" and degrade.
+pub fn build_embedder(cfg: &Config) -> Result<Arc<dyn Embedder>> {
+    let model = cfg
+        .embeddings
+        .model
+        .clone()
+        .unwrap_or_else(|| cfg.openai.embedding_model.clone());
+    let dimensions = cfg.openai.embedding_dimensions;
+    let embedder: Arc<dyn Embedder> = match cfg.embeddings.provider {
+        EmbeddingProviderKind::OpenAI => {
+            let api_key = cfg.openai.api_key()?;
+            Arc::new(OpenAIEmbedder::new(api_key, model, dimensions))
+        }
+        EmbeddingProviderKind::Http => {
+            let url = cfg.embeddings.url.clone().ok_or_else(|| {
+                UnifiedIntelligenceError::Internal(
+                    "embeddings.url is required for the http provider".to_string(),
+                )
+            })?;
+            Arc::new(HttpEmbedder::new(url, model, dimensions))
+        }
+    };
+    if cfg.embeddings.single_flight {
+        Ok(Arc::new(SingleFlightEmbedder::new(embedder)))
+    } else {
+        Ok(embedder)
+    }
+}
+
+type InFlight = Arc<OnceCell<std::result::Result<Vec<f32>, String>>>;
+
+/// Collapses concurrent `embed` calls for identical text into one provider
+/// request; the other callers wait for and share its result. Batches are
+/// passed straight through.
+pub struct SingleFlightEmbedder {
+    inner: Arc<dyn Embedder>,
+    in_flight: Mutex<HashMap<String, InFlight>>,
+}
+
+impl SingleFlightEmbedder {
+    pub fn new(inner: Arc<dyn Embedder>) -> Self {
+        Self {
+            inner,
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl Embedder for SingleFlightEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let cell = self
+            .in_flight
+            .lock()
+            .expect("single-flight map poisoned")
+            .entry(text.to_string())
+            .or_default()
+            .clone();
+        let result = cell
+            .get_or_init(|| async { self.inner.embed(text).await.map_err(|e| e.to_string()) })
+            .await
+            .clone();
+
+        // The first caller to finish retires the entry so later calls re-embed
+        // (or hit the Redis cache) instead of reusing a stale result forever
+        let mut in_flight = self.in_flight.lock().expect("single-flight map poisoned");
+        if in_flight.get(text).is_some_and(|c| Arc::ptr_eq(c, &cell)) {
+            in_flight.remove(text);
+        }
+        drop(in_flight);
+
+        result.map_err(UnifiedIntelligenceError::Internal)
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        self.inner.embed_batch(texts).await
+    }
+
+    fn dimensions(&self) -> usize {
+        self.inner.dimensions()
+    }
+
+    fn model_id(&self) -> String {
+        self.inner.model_id()
+    }
+}
+
+/// OpenAI embeddings API
+pub struct OpenAIEmbedder {
+    client: Client<OpenAIConfig>,
+    model: String,
+    dimensions: usize,
+}
+
+impl OpenAIEmbedder {
+    pub fn new(api_key: String, model: String, dimensions: usize) -> Self {
+        let config = OpenAIConfig::new().with_api_key(api_key);
+        Self {
+            client: Client::with_config(config),
+            model,
+            dimensions,
+        }
+    }
+}
+
+#[async_trait]
+impl Embedder for OpenAIEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let request = CreateEmbeddingRequestArgs::default()
+            .model(self.model.clone())
+            .input(EmbeddingInput::String(text.to_string()))
+            .build()
+            .map_err(anyhow::Error::from)?;
+        let started = std::time::Instant::now();
+        let response = self.client.embeddings().create(request).await;
+        crate::metrics::global().observe_upstream("openai", started.elapsed());
+        let response = response.map_err(anyhow::Error::from)?;
+        response
+            .data
+            .into_iter()
+            .next()
+            .map(|d| d.embedding)
+            .ok_or_else(|| UnifiedIntelligenceError::Internal("No embeddings returned".to_string()))
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    fn model_id(&self) -> String {
+        format!("openai/{}/{}", self.model, self.dimensions)
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let request = CreateEmbeddingRequestArgs::default()
+            .model(self.model.clone())
+            .input(EmbeddingInput::StringArray(texts.to_vec()))
+            .build()
+            .map_err(anyhow::Error::from)?;
+        let started = std::time::Instant::now();
+        let response = self.client.embeddings().create(request).await;
+        crate::metrics::global().observe_upstream("openai", started.elapsed());
+        let data = response
+            .map_err(anyhow::Error::from)?
+            .data
+            .into_iter()
+            .map(|d| (d.index, d.embedding))
+            .collect();
+        order_batch(data, texts.len())
+    }
+}
+
+/// OpenAI-compatible `/embeddings` endpoint (local model servers, proxies)
+pub struct HttpEmbedder {
+    client: reqwest::Client,
+    url: String,
+    model: String,
+    dimensions: usize,
+}
+
+#[derive(Deserialize)]
+struct HttpEmbeddingResponse {
+    data: Vec<HttpEmbeddingItem>,
+}
+
+#[derive(Deserialize)]
+struct HttpEmbeddingItem {
+    #[serde(default)]
+    index: u32,
+    embedding: Vec<f32>,
+}
+
+impl HttpEmbedder {
+    pub fn new(url: String, model: String, dimensions: usize) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+            model,
+            dimensions,
+        }
+    }
+}
+
+#[async_trait]
+impl Embedder for HttpEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let mut vectors = self.embed_batch(&[text.to_string()]).await?;
+        Ok(vectors.remove(0))
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    fn model_id(&self) -> String {
+        format!("http/{}/{}/{}", self.url, self.model, self.dimensions)
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let started = std::time::Instant::now();
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&serde_json::json!({"model": self.model, "input": texts}))
+            .send()
+            .await;
+        crate::metrics::global().observe_upstream("embeddings_http", started.elapsed());
+        let response = response.map_err(|e| {
+            UnifiedIntelligenceError::Internal(format!("Embedding request failed: {e}"))
+        })?;
+        if !response.status().is_success() {
+            return Err(UnifiedIntelligenceError::Internal(format!(
+                "Embedding endpoint returned {}",
+                response.status()
+            )));
+        }
+        let body: HttpEmbeddingResponse = response.json().await.map_err(|e| {
+            UnifiedIntelligenceError::Internal(format!("Invalid embedding response: {e}"))
+        })?;
+        let data = body
+            .data
+            .into_iter()
+            .map(|d| (d.index, d.embedding))
+            .collect();
+        order_batch(data, texts.len())
+    }
+}
+
+/// Sort `(index, vector)` pairs into input order, checking the count
+fn order_batch(mut data: Vec<(u32, Vec<f32>)>, expected: usize) -> Result<Vec<Vec<f32>>> {
+    if data.len() != expected {
+        return Err(UnifiedIntelligenceError::Internal(format!(
+            "Expected {} embeddings, got {}",
+            expected,
+            data.len()
+        )));
+    }
+    data.sort_by_key(|(index, _)| *index);
+    Ok(data.into_iter().map(|(_, v)| v).collect())
+}
+
+/// Set of items awaiting embedding by the backfill scheduler
+/// (`thought:{id}`, `kg_entity:{scope}:{id}`, `session-summaries:{chain}:{offset}`)
+pub fn pending_key(instance: &str) -> String {
+    format!("{instance}:embeddings:pending")
+}
+
+/// Pending member for a session summary chunk stored without its vector
+pub fn summary_chunk_member(chain_id: &str, offset: usize) -> String {
+    format!("session-summaries:{chain_id}:{offset}")
+}
+
+/// Cut `text` to at most `max_chars` characters, preferring the last word
+/// boundary in the final fifth of the window. `max_chars == 0` means no limit.
+pub fn truncate_for_embedding(text: &str, max_chars: usize) -> &str {
+    if max_chars == 0 {
+        return text;
+    }
+    let Some((cut, _)) = text.char_indices().nth(max_chars) else {
+        return text;
+    };
+    let head = &text[..cut];
+    let floor = head
+        .char_indices()
+        .nth(max_chars - max_chars / 5)
+        .map_or(0, |(i, _)| i);
+    match head.rfind(char::is_whitespace) {
+        Some(ws) if ws >= floor => head[..ws].trim_end(),
+        _ => head,
+    }
+}
+
+/// Embed `text` through `embedder`, truncating long inputs and using the
+/// persistent Redis embedding cache.
+pub async fn generate_embedding(
     text: &str,
-    openai_api_key: &str,
+    embedder: &dyn Embedder,
     redis_manager: &RedisManager, // Pass RedisManager for caching
+    max_input_chars: usize,
 ) -> Result<Vec<f32>> {
+    let original_len = text.len();
+    let text = truncate_for_embedding(text, max_input_chars);
+    if text.len() < original_len {
+        warn!(
+            "Truncated embedding input from {} to {} bytes (max {} chars)",
+            original_len,
+            text.len(),
+            max_input_chars
+        );
+    }
+
     // Check cache first
-    if let Ok(Some(cached_embedding)) = redis_manager.get_cached_embedding(text).await {
+    let model_id = embedder.model_id();
+    if let Ok(Some(cached_embedding)) = redis_manager.get_cached_embedding(&model_id, text).await {
         info!("Using cached embedding for text: {}", text);
         return Ok(cached_embedding);
     }
 
-    info!("Generating new OpenAI embedding for text: {}", text);
+    info!("Generating new embedding for text: {}", text);
+    let embedding = embedder.embed(text).await?;
 
-    let config = OpenAIConfig::new().with_api_key(openai_api_key.to_string());
-    let client = Client::with_config(config);
-    let request = CreateEmbeddingRequestArgs::default()
-        .model("text-embedding-3-small".to_string())
-        .input(EmbeddingInput::String(text.to_string()))
-        .build()?;
-
-    let response = client.embeddings().create(request).await?;
-
-    let embedding = response
-        .data
-        .into_iter()
-        .next()
-        .ok_or_else(|| UnifiedIntelligenceError::Other(anyhow::anyhow!("No embeddings returned")))?
-        .embedding;
+    // A wrong-sized vector is left for the caller's dimension check to reject;
+    // caching it would keep serving it after the provider is fixed
+    if embedding.len() != embedder.dimensions() {
+        warn!(
+            "Not caching embedding with {} dimensions (provider expects {})",
+            embedding.len(),
+            embedder.dimensions()
+        );
+        return Ok(embedding);
+    }
 
     // Cache the embedding persistently (no TTL)
-    if let Err(e) = redis_manager.set_cached_embedding(text, &embedding).await {
+    if let Err(e) = redis_manager
+        .set_cached_embedding(&model_id, text, &embedding)
+        .await
+    {
         warn!("Failed to cache embedding: {}", e);
     }
 
     Ok(embedding)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_for_embedding() {
+        assert_eq!(truncate_for_embedding("short text", 0), "short text");
+        assert_eq!(truncate_for_embedding("short text", 100), "short text");
+        // Prefers a word boundary near the limit
+        assert_eq!(
+            truncate_for_embedding("alpha beta gamma delta", 20),
+            "alpha beta gamma"
+        );
+        // Falls back to a hard cut when no boundary is close enough
+        assert_eq!(truncate_for_embedding("abcdefghij", 5), "abcde");
+        // Never splits a multi-byte character
+        assert_eq!(truncate_for_embedding("ééééé", 3), "ééé");
+    }
+
+    struct LengthEmbedder;
+
+    #[async_trait]
+    impl Embedder for LengthEmbedder {
+        async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            Ok(vec![text.len() as f32])
+        }
+
+        fn dimensions(&self) -> usize {
+            1
+        }
+
+        fn model_id(&self) -> String {
+            "test/length/1".to_string()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_embed_batch_preserves_order() {
+        let texts = vec!["a".to_string(), "abc".to_string(), "ab".to_string()];
+        let vectors = LengthEmbedder.embed_batch(&texts).await.unwrap();
+        assert_eq!(vectors, vec![vec![1.0], vec![3.0], vec![2.0]]);
+
+        let ordered = order_batch(vec![(1, vec![2.0]), (0, vec![1.0])], 2).unwrap();
+        assert_eq!(ordered, vec![vec![1.0], vec![2.0]]);
+        assert!(order_batch(vec![(0, vec![1.0])], 2).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_single_flight_collapses_concurrent_calls() {
+        struct SlowCounter(std::sync::atomic::AtomicUsize);
+
+        #[async_trait]
+        impl Embedder for SlowCounter {
+            async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+                self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                Ok(vec![text.len() as f32])
+            }
+
+            fn dimensions(&self) -> usize {
+                1
+            }
+
+            fn model_id(&self) -> String {
+                "test/counter/1".to_string()
+            }
+        }
+
+        let counter = Arc::new(SlowCounter(Default::default()));
+        let embedder = Arc::new(SingleFlightEmbedder::new(counter.clone()));
+        let calls = (0..5).map(|_| {
+            let embedder = embedder.clone();
+            tokio::spawn(async move { embedder.embed("same text").await.unwrap() })
+        });
+        for result in futures::future::join_all(calls).await {
+            assert_eq!(result.unwrap(), vec![9.0]);
+        }
+        assert_eq!(counter.0.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert!(embedder.in_flight.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_build_embedder_requires_http_url() {
+        let mut cfg = Config::default();
+        cfg.embeddings.provider = EmbeddingProviderKind::Http;
+        assert!(build_embedder(&cfg).is_err());
+        cfg.embeddings.url = Some("http://localhost:11434/v1/embeddings".to_string());
+        let embedder = build_embedder(&cfg).unwrap();
+        assert_eq!(embedder.dimensions(), cfg.openai.embedding_dimensions);
+    }
+}
@@ -16,6 +16,12 @@ pub enum UnifiedIntelligenceError {
     #[allow(dead_code)]
     PoolGet(String),
 
+    #[error("Redis connection pool exhausted: all {max_size} connections are in use")]
+    PoolExhausted { max_size: usize },
+
+    #[error("Redis unreachable: {0}")]
+    RedisUnavailable(String),
+
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
@@ -33,11 +39,16 @@ pub enum UnifiedIntelligenceError {
     #[allow(dead_code)]
     ChainOperation(String),
 
-    #[error("Rate limit exceeded")]
-    RateLimit,
+    #[error("Rate limit exceeded; retry after {retry_after_secs}s")]
+    RateLimit { retry_after_secs: u64 },
+
+    #[error("{service} circuit breaker is open; retry after {retry_after_secs}s")]
+    CircuitOpen {
+        service: String,
+        retry_after_secs: u64,
+    },
 
     #[error("Unauthorized access")]
-    #[allow(dead_code)]
     Unauthorized,
 
     #[error("Internal error: {0}")]
@@ -51,6 +62,46 @@ pub enum UnifiedIntelligenceError {
 
     #[error("Duplicate thought detected for instance {instance}: {preview}")]
     DuplicateThought { instance: String, preview: String },
+
+    #[error("Embedding dimension mismatch: expected {expected}, got {got}")]
+    EmbeddingDimMismatch { expected: usize, got: usize },
+
+    #[error("Tool {tool} exceeded its server-side budget of {budget_secs}s")]
+    Timeout { tool: String, budget_secs: u64 },
+
+    #[error(
+        "Redis storage full: {used} of {max} bytes used under maxmemory-policy noeviction; free memory or raise maxmemory before writing"
+    )]
+    StorageFull { used: u64, max: u64 },
+}
+
+impl UnifiedIntelligenceError {
+    /// Stable snake_case name of the variant, used as a metrics label
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Redis(_) => "redis",
+            Self::Pool(_) => "pool",
+            Self::PoolCreation(_) => "pool_creation",
+            Self::PoolGet(_) => "pool_get",
+            Self::PoolExhausted { .. } => "pool_exhausted",
+            Self::RedisUnavailable(_) => "redis_unavailable",
+            Self::Serialization(_) => "serialization",
+            Self::Json(_) => "json",
+            Self::Validation { .. } => "validation",
+            Self::InvalidAction(_) => "invalid_action",
+            Self::ChainOperation(_) => "chain_operation",
+            Self::RateLimit { .. } => "rate_limit",
+            Self::CircuitOpen { .. } => "circuit_open",
+            Self::Unauthorized => "unauthorized",
+            Self::Internal(_) => "internal",
+            Self::NotFound(_) => "not_found",
+            Self::Other(_) => "other",
+            Self::DuplicateThought { .. } => "duplicate_thought",
+            Self::EmbeddingDimMismatch { .. } => "embedding_dim_mismatch",
+            Self::Timeout { .. } => "timeout",
+            Self::StorageFull { .. } => "storage_full",
+        }
+    }
 }
 
 /// Convert ValidationError to UnifiedIntelligenceError
@@ -67,6 +118,7 @@ impl From<crate::validation::ValidationError> for UnifiedIntelligenceError {
                 }
                 crate::validation::ValidationError::ThoughtTooLong { .. } => "thought".to_string(),
                 crate::validation::ValidationError::EmptyThought => "thought".to_string(),
+                crate::validation::ValidationError::TooManyTags { .. } => "tags".to_string(),
             },
             reason: err.to_string(),
         }
@@ -563,12 +563,32 @@ pub struct FrameworkResult {
     pub _metadata: Option<serde_json::Value>,
 }
 
+/// Whether decorative stderr output is printed (`server.visual`)
+static VISUAL_ENABLED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(true);
+
 /// Visual display for frameworks
 pub struct FrameworkVisual;
 
 impl FrameworkVisual {
+    /// Enable or disable decorative stderr output process-wide. Colors are
+    /// dropped when `NO_COLOR` is set to a non-empty value.
+    pub fn configure(enabled: bool) {
+        VISUAL_ENABLED.store(enabled, std::sync::atomic::Ordering::Relaxed);
+        if std::env::var("NO_COLOR").is_ok_and(|v| !v.is_empty()) {
+            colored::control::set_override(false);
+        }
+    }
+
+    /// Whether decorative stderr output is enabled
+    pub fn enabled() -> bool {
+        VISUAL_ENABLED.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
     /// Display framework information with colored output
     pub fn display_framework_start(framework: &ThinkingMode) {
+        if !Self::enabled() {
+            return;
+        }
         let icon = match framework {
             ThinkingMode::Ooda => "🎯",
             ThinkingMode::Socratic => "❓",
@@ -582,6 +602,9 @@ impl FrameworkVisual {
 
     /// Display framework prompts
     pub fn display_prompts(prompts: &[String]) {
+        if !Self::enabled() {
+            return;
+        }
         if !prompts.is_empty() {
             eprintln!(
                 "   {} {}",
@@ -596,6 +619,9 @@ impl FrameworkVisual {
 
     /// Display framework insights
     pub fn display_insights(insights: &[String]) {
+        if !Self::enabled() {
+            return;
+        }
         if !insights.is_empty() {
             for insight in insights {
                 eprintln!("   {} {}", "💡".bright_yellow(), insight.yellow());
@@ -74,7 +74,10 @@ impl HelpHandler {
                     "purpose": "Access previously stored thoughts for context and continuity",
                     "modes": [
                         "thought - Retrieve a single thought by ID",
-                        "chain - Retrieve all thoughts in a chain"
+                        "chain - Retrieve all thoughts in a chain",
+                        "export - Page through a chain of any size with a cursor",
+                        "search - Full-text search over thoughts by content",
+                        "delete - Permanently delete a thought (admin_token, confirm: true)"
                     ]
                 },
                 "ui_remember": {
@@ -120,7 +123,7 @@ impl HelpHandler {
             }),
             tips: vec![
                 "Use chain_id to link related thoughts together for better context".to_string(),
-                "Set framework_state to guide interaction (conversation, debug, build, stuck, review); internal modes (first_principles, ooda, systems, root_cause, swot, socratic) are selected automatically".to_string(),
+                "Set framework_state to guide interaction (conversation, debug, build, stuck, review); internal modes (first_principles, ooda, systems, root_cause, swot, socratic) are selected automatically unless thinking_mode is set".to_string(),
                 "Add importance (1-10) and relevance (1-10) scores for prioritization".to_string(),
                 "Use tags and categories to organize thoughts for easier retrieval".to_string(),
                 "The thought_number and total_thoughts help track progress in multi-step thinking".to_string(),
@@ -213,10 +216,16 @@ impl HelpHandler {
             "optional_params": {
                 "chain_id": "Optional chain ID to link thoughts together (string)",
                 "framework_state": "Workflow framework state (string): 'conversation' (default), 'debug', 'build', 'stuck', 'review'",
+                "thinking_mode": "Override the state-derived mode (string): 'first_principles', 'socratic', 'systems', 'ooda', 'root_cause', 'swot'",
+                "timestamp": "Optional RFC3339 creation time for historical imports (string); defaults to now. Sets the thought and embedding time; chain order, chain metadata and metrics still record the import time",
+                "include_framework_prompts": "Persist the framework's generated prompts with the thought and return them as framework_prompts (boolean, default false)",
                 "importance": "Importance score from 1-10 scale (integer)",
                 "relevance": "Relevance score from 1-10 scale to current task (integer)",
                 "tags": "Tags for categorization (array of strings)",
-                "category": "Category: 'technical', 'strategic', 'operational', or 'relationship' (string)"
+                "category": "Category: 'technical', 'strategic', 'operational', or 'relationship' (string); when omitted and groq.auto_categorize is enabled, the server classifies the thought",
+                "synthesize_on_complete": "With chain_id and next_thought_needed=false, summarize the chain and return it as chain_synthesis; model/temperature come from ui_think.synthesis_profiles for the chain's dominant framework_state (boolean, default false)",
+                "parent_thought_id": "Thought in the same chain this one branches from, when it does not follow the previous thought (string; requires chain_id)",
+                "ttl_seconds": "Expire the thought after this many seconds, for ephemeral scratch thoughts; omitted or 0 persists it (integer)"
             }
         });
         let frameworks = json!({
@@ -284,7 +293,7 @@ impl HelpHandler {
                 "Choose frameworks that match your thinking needs".to_string(),
                 "Higher importance scores (8-10) indicate critical insights".to_string(),
                 "Tags should be lowercase and descriptive".to_string(),
-                "When framework_state='stuck', we persist a per-chain StuckTracker in Redis to rotate thinking modes; include chain_id to enable persistence".to_string(),
+                "When framework_state='stuck', we persist a per-chain StuckTracker in Redis ({instance}:stuck:{chain_id}) to rotate thinking modes; include chain_id to enable persistence. The response reports thinking_mode and stuck_attempts".to_string(),
             ],
         }
     }
@@ -293,8 +302,8 @@ impl HelpHandler {
         let base_info = json!({
             "description": "Retrieve thoughts and memories by ID or chain ID",
             "required_params": {
-                "mode": "The recall mode: 'thought' or 'chain' (string)",
-                "id": "The thought ID or chain ID to retrieve (string)"
+                "mode": "The recall mode: 'thought', 'chain', 'export', 'search' or 'delete' (string)",
+                "id": "The thought ID or chain ID to retrieve, or the thought ID to delete; not needed for search (string)"
             },
             "modes": {
                 "thought": {
@@ -304,7 +313,32 @@ impl HelpHandler {
                 "chain": {
                     "description": "Retrieve all thoughts in a chain",
                     "returns": "Array of thoughts ordered by thought_number"
+                },
+                "export": {
+                    "description": "Export a chain page by page, each page bounded in thoughts and bytes so it fits one message",
+                    "returns": "{chain_id, thoughts, total, cursor, done}; call again with cursor until done"
+                },
+                "search": {
+                    "description": "Full-text search over this instance's thoughts (RediSearch query syntax)",
+                    "returns": "{query, thoughts, offset, total, next_offset}; pass next_offset as offset for the next page. min_importance/min_relevance narrow the matches and sort them by importance, highest first"
+                },
+                "delete": {
+                    "description": "Permanently delete a thought, its embedding and usage counters, and remove it from its chain; requires admin_token and confirm: true",
+                    "returns": "{status: \"deleted\", thought_id, chain_id}; a thought_deleted event is published to the instance stream"
                 }
+            },
+            "optional_params": {
+                "include_framework_summary": "Chain mode: return {thoughts, framework_summary} with framework usage counts (bool)",
+                "as_tree": "Chain mode: nest thoughts under their parent_thought_id as children; chains without branches stay flat (bool)",
+                "since": "Chain mode: return only thoughts after the first N as {thoughts, since, tail}; pass the returned tail next time to poll for new thoughts (integer)",
+                "query": "Search mode: text to search for (string, required in search mode unless a min_* filter is given)",
+                "min_importance": "Search mode: only thoughts with importance >= this; results are sorted by importance descending (integer)",
+                "min_relevance": "Search mode: only thoughts with relevance >= this (integer)",
+                "offset": "Chain mode: first chain position to return; with offset or limit the result is {thoughts, offset, total, next_offset}, next_offset being absent on the last page. Search mode: matches to skip (integer, default 0)",
+                "limit": "Chain and search modes: thoughts per page, capped by server.max_page_size; search defaults to 10 (integer)",
+                "cursor": "Export mode: chain position to continue from, as returned by the previous page (integer, default 0)",
+                "confirm": "Delete mode: must be true to delete (bool)",
+                "admin_token": "Delete mode: must match server.admin_token (string)"
             }
         });
 
@@ -322,6 +356,47 @@ impl HelpHandler {
                     "mode": "chain",
                     "id": "20240129-architecture-review"
                 }
+            },
+            "poll_chain": {
+                "description": "Fetch thoughts added since a previous recall that returned tail 12",
+                "params": {
+                    "mode": "chain",
+                    "id": "20240129-architecture-review",
+                    "since": 12
+                }
+            },
+            "page_chain": {
+                "description": "Read the second page of 50 thoughts of a long chain",
+                "params": {
+                    "mode": "chain",
+                    "id": "20240129-architecture-review",
+                    "offset": 50,
+                    "limit": 50
+                }
+            },
+            "search_thoughts": {
+                "description": "Find thoughts about caching without knowing their ids",
+                "params": {
+                    "mode": "search",
+                    "query": "caching",
+                    "limit": 20
+                }
+            },
+            "search_important_thoughts": {
+                "description": "Most important thoughts about caching, importance 8 and up",
+                "params": {
+                    "mode": "search",
+                    "query": "caching",
+                    "min_importance": 8
+                }
+            },
+            "export_chain_next_page": {
+                "description": "Continue an export whose previous page returned cursor 200",
+                "params": {
+                    "mode": "export",
+                    "id": "20240129-architecture-review",
+                    "cursor": 200
+                }
             }
         });
 
@@ -1,16 +1,18 @@
 use chrono::Utc;
+use std::collections::{HashMap, HashSet};
 use tracing;
 use uuid::Uuid;
 
-use crate::config::Config;
-use crate::embeddings::generate_openai_embedding;
+use crate::config::{Config, EntitySearchType, EntityVector, TraversalLimitMode};
+use crate::embeddings::{Embedder, generate_embedding};
 use crate::error::Result;
 use crate::models::{
-    KnowledgeNode, KnowledgeRelation, KnowledgeResponse, NodeMetadata, RelationMetadata,
+    EntityExplain, EntityMatch, GraphEdge, GraphNode, KnowledgeGraph, KnowledgeNode,
+    KnowledgeRelation, KnowledgeResponse, KnowledgeScope, NodeMetadata, RelationMetadata,
     UiKnowledgeParams,
 };
+use crate::repository::text_relevance;
 use crate::repository_traits::{KnowledgeRepository, ThoughtRepository};
-use bytemuck::cast_slice;
 
 /// Trait for knowledge graph operations
 pub trait KnowledgeHandler {
@@ -29,6 +31,7 @@ async fn ensure_index_hash_hnsw(
     dims: usize,
     m: u32,
     ef_construction: u32,
+    vector_type: crate::config::VectorType,
 ) -> std::result::Result<bool, redis::RedisError> {
     let mut con = redis_manager.get_connection().await.map_err(|e| match e {
         crate::error::UnifiedIntelligenceError::Redis(e) => e,
@@ -73,7 +76,7 @@ async fn ensure_index_hash_hnsw(
         .arg("HNSW")
         .arg("6")
         .arg("TYPE")
-        .arg("FLOAT32")
+        .arg(vector_type.index_type())
         .arg("DIM")
         .arg(dims)
         .arg("DISTANCE_METRIC")
@@ -88,6 +91,324 @@ async fn ensure_index_hash_hnsw(
     create_res.map(|_| true)
 }
 
+/// Depth a walk may use under `knowledge.max_traversal_depth`: the request
+/// clamped to it, or a validation error in `error` limit mode
+fn traversal_depth(limits: &crate::config::KnowledgeConfig, requested: usize) -> Result<usize> {
+    if requested > limits.max_traversal_depth
+        && limits.traversal_limit_mode == TraversalLimitMode::Error
+    {
+        return Err(crate::error::UnifiedIntelligenceError::Validation {
+            field: "depth".to_string(),
+            reason: format!(
+                "depth {requested} exceeds the maximum of {}",
+                limits.max_traversal_depth
+            ),
+        });
+    }
+    Ok(requested.min(limits.max_traversal_depth))
+}
+
+/// Validation error for a walk that hit `knowledge.max_traversal_nodes`
+fn too_many_nodes(mode: &str, max_nodes: usize) -> crate::error::UnifiedIntelligenceError {
+    crate::error::UnifiedIntelligenceError::Validation {
+        field: "depth".to_string(),
+        reason: format!("{mode} exceeds the maximum of {max_nodes} nodes; request a smaller depth"),
+    }
+}
+
+/// One entity reached by [`walk_relations`]
+struct Reached {
+    node: KnowledgeNode,
+    hop: usize,
+    /// Relations that reached it from the previous hop (none for the root)
+    via: Vec<KnowledgeRelation>,
+}
+
+/// Result of [`walk_relations`]
+struct Walk {
+    /// Entities in order of distance, the root first
+    reached: Vec<Reached>,
+    /// Every relation seen from a visited entity, once each
+    relations: Vec<KnowledgeRelation>,
+    /// Whether `max_nodes` stopped the walk early
+    truncated: bool,
+}
+
+/// Breadth-first walk from `root` over relations in `scope` (optionally of one
+/// `relationship_type`), visiting up to `max_nodes` entities within `depth`
+/// hops. Every entity is visited once, so cycles terminate; relations to
+/// unreadable entities are skipped. Shared by the subgraph and traverse modes.
+async fn walk_relations<K: KnowledgeRepository + ?Sized>(
+    repository: &K,
+    root: &str,
+    scope: &KnowledgeScope,
+    depth: usize,
+    max_nodes: usize,
+    relationship_type: Option<&str>,
+) -> Result<Walk> {
+    let root_node = repository.get_entity(root, scope).await?;
+    let mut visited: HashSet<String> = HashSet::from([root_node.id.clone()]);
+    let mut seen_edges: HashSet<String> = HashSet::new();
+    let mut frontier = vec![root_node.id.clone()];
+    let mut walk = Walk {
+        reached: vec![Reached {
+            node: root_node,
+            hop: 0,
+            via: Vec::new(),
+        }],
+        relations: Vec::new(),
+        truncated: false,
+    };
+
+    for hop in 1..=depth {
+        // Entities first reached on this hop, in discovery order, with every
+        // relation leading to them from the frontier
+        let mut reached: Vec<(String, Vec<KnowledgeRelation>)> = Vec::new();
+        let mut slots: HashMap<String, usize> = HashMap::new();
+        for id in &frontier {
+            for relation in repository.get_relations(id, scope).await? {
+                if relationship_type.is_some_and(|t| relation.relationship_type != t) {
+                    continue;
+                }
+                let other = if relation.from_entity_id == *id {
+                    relation.to_entity_id.clone()
+                } else {
+                    relation.from_entity_id.clone()
+                };
+                if seen_edges.insert(relation.id.clone()) {
+                    walk.relations.push(relation.clone());
+                }
+                if let Some(&slot) = slots.get(&other) {
+                    if !reached[slot].1.iter().any(|r| r.id == relation.id) {
+                        reached[slot].1.push(relation);
+                    }
+                } else if !visited.contains(&other) {
+                    if walk.reached.len() + reached.len() >= max_nodes {
+                        walk.truncated = true;
+                        continue;
+                    }
+                    visited.insert(other.clone());
+                    slots.insert(other.clone(), reached.len());
+                    reached.push((other, vec![relation]));
+                }
+            }
+        }
+
+        let mut next = Vec::new();
+        for (id, via) in reached {
+            match repository.get_entity(&id, scope).await {
+                Ok(node) => {
+                    next.push(id);
+                    walk.reached.push(Reached { node, hop, via });
+                }
+                Err(e) => tracing::debug!("walk: skipping entity {}: {}", id, e),
+            }
+        }
+        if walk.truncated || next.is_empty() {
+            break;
+        }
+        frontier = next;
+    }
+    Ok(walk)
+}
+
+/// Subgraph of up to `max_nodes` entities within `depth` hops of `root`.
+/// Edges are kept only when both endpoints were returned.
+async fn collect_subgraph<K: KnowledgeRepository + ?Sized>(
+    repository: &K,
+    root: &str,
+    scope: &KnowledgeScope,
+    depth: usize,
+    max_nodes: usize,
+) -> Result<KnowledgeGraph> {
+    let walk = walk_relations(repository, root, scope, depth, max_nodes, None).await?;
+    let mut graph = KnowledgeGraph {
+        root: root.to_string(),
+        depth,
+        requested_depth: depth,
+        truncated: walk.truncated,
+        ..Default::default()
+    };
+    graph.nodes = walk
+        .reached
+        .into_iter()
+        .map(|r| graph_node(r.node, r.hop))
+        .collect();
+    let relations = walk.relations;
+
+    let returned: HashSet<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+    graph.edges = relations
+        .into_iter()
+        .filter(|r| {
+            returned.contains(r.from_entity_id.as_str())
+                && returned.contains(r.to_entity_id.as_str())
+        })
+        .map(|r| GraphEdge {
+            id: r.id,
+            source: r.from_entity_id,
+            target: r.to_entity_id,
+            relationship_type: r.relationship_type,
+            weight: r.metadata.weight,
+            bidirectional: r.metadata.bidirectional,
+            attributes: r.attributes,
+        })
+        .collect();
+    Ok(graph)
+}
+
+/// Compact text embedded for an entity: display name, tags and an attribute snapshot
+pub(crate) fn entity_embedding_text(node: &KnowledgeNode) -> String {
+    let mut text = node.display_name.clone();
+    if !node.tags.is_empty() {
+        text.push_str(" | tags: ");
+        text.push_str(&node.tags.join(", "));
+    }
+    if !node.attributes.is_empty()
+        && let Ok(snapshot) = serde_json::to_string(&node.attributes)
+    {
+        let snap = snapshot.chars().take(400).collect::<String>();
+        text.push_str(" | attrs: ");
+        text.push_str(&snap);
+    }
+    text
+}
+
+/// Relations folded into an entity's `context` vector
+const MAX_CONTEXT_RELATIONS: usize = 25;
+
+/// Text embedded for one named entity vector. `context` lists up to
+/// `MAX_CONTEXT_RELATIONS` relations phrased from this entity's side.
+pub(crate) async fn entity_vector_text<K: KnowledgeRepository + ?Sized>(
+    repository: &K,
+    node: &KnowledgeNode,
+    vector: EntityVector,
+) -> String {
+    if vector == EntityVector::Def {
+        return entity_embedding_text(node);
+    }
+    let mut text = node.display_name.clone();
+    let relations = repository
+        .get_relations(&node.id, &node.scope)
+        .await
+        .unwrap_or_default();
+    let mut parts = Vec::new();
+    for relation in relations.iter().take(MAX_CONTEXT_RELATIONS) {
+        let outgoing = relation.from_entity_id == node.id;
+        let other = if outgoing {
+            &relation.to_entity_id
+        } else {
+            &relation.from_entity_id
+        };
+        // Only the name is phrased, so skip loading the neighbour's document
+        let Some(other) = repository
+            .get_entity_fields(other, &node.scope, &["$.display_name"])
+            .await
+            .ok()
+            .and_then(|mut fields| fields.remove("$.display_name"))
+            .and_then(|name| name.as_str().map(str::to_string))
+        else {
+            continue;
+        };
+        parts.push(if outgoing {
+            format!("{} {}", relation.relationship_type, other)
+        } else {
+            format!("{} {} this", other, relation.relationship_type)
+        });
+    }
+    if !parts.is_empty() {
+        text.push_str(" | relations: ");
+        text.push_str(&parts.join("; "));
+    }
+    text
+}
+
+/// Split KNN hits for a thought into entities and their distances, dropping
+/// hits beyond `max_distance` and flagging entities already linked to the thought
+fn near_thought_matches(
+    hits: Vec<(KnowledgeNode, Option<f64>)>,
+    thought_id: &str,
+    max_distance: Option<f64>,
+) -> (Vec<KnowledgeNode>, Vec<EntityMatch>) {
+    hits.into_iter()
+        .filter_map(|(entity, score)| {
+            let distance = score.unwrap_or(f64::MAX);
+            if max_distance.is_some_and(|max| distance > max) {
+                return None;
+            }
+            let matched = EntityMatch {
+                entity_id: entity.id.clone(),
+                name: entity.name.clone(),
+                distance,
+                linked: entity.thought_ids.iter().any(|t| t == thought_id),
+            };
+            Some((entity, matched))
+        })
+        .unzip()
+}
+
+/// Check attributes against `knowledge.attribute_schemas` for the entity type
+fn validate_attributes(
+    config: &Config,
+    entity_type: &crate::models::EntityType,
+    attributes: &std::collections::HashMap<String, serde_json::Value>,
+) -> Result<()> {
+    let entity_type = entity_type.to_string();
+    let Some(schema) = config.knowledge.attribute_schemas.get(&entity_type) else {
+        return Ok(());
+    };
+    schema.validate(attributes).map_err(|reason| {
+        crate::error::UnifiedIntelligenceError::Validation {
+            field: "attributes".to_string(),
+            reason: format!("{entity_type} attributes: {reason}"),
+        }
+    })
+}
+
+/// Query terms (lowercased, two or more characters) found in an entity's
+/// name, tags and attributes, alongside its search score
+fn explain_match(node: &KnowledgeNode, query: &str) -> EntityExplain {
+    let mut terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.chars().count() >= 2)
+        .map(str::to_lowercase)
+        .collect();
+    let mut seen = HashSet::new();
+    terms.retain(|t| seen.insert(t.clone()));
+    let name = format!("{} {}", node.name, node.display_name).to_lowercase();
+    let tags: Vec<String> = node.tags.iter().map(|t| t.to_lowercase()).collect();
+    let attributes: Vec<String> = node
+        .attributes
+        .iter()
+        .map(|(key, value)| format!("{key} {value}").to_lowercase())
+        .collect();
+    let found = |haystacks: &[String]| -> Vec<String> {
+        terms
+            .iter()
+            .filter(|t| haystacks.iter().any(|h| h.contains(t.as_str())))
+            .cloned()
+            .collect()
+    };
+    EntityExplain {
+        entity_id: node.id.clone(),
+        name: node.name.clone(),
+        score: node.score,
+        name_terms: found(std::slice::from_ref(&name)),
+        tag_terms: found(&tags),
+        attribute_terms: found(&attributes),
+    }
+}
+
+fn graph_node(node: KnowledgeNode, depth: usize) -> GraphNode {
+    GraphNode {
+        id: node.id,
+        name: node.name,
+        display_name: node.display_name,
+        entity_type: node.entity_type,
+        tags: node.tags,
+        depth,
+    }
+}
+
 impl<R: ThoughtRepository + KnowledgeRepository> KnowledgeHandler for super::ToolHandlers<R> {
     async fn ui_knowledge(&self, params: UiKnowledgeParams) -> Result<KnowledgeResponse> {
         match params.mode.as_str() {
@@ -97,12 +418,23 @@ impl<R: ThoughtRepository + KnowledgeRepository> KnowledgeHandler for super::Too
             "get_entity" => self.get_entity(params).await,
             "create_relation" => self.create_relation(params).await,
             "get_relations" => self.get_relations(params).await,
+            "delete_relation" => self.delete_relation(params).await,
+            "subgraph" => self.subgraph(params).await,
+            "traverse" => self.traverse(params).await,
             "update_entity" => self.update_entity(params).await,
             "delete_entity" => self.delete_entity(params).await,
+            "archive" => self.set_archived(params, true).await,
+            "unarchive" => self.set_archived(params, false).await,
+            "merge_entities" => self.merge_entities(params).await,
+            "infer_relations" => self.infer_relations(params).await,
+            "entities_near_thought" => self.entities_near_thought(params).await,
+            "get_active" => self.get_active_entity().await,
+            "clear_active" => self.clear_active_entity().await,
+            "repair_name_index" => self.repair_name_index(params).await,
             _ => Err(crate::error::UnifiedIntelligenceError::Validation {
                 field: "mode".to_string(),
                 reason: format!(
-                    "Invalid mode: {}. Valid modes are: create, search, set_active, get_entity, create_relation, get_relations, update_entity, delete_entity",
+                    "Invalid mode: {}. Valid modes are: create, search, set_active, get_entity, create_relation, get_relations, delete_relation, subgraph, traverse, update_entity, delete_entity, archive, unarchive, merge_entities, infer_relations, entities_near_thought, get_active, clear_active, repair_name_index",
                     params.mode
                 ),
             }),
@@ -111,6 +443,213 @@ impl<R: ThoughtRepository + KnowledgeRepository> KnowledgeHandler for super::Too
 }
 
 impl<R: ThoughtRepository + KnowledgeRepository> super::ToolHandlers<R> {
+    /// Re-embed an entity's vectors (best-effort), or queue it for the
+    /// backfill when inline embedding is off. The entity is already stored,
+    /// so a failure (e.g. a dimension mismatch) is logged and returned as
+    /// response warnings rather than failing the call.
+    async fn refresh_entity_embeddings(
+        &self,
+        node: &KnowledgeNode,
+        config: &Config,
+    ) -> Option<Vec<String>> {
+        if !config.embeddings.inline {
+            crate::backfill::mark_pending(
+                &self.redis_manager,
+                &self.instance_id,
+                &crate::backfill::entity_member(&node.scope, &node.id),
+            )
+            .await;
+        } else if let Some(embedder) = self.embedder.as_deref()
+            && let Err(e) = self
+                .embed_entity(node, config, embedder, &config.knowledge.entity_vectors())
+                .await
+        {
+            crate::metrics::global().record_error(&e);
+            tracing::error!("Stored entity {} without embedding: {}", node.id, e);
+            return Some(vec![format!("entity stored without embedding: {e}")]);
+        }
+        None
+    }
+
+    /// Relations feed the `context` vector: after a relation change, re-embed
+    /// it for each affected entity (best-effort), or queue them for the backfill
+    async fn refresh_relation_context(&self, entity_ids: &[String], scope: &KnowledgeScope) {
+        let config = Config::load();
+        if !config
+            .knowledge
+            .entity_vectors()
+            .contains(&EntityVector::Context)
+        {
+            return;
+        }
+        let mut seen = HashSet::new();
+        for id in entity_ids.iter().filter(|id| seen.insert(id.as_str())) {
+            if !config.embeddings.inline {
+                crate::backfill::mark_pending(
+                    &self.redis_manager,
+                    &self.instance_id,
+                    &crate::backfill::entity_member(scope, id),
+                )
+                .await;
+                continue;
+            }
+            let Some(embedder) = self.embedder.as_deref() else {
+                return;
+            };
+            let refreshed = match self.repository.get_entity(id, scope).await {
+                Ok(node) => {
+                    self.embed_entity(&node, &config, embedder, &[EntityVector::Context])
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = refreshed {
+                tracing::warn!("Failed to refresh context vector of entity {}: {}", id, e);
+            }
+        }
+    }
+
+    /// Embed the given named vectors of an entity (best-effort per vector)
+    async fn embed_entity(
+        &self,
+        node: &KnowledgeNode,
+        config: &Config,
+        embedder: &dyn Embedder,
+        vectors: &[EntityVector],
+    ) -> Result<()> {
+        for &vector in vectors {
+            let kind = vector.kind();
+            let _ = ensure_index_hash_hnsw(
+                &self.redis_manager,
+                &format!("idx:{}:{kind}", self.instance_id),
+                &format!("{}:embeddings:{kind}:", self.instance_id),
+                config.openai.embedding_dimensions,
+                config.redis_search.hnsw.m,
+                config.redis_search.hnsw.ef_construction,
+                config.redis_search.vector_type,
+            )
+            .await;
+
+            let text = entity_vector_text(self.repository.as_ref(), node, vector).await;
+            let Ok(embedding) = generate_embedding(
+                &text,
+                embedder,
+                &self.redis_manager,
+                config.openai.embedding_max_input_chars,
+            )
+            .await
+            else {
+                continue;
+            };
+            if !config.openai.check_embedding_dims(embedding.len())? {
+                continue;
+            }
+            if let Ok(mut con) = self.redis_manager.get_connection().await {
+                let key = format!("{}:embeddings:{kind}:{}", self.instance_id, node.id);
+                let vec_bytes: Vec<u8> = config.redis_search.vector_type.encode(&embedding);
+                let ts = chrono::Utc::now().timestamp();
+                let _: () = redis::pipe()
+                    .hset(&key, "content", &text)
+                    .hset(&key, "tags", node.tags.join(","))
+                    .hset(&key, "category", kind)
+                    .hset(&key, "importance", "")
+                    .hset(&key, "chain_id", "")
+                    .hset(&key, "thought_id", "")
+                    .hset(&key, "ts", ts)
+                    .hset(&key, "vector", vec_bytes)
+                    .query_async(&mut *con)
+                    .await
+                    .unwrap_or(());
+            }
+        }
+        Ok(())
+    }
+
+    /// KNN over one named entity vector; hits outside `scope` are skipped, as
+    /// are archived entities unless `include_archived`
+    #[allow(clippy::too_many_arguments)]
+    async fn search_entities_by_vector(
+        &self,
+        query: &str,
+        scope: &KnowledgeScope,
+        vector: EntityVector,
+        entity_type: Option<&crate::models::EntityType>,
+        limit: usize,
+        include_archived: bool,
+        config: &Config,
+    ) -> Result<Vec<(KnowledgeNode, Option<f64>)>> {
+        let invalid = |reason: String| crate::error::UnifiedIntelligenceError::Validation {
+            field: "vector".to_string(),
+            reason,
+        };
+        if !config.knowledge.entity_vectors().contains(&vector) {
+            return Err(invalid(format!(
+                "vector '{}' is not enabled in knowledge.entity_vectors",
+                vector.kind()
+            )));
+        }
+        let embedder = self
+            .embedder
+            .as_deref()
+            .ok_or_else(|| invalid("semantic search needs an embedding provider".to_string()))?;
+        let embedding = generate_embedding(
+            query,
+            embedder,
+            &self.redis_manager,
+            config.openai.embedding_max_input_chars,
+        )
+        .await?;
+        if !config.openai.check_embedding_dims(embedding.len())? {
+            return Ok(Vec::new());
+        }
+
+        let kind = vector.kind();
+        let index = format!("idx:{}:{kind}", self.instance_id);
+        let prefix = format!("{}:embeddings:{kind}:", self.instance_id);
+        let vec_bytes = config.redis_search.vector_type.encode(&embedding);
+        let max_k = config.redis_search.knn_max_k.max(1);
+        let mut k = config
+            .redis_search
+            .knn_k(&index, Some(u32::try_from(limit).unwrap_or(u32::MAX)));
+
+        // Scope, type and archive filters drop hits after the KNN, so widen it
+        // until the page fills or the index runs out. Rows come back nearest
+        // first, so each wider pass only appends farther hits.
+        let mut seen = HashSet::new();
+        let mut entities = Vec::new();
+        loop {
+            let rows = self
+                .redis_manager
+                .knn_search_with_fields(&index, &vec_bytes, k, &[])
+                .await?;
+            let exhausted = rows.len() < k as usize;
+            for row in rows {
+                if !seen.insert(row.key.clone()) {
+                    continue;
+                }
+                let Some(id) = row.key.strip_prefix(&prefix) else {
+                    continue;
+                };
+                let Ok(entity) = self.repository.get_entity(id, scope).await else {
+                    continue;
+                };
+                if entity_type.is_some_and(|t| t.to_string() != entity.entity_type.to_string()) {
+                    continue;
+                }
+                if entity.archived && !include_archived {
+                    continue;
+                }
+                entities.push((entity, row.score));
+            }
+            if entities.len() >= limit || exhausted || k >= max_k {
+                break;
+            }
+            k = k.saturating_mul(2).min(max_k);
+        }
+        entities.truncate(limit);
+        Ok(entities)
+    }
+
     async fn create_entity(&self, params: UiKnowledgeParams) -> Result<KnowledgeResponse> {
         // Validate required fields for create mode
         let name =
@@ -127,6 +666,11 @@ impl<R: ThoughtRepository + KnowledgeRepository> super::ToolHandlers<R> {
             }
         })?;
         let scope = params.scope.unwrap_or_default();
+        let tags = self
+            .validator
+            .normalize_tags(params.tags.as_deref().unwrap_or_default())?;
+        let attributes = params.attributes.unwrap_or_default();
+        validate_attributes(&Config::load(), &entity_type, &attributes)?;
 
         tracing::info!("Creating entity '{}' in {} scope", name, scope);
 
@@ -138,6 +682,13 @@ impl<R: ThoughtRepository + KnowledgeRepository> super::ToolHandlers<R> {
                 entities: Some(vec![existing]),
                 relations: None,
                 message: Some(format!("Entity '{name}' already exists")),
+                total: None,
+                name_index_report: None,
+                graph: None,
+                near_entities: None,
+                explain: None,
+                warnings: None,
+                truncated: None,
             });
         }
 
@@ -151,8 +702,8 @@ impl<R: ThoughtRepository + KnowledgeRepository> super::ToolHandlers<R> {
             created_at: Utc::now(),
             updated_at: Utc::now(),
             created_by: self.instance_id.clone(),
-            attributes: params.attributes.unwrap_or_default(),
-            tags: params.tags.unwrap_or_default(),
+            attributes,
+            tags,
             thought_ids: vec![],
             embedding: None,
             metadata: NodeMetadata {
@@ -160,6 +711,10 @@ impl<R: ThoughtRepository + KnowledgeRepository> super::ToolHandlers<R> {
                 extraction_source: None,
                 extraction_timestamp: None,
             },
+            relation_count: None,
+            score: None,
+            archived: false,
+            archived_at: None,
         };
 
         // Store in Redis
@@ -170,68 +725,8 @@ impl<R: ThoughtRepository + KnowledgeRepository> super::ToolHandlers<R> {
             .update_name_index(&name, &node.id, &scope)
             .await?;
 
-        // Embed-on-create (best-effort)
-        if let Ok(openai_key) = std::env::var("OPENAI_API_KEY") {
-            if !openai_key.is_empty() {
-                let config = Config::load();
-                let dims = config.openai.embedding_dimensions;
-                let index = format!("idx:{}:kg_entity", self.instance_id);
-                let prefix = format!("{}:embeddings:kg_entity:", self.instance_id);
-                let _ = ensure_index_hash_hnsw(
-                    &self.redis_manager,
-                    &index,
-                    &prefix,
-                    dims,
-                    config.redis_search.hnsw.m,
-                    config.redis_search.hnsw.ef_construction,
-                )
-                .await;
-
-                // Compact text for embedding
-                let mut text = node.display_name.clone();
-                if !node.tags.is_empty() {
-                    text.push_str(" | tags: ");
-                    text.push_str(&node.tags.join(", "));
-                }
-                if !node.attributes.is_empty() {
-                    if let Ok(snapshot) = serde_json::to_string(&node.attributes) {
-                        let snap = snapshot.chars().take(400).collect::<String>();
-                        text.push_str(" | attrs: ");
-                        text.push_str(&snap);
-                    }
-                }
-
-                if let Ok(embedding) =
-                    generate_openai_embedding(&text, &openai_key, &self.redis_manager).await
-                {
-                    if embedding.len() == dims {
-                        if let Ok(mut con) = self.redis_manager.get_connection().await {
-                            let key =
-                                format!("{}:embeddings:kg_entity:{}", self.instance_id, node.id);
-                            let vec_bytes: Vec<u8> = cast_slice(&embedding).to_vec();
-                            let ts = chrono::Utc::now().timestamp();
-                            let tags_csv = if node.tags.is_empty() {
-                                String::new()
-                            } else {
-                                node.tags.join(",")
-                            };
-                            let _: () = redis::pipe()
-                                .hset(&key, "content", &text)
-                                .hset(&key, "tags", tags_csv)
-                                .hset(&key, "category", "kg_entity")
-                                .hset(&key, "importance", "")
-                                .hset(&key, "chain_id", "")
-                                .hset(&key, "thought_id", "")
-                                .hset(&key, "ts", ts)
-                                .hset(&key, "vector", vec_bytes)
-                                .query_async(&mut *con)
-                                .await
-                                .unwrap_or(());
-                        }
-                    }
-                }
-            }
-        }
+        // Embed-on-create (best-effort); queued for the backfill when inline embedding is off
+        let warnings = self.refresh_entity_embeddings(&node, &Config::load()).await;
 
         Ok(KnowledgeResponse {
             status: "created".to_string(),
@@ -239,6 +734,13 @@ impl<R: ThoughtRepository + KnowledgeRepository> super::ToolHandlers<R> {
             entities: Some(vec![node]),
             relations: None,
             message: Some(format!("Entity '{name}' created successfully")),
+            total: None,
+            name_index_report: None,
+            graph: None,
+            near_entities: None,
+            explain: None,
+            warnings,
+            truncated: None,
         })
     }
 
@@ -252,21 +754,183 @@ impl<R: ThoughtRepository + KnowledgeRepository> super::ToolHandlers<R> {
                     reason: "query is required for search mode".to_string(),
                 })?;
         let scope = params.scope.unwrap_or_default();
-        let limit = params.limit.unwrap_or(10);
+        let config = Config::load();
+        let limit = config.server.clamp_limit(params.limit.unwrap_or(10));
+        let include_archived = params.include_archived.unwrap_or(false);
 
         tracing::info!("Searching for '{}' in {} scope", query, scope);
 
-        let entities = self
+        let search_type = match params.search_type.as_deref() {
+            Some(s) => s.parse::<EntitySearchType>().map_err(|reason| {
+                crate::error::UnifiedIntelligenceError::Validation {
+                    field: "search_type".to_string(),
+                    reason,
+                }
+            })?,
+            // A named vector implies semantic search
+            None if params.vector.is_some() => EntitySearchType::Semantic,
+            None => config.knowledge.search_type,
+        };
+
+        let (mut entities, total, truncated): (Vec<KnowledgeNode>, usize, bool) = match search_type
+        {
+            EntitySearchType::Semantic => {
+                let vector = match params.vector.as_deref() {
+                    Some(name) => name.parse().map_err(|reason| {
+                        crate::error::UnifiedIntelligenceError::Validation {
+                            field: "vector".to_string(),
+                            reason,
+                        }
+                    })?,
+                    None => config.knowledge.entity_vectors()[0],
+                };
+                let entities: Vec<KnowledgeNode> = self
+                    .search_entities_by_vector(
+                        &query,
+                        &scope,
+                        vector,
+                        params.entity_type.as_ref(),
+                        limit,
+                        include_archived,
+                        &config,
+                    )
+                    .await?
+                    .into_iter()
+                    .map(|(mut entity, distance)| {
+                        // Cosine distance -> similarity
+                        entity.score = distance.map(|d| 1.0 - d);
+                        entity
+                    })
+                    .collect();
+                let total = entities.len();
+                (entities, total, false)
+            }
+            // The repository ranks every match by text relevance before paging
+            EntitySearchType::Text => {
+                let (mut entities, total, truncated) = self
+                    .repository
+                    .search_entities(
+                        &query,
+                        &scope,
+                        params.entity_type.as_ref(),
+                        limit,
+                        include_archived,
+                    )
+                    .await?;
+                for entity in &mut entities {
+                    entity.score = Some(text_relevance(entity, &query));
+                }
+                (entities, total, truncated)
+            }
+        };
+        entities.truncate(limit);
+
+        if params.include_relation_counts.unwrap_or(false) && !entities.is_empty() {
+            let ids: Vec<String> = entities.iter().map(|e| e.id.clone()).collect();
+            let counts = self.repository.get_relation_counts(&ids, &scope).await?;
+            for (entity, count) in entities.iter_mut().zip(counts) {
+                entity.relation_count = Some(count);
+            }
+        }
+        let explain = params.explain.unwrap_or(false).then(|| {
+            entities
+                .iter()
+                .map(|entity| explain_match(entity, &query))
+                .collect()
+        });
+
+        Ok(KnowledgeResponse {
+            status: "success".to_string(),
+            entity_id: None,
+            message: Some(format!("Found {} entities", entities.len())),
+            entities: Some(entities),
+            relations: None,
+            total: Some(total),
+            name_index_report: None,
+            graph: None,
+            near_entities: None,
+            explain,
+            warnings: None,
+            truncated: truncated.then_some(true),
+        })
+    }
+
+    async fn entities_near_thought(&self, params: UiKnowledgeParams) -> Result<KnowledgeResponse> {
+        let thought_id = params.thought_id.ok_or_else(|| {
+            crate::error::UnifiedIntelligenceError::Validation {
+                field: "thought_id".to_string(),
+                reason: "thought_id is required for entities_near_thought mode".to_string(),
+            }
+        })?;
+        let vector: EntityVector = match params.vector.as_deref() {
+            Some(name) => name.parse().map_err(|reason| {
+                crate::error::UnifiedIntelligenceError::Validation {
+                    field: "vector".to_string(),
+                    reason,
+                }
+            })?,
+            None => EntityVector::Def,
+        };
+        let scope = params.scope.unwrap_or_default();
+        let config = Config::load();
+        let limit = config
+            .server
+            .clamp_limit(params.limit.unwrap_or(config.knowledge.near_thought_limit));
+
+        let thought = self
             .repository
-            .search_entities(&query, &scope, params.entity_type.as_ref(), limit)
+            .get_thought(&self.instance_id, &thought_id)
+            .await?
+            .ok_or_else(|| {
+                crate::error::UnifiedIntelligenceError::NotFound(format!(
+                    "Thought {thought_id} not found"
+                ))
+            })?;
+
+        tracing::info!(
+            "Finding {} entities near thought {} in {} scope",
+            vector.kind(),
+            thought_id,
+            scope
+        );
+
+        let hits = self
+            .search_entities_by_vector(
+                &thought.thought,
+                &scope,
+                vector,
+                params.entity_type.as_ref(),
+                limit,
+                false,
+                &config,
+            )
             .await?;
+        let (mut entities, mut near) = near_thought_matches(
+            hits,
+            &thought_id,
+            config.knowledge.near_thought_max_distance,
+        );
+        entities.truncate(limit);
+        near.truncate(limit);
 
         Ok(KnowledgeResponse {
             status: "success".to_string(),
             entity_id: None,
-            entities: Some(entities.clone()),
+            message: Some(format!(
+                "Found {} entities near thought {} ({} already linked)",
+                near.len(),
+                thought_id,
+                near.iter().filter(|m| m.linked).count()
+            )),
+            entities: Some(entities),
             relations: None,
-            message: Some(format!("Found {} entities", entities.len())),
+            total: None,
+            name_index_report: None,
+            graph: None,
+            near_entities: Some(near),
+            explain: None,
+            warnings: None,
+            truncated: None,
         })
     }
 
@@ -298,6 +962,81 @@ impl<R: ThoughtRepository + KnowledgeRepository> super::ToolHandlers<R> {
             entities: Some(vec![entity]),
             relations: None,
             message: Some("Entity set as active context".to_string()),
+            total: None,
+            name_index_report: None,
+            graph: None,
+            near_entities: None,
+            explain: None,
+            warnings: None,
+            truncated: None,
+        })
+    }
+
+    async fn get_active_entity(&self) -> Result<KnowledgeResponse> {
+        let session_key = format!("{}:KG:active_entity", self.instance_id);
+
+        let Some((entity_id, scope)) = self.repository.get_active_entity(&session_key).await?
+        else {
+            return Ok(KnowledgeResponse {
+                status: "none".to_string(),
+                entity_id: None,
+                entities: None,
+                relations: None,
+                message: Some("No active entity set".to_string()),
+                total: None,
+                name_index_report: None,
+                graph: None,
+                near_entities: None,
+                explain: None,
+                warnings: None,
+                truncated: None,
+            });
+        };
+
+        tracing::info!("Resolving active entity '{}' in {} scope", entity_id, scope);
+
+        let entity = self.repository.get_entity(&entity_id, &scope).await?;
+
+        Ok(KnowledgeResponse {
+            status: "active".to_string(),
+            entity_id: Some(entity.id.clone()),
+            entities: Some(vec![entity]),
+            relations: None,
+            message: Some("Active entity retrieved".to_string()),
+            total: None,
+            name_index_report: None,
+            graph: None,
+            near_entities: None,
+            explain: None,
+            warnings: None,
+            truncated: None,
+        })
+    }
+
+    async fn clear_active_entity(&self) -> Result<KnowledgeResponse> {
+        let session_key = format!("{}:KG:active_entity", self.instance_id);
+
+        tracing::info!("Clearing active entity for instance '{}'", self.instance_id);
+
+        let cleared = self.repository.clear_active_entity(&session_key).await?;
+
+        Ok(KnowledgeResponse {
+            status: "cleared".to_string(),
+            entity_id: None,
+            entities: None,
+            relations: None,
+            message: Some(if cleared {
+                "Active entity cleared".to_string()
+            } else {
+                "No active entity was set".to_string()
+            }),
+            total: None,
+            name_index_report: None,
+            graph: None,
+            near_entities: None,
+            explain: None,
+            warnings: None,
+            truncated: None,
         })
     }
 
@@ -322,6 +1061,13 @@ impl<R: ThoughtRepository + KnowledgeRepository> super::ToolHandlers<R> {
             entities: Some(vec![entity]),
             relations: None,
             message: Some("Entity retrieved successfully".to_string()),
+            total: None,
+            name_index_report: None,
+            graph: None,
+            near_entities: None,
+            explain: None,
+            warnings: None,
+            truncated: None,
         })
     }
 
@@ -379,6 +1125,14 @@ impl<R: ThoughtRepository + KnowledgeRepository> super::ToolHandlers<R> {
         };
 
         self.repository.create_relation(relation.clone()).await?;
+        self.refresh_relation_context(
+            &[
+                relation.from_entity_id.clone(),
+                relation.to_entity_id.clone(),
+            ],
+            &relation.scope,
+        )
+        .await;
 
         Ok(KnowledgeResponse {
             status: "created".to_string(),
@@ -386,6 +1140,13 @@ impl<R: ThoughtRepository + KnowledgeRepository> super::ToolHandlers<R> {
             entities: None,
             relations: Some(vec![relation]),
             message: Some("Relation created successfully".to_string()),
+            total: None,
+            name_index_report: None,
+            graph: None,
+            near_entities: None,
+            explain: None,
+            warnings: None,
+            truncated: None,
         })
     }
 
@@ -399,6 +1160,9 @@ impl<R: ThoughtRepository + KnowledgeRepository> super::ToolHandlers<R> {
                     reason: "entity_id is required for get_relations mode".to_string(),
                 })?;
         let scope = params.scope.unwrap_or_default();
+        let include_attributes = params
+            .include_attributes
+            .unwrap_or(Config::load().knowledge.relation_attributes);
 
         tracing::info!(
             "Getting relations for entity '{}' in {} scope",
@@ -406,7 +1170,10 @@ impl<R: ThoughtRepository + KnowledgeRepository> super::ToolHandlers<R> {
             scope
         );
 
-        let relations = self.repository.get_relations(&entity_id, &scope).await?;
+        let mut relations = self.repository.get_relations(&entity_id, &scope).await?;
+        if !include_attributes {
+            relations.iter_mut().for_each(|r| r.attributes.clear());
+        }
 
         Ok(KnowledgeResponse {
             status: "success".to_string(),
@@ -414,6 +1181,166 @@ impl<R: ThoughtRepository + KnowledgeRepository> super::ToolHandlers<R> {
             entities: None,
             relations: Some(relations.clone()),
             message: Some(format!("Found {} relations", relations.len())),
+            total: None,
+            name_index_report: None,
+            graph: None,
+            near_entities: None,
+            explain: None,
+            warnings: None,
+            truncated: None,
+        })
+    }
+
+    async fn subgraph(&self, params: UiKnowledgeParams) -> Result<KnowledgeResponse> {
+        let entity_id =
+            params
+                .entity_id
+                .ok_or_else(|| crate::error::UnifiedIntelligenceError::Validation {
+                    field: "entity_id".to_string(),
+                    reason: "entity_id is required for subgraph mode".to_string(),
+                })?;
+        let scope = params.scope.unwrap_or_default();
+        let config = Config::load();
+        let limits = &config.knowledge;
+        let requested_depth = params.depth.unwrap_or(1);
+        let max_nodes = limits.max_traversal_nodes.max(1);
+        let depth = traversal_depth(limits, requested_depth)?;
+
+        tracing::info!(
+            "Building subgraph around '{}' in {} scope (depth={})",
+            entity_id,
+            scope,
+            depth
+        );
+
+        let mut graph = collect_subgraph(
+            self.repository.as_ref(),
+            &entity_id,
+            &scope,
+            depth,
+            max_nodes,
+        )
+        .await?;
+        if graph.truncated && limits.traversal_limit_mode == TraversalLimitMode::Error {
+            return Err(too_many_nodes("subgraph", max_nodes));
+        }
+        graph.requested_depth = requested_depth;
+        graph.depth_clamped = depth < requested_depth;
+        if !params
+            .include_attributes
+            .unwrap_or(limits.relation_attributes)
+        {
+            graph.edges.iter_mut().for_each(|e| e.attributes.clear());
+        }
+
+        let mut notes = Vec::new();
+        if graph.depth_clamped {
+            notes.push(format!("depth clamped to {depth}"));
+        }
+        if graph.truncated {
+            notes.push(format!("truncated at {max_nodes} nodes"));
+        }
+        let message = format!(
+            "Subgraph with {} nodes and {} edges{}",
+            graph.nodes.len(),
+            graph.edges.len(),
+            if notes.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", notes.join(", "))
+            }
+        );
+
+        Ok(KnowledgeResponse {
+            status: "success".to_string(),
+            entity_id: Some(entity_id),
+            entities: None,
+            relations: None,
+            message: Some(message),
+            total: None,
+            name_index_report: None,
+            graph: Some(graph),
+            near_entities: None,
+            explain: None,
+            warnings: None,
+            truncated: None,
+        })
+    }
+
+    async fn traverse(&self, params: UiKnowledgeParams) -> Result<KnowledgeResponse> {
+        let entity_id =
+            params
+                .entity_id
+                .ok_or_else(|| crate::error::UnifiedIntelligenceError::Validation {
+                    field: "entity_id".to_string(),
+                    reason: "entity_id is required for traverse mode".to_string(),
+                })?;
+        let scope = params.scope.unwrap_or_default();
+        let config = Config::load();
+        let limits = &config.knowledge;
+        let requested_depth = params.depth.unwrap_or(1);
+        let max_nodes = limits.max_traversal_nodes.max(1);
+        let depth = traversal_depth(limits, requested_depth)?;
+        let include_attributes = params
+            .include_attributes
+            .unwrap_or(limits.relation_attributes);
+
+        tracing::info!(
+            "Traversing from '{}' in {} scope (depth={}, relationship_type={:?})",
+            entity_id,
+            scope,
+            depth,
+            params.relationship_type
+        );
+
+        let walk = walk_relations(
+            self.repository.as_ref(),
+            &entity_id,
+            &scope,
+            depth,
+            max_nodes,
+            params.relationship_type.as_deref(),
+        )
+        .await?;
+        if walk.truncated && limits.traversal_limit_mode == TraversalLimitMode::Error {
+            return Err(too_many_nodes("traverse", max_nodes));
+        }
+        let (entities, relations): (Vec<KnowledgeNode>, Vec<Vec<KnowledgeRelation>>) =
+            walk.reached.into_iter().map(|r| (r.node, r.via)).unzip();
+        let mut relations: Vec<KnowledgeRelation> = relations.into_iter().flatten().collect();
+        if !include_attributes {
+            relations.iter_mut().for_each(|r| r.attributes.clear());
+        }
+
+        let mut notes = Vec::new();
+        if depth < requested_depth {
+            notes.push(format!("depth clamped to {depth}"));
+        }
+        if walk.truncated {
+            notes.push(format!("truncated at {max_nodes} nodes"));
+        }
+        let notes = if notes.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", notes.join(", "))
+        };
+        Ok(KnowledgeResponse {
+            status: "success".to_string(),
+            entity_id: Some(entity_id),
+            message: Some(format!(
+                "Reached {} entities over {} relations within {depth} hops{notes}",
+                entities.len(),
+                relations.len()
+            )),
+            entities: Some(entities),
+            relations: Some(relations),
+            total: None,
+            name_index_report: None,
+            graph: None,
+            near_entities: None,
+            explain: None,
+            warnings: None,
+            truncated: None,
         })
     }
 
@@ -439,11 +1366,12 @@ impl<R: ThoughtRepository + KnowledgeRepository> super::ToolHandlers<R> {
         }
 
         if let Some(attributes) = params.attributes {
+            validate_attributes(&Config::load(), &entity.entity_type, &attributes)?;
             entity.attributes = attributes;
         }
 
         if let Some(tags) = params.tags {
-            entity.tags = tags;
+            entity.tags = self.validator.normalize_tags(&tags)?;
         }
 
         entity.updated_at = Utc::now();
@@ -451,67 +1379,10 @@ impl<R: ThoughtRepository + KnowledgeRepository> super::ToolHandlers<R> {
         // Save updated entity
         self.repository.update_entity(entity.clone()).await?;
 
-        // Embed-on-update (best-effort)
-        if let Ok(openai_key) = std::env::var("OPENAI_API_KEY") {
-            if !openai_key.is_empty() {
-                let config = Config::load();
-                let dims = config.openai.embedding_dimensions;
-                let index = format!("idx:{}:kg_entity", self.instance_id);
-                let prefix = format!("{}:embeddings:kg_entity:", self.instance_id);
-                let _ = ensure_index_hash_hnsw(
-                    &self.redis_manager,
-                    &index,
-                    &prefix,
-                    dims,
-                    config.redis_search.hnsw.m,
-                    config.redis_search.hnsw.ef_construction,
-                )
-                .await;
-
-                let mut text = entity.display_name.clone();
-                if !entity.tags.is_empty() {
-                    text.push_str(" | tags: ");
-                    text.push_str(&entity.tags.join(", "));
-                }
-                if !entity.attributes.is_empty() {
-                    if let Ok(snapshot) = serde_json::to_string(&entity.attributes) {
-                        let snap = snapshot.chars().take(400).collect::<String>();
-                        text.push_str(" | attrs: ");
-                        text.push_str(&snap);
-                    }
-                }
-
-                if let Ok(embedding) =
-                    generate_openai_embedding(&text, &openai_key, &self.redis_manager).await
-                {
-                    if embedding.len() == dims {
-                        if let Ok(mut con) = self.redis_manager.get_connection().await {
-                            let key =
-                                format!("{}:embeddings:kg_entity:{}", self.instance_id, entity.id);
-                            let vec_bytes: Vec<u8> = cast_slice(&embedding).to_vec();
-                            let ts = chrono::Utc::now().timestamp();
-                            let tags_csv = if entity.tags.is_empty() {
-                                String::new()
-                            } else {
-                                entity.tags.join(",")
-                            };
-                            let _: () = redis::pipe()
-                                .hset(&key, "content", &text)
-                                .hset(&key, "tags", tags_csv)
-                                .hset(&key, "category", "kg_entity")
-                                .hset(&key, "importance", "")
-                                .hset(&key, "chain_id", "")
-                                .hset(&key, "thought_id", "")
-                                .hset(&key, "ts", ts)
-                                .hset(&key, "vector", vec_bytes)
-                                .query_async(&mut *con)
-                                .await
-                                .unwrap_or(());
-                        }
-                    }
-                }
-            }
-        }
+        // Embed-on-update (best-effort); queued for the backfill when inline embedding is off
+        let warnings = self
+            .refresh_entity_embeddings(&entity, &Config::load())
+            .await;
 
         Ok(KnowledgeResponse {
             status: "updated".to_string(),
@@ -519,6 +1390,109 @@ impl<R: ThoughtRepository + KnowledgeRepository> super::ToolHandlers<R> {
             entities: Some(vec![entity]),
             relations: None,
             message: Some("Entity updated successfully".to_string()),
+            total: None,
+            name_index_report: None,
+            graph: None,
+            near_entities: None,
+            explain: None,
+            warnings,
+            truncated: None,
+        })
+    }
+
+    async fn delete_relation(&self, params: UiKnowledgeParams) -> Result<KnowledgeResponse> {
+        let relation_id = params.relation_id.ok_or_else(|| {
+            crate::error::UnifiedIntelligenceError::Validation {
+                field: "relation_id".to_string(),
+                reason: "relation_id is required for delete_relation mode".to_string(),
+            }
+        })?;
+        let scope = params.scope.unwrap_or_default();
+
+        tracing::info!("Deleting relation '{}' from {} scope", relation_id, scope);
+
+        let relation = self
+            .repository
+            .delete_relation(&relation_id, &scope)
+            .await?;
+        self.refresh_relation_context(
+            &[
+                relation.from_entity_id.clone(),
+                relation.to_entity_id.clone(),
+            ],
+            &scope,
+        )
+        .await;
+
+        Ok(KnowledgeResponse {
+            status: "deleted".to_string(),
+            entity_id: None,
+            entities: None,
+            message: Some(format!(
+                "Relation '{}' from {} to {} deleted",
+                relation.relationship_type, relation.from_entity_id, relation.to_entity_id
+            )),
+            relations: Some(vec![relation]),
+            total: None,
+            name_index_report: None,
+            graph: None,
+            near_entities: None,
+            explain: None,
+            warnings: None,
+            truncated: None,
+        })
+    }
+
+    async fn merge_entities(&self, params: UiKnowledgeParams) -> Result<KnowledgeResponse> {
+        let required = |value: Option<String>, field: &str| {
+            value.ok_or_else(|| crate::error::UnifiedIntelligenceError::Validation {
+                field: field.to_string(),
+                reason: format!("{field} is required for merge_entities mode"),
+            })
+        };
+        let from_id = required(params.from_entity_id, "from_entity_id")?;
+        let to_id = required(params.to_entity_id, "to_entity_id")?;
+        let scope = params.scope.unwrap_or_default();
+
+        tracing::info!(
+            "Merging entity '{}' into '{}' in {} scope",
+            from_id,
+            to_id,
+            scope
+        );
+
+        // Entities related to `from` now relate to `to` instead
+        let neighbours: Vec<String> = self
+            .repository
+            .get_relations(&from_id, &scope)
+            .await?
+            .into_iter()
+            .flat_map(|r| [r.from_entity_id, r.to_entity_id])
+            .filter(|id| *id != from_id && *id != to_id)
+            .collect();
+        let merged = self
+            .repository
+            .merge_entities(&from_id, &to_id, &scope)
+            .await?;
+        // The survivor gained fields and relations; `from`'s vectors went with it
+        let warnings = self
+            .refresh_entity_embeddings(&merged, &Config::load())
+            .await;
+        self.refresh_relation_context(&neighbours, &scope).await;
+
+        Ok(KnowledgeResponse {
+            status: "merged".to_string(),
+            entity_id: Some(merged.id.clone()),
+            message: Some(format!("Entity {} merged into '{}'", from_id, merged.name)),
+            entities: Some(vec![merged]),
+            relations: None,
+            total: None,
+            name_index_report: None,
+            graph: None,
+            near_entities: None,
+            explain: None,
+            warnings,
+            truncated: None,
         })
     }
 
@@ -547,6 +1521,526 @@ impl<R: ThoughtRepository + KnowledgeRepository> super::ToolHandlers<R> {
             entities: None,
             relations: None,
             message: Some(format!("Entity '{}' deleted successfully", entity.name)),
+            total: None,
+            name_index_report: None,
+            graph: None,
+            near_entities: None,
+            explain: None,
+            warnings: None,
+            truncated: None,
+        })
+    }
+
+    /// Archive (soft-delete) or restore an entity; relations and `thought_ids`
+    /// are kept either way
+    async fn set_archived(
+        &self,
+        params: UiKnowledgeParams,
+        archived: bool,
+    ) -> Result<KnowledgeResponse> {
+        let mode = if archived { "archive" } else { "unarchive" };
+        let entity_id =
+            params
+                .entity_id
+                .ok_or_else(|| crate::error::UnifiedIntelligenceError::Validation {
+                    field: "entity_id".to_string(),
+                    reason: format!("entity_id is required for {mode} mode"),
+                })?;
+        let scope = params.scope.unwrap_or_default();
+
+        tracing::info!("{} entity '{}' in {} scope", mode, entity_id, scope);
+
+        let entity = if archived {
+            self.repository.archive_entity(&entity_id, &scope).await?
+        } else {
+            self.repository.unarchive_entity(&entity_id, &scope).await?
+        };
+
+        Ok(KnowledgeResponse {
+            status: if archived { "archived" } else { "active" }.to_string(),
+            entity_id: Some(entity_id),
+            message: Some(format!(
+                "Entity '{}' {}",
+                entity.name,
+                if archived { "archived" } else { "restored" }
+            )),
+            entities: Some(vec![entity]),
+            relations: None,
+            total: None,
+            name_index_report: None,
+            graph: None,
+            near_entities: None,
+            explain: None,
+            warnings: None,
+            truncated: None,
         })
     }
+
+    async fn infer_relations(&self, params: UiKnowledgeParams) -> Result<KnowledgeResponse> {
+        // Validate required fields for infer_relations mode
+        let entity_id =
+            params
+                .entity_id
+                .ok_or_else(|| crate::error::UnifiedIntelligenceError::Validation {
+                    field: "entity_id".to_string(),
+                    reason: "entity_id is required for infer_relations mode".to_string(),
+                })?;
+        let scope = params.scope.unwrap_or_default();
+        let apply = params.apply.unwrap_or(false);
+        let config = Config::load();
+        let min_shared = config.knowledge.inference_min_cooccurrence.max(1);
+
+        tracing::info!(
+            "Inferring relations for entity '{}' in {} scope (apply={})",
+            entity_id,
+            scope,
+            apply
+        );
+
+        let entity = self.repository.get_entity(&entity_id, &scope).await?;
+        let own_thoughts: HashSet<&str> = entity.thought_ids.iter().map(String::as_str).collect();
+        if own_thoughts.is_empty() {
+            return Ok(KnowledgeResponse {
+                status: "success".to_string(),
+                entity_id: Some(entity_id),
+                entities: None,
+                relations: Some(Vec::new()),
+                message: Some("Entity has no linked thoughts to infer from".to_string()),
+                total: None,
+                name_index_report: None,
+                graph: None,
+                near_entities: None,
+                explain: None,
+                warnings: None,
+                truncated: None,
+            });
+        }
+
+        // Skip entities that are already related to this one
+        let already_related: HashSet<String> = self
+            .repository
+            .get_relations(&entity_id, &scope)
+            .await?
+            .into_iter()
+            .filter(|r| r.relationship_type == "related_to")
+            .map(|r| {
+                if r.from_entity_id == entity_id {
+                    r.to_entity_id
+                } else {
+                    r.from_entity_id
+                }
+            })
+            .collect();
+
+        // Count thoughts each candidate shares with the entity
+        let (candidates, scanned, scan_truncated) = self
+            .repository
+            .search_entities(
+                "",
+                &scope,
+                None,
+                config.knowledge.inference_max_entities,
+                false,
+            )
+            .await?;
+        // Entities past `inference_max_entities` (or the key scan cap) were never compared
+        let truncated = scan_truncated || scanned > candidates.len();
+        // Projected search leaves `thought_ids` empty; load the full documents
+        let candidates = if config.knowledge.projected_search {
+            let mut full = Vec::with_capacity(candidates.len());
+            for node in candidates {
+                if let Ok(node) = self.repository.get_entity(&node.id, &scope).await {
+                    full.push(node);
+                }
+            }
+            full
+        } else {
+            candidates
+        };
+        let mut co_mentioned: Vec<(KnowledgeNode, usize)> = candidates
+            .into_iter()
+            .filter(|n| n.id != entity.id && !already_related.contains(&n.id))
+            .filter_map(|n| {
+                let shared = n
+                    .thought_ids
+                    .iter()
+                    .map(String::as_str)
+                    .filter(|t| own_thoughts.contains(t))
+                    .collect::<HashSet<_>>()
+                    .len();
+                (shared >= min_shared).then_some((n, shared))
+            })
+            .collect();
+        co_mentioned.sort_by_key(|(_, shared)| std::cmp::Reverse(*shared));
+        let total = co_mentioned.len();
+        co_mentioned.truncate(
+            config
+                .server
+                .clamp_limit(params.limit.unwrap_or(config.server.max_page_size)),
+        );
+
+        let mut relations = Vec::with_capacity(co_mentioned.len());
+        let mut related = Vec::new();
+        for (node, shared) in co_mentioned {
+            let mut attributes = std::collections::HashMap::new();
+            attributes.insert("inferred".to_string(), serde_json::json!(true));
+            attributes.insert("co_occurrence".to_string(), serde_json::json!(shared));
+            let relation = KnowledgeRelation {
+                id: Uuid::new_v4().to_string(),
+                from_entity_id: entity.id.clone(),
+                to_entity_id: node.id,
+                relationship_type: "related_to".to_string(),
+                scope: scope.clone(),
+                created_at: Utc::now(),
+                created_by: self.instance_id.clone(),
+                attributes,
+                metadata: RelationMetadata {
+                    bidirectional: true,
+                    // Weight is the share of this entity's thoughts that mention the other
+                    weight: shared as f32 / own_thoughts.len() as f32,
+                },
+            };
+            if apply {
+                self.repository.create_relation(relation.clone()).await?;
+                related.push(relation.to_entity_id.clone());
+            }
+            relations.push(relation);
+        }
+        if !related.is_empty() {
+            related.push(entity.id.clone());
+            self.refresh_relation_context(&related, &scope).await;
+        }
+
+        if !params
+            .include_attributes
+            .unwrap_or(config.knowledge.relation_attributes)
+        {
+            relations.iter_mut().for_each(|r| r.attributes.clear());
+        }
+        let count = relations.len();
+        Ok(KnowledgeResponse {
+            status: if apply { "created" } else { "suggested" }.to_string(),
+            entity_id: Some(entity_id),
+            entities: None,
+            relations: Some(relations),
+            message: Some(if apply {
+                format!("Created {count} inferred relations")
+            } else {
+                format!("Suggested {count} relations; pass apply=true to create them")
+            }),
+            total: Some(total),
+            name_index_report: None,
+            graph: None,
+            near_entities: None,
+            explain: None,
+            warnings: None,
+            truncated: truncated.then_some(true),
+        })
+    }
+
+    /// Admin-only: diff the name index against entity keys, optionally repairing it
+    async fn repair_name_index(&self, params: UiKnowledgeParams) -> Result<KnowledgeResponse> {
+        let config = Config::load();
+        if !config.server.is_admin(params.admin_token.as_deref()) {
+            tracing::warn!("repair_name_index rejected: missing or invalid admin token");
+            return Err(crate::error::UnifiedIntelligenceError::Unauthorized);
+        }
+        let scope = params.scope.unwrap_or_default();
+        let apply = params.apply.unwrap_or(false);
+
+        tracing::info!("Checking name index in {} scope (apply={})", scope, apply);
+        let report = self.repository.repair_name_index(&scope, apply).await?;
+        let issues = report.missing.len() + report.stale.len() + report.orphaned.len();
+
+        Ok(KnowledgeResponse {
+            status: if apply { "repaired" } else { "checked" }.to_string(),
+            entity_id: None,
+            entities: None,
+            relations: None,
+            message: Some(if apply {
+                format!(
+                    "Repaired {issues} name index entries across {} entities",
+                    report.scanned_entities
+                )
+            } else {
+                format!("Found {issues} name index issues; pass apply=true to repair them")
+            }),
+            total: Some(issues),
+            name_index_report: Some(report),
+            graph: None,
+            near_entities: None,
+            explain: None,
+            warnings: None,
+            truncated: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::EntityType;
+    use crate::repository_traits::MockKnowledgeRepository;
+
+    fn node(id: &str) -> KnowledgeNode {
+        KnowledgeNode {
+            id: id.to_string(),
+            name: id.to_string(),
+            display_name: id.to_uppercase(),
+            entity_type: EntityType::Concept,
+            scope: KnowledgeScope::Federation,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            created_by: "test".to_string(),
+            attributes: Default::default(),
+            tags: vec![],
+            thought_ids: vec![],
+            embedding: None,
+            metadata: NodeMetadata {
+                auto_extracted: false,
+                extraction_source: None,
+                extraction_timestamp: None,
+            },
+            relation_count: None,
+            score: None,
+            archived: false,
+            archived_at: None,
+        }
+    }
+
+    fn relation(id: &str, from: &str, to: &str) -> KnowledgeRelation {
+        KnowledgeRelation {
+            id: id.to_string(),
+            from_entity_id: from.to_string(),
+            to_entity_id: to.to_string(),
+            relationship_type: "related_to".to_string(),
+            scope: KnowledgeScope::Federation,
+            created_at: Utc::now(),
+            created_by: "test".to_string(),
+            attributes: Default::default(),
+            metadata: RelationMetadata {
+                bidirectional: false,
+                weight: 1.0,
+            },
+        }
+    }
+
+    /// a -> b -> c, a -> d
+    fn mock_graph() -> MockKnowledgeRepository {
+        let mut repo = MockKnowledgeRepository::new();
+        repo.expect_get_entity()
+            .returning(|id, _| Box::pin(std::future::ready(Ok(node(id)))));
+        // The mock is generated ahead of async_trait, so it returns boxed futures
+        repo.expect_get_relations().returning(|id, _| {
+            let relations = match id {
+                "a" => vec![relation("r1", "a", "b"), relation("r3", "a", "d")],
+                "b" => vec![relation("r1", "a", "b"), relation("r2", "b", "c")],
+                "c" => vec![relation("r2", "b", "c")],
+                "d" => vec![relation("r3", "a", "d")],
+                _ => vec![],
+            };
+            Box::pin(std::future::ready(Ok(relations)))
+        });
+        repo
+    }
+
+    #[test]
+    fn test_near_thought_matches_filters_and_flags_links() {
+        let mut linked = node("a");
+        linked.thought_ids = vec!["t1".to_string()];
+        let hits = vec![
+            (linked, Some(0.1)),
+            (node("b"), Some(0.4)),
+            (node("c"), Some(0.9)),
+        ];
+
+        let (entities, near) = near_thought_matches(hits, "t1", Some(0.5));
+        assert_eq!(entities.len(), 2);
+        assert_eq!(near[0].entity_id, "a");
+        assert!(near[0].linked);
+        assert!(!near[1].linked);
+        assert!(near.iter().all(|m| m.distance <= 0.5));
+    }
+
+    #[test]
+    fn test_text_relevance_ranks_exact_prefix_substring_tag() {
+        let exact = node("redis");
+        let prefix = node("redisearch");
+        let substring = node("fast-redis-cache");
+        let mut tagged = node("cache");
+        tagged.tags = vec!["redis".to_string()];
+        let scores: Vec<f64> = [&exact, &prefix, &substring, &tagged]
+            .iter()
+            .map(|n| text_relevance(n, "Redis"))
+            .collect();
+        assert!(scores.windows(2).all(|w| w[0] > w[1]));
+        assert_eq!(text_relevance(&node("postgres"), "redis"), 0.0);
+    }
+
+    #[test]
+    fn test_explain_match_reports_terms_per_field() {
+        let mut entity = node("redis-cache");
+        entity.tags = vec!["storage".to_string()];
+        entity
+            .attributes
+            .insert("owner".to_string(), serde_json::json!("platform team"));
+        entity.score = Some(0.8);
+
+        let explain = explain_match(&entity, "Redis storage in the platform, x");
+        assert_eq!(explain.score, Some(0.8));
+        assert_eq!(explain.name_terms, vec!["redis"]);
+        assert_eq!(explain.tag_terms, vec!["storage"]);
+        assert_eq!(explain.attribute_terms, vec!["platform"]);
+    }
+
+    #[test]
+    fn test_set_archived_stamps_and_round_trips() {
+        let mut entity = node("a");
+        let now = Utc::now();
+        assert!(entity.set_archived(true, now));
+        assert!(!entity.set_archived(true, Utc::now()));
+        assert_eq!(entity.archived_at, Some(now));
+        assert_eq!(entity.updated_at, now);
+
+        let json = serde_json::to_value(&entity).expect("serializable");
+        assert_eq!(json["archived"], true);
+        assert!(entity.set_archived(false, Utc::now()));
+        assert_eq!(entity.archived_at, None);
+        let json = serde_json::to_value(&entity).expect("serializable");
+        assert!(json.get("archived").is_none() && json.get("archived_at").is_none());
+        let restored: KnowledgeNode = serde_json::from_value(json).expect("deserializable");
+        assert!(!restored.archived);
+    }
+
+    #[test]
+    fn test_merge_entity_fields_unions_and_keeps_target_attributes() {
+        use crate::repository::merge_entity_fields;
+
+        let mut to = node("sam");
+        to.tags = vec!["person".into()];
+        to.thought_ids = vec!["t1".into()];
+        to.attributes
+            .insert("role".into(), serde_json::json!("lead"));
+        let mut from = node("samuel");
+        from.tags = vec!["person".into(), "team".into()];
+        from.thought_ids = vec!["t2".into(), "t1".into()];
+        from.attributes
+            .insert("role".into(), serde_json::json!("dev"));
+        from.attributes
+            .insert("email".into(), serde_json::json!("s@x"));
+
+        let merged = merge_entity_fields(to, &from);
+        assert_eq!(merged.id, "sam");
+        assert_eq!(merged.tags, vec!["person", "team"]);
+        assert_eq!(merged.thought_ids, vec!["t1", "t2"]);
+        assert_eq!(merged.attributes["role"], "lead");
+        assert_eq!(merged.attributes["email"], "s@x");
+    }
+
+    #[test]
+    fn test_repoint_relation_drops_links_between_merged_pair() {
+        use crate::repository::repoint_relation;
+
+        let out = repoint_relation(relation("r1", "samuel", "x"), "samuel", "sam").unwrap();
+        assert_eq!(
+            (out.from_entity_id.as_str(), out.to_entity_id.as_str()),
+            ("sam", "x")
+        );
+        let inc = repoint_relation(relation("r2", "x", "samuel"), "samuel", "sam").unwrap();
+        assert_eq!(
+            (inc.from_entity_id.as_str(), inc.to_entity_id.as_str()),
+            ("x", "sam")
+        );
+        assert!(repoint_relation(relation("r3", "sam", "samuel"), "samuel", "sam").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_walk_relations_orders_by_distance_and_stops_on_cycles() {
+        let mut repo = MockKnowledgeRepository::new();
+        repo.expect_get_entity()
+            .returning(|id, _| Box::pin(std::future::ready(Ok(node(id)))));
+        // a -> b -> c -> a forms a cycle; a -> d is a different relationship type
+        repo.expect_get_relations().returning(|id, _| {
+            let mut other = relation("r4", "a", "d");
+            other.relationship_type = "owns".to_string();
+            let relations = match id {
+                "a" => vec![relation("r1", "a", "b"), relation("r3", "c", "a"), other],
+                "b" => vec![relation("r1", "a", "b"), relation("r2", "b", "c")],
+                "c" => vec![relation("r2", "b", "c"), relation("r3", "c", "a")],
+                _ => vec![],
+            };
+            Box::pin(std::future::ready(Ok(relations)))
+        });
+        let scope = KnowledgeScope::Federation;
+
+        let walk = walk_relations(&repo, "a", &scope, 10, 100, None)
+            .await
+            .unwrap();
+        let ids: Vec<&str> = walk.reached.iter().map(|r| r.node.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "c", "d"]);
+        assert!(walk.reached[0].via.is_empty());
+        assert_eq!(walk.reached[1].via[0].id, "r1");
+
+        let typed = walk_relations(&repo, "a", &scope, 10, 100, Some("owns"))
+            .await
+            .unwrap();
+        let ids: Vec<&str> = typed.reached.iter().map(|r| r.node.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "d"]);
+
+        let shallow = walk_relations(&repo, "b", &scope, 1, 100, None)
+            .await
+            .unwrap();
+        assert_eq!(shallow.reached.len(), 3);
+
+        let capped = walk_relations(&repo, "a", &scope, 10, 2, None)
+            .await
+            .unwrap();
+        assert_eq!(capped.reached.len(), 2);
+        assert!(capped.truncated);
+    }
+
+    #[tokio::test]
+    async fn test_entity_vector_text_phrases_relations_from_entity_side() {
+        let mut repo = mock_graph();
+        repo.expect_get_entity_fields().returning(|id, _, _| {
+            let fields = HashMap::from([(
+                "$.display_name".to_string(),
+                serde_json::json!(id.to_uppercase()),
+            )]);
+            Box::pin(std::future::ready(Ok(fields)))
+        });
+
+        let text = entity_vector_text(&repo, &node("b"), EntityVector::Context).await;
+        assert_eq!(text, "B | relations: A related_to this; related_to C");
+    }
+
+    #[tokio::test]
+    async fn test_collect_subgraph_depth_and_dedup() {
+        let repo = mock_graph();
+        let scope = KnowledgeScope::Federation;
+
+        let one_hop = collect_subgraph(&repo, "a", &scope, 1, 100).await.unwrap();
+        let ids: Vec<&str> = one_hop.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b", "d"]);
+        assert_eq!(one_hop.edges.len(), 2);
+        assert!(!one_hop.truncated);
+
+        let two_hops = collect_subgraph(&repo, "a", &scope, 2, 100).await.unwrap();
+        assert_eq!(two_hops.nodes.len(), 4);
+        assert_eq!(two_hops.edges.len(), 3);
+        assert_eq!(
+            two_hops.nodes.iter().find(|n| n.id == "c").unwrap().depth,
+            2
+        );
+
+        let capped = collect_subgraph(&repo, "a", &scope, 2, 2).await.unwrap();
+        assert_eq!(capped.nodes.len(), 2);
+        assert!(capped.truncated);
+        assert!(
+            capped
+                .edges
+                .iter()
+                .all(|e| e.source == "a" && e.target == "b")
+        );
+    }
 }
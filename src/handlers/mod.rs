@@ -7,6 +7,7 @@ pub mod thoughts;
 #[cfg(test)]
 mod test_handlers;
 
+use crate::embeddings::Embedder;
 use crate::redis::RedisManager;
 use crate::repository_traits::{KnowledgeRepository, ThoughtRepository};
 use crate::validation::InputValidator;
@@ -26,6 +27,8 @@ pub struct ToolHandlers<R: ThoughtRepository + KnowledgeRepository> {
     pub(crate) recall: RecallHandler<R>,
     pub(crate) help: HelpHandler,
     pub(crate) redis_manager: Arc<RedisManager>,
+    /// Configured embedding backend; `None` when embeddings are unavailable
+    pub(crate) embedder: Option<Arc<dyn Embedder>>,
 }
 
 impl<R: ThoughtRepository + KnowledgeRepository> ToolHandlers<R> {
@@ -34,6 +37,7 @@ impl<R: ThoughtRepository + KnowledgeRepository> ToolHandlers<R> {
         instance_id: String,
         validator: Arc<InputValidator>,
         redis_manager: Arc<RedisManager>,
+        embedder: Option<Arc<dyn Embedder>>,
     ) -> Self {
         Self {
             repository: repository.clone(),
@@ -43,6 +47,7 @@ impl<R: ThoughtRepository + KnowledgeRepository> ToolHandlers<R> {
             recall: RecallHandler::new(repository.clone(), instance_id.clone()),
             help: HelpHandler::new(instance_id.clone()),
             redis_manager,
+            embedder,
         }
     }
 }
@@ -1,3 +1,5 @@
+use crate::config::Config;
+use crate::models::{ThoughtRecord, ThoughtSearchFilter};
 use crate::repository_traits::{KnowledgeRepository, ThoughtRepository};
 use rmcp::model::{CallToolResult, Content, ErrorData};
 use schemars::JsonSchema;
@@ -7,9 +9,179 @@ use tracing::{info, warn};
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct UiRecallParams {
-    #[schemars(regex(pattern = r"^(thought|chain|help)$"))]
+    #[schemars(regex(pattern = r"^(thought|chain|export|search|delete|help)$"))]
     pub mode: String,
+    /// Thought or chain id (the thought to remove in delete mode); unused in
+    /// search mode
+    #[serde(default)]
     pub id: String,
+    /// Search mode: RediSearch full-text query over the instance's thoughts
+    /// (optional when a `min_*` filter is given)
+    #[serde(default)]
+    pub query: Option<String>,
+    /// Search mode: only thoughts with importance >= this; filtered results
+    /// are sorted by importance, highest first
+    #[serde(default)]
+    pub min_importance: Option<i32>,
+    /// Search mode: only thoughts with relevance >= this
+    #[serde(default)]
+    pub min_relevance: Option<i32>,
+    /// Chain mode: wrap the result as {thoughts, framework_summary}
+    #[serde(default)]
+    pub include_framework_summary: Option<bool>,
+    /// Chain mode: nest thoughts under their `parent_thought_id` (flat when the
+    /// chain has no branches)
+    #[serde(default)]
+    pub as_tree: Option<bool>,
+    /// Chain mode: only thoughts after the first `since` (the `tail` of an
+    /// earlier recall); the result becomes {thoughts, since, tail}
+    #[serde(default)]
+    pub since: Option<usize>,
+    /// Chain mode: first chain position to return; with `offset` or `limit`
+    /// the result becomes {thoughts, offset, total, next_offset}. Search
+    /// mode: number of matches to skip
+    #[serde(default)]
+    pub offset: Option<usize>,
+    /// Chain and search modes: page size (capped by `server.max_page_size`;
+    /// search defaults to 10)
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Export mode: chain position to continue from (the `cursor` of the
+    /// previous page); starts at 0
+    #[serde(default)]
+    pub cursor: Option<usize>,
+    /// Delete mode: must be true; deletion cannot be undone
+    #[serde(default)]
+    pub confirm: Option<bool>,
+    /// Delete mode: must match `server.admin_token`
+    #[serde(default)]
+    pub admin_token: Option<String>,
+}
+
+/// Search mode page size when `limit` is not given
+const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+/// Number of leading thoughts that fit in `max_bytes` of serialized JSON.
+/// Always at least one, so an oversized thought cannot stall an export.
+pub fn export_page_len(thoughts: &[ThoughtRecord], max_bytes: usize) -> usize {
+    let mut used = 0;
+    for (i, thought) in thoughts.iter().enumerate() {
+        let size = serde_json::to_vec(thought).map_or(0, |b| b.len()) + 1;
+        if i > 0 && used + size > max_bytes {
+            return i;
+        }
+        used += size;
+    }
+    thoughts.len()
+}
+
+/// Chain position an export resumes from after a page that read
+/// `cursor..cursor + page_size` and kept the first `kept` of the found
+/// thoughts at `positions`: right after the last kept one when the byte cap
+/// cut the page, else past the whole read range (ids with no thought included)
+pub fn export_next_cursor(
+    cursor: usize,
+    page_size: usize,
+    total: usize,
+    positions: &[usize],
+    kept: usize,
+) -> usize {
+    match positions.get(kept) {
+        Some(&dropped) => dropped,
+        None => (cursor + page_size).min(total).max(cursor),
+    }
+}
+
+/// Start of the page after `offset..offset + limit`; `None` once it reaches
+/// the end of a chain of `total` thoughts
+pub fn next_offset(offset: usize, limit: Option<usize>, total: usize) -> Option<usize> {
+    limit
+        .map(|n| offset + n.max(1))
+        .filter(|&next| next < total)
+}
+
+/// A thought with the thoughts that branch from it
+#[derive(Debug, Serialize)]
+pub struct ThoughtNode {
+    #[serde(flatten)]
+    pub thought: ThoughtRecord,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<ThoughtNode>,
+}
+
+/// Nest chain thoughts under their `parent_thought_id`, keeping chain order
+/// among siblings. Thoughts without a parent in the chain are roots, so a
+/// chain without branches serializes exactly like the flat list.
+pub fn build_thought_tree(thoughts: Vec<ThoughtRecord>) -> Vec<ThoughtNode> {
+    use std::collections::HashMap;
+
+    let index: HashMap<&str, usize> = thoughts
+        .iter()
+        .enumerate()
+        .map(|(i, t)| (t.id.as_str(), i))
+        .collect();
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); thoughts.len()];
+    let mut roots = Vec::new();
+    for (i, thought) in thoughts.iter().enumerate() {
+        match thought
+            .parent_thought_id
+            .as_deref()
+            .and_then(|p| index.get(p))
+        {
+            Some(&parent) if parent != i => children[parent].push(i),
+            _ => roots.push(i),
+        }
+    }
+
+    fn take(
+        i: usize,
+        slots: &mut [Option<ThoughtRecord>],
+        children: &[Vec<usize>],
+    ) -> Option<ThoughtNode> {
+        let thought = slots[i].take()?;
+        let children = children[i]
+            .iter()
+            .filter_map(|&c| take(c, slots, children))
+            .collect();
+        Some(ThoughtNode { thought, children })
+    }
+
+    let mut slots: Vec<Option<ThoughtRecord>> = thoughts.into_iter().map(Some).collect();
+    let mut tree: Vec<ThoughtNode> = roots
+        .into_iter()
+        .filter_map(|i| take(i, &mut slots, &children))
+        .collect();
+    // Parent cycles leave thoughts unreachable from any root; surface them as roots
+    for i in 0..slots.len() {
+        if let Some(node) = take(i, &mut slots, &children) {
+            tree.push(node);
+        }
+    }
+    tree
+}
+
+/// How often a framework was used across a chain
+#[derive(Debug, Serialize, PartialEq)]
+pub struct FrameworkUsage {
+    pub framework: String,
+    pub count: usize,
+}
+
+/// Distinct `framework` values with counts, in order of first use.
+/// Thoughts without a framework are counted under "none".
+pub fn summarize_frameworks(thoughts: &[ThoughtRecord]) -> Vec<FrameworkUsage> {
+    let mut summary: Vec<FrameworkUsage> = Vec::new();
+    for thought in thoughts {
+        let name = thought.framework.as_deref().unwrap_or("none");
+        match summary.iter_mut().find(|u| u.framework == name) {
+            Some(usage) => usage.count += 1,
+            None => summary.push(FrameworkUsage {
+                framework: name.to_string(),
+                count: 1,
+            }),
+        }
+    }
+    summary
 }
 
 pub struct RecallHandler<R: ThoughtRepository> {
@@ -25,6 +197,195 @@ impl<R: ThoughtRepository + KnowledgeRepository> RecallHandler<R> {
         }
     }
 
+    /// Reject chains owned by another instance when `chains.verify_instance` is set
+    async fn verify_chain(&self, config: &Config, chain_id: &str) -> Result<(), ErrorData> {
+        if !config.chains.verify_instance {
+            return Ok(());
+        }
+        match self
+            .repository
+            .chain_belongs_to(&self.instance_id, chain_id)
+            .await
+        {
+            Ok(true) => Ok(()),
+            Ok(false) => {
+                warn!(
+                    "Chain {} does not belong to instance {}",
+                    chain_id, self.instance_id
+                );
+                Err(ErrorData::resource_not_found(
+                    format!("Chain {chain_id} not found."),
+                    None,
+                ))
+            }
+            Err(e) => Err(ErrorData::internal_error(
+                format!("Error checking chain: {e}"),
+                None,
+            )),
+        }
+    }
+
+    /// One page of a chain export, bounded by `chains.export_page_size` thoughts
+    /// and `chains.export_max_bytes`, with the cursor for the next page
+    async fn export_chain(
+        &self,
+        chain_id: String,
+        cursor: usize,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let config = Config::load();
+        self.verify_chain(&config, &chain_id).await?;
+
+        let page_size = config.chains.export_page_size.max(1);
+        let (page, total) = self
+            .repository
+            .get_chain_page(&self.instance_id, &chain_id, cursor, Some(page_size))
+            .await
+            .map_err(|e| {
+                warn!("Error exporting chain {}: {}", chain_id, e);
+                ErrorData::internal_error(format!("Error exporting chain: {e}"), None)
+            })?;
+        let (positions, mut thoughts): (Vec<usize>, Vec<ThoughtRecord>) = page.into_iter().unzip();
+        thoughts.truncate(export_page_len(&thoughts, config.chains.export_max_bytes));
+        let next = export_next_cursor(cursor, page_size, total, &positions, thoughts.len());
+        let done = next >= total;
+        info!(
+            "Exported chain {} page at {}: {} thoughts of {}",
+            chain_id,
+            cursor,
+            thoughts.len(),
+            total
+        );
+
+        let payload = serde_json::json!({
+            "chain_id": chain_id,
+            "thoughts": thoughts,
+            "total": total,
+            "cursor": (!done).then_some(next),
+            "done": done,
+        });
+        let content = Content::json(payload).map_err(|e| {
+            ErrorData::internal_error(format!("Failed to serialize chain export: {e}"), None)
+        })?;
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    /// One page of a full-text search over the instance's thoughts, with the
+    /// total match count for pagination
+    async fn search(
+        &self,
+        query: Option<String>,
+        filter: ThoughtSearchFilter,
+        offset: usize,
+        limit: Option<usize>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let query = query.filter(|q| !q.trim().is_empty()).unwrap_or_default();
+        if query.is_empty() && filter.is_empty() {
+            return Err(ErrorData::invalid_params(
+                "search mode requires a non-empty query or a min_importance/min_relevance filter",
+                None,
+            ));
+        }
+        let config = Config::load();
+        let limit = config
+            .server
+            .clamp_limit(limit.unwrap_or(DEFAULT_SEARCH_LIMIT))
+            .max(1);
+        let (thoughts, total) = self
+            .repository
+            .search_thoughts(
+                &self.instance_id,
+                &query,
+                filter,
+                offset as i64,
+                limit as i64,
+            )
+            .await
+            .map_err(|e| {
+                warn!("Error searching thoughts for '{}': {}", query, e);
+                ErrorData::internal_error(format!("Error searching thoughts: {e}"), None)
+            })?;
+        info!(
+            "Search '{}' matched {} thoughts, returning {} from {}",
+            query,
+            total,
+            thoughts.len(),
+            offset
+        );
+        if config.usage.track_access {
+            self.repository.track_access(
+                &self.instance_id,
+                thoughts.iter().map(|t| t.id.clone()).collect(),
+            );
+        }
+
+        let mut payload = serde_json::json!({
+            "query": query,
+            "thoughts": thoughts,
+            "offset": offset,
+            "total": total,
+        });
+        if let Some(min) = filter.min_importance {
+            payload["min_importance"] = serde_json::json!(min);
+        }
+        if let Some(min) = filter.min_relevance {
+            payload["min_relevance"] = serde_json::json!(min);
+        }
+        if let Some(next) = next_offset(offset, Some(limit), total) {
+            payload["next_offset"] = serde_json::json!(next);
+        }
+        let content = Content::json(payload).map_err(|e| {
+            ErrorData::internal_error(format!("Failed to serialize search results: {e}"), None)
+        })?;
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    /// Admin-only: permanently remove one thought; requires `confirm: true`
+    async fn delete(
+        &self,
+        thought_id: String,
+        confirm: bool,
+        admin_token: Option<&str>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        if !Config::load().server.is_admin(admin_token) {
+            warn!("ui_recall delete rejected: missing or invalid admin token");
+            return Err(ErrorData::invalid_request(
+                "delete mode requires a valid admin_token".to_string(),
+                None,
+            ));
+        }
+        if !confirm {
+            return Err(ErrorData::invalid_params(
+                "delete mode permanently removes the thought; pass confirm: true",
+                None,
+            ));
+        }
+        let deleted = self
+            .repository
+            .delete_thought(&self.instance_id, &thought_id)
+            .await
+            .map_err(|e| {
+                warn!("Error deleting thought {}: {}", thought_id, e);
+                ErrorData::internal_error(format!("Error deleting thought: {e}"), None)
+            })?;
+        let Some(thought) = deleted else {
+            return Err(ErrorData::invalid_params(
+                format!("Thought with ID {thought_id} not found."),
+                None,
+            ));
+        };
+        info!("Deleted thought: {}", thought_id);
+
+        let content = Content::json(serde_json::json!({
+            "status": "deleted",
+            "thought_id": thought.id,
+            "chain_id": thought.chain_id,
+        }))
+        .map_err(|e| {
+            ErrorData::internal_error(format!("Failed to serialize delete result: {e}"), None)
+        })?;
+        Ok(CallToolResult::success(vec![content]))
+    }
+
     pub async fn recall(
         &self,
         params: UiRecallParams,
@@ -41,6 +402,10 @@ impl<R: ThoughtRepository + KnowledgeRepository> RecallHandler<R> {
                 {
                     Ok(Some(thought)) => {
                         info!("Successfully recalled thought: {}", thought_id);
+                        if Config::load().usage.track_access {
+                            self.repository
+                                .track_access(&self.instance_id, vec![thought.id.clone()]);
+                        }
                         let content = Content::json(thought).map_err(|e| {
                             ErrorData::internal_error(
                                 format!("Failed to serialize thought: {e}"),
@@ -68,18 +433,79 @@ impl<R: ThoughtRepository + KnowledgeRepository> RecallHandler<R> {
             "chain" => {
                 let chain_id = params.id;
 
-                match self
-                    .repository
-                    .get_chain_thoughts(&self.instance_id, &chain_id)
-                    .await
-                {
-                    Ok(thoughts) => {
+                let config = Config::load();
+                self.verify_chain(&config, &chain_id).await?;
+
+                let paged = params.offset.is_some() || params.limit.is_some();
+                if paged && params.since.is_some() {
+                    return Err(ErrorData::invalid_params(
+                        "since cannot be combined with offset/limit",
+                        None,
+                    ));
+                }
+                let offset = params.offset.unwrap_or(0);
+                let limit = params.limit.map(|n| config.server.clamp_limit(n));
+
+                let fetched = match params.since {
+                    None if paged => self
+                        .repository
+                        .get_chain_thoughts_since(&self.instance_id, &chain_id, offset, limit)
+                        .await
+                        .map(|(thoughts, total)| (thoughts, Some(total))),
+                    Some(since) => self
+                        .repository
+                        .get_chain_thoughts_since(&self.instance_id, &chain_id, since, None)
+                        .await
+                        .map(|(thoughts, tail)| (thoughts, Some(tail))),
+                    None => self
+                        .repository
+                        .get_chain_thoughts(&self.instance_id, &chain_id)
+                        .await
+                        .map(|thoughts| (thoughts, None)),
+                };
+                match fetched {
+                    Ok((thoughts, tail)) => {
                         info!(
                             "Successfully recalled chain {}: {} thoughts",
                             chain_id,
                             thoughts.len()
                         );
-                        let content = Content::json(thoughts).map_err(|e| {
+                        if config.usage.track_access {
+                            self.repository.track_access(
+                                &self.instance_id,
+                                thoughts.iter().map(|t| t.id.clone()).collect(),
+                            );
+                        }
+                        let framework_summary = params
+                            .include_framework_summary
+                            .unwrap_or(false)
+                            .then(|| summarize_frameworks(&thoughts));
+                        let thoughts = if params.as_tree.unwrap_or(false) {
+                            serde_json::json!(build_thought_tree(thoughts))
+                        } else {
+                            serde_json::json!(thoughts)
+                        };
+                        let payload = if framework_summary.is_none() && tail.is_none() {
+                            thoughts
+                        } else {
+                            let mut payload = serde_json::json!({ "thoughts": thoughts });
+                            if let Some(framework_summary) = framework_summary {
+                                payload["framework_summary"] = serde_json::json!(framework_summary);
+                            }
+                            if let Some(total) = tail.filter(|_| paged) {
+                                payload["offset"] = serde_json::json!(offset);
+                                payload["total"] = serde_json::json!(total);
+                                if let Some(next) = next_offset(offset, limit, total) {
+                                    payload["next_offset"] = serde_json::json!(next);
+                                }
+                            } else if let Some(tail) = tail {
+                                // A tail below `since` means the chain was pruned; refetch it
+                                payload["since"] = serde_json::json!(params.since);
+                                payload["tail"] = serde_json::json!(tail);
+                            }
+                            payload
+                        };
+                        let content = Content::json(payload).map_err(|e| {
                             ErrorData::internal_error(
                                 format!("Failed to serialize chain thoughts: {e}"),
                                 None,
@@ -96,12 +522,37 @@ impl<R: ThoughtRepository + KnowledgeRepository> RecallHandler<R> {
                     }
                 }
             }
+            "export" => {
+                self.export_chain(params.id, params.cursor.unwrap_or(0))
+                    .await
+            }
+            "search" => {
+                let filter = ThoughtSearchFilter {
+                    min_importance: params.min_importance,
+                    min_relevance: params.min_relevance,
+                };
+                self.search(
+                    params.query,
+                    filter,
+                    params.offset.unwrap_or(0),
+                    params.limit,
+                )
+                .await
+            }
+            "delete" => {
+                self.delete(
+                    params.id,
+                    params.confirm.unwrap_or(false),
+                    params.admin_token.as_deref(),
+                )
+                .await
+            }
             _ => {
                 // This should never happen due to regex validation, but we handle it gracefully
                 warn!("Invalid recall mode: {}", params.mode);
                 Err(ErrorData::invalid_params(
                     format!(
-                        "Invalid recall mode '{}'. Must be 'thought' or 'chain'.",
+                        "Invalid recall mode '{}'. Must be 'thought', 'chain', 'export', 'search' or 'delete'.",
                         params.mode
                     ),
                     None,
@@ -110,3 +561,112 @@ impl<R: ThoughtRepository + KnowledgeRepository> RecallHandler<R> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thought(framework: Option<&str>) -> ThoughtRecord {
+        ThoughtRecord::new(
+            "test".into(),
+            "t".into(),
+            1,
+            1,
+            Some("chain".into()),
+            false,
+            framework.map(String::from),
+            None,
+            None,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_build_thought_tree_nests_branches() {
+        let root = thought(None);
+        let mut a = thought(None);
+        a.parent_thought_id = Some(root.id.clone());
+        let mut b = thought(None);
+        b.parent_thought_id = Some(root.id.clone());
+        let mut a1 = thought(None);
+        a1.parent_thought_id = Some(a.id.clone());
+        let (root_id, a_id, b_id, a1_id) =
+            (root.id.clone(), a.id.clone(), b.id.clone(), a1.id.clone());
+
+        let tree = build_thought_tree(vec![root, a, b, a1]);
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree[0].thought.id, root_id);
+        let branches: Vec<&str> = tree[0]
+            .children
+            .iter()
+            .map(|n| n.thought.id.as_str())
+            .collect();
+        assert_eq!(branches, vec![a_id.as_str(), b_id.as_str()]);
+        assert_eq!(tree[0].children[0].children[0].thought.id, a1_id);
+
+        // No parents: every thought is a childless root, i.e. the flat list
+        let flat = vec![thought(None), thought(None)];
+        let expected = serde_json::json!(flat);
+        assert_eq!(serde_json::json!(build_thought_tree(flat)), expected);
+    }
+
+    #[test]
+    fn test_next_offset_stops_at_chain_end() {
+        assert_eq!(next_offset(0, Some(10), 25), Some(10));
+        assert_eq!(next_offset(20, Some(10), 25), None);
+        assert_eq!(next_offset(15, Some(10), 25), None);
+        assert_eq!(next_offset(0, Some(0), 25), Some(1));
+        assert_eq!(next_offset(5, None, 25), None);
+    }
+
+    #[test]
+    fn test_export_page_len_respects_byte_cap() {
+        let thoughts = vec![thought(None), thought(None), thought(None)];
+        let one = serde_json::to_vec(&thoughts[0]).unwrap().len() + 1;
+        assert_eq!(export_page_len(&thoughts, usize::MAX), 3);
+        assert_eq!(export_page_len(&thoughts, one * 2), 2);
+        // An oversized first thought is still exported on its own
+        assert_eq!(export_page_len(&thoughts, 1), 1);
+        assert_eq!(export_page_len(&[], 1), 0);
+    }
+
+    #[test]
+    fn test_export_next_cursor_advances_by_chain_position() {
+        // Positions 11 and 13 had no thought; the byte cap kept two of four
+        let positions = [10, 12, 14, 15];
+        assert_eq!(export_next_cursor(10, 6, 100, &positions, 2), 14);
+        // Nothing cut: skip the whole read range, missing ids included
+        assert_eq!(export_next_cursor(10, 6, 100, &positions, 4), 16);
+        assert_eq!(export_next_cursor(10, 6, 13, &[10, 12], 2), 13);
+        // A range of only missing ids still advances
+        assert_eq!(export_next_cursor(10, 6, 100, &[], 0), 16);
+    }
+
+    #[test]
+    fn test_summarize_frameworks_counts_in_first_use_order() {
+        let thoughts = vec![
+            thought(Some("debug")),
+            thought(None),
+            thought(Some("review")),
+            thought(Some("debug")),
+        ];
+        assert_eq!(
+            summarize_frameworks(&thoughts),
+            vec![
+                FrameworkUsage {
+                    framework: "debug".into(),
+                    count: 2
+                },
+                FrameworkUsage {
+                    framework: "none".into(),
+                    count: 1
+                },
+                FrameworkUsage {
+                    framework: "review".into(),
+                    count: 1
+                },
+            ]
+        );
+    }
+}
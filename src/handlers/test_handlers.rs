@@ -16,6 +16,37 @@ impl ThoughtRepository for MockCombinedMockRepository {
     ) -> crate::error::Result<()> {
         unimplemented!()
     }
+    async fn save_thought_with_options(
+        &self,
+        _thought: &crate::models::ThoughtRecord,
+        _skip_event: bool,
+    ) -> crate::error::Result<()> {
+        unimplemented!()
+    }
+    async fn load_stuck_tracker(
+        &self,
+        _instance: &str,
+        _chain_id: &str,
+    ) -> crate::error::Result<Option<crate::frameworks::StuckTracker>> {
+        unimplemented!()
+    }
+    async fn save_stuck_tracker(
+        &self,
+        _instance: &str,
+        _tracker: &crate::frameworks::StuckTracker,
+    ) -> crate::error::Result<()> {
+        unimplemented!()
+    }
+    async fn advance_stuck_tracker(
+        &self,
+        _instance: &str,
+        _chain_id: &str,
+    ) -> crate::error::Result<(
+        crate::frameworks::ThinkingMode,
+        crate::frameworks::StuckTracker,
+    )> {
+        unimplemented!()
+    }
     async fn save_chain_metadata(
         &self,
         _metadata: &crate::models::ChainMetadata,
@@ -25,6 +56,22 @@ impl ThoughtRepository for MockCombinedMockRepository {
     async fn chain_exists(&self, _chain_id: &str) -> crate::error::Result<bool> {
         unimplemented!()
     }
+    async fn prune_chains(
+        &self,
+        _instance: &str,
+        _max_thoughts: usize,
+        _idle_since: chrono::DateTime<chrono::Utc>,
+        _apply: bool,
+    ) -> crate::error::Result<crate::models::ChainPruneReport> {
+        unimplemented!()
+    }
+    async fn chain_belongs_to(
+        &self,
+        _instance: &str,
+        _chain_id: &str,
+    ) -> crate::error::Result<bool> {
+        unimplemented!()
+    }
     async fn get_thought(
         &self,
         _instance: &str,
@@ -32,6 +79,13 @@ impl ThoughtRepository for MockCombinedMockRepository {
     ) -> crate::error::Result<Option<crate::models::ThoughtRecord>> {
         unimplemented!()
     }
+    async fn delete_thought(
+        &self,
+        _instance: &str,
+        _thought_id: &str,
+    ) -> crate::error::Result<Option<crate::models::ThoughtRecord>> {
+        unimplemented!()
+    }
     async fn get_chain_thoughts(
         &self,
         _instance: &str,
@@ -39,13 +93,33 @@ impl ThoughtRepository for MockCombinedMockRepository {
     ) -> crate::error::Result<Vec<crate::models::ThoughtRecord>> {
         unimplemented!()
     }
+    fn track_access(&self, _instance: &str, _thought_ids: Vec<String>) {}
+    async fn get_chain_thoughts_since(
+        &self,
+        _instance: &str,
+        _chain_id: &str,
+        _start: usize,
+        _limit: Option<usize>,
+    ) -> crate::error::Result<(Vec<crate::models::ThoughtRecord>, usize)> {
+        unimplemented!()
+    }
+    async fn get_chain_page(
+        &self,
+        _instance: &str,
+        _chain_id: &str,
+        _start: usize,
+        _limit: Option<usize>,
+    ) -> crate::error::Result<(Vec<(usize, crate::models::ThoughtRecord)>, usize)> {
+        unimplemented!()
+    }
     async fn search_thoughts(
         &self,
         _instance: &str,
         _query: &str,
+        _filter: crate::models::ThoughtSearchFilter,
         _offset: i64,
         _limit: i64,
-    ) -> crate::error::Result<Vec<crate::models::ThoughtRecord>> {
+    ) -> crate::error::Result<(Vec<crate::models::ThoughtRecord>, usize)> {
         unimplemented!()
     }
 }
@@ -56,6 +130,14 @@ impl KnowledgeRepository for MockCombinedMockRepository {
     async fn create_entity(&self, _node: crate::models::KnowledgeNode) -> crate::error::Result<()> {
         unimplemented!()
     }
+    async fn get_entity_fields(
+        &self,
+        _id: &str,
+        _scope: &crate::models::KnowledgeScope,
+        _paths: &[&str],
+    ) -> crate::error::Result<std::collections::HashMap<String, serde_json::Value>> {
+        unimplemented!()
+    }
     async fn get_entity(
         &self,
         _id: &str,
@@ -80,13 +162,28 @@ impl KnowledgeRepository for MockCombinedMockRepository {
     ) -> crate::error::Result<()> {
         unimplemented!()
     }
+    async fn archive_entity(
+        &self,
+        _id: &str,
+        _scope: &crate::models::KnowledgeScope,
+    ) -> crate::error::Result<crate::models::KnowledgeNode> {
+        unimplemented!()
+    }
+    async fn unarchive_entity(
+        &self,
+        _id: &str,
+        _scope: &crate::models::KnowledgeScope,
+    ) -> crate::error::Result<crate::models::KnowledgeNode> {
+        unimplemented!()
+    }
     async fn search_entities(
         &self,
         _query: &str,
         _scope: &crate::models::KnowledgeScope,
         _entity_type: Option<&crate::models::EntityType>,
         _limit: usize,
-    ) -> crate::error::Result<Vec<crate::models::KnowledgeNode>> {
+        _include_archived: bool,
+    ) -> crate::error::Result<(Vec<crate::models::KnowledgeNode>, usize, bool)> {
         unimplemented!()
     }
     async fn create_relation(
@@ -118,6 +215,44 @@ impl KnowledgeRepository for MockCombinedMockRepository {
     ) -> crate::error::Result<()> {
         unimplemented!()
     }
+    async fn get_active_entity(
+        &self,
+        _session_key: &str,
+    ) -> crate::error::Result<Option<(String, crate::models::KnowledgeScope)>> {
+        unimplemented!()
+    }
+    async fn clear_active_entity(&self, _session_key: &str) -> crate::error::Result<bool> {
+        unimplemented!()
+    }
+    async fn delete_relation(
+        &self,
+        _relation_id: &str,
+        _scope: &crate::models::KnowledgeScope,
+    ) -> crate::error::Result<crate::models::KnowledgeRelation> {
+        unimplemented!()
+    }
+    async fn merge_entities(
+        &self,
+        _from_id: &str,
+        _to_id: &str,
+        _scope: &crate::models::KnowledgeScope,
+    ) -> crate::error::Result<crate::models::KnowledgeNode> {
+        unimplemented!()
+    }
+    async fn repair_name_index(
+        &self,
+        _scope: &crate::models::KnowledgeScope,
+        _apply: bool,
+    ) -> crate::error::Result<crate::models::NameIndexReport> {
+        unimplemented!()
+    }
+    async fn get_relation_counts(
+        &self,
+        _entity_ids: &[String],
+        _scope: &crate::models::KnowledgeScope,
+    ) -> crate::error::Result<Vec<usize>> {
+        unimplemented!()
+    }
     async fn add_thought_to_entity(
         &self,
         _entity_name: &str,
@@ -155,6 +290,7 @@ async fn create_test_handler() -> Option<ToolHandlers<MockCombinedMockRepository
         "test".to_string(),
         validator,
         redis_manager,
+        None,
     ))
 }
 
@@ -1,12 +1,15 @@
-use crate::config::Config;
-use crate::embeddings::generate_openai_embedding;
+use std::collections::HashMap;
+
+use crate::categorize::GroqCategorizer;
+use crate::config::{Config, SentimentMode};
+use crate::embeddings::generate_embedding;
 use crate::error::Result;
-use crate::frameworks::{
-    FrameworkProcessor, FrameworkVisual, StuckTracker, ThinkingMode, WorkflowState,
+use crate::frameworks::{FrameworkProcessor, FrameworkVisual, ThinkingMode, WorkflowState};
+use crate::models::{
+    ChainMetadata, ChainSynthesis, QueryIntent, ThinkResponse, ThoughtRecord, UiThinkParams,
 };
-use crate::models::{ChainMetadata, ThinkResponse, ThoughtRecord, UiThinkParams};
 use crate::repository_traits::{KnowledgeRepository, ThoughtRepository};
-use bytemuck::cast_slice;
+use crate::sentiment::GroqSentiment;
 
 /// Trait for thought-related operations
 pub trait ThoughtsHandler {
@@ -14,14 +17,15 @@ pub trait ThoughtsHandler {
     async fn ui_think(&self, params: UiThinkParams) -> Result<ThinkResponse>;
 }
 
-// Local helper to ensure an HNSW RediSearch index exists for HASH prefixes
-async fn ensure_index_hash_hnsw(
+// Helper to ensure an HNSW RediSearch index exists for HASH prefixes
+pub(crate) async fn ensure_index_hash_hnsw(
     redis_manager: &crate::redis::RedisManager,
     index: &str,
     prefix: &str,
     dims: usize,
     m: u32,
     ef_construction: u32,
+    vector_type: crate::config::VectorType,
 ) -> std::result::Result<bool, redis::RedisError> {
     let mut con = redis_manager.get_connection().await.map_err(|e| match e {
         crate::error::UnifiedIntelligenceError::Redis(e) => e,
@@ -61,12 +65,15 @@ async fn ensure_index_hash_hnsw(
         .arg("ts")
         .arg("NUMERIC")
         .arg("SORTABLE")
+        .arg("sentiment")
+        .arg("NUMERIC")
+        .arg("SORTABLE")
         .arg("vector")
         .arg("VECTOR")
         .arg("HNSW")
         .arg("6")
         .arg("TYPE")
-        .arg("FLOAT32")
+        .arg(vector_type.index_type())
         .arg("DIM")
         .arg(dims)
         .arg("DISTANCE_METRIC")
@@ -81,36 +88,189 @@ async fn ensure_index_hash_hnsw(
     create_res.map(|_| true)
 }
 
+/// Classify a thought via Groq; any failure leaves it uncategorized
+async fn auto_categorize(config: &Config, thought: &str) -> Option<String> {
+    let tx = match crate::circuit_breaker::groq_transport(config) {
+        Ok(tx) => tx,
+        Err(e) => {
+            tracing::warn!("auto_categorize: transport init failed: {}", e);
+            return None;
+        }
+    };
+    let categorizer = GroqCategorizer::new(tx, config.groq.model_fast.clone());
+    match categorizer.categorize(thought).await {
+        Ok(category) => category,
+        Err(e) => {
+            tracing::warn!("auto_categorize failed: {}", e);
+            None
+        }
+    }
+}
+
+/// Sentiment per `ui_think.sentiment`; an LLM failure falls back to the lexicon
+async fn score_sentiment(config: &Config, thought: &str) -> Option<f32> {
+    match config.ui_think.sentiment {
+        SentimentMode::Off => None,
+        SentimentMode::Lexicon => Some(crate::sentiment::lexicon_score(thought)),
+        SentimentMode::Llm => {
+            let scored = match crate::circuit_breaker::groq_transport(config) {
+                Ok(tx) => {
+                    GroqSentiment::new(tx, config.groq.model_fast.clone())
+                        .score(thought)
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+            match scored {
+                Ok(Some(score)) => Some(score),
+                Ok(None) => Some(crate::sentiment::lexicon_score(thought)),
+                Err(e) => {
+                    tracing::warn!("sentiment scoring failed, using lexicon: {}", e);
+                    Some(crate::sentiment::lexicon_score(thought))
+                }
+            }
+        }
+    }
+}
+
+/// Most frequent workflow state among chain thoughts; ties go to the state
+/// used most recently, and an empty chain yields `fallback`
+fn dominant_state(records: &[ThoughtRecord], fallback: WorkflowState) -> WorkflowState {
+    let mut counts: HashMap<WorkflowState, (usize, usize)> = HashMap::new();
+    for (idx, record) in records.iter().enumerate() {
+        let state = record
+            .framework
+            .as_deref()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_default();
+        let entry = counts.entry(state).or_insert((0, idx));
+        entry.0 += 1;
+        entry.1 = idx;
+    }
+    counts
+        .into_iter()
+        .max_by_key(|(_, rank)| *rank)
+        .map(|(state, _)| state)
+        .unwrap_or(fallback)
+}
+
+/// Summarize a chain with the synthesis profile of its dominant workflow state
+async fn synthesize_chain<R: ThoughtRepository + ?Sized>(
+    config: &Config,
+    repository: &R,
+    instance: &str,
+    chain_id: &str,
+    current: WorkflowState,
+) -> Result<ChainSynthesis> {
+    use crate::synth::Synthesizer;
+
+    let mut records = repository.get_chain_thoughts(instance, chain_id).await?;
+    let state = dominant_state(&records, current).to_string();
+    let profile = config.ui_think.profile_for(&state);
+    let limit = profile
+        .top_k
+        .map(|k| k as usize)
+        .unwrap_or(config.ui_think.synthesis_max_thoughts)
+        .max(1);
+    if records.len() > limit {
+        records.drain(..records.len() - limit);
+    }
+    let ctx: Vec<crate::models::Thought> = records
+        .iter()
+        .map(|r| {
+            let ts = chrono::DateTime::parse_from_rfc3339(&r.timestamp)
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .unwrap_or_else(|_| chrono::Utc::now());
+            crate::models::Thought {
+                id: uuid::Uuid::parse_str(&r.id).unwrap_or_else(|_| uuid::Uuid::new_v4()),
+                content: r.content.clone(),
+                category: r.category.clone(),
+                tags: r.tags.clone().unwrap_or_default(),
+                instance_id: r.instance.clone(),
+                created_at: ts,
+                updated_at: ts,
+                importance: r.importance.unwrap_or(5),
+                relevance: r.relevance.unwrap_or(5),
+                semantic_score: None,
+                temporal_score: None,
+                usage_score: None,
+                combined_score: None,
+            }
+        })
+        .collect();
+
+    let tx = crate::circuit_breaker::synth_transport(config)?;
+    let (model_fast, model_deep) = config.synth.models(&config.groq);
+    let (model_fast, model_deep) = profile.model_pair(model_fast, model_deep);
+    let synth = crate::synth::GroqSynth::new(tx, model_fast.to_string(), model_deep.to_string())
+        .with_deep_token_threshold(config.groq.deep_token_threshold)
+        .with_temperature(profile.temperature);
+    let intent = QueryIntent {
+        original_query: format!(
+            "Summarize the conclusions and open questions of this {state} chain of thoughts."
+        ),
+        temporal_filter: None,
+        synthesis_style: profile.style.clone(),
+        confidence: None,
+        low_confidence: false,
+    };
+    let request = synth.render_request(&intent, &ctx);
+    let result = synth.synth(&intent, &ctx).await?;
+
+    Ok(ChainSynthesis {
+        state,
+        model: result.model_used,
+        temperature: request.temperature,
+        thought_count: ctx.len(),
+        text: result.text,
+    })
+}
+
 impl<R: ThoughtRepository + KnowledgeRepository> ThoughtsHandler for super::ToolHandlers<R> {
     /// Handle ui_think tool
     async fn ui_think(&self, params: UiThinkParams) -> Result<ThinkResponse> {
         // Use parsed, forgiving framework_state (defaults to Conversation)
         let state: WorkflowState = params.framework_state;
 
+        // An explicit thinking_mode overrides the state-derived mode
+        let override_mode = match params.thinking_mode.as_deref() {
+            Some(mode) => Some(ThinkingMode::from_string(mode).map_err(|e| {
+                crate::error::UnifiedIntelligenceError::Validation {
+                    field: "thinking_mode".to_string(),
+                    reason: e.to_string(),
+                }
+            })?),
+            None => None,
+        };
+
+        let config = Config::load();
+
         // Show framework banner and choose a thinking mode (persisting cycle if stuck)
         self.visual.framework_state(state);
-        let chosen_mode: Option<ThinkingMode> = if matches!(state, WorkflowState::Stuck) {
-            if let Some(ref chain_id) = params.chain_id {
-                // Persist StuckTracker per chain: {instance}:stuck:chain:{chain_id}
-                let key = format!("{}:stuck:chain:{}", self.instance_id, chain_id);
-                // Load or initialize tracker
-                let mut tracker: StuckTracker = match self
-                    .redis_manager
-                    .json_get::<StuckTracker>(&key, "$")
-                    .await
-                    .ok()
-                    .flatten()
-                {
-                    Some(t) => t,
-                    None => StuckTracker::new(chain_id.clone()),
-                };
-                // Pick next approach and persist
-                let next = tracker.next_approach();
-                let _ = self.redis_manager.json_set(&key, "$", &tracker).await;
-                Some(next)
-            } else {
-                // Fallback to first recommended if no chain
-                state.thinking_modes().first().copied()
+        let mut stuck_attempts: Option<usize> = None;
+        let chosen_mode: Option<ThinkingMode> = if override_mode.is_some() {
+            override_mode
+        } else if matches!(state, WorkflowState::Stuck) {
+            match params.chain_id {
+                Some(ref chain_id) if config.stuck_tracker.enabled => {
+                    // Rotate modes via the per-chain tracker: {instance}:stuck:{chain_id}
+                    match self
+                        .repository
+                        .advance_stuck_tracker(&self.instance_id, chain_id)
+                        .await
+                    {
+                        Ok((next, tracker)) => {
+                            stuck_attempts = Some(tracker.attempts_count());
+                            Some(next)
+                        }
+                        Err(e) => {
+                            tracing::warn!("StuckTracker persistence failed: {}", e);
+                            state.thinking_modes().first().copied()
+                        }
+                    }
+                }
+                // Fallback to first recommended if no chain or persistence disabled
+                _ => state.thinking_modes().first().copied(),
             }
         } else {
             // Non-stuck: use the first recommended mode, if any
@@ -126,20 +286,58 @@ impl<R: ThoughtRepository + KnowledgeRepository> ThoughtsHandler for super::Tool
         self.visual.thought_content(&params.thought);
 
         // Process through framework
+        let mut framework_prompts: Option<Vec<String>> = None;
         if let Some(mode) = chosen_mode {
             let processor = FrameworkProcessor::new(mode);
             let result = processor.process_thought(&params.thought, params.thought_number);
             FrameworkVisual::display_insights(&result.insights);
             FrameworkVisual::display_prompts(&result.prompts);
+            if params.include_framework_prompts.unwrap_or(false) && !result.prompts.is_empty() {
+                framework_prompts = Some(result.prompts);
+            }
         }
 
         // Validate input
+        let explicit_timestamp = match params.timestamp.as_deref() {
+            Some(raw) => Some(
+                chrono::DateTime::parse_from_rfc3339(raw)
+                    .map(|dt| dt.with_timezone(&chrono::Utc))
+                    .map_err(|e| crate::error::UnifiedIntelligenceError::Validation {
+                        field: "timestamp".to_string(),
+                        reason: format!("expected RFC3339 date-time: {e}"),
+                    })?,
+            ),
+            None => None,
+        };
         self.validator.validate_thought_content(&params.thought)?;
         self.validator
             .validate_thought_numbers(params.thought_number, params.total_thoughts)?;
         if let Some(chain_id) = &params.chain_id {
             self.validator.validate_chain_id(chain_id)?;
         }
+        if let Some(parent_id) = &params.parent_thought_id {
+            let invalid = |reason: String| crate::error::UnifiedIntelligenceError::Validation {
+                field: "parent_thought_id".to_string(),
+                reason,
+            };
+            let Some(chain_id) = &params.chain_id else {
+                return Err(invalid("parent_thought_id requires chain_id".to_string()));
+            };
+            let parent = self
+                .repository
+                .get_thought(&self.instance_id, parent_id)
+                .await?;
+            if parent.as_ref().and_then(|p| p.chain_id.as_ref()) != Some(chain_id) {
+                return Err(invalid(format!(
+                    "thought {parent_id} is not part of chain {chain_id}"
+                )));
+            }
+        }
+        let tags = params
+            .tags
+            .as_deref()
+            .map(|t| self.validator.normalize_tags(t))
+            .transpose()?;
 
         tracing::info!(
             "Processing thought {} of {} for instance '{}'",
@@ -148,8 +346,17 @@ impl<R: ThoughtRepository + KnowledgeRepository> ThoughtsHandler for super::Tool
             self.instance_id
         );
 
+        // Optional LLM categorization when no category was given ("general" is the unset default)
+        let mut category = params.category.clone();
+        if config.groq.auto_categorize
+            && matches!(category.as_deref(), None | Some("general"))
+            && let Some(auto) = auto_categorize(&config, &params.thought).await
+        {
+            category = Some(auto);
+        }
+
         // Create thought record
-        let thought = ThoughtRecord::new(
+        let mut thought = ThoughtRecord::new(
             self.instance_id.clone(),
             params.thought.clone(),
             params.thought_number,
@@ -159,9 +366,16 @@ impl<R: ThoughtRepository + KnowledgeRepository> ThoughtsHandler for super::Tool
             Some(state.to_string()),
             params.importance,
             params.relevance,
-            params.tags.clone(),
-            params.category.clone(),
+            tags.clone(),
+            category.clone(),
         );
+        thought.framework_prompts = framework_prompts.clone();
+        thought.parent_thought_id = params.parent_thought_id.clone();
+        thought.ttl_seconds = params.ttl_seconds.filter(|&ttl| ttl > 0);
+        thought.sentiment = score_sentiment(&config, &params.thought).await;
+        if let Some(ts) = explicit_timestamp {
+            thought.timestamp = ts.to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+        }
 
         let thought_id = thought.id.clone();
 
@@ -174,6 +388,7 @@ impl<R: ThoughtRepository + KnowledgeRepository> ThoughtsHandler for super::Tool
                     created_at: chrono::Utc::now().to_rfc3339(),
                     thought_count: params.total_thoughts,
                     instance: self.instance_id.clone(),
+                    updated_at: None,
                 };
                 self.repository.save_chain_metadata(&metadata).await?;
             }
@@ -186,64 +401,95 @@ impl<R: ThoughtRepository + KnowledgeRepository> ThoughtsHandler for super::Tool
         // Save thought
         self.repository.save_thought(&thought).await?;
 
-        // Embed-on-save (best-effort, non-fatal)
-        if let Ok(openai_key) = std::env::var("OPENAI_API_KEY") {
-            if !openai_key.is_empty() {
-                // Ensure index exists for thoughts embeddings
-                let config = Config::load();
-                let dims = config.openai.embedding_dimensions;
-                let index = format!("idx:{}:thought", self.instance_id);
-                let prefix = format!("{}:embeddings:thought:", self.instance_id);
-                let _ = ensure_index_hash_hnsw(
-                    &self.redis_manager,
-                    &index,
-                    &prefix,
-                    dims,
-                    config.redis_search.hnsw.m,
-                    config.redis_search.hnsw.ef_construction,
-                )
-                .await;
+        // Embed-on-save (best-effort; dimension mismatches surface as `warnings` unless
+        // warn_on_dim_mismatch). With inline embedding off the thought is queued for the
+        // backfill scheduler.
+        let mut warnings = Vec::new();
+        if !config.embeddings.inline {
+            crate::backfill::mark_pending(
+                &self.redis_manager,
+                &self.instance_id,
+                &crate::backfill::thought_member(&thought.id),
+            )
+            .await;
+        } else if let Some(embedder) = self.embedder.as_deref() {
+            // Ensure index exists for thoughts embeddings
+            let dims = config.openai.embedding_dimensions;
+            let index = format!("idx:{}:thought", self.instance_id);
+            let prefix = format!("{}:embeddings:thought:", self.instance_id);
+            let _ = ensure_index_hash_hnsw(
+                &self.redis_manager,
+                &index,
+                &prefix,
+                dims,
+                config.redis_search.hnsw.m,
+                config.redis_search.hnsw.ef_construction,
+                config.redis_search.vector_type,
+            )
+            .await;
 
-                if let Ok(embedding) =
-                    generate_openai_embedding(&params.thought, &openai_key, &self.redis_manager)
-                        .await
-                {
-                    if embedding.len() == dims {
-                        if let Ok(mut con) = self.redis_manager.get_connection().await {
-                            let key =
-                                format!("{}:embeddings:thought:{}", self.instance_id, thought.id);
-                            let vec_bytes: Vec<u8> = cast_slice(&embedding).to_vec();
-                            let ts = chrono::Utc::now().timestamp();
-                            let tags_csv = params
-                                .tags
-                                .as_ref()
-                                .map(|v| v.join(","))
-                                .unwrap_or_default();
-                            let _: () = redis::pipe()
-                                .hset(&key, "content", &params.thought)
-                                .hset(&key, "tags", tags_csv)
-                                .hset(
-                                    &key,
-                                    "category",
-                                    params.category.clone().unwrap_or_default(),
-                                )
-                                .hset(
-                                    &key,
-                                    "importance",
-                                    params.importance.unwrap_or(5).to_string(),
-                                )
-                                .hset(
-                                    &key,
-                                    "chain_id",
-                                    params.chain_id.clone().unwrap_or_default(),
-                                )
-                                .hset(&key, "thought_id", &thought.id)
-                                .hset(&key, "ts", ts)
-                                .hset(&key, "vector", vec_bytes)
-                                .query_async(&mut *con)
-                                .await
-                                .unwrap_or(());
-                        }
+            if let Ok(embedding) = generate_embedding(
+                &params.thought,
+                embedder,
+                &self.redis_manager,
+                config.openai.embedding_max_input_chars,
+            )
+            .await
+            {
+                // The thought is already stored: a dimension mismatch or a failed
+                // write leaves it unembedded (reported in `warnings`) instead of
+                // failing the call
+                let dims_ok = match config.openai.check_embedding_dims(embedding.len()) {
+                    Ok(ok) => ok,
+                    Err(e) => {
+                        crate::metrics::global().record_error(&e);
+                        tracing::error!(
+                            "ui_think: stored thought {} without embedding: {}",
+                            thought.id,
+                            e
+                        );
+                        warnings.push(format!("thought stored without embedding: {e}"));
+                        false
+                    }
+                };
+                if dims_ok && let Ok(mut con) = self.redis_manager.get_connection().await {
+                    let key = format!("{}:embeddings:thought:{}", self.instance_id, thought.id);
+                    let vec_bytes: Vec<u8> = config.redis_search.vector_type.encode(&embedding);
+                    // Score recency by the thought's own time (explicit imports included)
+                    let ts = explicit_timestamp
+                        .unwrap_or_else(chrono::Utc::now)
+                        .timestamp();
+                    let tags_csv = tags.as_ref().map(|v| v.join(",")).unwrap_or_default();
+                    let mut pipe = redis::pipe();
+                    if let Some(sentiment) = thought.sentiment {
+                        pipe.hset(&key, "sentiment", sentiment);
+                    }
+                    let stored: redis::RedisResult<()> = pipe
+                        .hset(&key, "content", &params.thought)
+                        .hset(&key, "tags", tags_csv)
+                        .hset(&key, "category", category.clone().unwrap_or_default())
+                        .hset(
+                            &key,
+                            "importance",
+                            params.importance.unwrap_or(5).to_string(),
+                        )
+                        .hset(
+                            &key,
+                            "chain_id",
+                            params.chain_id.clone().unwrap_or_default(),
+                        )
+                        .hset(&key, "thought_id", &thought.id)
+                        .hset(&key, "ts", ts)
+                        .hset(&key, "vector", vec_bytes)
+                        .query_async(&mut *con)
+                        .await;
+                    if let Err(e) = stored {
+                        tracing::warn!(
+                            "ui_think: failed to store embedding for {}: {}",
+                            thought.id,
+                            e
+                        );
+                        warnings.push(format!("failed to store embedding: {e}"));
                     }
                 }
             }
@@ -251,6 +497,31 @@ impl<R: ThoughtRepository + KnowledgeRepository> ThoughtsHandler for super::Tool
 
         let auto_generated_thought: Option<ThoughtRecord> = None;
 
+        // Optional end-of-chain synthesis (best-effort; failures leave it unset)
+        let chain_synthesis = match params.chain_id.as_deref() {
+            Some(chain_id)
+                if params.synthesize_on_complete.unwrap_or(false)
+                    && !params.next_thought_needed =>
+            {
+                match synthesize_chain(
+                    &config,
+                    self.repository.as_ref(),
+                    &self.instance_id,
+                    chain_id,
+                    state,
+                )
+                .await
+                {
+                    Ok(summary) => Some(summary),
+                    Err(e) => {
+                        tracing::warn!("ui_think: chain synthesis failed: {}", e);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
         // Display success and completion status
         self.visual.thought_stored(&thought_id);
 
@@ -269,6 +540,61 @@ impl<R: ThoughtRepository + KnowledgeRepository> ThoughtsHandler for super::Tool
             thought_id,
             next_thought_needed: params.next_thought_needed,
             auto_generated_thought,
+            framework_prompts,
+            thinking_mode: chosen_mode.map(|m| m.to_string()),
+            stuck_attempts,
+            chain_synthesis,
+            warnings: (!warnings.is_empty()).then_some(warnings),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thought(framework: Option<&str>) -> ThoughtRecord {
+        ThoughtRecord::new(
+            "test".into(),
+            "t".into(),
+            1,
+            1,
+            Some("chain".into()),
+            false,
+            framework.map(String::from),
+            None,
+            None,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_dominant_state_majority_then_recency() {
+        let majority = vec![
+            thought(Some("debug")),
+            thought(Some("review")),
+            thought(Some("debug")),
+        ];
+        assert_eq!(
+            dominant_state(&majority, WorkflowState::Build),
+            WorkflowState::Debug
+        );
+
+        let tied = vec![thought(Some("review")), thought(Some("debug"))];
+        assert_eq!(
+            dominant_state(&tied, WorkflowState::Build),
+            WorkflowState::Debug
+        );
+
+        // Missing framework counts as conversation; empty chains use the fallback
+        assert_eq!(
+            dominant_state(&[thought(None)], WorkflowState::Build),
+            WorkflowState::Conversation
+        );
+        assert_eq!(
+            dominant_state(&[], WorkflowState::Build),
+            WorkflowState::Build
+        );
+    }
+}
@@ -8,18 +8,50 @@ use crate::transport::Transport;
 pub struct GroqIntent {
     tx: Arc<dyn Transport>,
     model: String,
+    min_confidence: f32,
 }
 
 impl GroqIntent {
-    #[cfg_attr(not(test), allow(dead_code))]
     pub fn new(tx: Arc<dyn Transport>, model: String) -> Self {
-        Self { tx, model }
+        Self {
+            tx,
+            model,
+            min_confidence: 0.0,
+        }
+    }
+
+    /// Below this confidence the parsed intent is replaced by a safe default
+    pub fn with_min_confidence(mut self, min_confidence: f32) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+}
+
+/// Replace a low-confidence intent with a safe default: the raw query, no
+/// temporal filter and the default synthesis style. A missing confidence is
+/// treated as confident for parsers that don't report one.
+pub fn apply_min_confidence(intent: QueryIntent, query: &str, min_confidence: f32) -> QueryIntent {
+    match intent.confidence {
+        Some(c) if c < min_confidence => {
+            tracing::warn!(
+                "Intent confidence {:.2} below {:.2}; using default intent",
+                c,
+                min_confidence
+            );
+            QueryIntent {
+                original_query: query.to_string(),
+                temporal_filter: None,
+                synthesis_style: None,
+                confidence: Some(c),
+                low_confidence: true,
+            }
+        }
+        _ => intent,
     }
 }
 
 #[async_trait]
 pub trait IntentParser: Send + Sync {
-    #[cfg_attr(not(test), allow(dead_code))]
     async fn parse(&self, query: &str) -> Result<QueryIntent>;
 }
 
@@ -39,7 +71,8 @@ The JSON object should have the following structure:
         "end_date": "YYYY-MM-DD",   // Optional: Specific end date
         "relative_timeframe": "string" // Optional: e.g., "yesterday", "last week", "last month", "last year", "past 3 days"
     },
-    "synthesis_style": "string" // Optional: "chronological" or "one_block"
+    "synthesis_style": "string", // Optional: "chronological" or "one_block"
+    "confidence": 0.0 // Required: how confident you are in this parse, from 0.0 to 1.0
 }
 
 If a field is not explicitly mentioned or implied in the user's query, omit it from the JSON.
@@ -101,11 +134,12 @@ Output:
 
         if let Some(choice) = groq_response.choices.first() {
             let json_string = choice.message.content.clone();
-            serde_json::from_str(&json_string).map_err(|e| {
+            let intent: QueryIntent = serde_json::from_str(&json_string).map_err(|e| {
                 UnifiedIntelligenceError::Internal(format!(
                     "Failed to deserialize Groq intent JSON: {e}. Raw: {json_string}"
                 ))
-            })
+            })?;
+            Ok(apply_min_confidence(intent, query, self.min_confidence))
         } else {
             Err(UnifiedIntelligenceError::Internal(
                 "Groq API returned empty choices for intent parsing".to_string(),
@@ -194,4 +228,39 @@ mod tests {
             "chronological"
         );
     }
+
+    #[tokio::test]
+    async fn test_groq_intent_low_confidence_falls_back() {
+        let mock_response = GroqResponse {
+            choices: vec![Choice {
+                message: ChatMessage {
+                    role: "assistant".to_string(),
+                    content: r#"{
+                        "original_query": "What happened?",
+                        "temporal_filter": { "relative_timeframe": "last year" },
+                        "synthesis_style": "chronological",
+                        "confidence": 0.2
+                    }"#
+                    .to_string(),
+                },
+            }],
+            usage: None,
+        };
+        let groq_intent = GroqIntent::new(
+            Arc::new(MockTransport::new(vec![mock_response])),
+            "test-model".to_string(),
+        )
+        .with_min_confidence(0.5);
+
+        let intent = groq_intent
+            .parse("what happened around then")
+            .await
+            .expect("Intent parsing should succeed in test");
+
+        assert!(intent.low_confidence);
+        assert_eq!(intent.confidence, Some(0.2));
+        assert_eq!(intent.original_query, "what happened around then");
+        assert!(intent.temporal_filter.is_none());
+        assert!(intent.synthesis_style.is_none());
+    }
 }
@@ -1,7 +1,10 @@
+pub mod circuit_breaker;
 pub mod config;
+pub mod embeddings;
 pub mod error;
 pub mod frameworks;
 pub mod intent;
+pub mod metrics;
 pub mod models;
 pub mod synth;
 pub mod tools;
@@ -11,15 +14,13 @@ pub mod validation;
 // Expose modules used by library submodules (e.g., tools::ui_context)
 pub mod lua_scripts;
 pub mod redis;
-
-use std::sync::Arc;
+pub mod retry;
 
 use crate::config::Config;
 use crate::error::Result;
 use crate::intent::{GroqIntent, IntentParser};
 use crate::models::Thought;
 use crate::synth::{GroqSynth, Synthesizer};
-use crate::transport::{GroqTransport, Transport};
 
 pub struct UiService {
     parser: GroqIntent,
@@ -28,18 +29,19 @@ pub struct UiService {
 
 impl UiService {
     pub fn new(cfg: &Config) -> Result<Self> {
-        let transport = Arc::new(GroqTransport::new(cfg.groq.api_key.clone())?);
-
         let parser = GroqIntent::new(
-            Arc::clone(&transport) as Arc<dyn Transport>,
+            circuit_breaker::groq_transport(cfg)?,
             cfg.groq.intent_model.clone(),
-        );
+        )
+        .with_min_confidence(cfg.groq.intent_min_confidence);
 
+        let (model_fast, model_deep) = cfg.synth.models(&cfg.groq);
         let synth = GroqSynth::new(
-            Arc::clone(&transport) as Arc<dyn Transport>,
-            cfg.groq.model_fast.clone(),
-            cfg.groq.model_deep.clone(),
-        );
+            circuit_breaker::synth_transport(cfg)?,
+            model_fast.to_string(),
+            model_deep.to_string(),
+        )
+        .with_deep_token_threshold(cfg.groq.deep_token_threshold);
 
         Ok(Self { parser, synth })
     }
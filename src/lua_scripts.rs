@@ -6,11 +6,18 @@
 /// KEYS[2] = bloom filter key ({instance}:bloom:thoughts)
 /// KEYS[3] = time series key ({instance}:metrics:thought_count)
 /// KEYS[4] = chain key ({instance}:chains:{chain_id}) - optional
+/// KEYS[5] = content hash set key ({instance}:content_hashes)
+/// KEYS[6] = chain metadata key (Chains:metadata:{chain_id}) - optional
 ///
 /// ARGV[1] = thought JSON data
 /// ARGV[2] = thought UUID
 /// ARGV[3] = timestamp (epoch seconds)
 /// ARGV[4] = chain_id (optional)
+/// ARGV[5] = normalized content hash (optional; empty skips content dedup;
+///           checked but not registered when the thought expires)
+/// ARGV[6] = instance id (for metadata created on first append)
+/// ARGV[7] = RFC3339 time of the append; empty skips the metadata update
+/// ARGV[8] = thought TTL in milliseconds (optional; empty or 0 persists)
 ///
 /// Returns: "OK" on success, "DUPLICATE" if already exists
 pub const STORE_THOUGHT_SCRIPT: &str = r#"
@@ -32,13 +39,32 @@ if bloom_exists == 1 then
     end
 end
 
+-- Content dedup: bloom fast path, confirmed against the exact hash set
+local content_hash = ARGV[5]
+if content_hash and content_hash ~= '' then
+    if redis.call('BF.EXISTS', bloom_key, content_hash) == 1 then
+        if redis.call('SISMEMBER', KEYS[5], content_hash) == 1 then
+            return 'DUPLICATE'
+        end
+    end
+end
+
 -- Store the thought as JSON
 redis.call('JSON.SET', KEYS[1], '.', ARGV[1])
 
--- No TTL on the thought (persist)
+-- Only ephemeral thoughts expire; everything else persists
+local ttl_ms = tonumber(ARGV[8])
+if ttl_ms and ttl_ms > 0 then
+    redis.call('PEXPIRE', KEYS[1], ttl_ms)
+end
 
 -- Add to bloom filter using BF.ADD
 redis.call('BF.ADD', bloom_key, uuid)
+-- An expiring thought cannot release its hash, so it never registers one
+if content_hash and content_hash ~= '' and not (ttl_ms and ttl_ms > 0) then
+    redis.call('BF.ADD', bloom_key, content_hash)
+    redis.call('SADD', KEYS[5], content_hash)
+end
 
 -- No TTL on bloom filter (persist)
 
@@ -52,8 +78,33 @@ redis.call('TS.INCRBY', ts_key, 1, 'TIMESTAMP', timestamp)
 -- Add to chain if chain_id is provided
 if ARGV[4] and ARGV[4] ~= '' then
     local chain_key = KEYS[4]
-    redis.call('RPUSH', chain_key, uuid)
+    -- Expired thoughts leave their ids behind; drop them so counts stay truthful
+    local thought_prefix = string.sub(KEYS[1], 1, #KEYS[1] - #uuid)
+    for _, id in ipairs(redis.call('LRANGE', chain_key, 0, -1)) do
+        if redis.call('EXISTS', thought_prefix .. id) == 0 then
+            redis.call('LREM', chain_key, 0, id)
+        end
+    end
+    local chain_len = redis.call('RPUSH', chain_key, uuid)
     -- No TTL on chain (persist)
+
+    -- Keep chain metadata counts truthful; create minimal metadata if missing
+    local meta_key = KEYS[6]
+    local now = ARGV[7]
+    if meta_key and meta_key ~= '' and now and now ~= '' then
+        if redis.call('EXISTS', meta_key) == 1 then
+            redis.call('JSON.SET', meta_key, '$.thought_count', chain_len)
+            redis.call('JSON.SET', meta_key, '$.updated_at', cjson.encode(now))
+        else
+            redis.call('JSON.SET', meta_key, '$', cjson.encode({
+                chain_id = ARGV[4],
+                created_at = now,
+                updated_at = now,
+                thought_count = chain_len,
+                instance = ARGV[6],
+            }))
+        end
+    end
 end
 
 return 'OK'
@@ -88,6 +139,7 @@ return thought
 /// ARGV[1] = search query (e.g., "@content:Rust")
 /// ARGV[2] = offset
 /// ARGV[3] = limit
+/// ARGV[4] = optional SORTBY field, sorted descending (empty for relevance order)
 ///
 /// Returns: array of [total_count, thought_json1, thought_json2, ...]
 ///
@@ -98,10 +150,21 @@ local index_name = KEYS[1]
 local query = ARGV[1]
 local offset = ARGV[2]
 local limit = ARGV[3]
+local sort_by = ARGV[4]
+
+local args = {index_name, query}
+if sort_by and sort_by ~= '' then
+    table.insert(args, 'SORTBY')
+    table.insert(args, sort_by)
+    table.insert(args, 'DESC')
+end
+for _, arg in ipairs({'LIMIT', offset, limit, 'RETURN', 1, '$.', 'NOCONTENT'}) do
+    table.insert(args, arg)
+end
 
 -- Perform RediSearch FT.SEARCH
 -- The result format is [total_results, doc1_id, [field1, value1, ...], doc2_id, [field1, value1, ...], ...]
-local search_results = redis.call('FT.SEARCH', index_name, query, 'LIMIT', offset, limit, 'RETURN', 1, '$.', 'NOCONTENT')
+local search_results = redis.call('FT.SEARCH', unpack(args))
 
 local total_results = search_results[1]
 local results = {total_results}
@@ -161,23 +224,48 @@ return 1
 /// KEYS[1] = chain key ({instance}:chains:{chain_id})
 ///
 /// ARGV[1] = instance (e.g., "Claude")
+/// ARGV[2] = start index (optional, default 0)
+/// ARGV[3] = stop index, inclusive (optional, default -1)
 ///
-/// Returns: array of thought JSONs in chain order
+/// Returns: [chain length, array of thought JSONs in chain order,
+/// chain position of each returned thought]
 pub const GET_CHAIN_THOUGHTS_SCRIPT: &str = r#"
-local chain_ids = redis.call('LRANGE', KEYS[1], 0, -1)
+local start = tonumber(ARGV[2]) or 0
+local stop = tonumber(ARGV[3]) or -1
+local total = redis.call('LLEN', KEYS[1])
+local chain_ids = redis.call('LRANGE', KEYS[1], start, stop)
 local thoughts = {}
+local positions = {}
 
-for _, uuid in ipairs(chain_ids) do
+for i, uuid in ipairs(chain_ids) do
     -- Build key using instance from ARGV
     local instance = ARGV[1]
     local thought_key = instance .. ':Thoughts:' .. uuid
     local thought = redis.call('JSON.GET', thought_key, '.')
     if thought then
         table.insert(thoughts, thought)
+        -- Missing ids are skipped, so positions let callers resume exactly
+        table.insert(positions, start + i - 1)
     end
 end
 
-return thoughts
+return {total, thoughts, positions}
+"#;
+
+/// Script to record reads of a batch of thoughts
+///
+/// KEYS[n] = usage hash per thought ({instance}:usage:{uuid})
+///
+/// ARGV[1] = timestamp (epoch seconds)
+///
+/// Returns: number of thoughts updated
+pub const RECORD_ACCESS_SCRIPT: &str = r#"
+for _, key in ipairs(KEYS) do
+    redis.call('HINCRBY', key, 'access_count', 1)
+    redis.call('HSET', key, 'last_accessed', ARGV[1])
+end
+
+return #KEYS
 "#;
 
 /// Script to cleanup expired data
@@ -230,6 +318,7 @@ pub struct LoadedScripts {
     pub update_chain: String,
     pub get_chain_thoughts: String,
     pub cleanup_expired: String,
+    pub record_access: String,
 }
 
 impl LoadedScripts {
@@ -241,10 +330,34 @@ impl LoadedScripts {
             update_chain: String::new(),
             get_chain_thoughts: String::new(),
             cleanup_expired: String::new(),
+            record_access: String::new(),
         }
     }
 }
 
+impl LoadedScripts {
+    /// (name, SHA) of every cached script; an empty SHA was never loaded
+    pub fn entries(&self) -> [(&'static str, &str); 7] {
+        [
+            ("store_thought", &self.store_thought),
+            ("get_thought", &self.get_thought),
+            ("search_thoughts", &self.search_thoughts),
+            ("update_chain", &self.update_chain),
+            ("get_chain_thoughts", &self.get_chain_thoughts),
+            ("cleanup_expired", &self.cleanup_expired),
+            ("record_access", &self.record_access),
+        ]
+    }
+}
+
+/// A cached script SHA and whether Redis still holds it (`SCRIPT EXISTS`)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ScriptStatus {
+    pub name: String,
+    pub sha: String,
+    pub exists: bool,
+}
+
 impl Default for LoadedScripts {
     fn default() -> Self {
         Self::new()
@@ -16,11 +16,14 @@ use axum::http::Request;
 use axum::middleware::Next;
 use axum::{
     Router,
-    http::{HeaderMap, StatusCode},
+    http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
     middleware,
     response::IntoResponse,
 };
+use tower_http::cors::{AllowOrigin, CorsLayer};
 
+mod backfill;
+mod categorize;
 mod circuit_breaker;
 mod config;
 mod embeddings; // New module
@@ -29,12 +32,14 @@ mod frameworks;
 mod handlers;
 mod intent;
 mod lua_scripts;
+mod metrics;
 mod models;
 mod rate_limit;
 mod redis;
 mod repository;
 mod repository_traits;
 mod retry;
+mod sentiment;
 mod service;
 mod synth;
 mod tools;
@@ -43,6 +48,7 @@ mod validation;
 mod visual;
 
 use crate::config::Config; // Import Config
+use crate::rate_limit::RateLimiter;
 use crate::redis::RedisManager;
 use crate::service::UnifiedIntelligenceService;
 use std::sync::Arc; // Import Arc
@@ -58,10 +64,13 @@ async fn main() -> Result<()> {
 
     // Load configuration
     let config = Arc::new(Config::load());
+    frameworks::FrameworkVisual::configure(config.server.visual);
 
     // Initialize RedisManager
     let redis_manager = Arc::new(RedisManager::new_with_config(&config).await?);
 
+    let grace = Duration::from_secs(config.server.shutdown_grace_secs);
+
     // Create service (no Qdrant dependency)
     let service = UnifiedIntelligenceService::new(redis_manager.clone()).await?;
 
@@ -94,6 +103,34 @@ async fn main() -> Result<()> {
 
             // Axum router with optional bearer auth middleware
             let mut router = Router::new().nest_service(path.as_str(), http_service);
+
+            // Prometheus metrics; behind bearer auth unless server.metrics_public
+            let metrics_redis = redis_manager.clone();
+            let metrics_route = axum::routing::get(move || async move {
+                (
+                    [(
+                        axum::http::header::CONTENT_TYPE,
+                        "text/plain; version=0.0.4",
+                    )],
+                    metrics::global().render(metrics_redis.pool_gauge()),
+                )
+            });
+            if !config.server.metrics_public {
+                router = router.route("/metrics", metrics_route.clone());
+            }
+
+            // Answer over-limit clients with 429 + Retry-After (runs after bearer auth)
+            if config.rate_limiter.http_retry_after {
+                let limiter_state = (
+                    service.rate_limiter(),
+                    Arc::new(service.instance_id().to_string()),
+                    config.server.max_message_bytes,
+                );
+                router = router.layer(middleware::from_fn_with_state(
+                    limiter_state,
+                    retry_after_on_limit,
+                ));
+            }
             if let Some(expected) = bearer_token.clone() {
                 let expected = Arc::new(expected);
                 router = router.layer(middleware::from_fn_with_state(
@@ -101,9 +138,47 @@ async fn main() -> Result<()> {
                     require_bearer,
                 ));
             }
+            // Outermost so browser preflights are answered before bearer auth
+            if let Some(cors) = std::env::var("UI_HTTP_CORS_ORIGINS")
+                .ok()
+                .and_then(|origins| cors_layer(&origins))
+            {
+                router = router.layer(cors);
+            }
 
-            // Add a simple health endpoint
-            let router = router.route("/health", axum::routing::get(|| async { "ok" }));
+            // Health endpoint: Redis PING latency and pool figures plus memory
+            // guard warnings; 503 when Redis cannot be reached
+            let health_redis = redis_manager.clone();
+            let router = router.route(
+                "/health",
+                axum::routing::get(move || async move {
+                    let warning = health_redis.memory_pressure().warning();
+                    match health_redis.health_check().await {
+                        Ok(redis) => (
+                            axum::http::StatusCode::OK,
+                            axum::Json(serde_json::json!({
+                                "status": if warning.is_some() { "degraded" } else { "ok" },
+                                "redis": redis,
+                                "warning": warning,
+                            })),
+                        ),
+                        Err(e) => (
+                            axum::http::StatusCode::SERVICE_UNAVAILABLE,
+                            axum::Json(serde_json::json!({
+                                "status": "unavailable",
+                                "error": e.to_string(),
+                                "error_kind": e.kind(),
+                                "warning": warning,
+                            })),
+                        ),
+                    }
+                }),
+            );
+            let router = if config.server.metrics_public {
+                router.route("/metrics", metrics_route)
+            } else {
+                router
+            };
 
             let listener = tokio::net::TcpListener::bind(bind).await?;
             tracing::info!(
@@ -113,21 +188,145 @@ async fn main() -> Result<()> {
                 "Starting Streamable HTTP MCP server"
             );
 
-            axum::serve(listener, router).await?;
+            // Stop accepting connections on a signal and let in-flight requests
+            // finish; open SSE sessions are cut off after the grace period
+            let (stopping_tx, mut stopping) = tokio::sync::watch::channel(false);
+            let server = axum::serve(listener, router).with_graceful_shutdown(async move {
+                shutdown_signal().await;
+                let _ = stopping_tx.send(true);
+            });
+            tokio::select! {
+                result = server => result?,
+                _ = async {
+                    let _ = stopping.wait_for(|stopping| *stopping).await;
+                    tokio::time::sleep(grace).await;
+                } => tracing::warn!("main: Grace period elapsed with open connections"),
+            }
+            finish_shutdown(&redis_manager, grace).await;
             Ok(())
         }
         _ => {
             tracing::info!("main: Service created, starting server on stdio transport");
             let server = service.serve(stdio()).await?;
             tracing::info!("main: Server started, waiting for connection to close");
-            server.waiting().await?;
-            tracing::info!("main: Server connection closed");
+            let cancel = server.cancellation_token();
+            tokio::spawn(async move {
+                shutdown_signal().await;
+                cancel.cancel();
+            });
+            let reason = server.waiting().await?;
+            tracing::info!("main: Server connection closed ({:?})", reason);
+            finish_shutdown(&redis_manager, grace).await;
             eprintln!("Server shutting down");
             Ok(())
         }
     }
 }
 
+/// Resolves on Ctrl-C or SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::warn!("main: Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::warn!("main: Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    tracing::info!("main: Shutdown signal received");
+}
+
+/// Let request tasks still holding Redis connections finish their writes.
+/// Thought stores are single Lua scripts, so a save cut off by the deadline
+/// was never applied or acknowledged.
+async fn finish_shutdown(redis_manager: &RedisManager, grace: Duration) {
+    if redis_manager.drain(grace).await {
+        tracing::info!("main: Redis operations flushed, clean exit");
+    } else {
+        tracing::warn!("main: Exiting with Redis operations still in flight");
+    }
+}
+
+async fn retry_after_on_limit(
+    State((limiter, instance_id, max_bytes)): State<(Arc<RateLimiter>, Arc<String>, usize)>,
+    req: Request<Body>,
+    next: Next,
+) -> impl IntoResponse {
+    // Limits are per tool, so peek at the JSON-RPC body for the called tool
+    let (parts, body) = req.into_parts();
+    // Bounded so an oversized body cannot exhaust memory before the limiter runs
+    let bytes = match axum::body::to_bytes(body, max_bytes).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+    if let Some(tool) = rate_limit::called_tool(&bytes)
+        && let Some(secs) = limiter.retry_after(instance_id.as_str(), &tool).await
+    {
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(axum::http::header::RETRY_AFTER, secs.to_string())],
+            "Rate limit exceeded",
+        )
+            .into_response();
+    }
+    next.run(Request::from_parts(parts, Body::from(bytes)))
+        .await
+}
+
+/// CORS for browser clients from a comma-separated origin allowlist or `*`;
+/// `None` (no CORS headers) when the list is empty
+fn cors_layer(origins: &str) -> Option<CorsLayer> {
+    let origins: Vec<&str> = origins
+        .split(',')
+        .map(str::trim)
+        .filter(|origin| !origin.is_empty())
+        .collect();
+    if origins.is_empty() {
+        return None;
+    }
+    let allow_origin = if origins.contains(&"*") {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(origins.iter().filter_map(|origin| {
+            HeaderValue::from_str(origin)
+                .inspect_err(|_| tracing::warn!("Ignoring invalid CORS origin {}", origin))
+                .ok()
+        }))
+    };
+    // Streamable HTTP: POST messages, GET the SSE stream, DELETE the session
+    Some(
+        CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods([Method::GET, Method::POST, Method::DELETE])
+            .allow_headers([
+                axum::http::header::AUTHORIZATION,
+                axum::http::header::CONTENT_TYPE,
+                axum::http::header::ACCEPT,
+                HeaderName::from_static("mcp-session-id"),
+                HeaderName::from_static("mcp-protocol-version"),
+                HeaderName::from_static("last-event-id"),
+            ])
+            .expose_headers([HeaderName::from_static("mcp-session-id")]),
+    )
+}
+
 async fn require_bearer(
     State(expected): State<Arc<String>>,
     req: Request<Body>,
@@ -0,0 +1,224 @@
+//! Process-wide counters for the HTTP `/metrics` endpoint, rendered in the
+//! Prometheus text exposition format.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::error::UnifiedIntelligenceError;
+
+/// Upper bounds (seconds) of the upstream latency histogram buckets
+const LATENCY_BUCKETS: [f64; 10] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+#[derive(Debug, Default, Clone)]
+struct Histogram {
+    /// Non-cumulative counts per bucket; rendering accumulates them
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, secs: f64) {
+        if let Some(i) = LATENCY_BUCKETS.iter().position(|bound| secs <= *bound) {
+            self.buckets[i] += 1;
+        }
+        self.sum += secs;
+        self.count += 1;
+    }
+}
+
+/// Redis pool figures sampled when metrics are rendered
+#[derive(Debug, Clone, Copy)]
+pub struct PoolGauge {
+    pub max_size: usize,
+    pub size: usize,
+    pub available: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct Metrics {
+    tool_calls: Mutex<BTreeMap<String, u64>>,
+    errors: Mutex<BTreeMap<&'static str, u64>>,
+    redis_acquired: AtomicU64,
+    redis_acquire_errors: AtomicU64,
+    upstream: Mutex<BTreeMap<String, Histogram>>,
+}
+
+/// Metrics shared by the whole process
+pub fn global() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(Metrics::default)
+}
+
+impl Metrics {
+    pub fn record_tool_call(&self, tool: &str) {
+        let mut calls = self.tool_calls.lock().unwrap_or_else(|e| e.into_inner());
+        *calls.entry(tool.to_string()).or_insert(0) += 1;
+    }
+
+    pub fn record_error(&self, error: &UnifiedIntelligenceError) {
+        self.record_error_kind(error.kind());
+    }
+
+    /// For errors that reach a tool already converted away from
+    /// `UnifiedIntelligenceError`; `kind` should match a variant's `kind()`
+    pub fn record_error_kind(&self, kind: &'static str) {
+        let mut errors = self.errors.lock().unwrap_or_else(|e| e.into_inner());
+        *errors.entry(kind).or_insert(0) += 1;
+    }
+
+    pub fn record_redis_acquire(&self, ok: bool) {
+        let counter = if ok {
+            &self.redis_acquired
+        } else {
+            &self.redis_acquire_errors
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record the latency of one call to an upstream API (`groq`, `anthropic`,
+    /// `openai`, `embeddings_http`)
+    pub fn observe_upstream(&self, service: &str, elapsed: Duration) {
+        let mut upstream = self.upstream.lock().unwrap_or_else(|e| e.into_inner());
+        upstream
+            .entry(service.to_lowercase())
+            .or_default()
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Prometheus text format
+    pub fn render(&self, pool: PoolGauge) -> String {
+        let mut out = String::new();
+
+        out.push_str("# HELP ui_tool_calls_total Tool invocations by tool name.\n");
+        out.push_str("# TYPE ui_tool_calls_total counter\n");
+        for (tool, count) in self
+            .tool_calls
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+        {
+            let _ = writeln!(out, "ui_tool_calls_total{{tool=\"{tool}\"}} {count}");
+        }
+
+        out.push_str("# HELP ui_errors_total Tool errors by error kind.\n");
+        out.push_str("# TYPE ui_errors_total counter\n");
+        for (kind, count) in self.errors.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+            let _ = writeln!(out, "ui_errors_total{{kind=\"{kind}\"}} {count}");
+        }
+
+        out.push_str("# HELP ui_redis_connections_acquired_total Redis pool checkouts.\n");
+        out.push_str("# TYPE ui_redis_connections_acquired_total counter\n");
+        let _ = writeln!(
+            out,
+            "ui_redis_connections_acquired_total {}",
+            self.redis_acquired.load(Ordering::Relaxed)
+        );
+        out.push_str("# HELP ui_redis_connection_errors_total Failed Redis pool checkouts.\n");
+        out.push_str("# TYPE ui_redis_connection_errors_total counter\n");
+        let _ = writeln!(
+            out,
+            "ui_redis_connection_errors_total {}",
+            self.redis_acquire_errors.load(Ordering::Relaxed)
+        );
+
+        out.push_str("# HELP ui_redis_pool_in_use Redis connections currently checked out.\n");
+        out.push_str("# TYPE ui_redis_pool_in_use gauge\n");
+        let _ = writeln!(
+            out,
+            "ui_redis_pool_in_use {}",
+            pool.size.saturating_sub(pool.available)
+        );
+        out.push_str("# HELP ui_redis_pool_size Open Redis connections.\n");
+        out.push_str("# TYPE ui_redis_pool_size gauge\n");
+        let _ = writeln!(out, "ui_redis_pool_size {}", pool.size);
+        out.push_str("# HELP ui_redis_pool_max_size Configured Redis pool size.\n");
+        out.push_str("# TYPE ui_redis_pool_max_size gauge\n");
+        let _ = writeln!(out, "ui_redis_pool_max_size {}", pool.max_size);
+
+        out.push_str(
+            "# HELP ui_upstream_request_duration_seconds Latency of LLM and embedding API calls.\n",
+        );
+        out.push_str("# TYPE ui_upstream_request_duration_seconds histogram\n");
+        for (service, histogram) in self
+            .upstream
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+        {
+            let mut cumulative = 0;
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+                cumulative += count;
+                let _ = writeln!(
+                    out,
+                    "ui_upstream_request_duration_seconds_bucket{{service=\"{service}\",le=\"{bound}\"}} {cumulative}"
+                );
+            }
+            let _ = writeln!(
+                out,
+                "ui_upstream_request_duration_seconds_bucket{{service=\"{service}\",le=\"+Inf\"}} {}",
+                histogram.count
+            );
+            let _ = writeln!(
+                out,
+                "ui_upstream_request_duration_seconds_sum{{service=\"{service}\"}} {}",
+                histogram.sum
+            );
+            let _ = writeln!(
+                out,
+                "ui_upstream_request_duration_seconds_count{{service=\"{service}\"}} {}",
+                histogram.count
+            );
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_counters_and_histogram() {
+        let metrics = Metrics::default();
+        metrics.record_tool_call("ui_think");
+        metrics.record_tool_call("ui_think");
+        metrics.record_tool_call("ui_recall");
+        metrics.record_error(&UnifiedIntelligenceError::RateLimit {
+            retry_after_secs: 1,
+        });
+        metrics.record_redis_acquire(true);
+        metrics.record_redis_acquire(false);
+        metrics.observe_upstream("groq", Duration::from_millis(200));
+        metrics.observe_upstream("groq", Duration::from_secs(120));
+
+        let text = metrics.render(PoolGauge {
+            max_size: 16,
+            size: 4,
+            available: 1,
+        });
+        assert!(text.contains("ui_tool_calls_total{tool=\"ui_think\"} 2\n"));
+        assert!(text.contains("ui_tool_calls_total{tool=\"ui_recall\"} 1\n"));
+        assert!(text.contains("ui_errors_total{kind=\"rate_limit\"} 1\n"));
+        assert!(text.contains("ui_redis_connections_acquired_total 1\n"));
+        assert!(text.contains("ui_redis_connection_errors_total 1\n"));
+        assert!(text.contains("ui_redis_pool_in_use 3\n"));
+        assert!(text.contains(
+            "ui_upstream_request_duration_seconds_bucket{service=\"groq\",le=\"0.1\"} 0\n"
+        ));
+        assert!(text.contains(
+            "ui_upstream_request_duration_seconds_bucket{service=\"groq\",le=\"0.25\"} 1\n"
+        ));
+        assert!(text.contains(
+            "ui_upstream_request_duration_seconds_bucket{service=\"groq\",le=\"60\"} 1\n"
+        ));
+        assert!(text.contains(
+            "ui_upstream_request_duration_seconds_bucket{service=\"groq\",le=\"+Inf\"} 2\n"
+        ));
+        assert!(text.contains("ui_upstream_request_duration_seconds_count{service=\"groq\"} 2\n"));
+    }
+}
@@ -121,6 +121,24 @@ pub struct UiThinkParams {
     #[serde(default, alias = "framework", alias = "state")]
     pub framework_state: WorkflowState,
 
+    #[schemars(
+        description = "Optional thinking mode override: first_principles, socratic, systems, ooda, root_cause, swot. Wins over the mode derived from framework_state"
+    )]
+    #[serde(default)]
+    pub thinking_mode: Option<String>,
+
+    #[schemars(
+        description = "Persist the framework's generated prompts with the thought and return them in the response (default: false)"
+    )]
+    #[serde(default)]
+    pub include_framework_prompts: Option<bool>,
+
+    #[schemars(
+        description = "Optional RFC3339 creation time (e.g. for historical imports); defaults to now. Chain order, chain metadata and metrics still use the import time"
+    )]
+    #[serde(default)]
+    pub timestamp: Option<String>,
+
     // NEW METADATA FIELDS FOR FEEDBACK LOOP SYSTEM
     #[schemars(description = "Importance score from 1-10 scale")]
     #[serde(
@@ -147,6 +165,24 @@ pub struct UiThinkParams {
     )]
     #[serde(default = "default_category")]
     pub category: Option<String>,
+
+    #[schemars(
+        description = "When next_thought_needed is false, synthesize a summary of the chain using the profile for its dominant framework_state (requires chain_id; default: false)"
+    )]
+    #[serde(default)]
+    pub synthesize_on_complete: Option<bool>,
+
+    #[schemars(
+        description = "Thought in the same chain this one branches from, when not the immediate predecessor (requires chain_id)"
+    )]
+    #[serde(default)]
+    pub parent_thought_id: Option<String>,
+
+    #[schemars(
+        description = "Expire the thought after this many seconds, for ephemeral scratch thoughts (default: persist)"
+    )]
+    #[serde(default)]
+    pub ttl_seconds: Option<u64>,
 }
 
 /// Core thought record structure stored in Redis
@@ -166,6 +202,24 @@ pub struct ThoughtRecord {
     pub relevance: Option<i32>,
     pub tags: Option<Vec<String>>,
     pub category: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub framework_prompts: Option<Vec<String>>,
+    /// SHA-256 of the normalized content, set at save time (`bloom_filter.store_content_hash`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    /// Whether this thought's hash was added to `{instance}:content_hashes`;
+    /// `None` on thoughts saved before this was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash_registered: Option<bool>,
+    /// Thought this one branches from within the chain (branching chains only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_thought_id: Option<String>,
+    /// Tone in [-1, 1] scored at capture (`ui_think.sentiment`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sentiment: Option<f32>,
+    /// Seconds until the thought expires; `None` (or 0) persists it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_seconds: Option<u64>,
 }
 
 impl ThoughtRecord {
@@ -199,8 +253,67 @@ impl ThoughtRecord {
             relevance,
             tags,
             category,
+            framework_prompts: None,
+            content_hash: None,
+            content_hash_registered: None,
+            parent_thought_id: None,
+            sentiment: None,
+            ttl_seconds: None,
         }
     }
+
+    /// Hash this thought should release from `{instance}:content_hashes` on
+    /// removal: only one it registered itself, so removing an unregistered
+    /// (ephemeral, expiring or dedup-off) thought cannot free another's entry.
+    /// Legacy records without a stored hash release nothing, since rehashing
+    /// under the current normalization could match another thought's entry.
+    pub fn registered_content_hash(&self) -> Option<String> {
+        if self.content_hash_registered == Some(false) {
+            return None;
+        }
+        self.content_hash.clone()
+    }
+}
+
+/// Lower bounds applied to a thought full-text search
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ThoughtSearchFilter {
+    pub min_importance: Option<i32>,
+    pub min_relevance: Option<i32>,
+}
+
+impl ThoughtSearchFilter {
+    pub fn is_empty(&self) -> bool {
+        self.min_importance.is_none() && self.min_relevance.is_none()
+    }
+
+    /// `query` with `@field:[min +inf]` clauses appended; an empty or `*`
+    /// query matches every thought in range
+    pub fn apply(&self, query: &str) -> String {
+        let mut clauses = Vec::new();
+        let query = query.trim();
+        if !query.is_empty() && query != "*" {
+            clauses.push(format!("({query})"));
+        }
+        if let Some(min) = self.min_importance {
+            clauses.push(format!("@importance:[{min} +inf]"));
+        }
+        if let Some(min) = self.min_relevance {
+            clauses.push(format!("@relevance:[{min} +inf]"));
+        }
+        if clauses.is_empty() {
+            return "*".to_string();
+        }
+        if self.is_empty() {
+            return query.to_string();
+        }
+        clauses.join(" ")
+    }
+
+    /// Filtered searches are ordered by importance, highest first
+    pub fn sort_by(&self) -> Option<&'static str> {
+        (!self.is_empty()).then_some("importance")
+    }
 }
 
 /// Response from ui_think tool
@@ -211,6 +324,33 @@ pub struct ThinkResponse {
     pub next_thought_needed: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub auto_generated_thought: Option<ThoughtRecord>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub framework_prompts: Option<Vec<String>>,
+    /// Thinking mode applied to this thought, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thinking_mode: Option<String>,
+    /// Modes attempted in the current stuck cycle (stuck state with chain_id only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stuck_attempts: Option<usize>,
+    /// End-of-chain summary (synthesize_on_complete only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_synthesis: Option<ChainSynthesis>,
+    /// Best-effort follow-ups that failed after the thought was stored (e.g. a
+    /// rejected embedding)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warnings: Option<Vec<String>>,
+}
+
+/// Summary of a completed chain produced by ui_think
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainSynthesis {
+    /// Dominant framework_state across the chain's thoughts
+    pub state: String,
+    pub model: String,
+    pub temperature: f32,
+    /// Thoughts included as context
+    pub thought_count: usize,
+    pub text: String,
 }
 
 /// Chain metadata stored in Redis
@@ -220,6 +360,9 @@ pub struct ChainMetadata {
     pub created_at: String,
     pub thought_count: i32,
     pub instance: String,
+    /// Last append time; maintained by the store script
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -251,6 +394,12 @@ pub struct QueryIntent {
     pub original_query: String,
     pub temporal_filter: Option<TemporalFilter>,
     pub synthesis_style: Option<String>,
+    /// Parser's self-reported confidence in [0, 1], when provided
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    /// Set when confidence fell below the threshold and defaults were applied
+    #[serde(default)]
+    pub low_confidence: bool,
 }
 
 // Groq chat message format
@@ -368,6 +517,32 @@ pub struct KnowledgeNode {
     pub thought_ids: Vec<String>,
     pub embedding: Option<Vec<f32>>,
     pub metadata: NodeMetadata,
+    /// Number of relations indexed for this entity (search responses only; not persisted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relation_count: Option<usize>,
+    /// Search relevance, higher is better (search responses only; not persisted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    /// Soft-deleted: out of the name index and skipped by search, but the
+    /// document and its relations are kept (`archive` / `unarchive` modes)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl KnowledgeNode {
+    /// Flip the archive flag, stamping `archived_at`/`updated_at`; `false`
+    /// when the entity was already in that state
+    pub fn set_archived(&mut self, archived: bool, now: chrono::DateTime<chrono::Utc>) -> bool {
+        if self.archived == archived {
+            return false;
+        }
+        self.archived = archived;
+        self.archived_at = archived.then_some(now);
+        self.updated_at = now;
+        true
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -401,9 +576,9 @@ pub struct RelationMetadata {
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct UiKnowledgeParams {
     #[schemars(
-        description = "Operation mode: create, search, set_active, get_entity, create_relation, get_relations, update_entity, delete_entity, help",
+        description = "Operation mode: create, search, set_active, get_entity, create_relation, get_relations, delete_relation, subgraph, traverse, update_entity, delete_entity, archive, unarchive, merge_entities, infer_relations, entities_near_thought, get_active, clear_active, repair_name_index (admin), help",
         regex(
-            pattern = r"^(create|search|set_active|get_entity|create_relation|get_relations|update_entity|delete_entity|help)$"
+            pattern = r"^(create|search|set_active|get_entity|create_relation|get_relations|delete_relation|subgraph|traverse|update_entity|delete_entity|archive|unarchive|merge_entities|infer_relations|entities_near_thought|get_active|clear_active|repair_name_index|help)$"
         )
     )]
     pub mode: String,
@@ -431,6 +606,24 @@ pub struct UiKnowledgeParams {
     pub query: Option<String>,
     #[serde(default)]
     pub limit: Option<usize>,
+    #[serde(default)]
+    pub include_relation_counts: Option<bool>,
+    // Search ranking: text (match quality) or semantic (KNN similarity); config default
+    #[serde(default)]
+    pub search_type: Option<String>,
+    // Search: return per-entity scores and matched query terms as `explain`
+    #[serde(default)]
+    pub explain: Option<bool>,
+    // Semantic search over a named entity vector (def, context) instead of text match
+    #[serde(default)]
+    pub vector: Option<String>,
+    // Search: also return archived entities
+    #[serde(default)]
+    pub include_archived: Option<bool>,
+
+    // For entities_near_thought: thought whose content is matched against entity vectors
+    #[serde(default)]
+    pub thought_id: Option<String>,
 
     // For relations
     #[serde(default)]
@@ -443,6 +636,26 @@ pub struct UiKnowledgeParams {
     pub bidirectional: Option<bool>,
     #[serde(default)]
     pub weight: Option<f32>,
+    // For delete_relation
+    #[serde(default)]
+    pub relation_id: Option<String>,
+
+    // For subgraph/traverse: hops from entity_id (default 1); traverse also
+    // filters on relationship_type
+    #[serde(default)]
+    pub depth: Option<usize>,
+
+    // For get_relations / subgraph / traverse / infer_relations: false strips relation attributes
+    #[serde(default)]
+    pub include_attributes: Option<bool>,
+
+    // For infer_relations / repair_name_index
+    #[serde(default)]
+    pub apply: Option<bool>,
+
+    // For admin modes (repair_name_index); must match UI_ADMIN_TOKEN
+    #[serde(default)]
+    pub admin_token: Option<String>,
 }
 
 /// Response from knowledge operations
@@ -453,6 +666,130 @@ pub struct KnowledgeResponse {
     pub entities: Option<Vec<KnowledgeNode>>,
     pub relations: Option<Vec<KnowledgeRelation>>,
     pub message: Option<String>,
+    /// Total matches before the page limit was applied (list/search modes)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_index_report: Option<NameIndexReport>,
+    /// Render-ready neighbourhood (subgraph mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub graph: Option<KnowledgeGraph>,
+    /// KNN distance per returned entity, closest first (entities_near_thought mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub near_entities: Option<Vec<EntityMatch>>,
+    /// Per-entity score and matched query terms (search mode, `explain`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explain: Option<Vec<EntityExplain>>,
+    /// Best-effort follow-ups that failed after the write succeeded (e.g. a
+    /// rejected embedding); the entity itself was stored
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warnings: Option<Vec<String>>,
+    /// Set when a scan cap stopped the search early (search and
+    /// infer_relations modes); `total` then only covers the entities examined
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub truncated: Option<bool>,
+}
+
+/// Why a search returned an entity: its score and the query terms found in
+/// each part of the embedded entity text
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EntityExplain {
+    pub entity_id: String,
+    pub name: String,
+    /// Similarity (semantic) or match quality (text); higher is better
+    pub score: Option<f64>,
+    /// Terms found in the name or display name
+    pub name_terms: Vec<String>,
+    pub tag_terms: Vec<String>,
+    /// Terms found in attribute keys or values
+    pub attribute_terms: Vec<String>,
+}
+
+/// Entity semantically close to a thought
+#[derive(Debug, Clone, Serialize)]
+pub struct EntityMatch {
+    pub entity_id: String,
+    pub name: String,
+    /// Vector distance (lower is closer)
+    pub distance: f64,
+    /// Whether the thought is already linked to the entity (`thought_ids`)
+    pub linked: bool,
+}
+
+/// Entity as a graph vertex
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphNode {
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+    pub entity_type: EntityType,
+    pub tags: Vec<String>,
+    /// Hops from the root entity
+    pub depth: usize,
+}
+
+/// Relation as a graph edge between two returned nodes
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphEdge {
+    pub id: String,
+    pub source: String,
+    pub target: String,
+    pub relationship_type: String,
+    pub weight: f32,
+    pub bidirectional: bool,
+    #[serde(skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub attributes: std::collections::HashMap<String, serde_json::Value>,
+}
+
+/// Nodes and edges within `depth` hops of a root entity
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct KnowledgeGraph {
+    pub root: String,
+    pub depth: usize,
+    /// Depth asked for by the caller, before clamping
+    pub requested_depth: usize,
+    /// Set when `requested_depth` exceeded `knowledge.max_traversal_depth`
+    pub depth_clamped: bool,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    /// Set when the node cap stopped the expansion early
+    pub truncated: bool,
+}
+
+/// A chain selected by `prune_chains`
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct PrunedChain {
+    pub chain_id: String,
+    pub thought_count: usize,
+    /// Newest thought (or metadata) time; `None` when nothing was recorded
+    pub last_activity: Option<String>,
+}
+
+/// Outcome of pruning short, idle chains
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ChainPruneReport {
+    pub scanned_chains: usize,
+    /// The chain key scan hit `scan_max_keys`; later chains were not checked
+    pub truncated: bool,
+    pub pruned: Vec<PrunedChain>,
+    /// Whether the chains were deleted (false = dry run)
+    pub applied: bool,
+}
+
+/// Outcome of validating the name index against entity keys
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NameIndexReport {
+    pub scanned_entities: usize,
+    /// The entity key scan hit `scan_max_keys`; orphans are then not reported
+    pub truncated: bool,
+    /// Entity names absent from the index
+    pub missing: Vec<String>,
+    /// Index entries pointing at the wrong entity id
+    pub stale: Vec<String>,
+    /// Index entries with no matching entity
+    pub orphaned: Vec<String>,
+    /// Whether the index was rewritten (false = report only)
+    pub applied: bool,
 }
 
 impl KnowledgeScope {
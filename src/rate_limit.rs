@@ -1,20 +1,34 @@
+use crate::config::RateLimiterConfig;
 use crate::error::{Result, UnifiedIntelligenceError};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::time::{Duration, Instant};
 
-/// Simple in-memory rate limiter for protecting against runaway processes
-#[derive(Clone)]
-pub struct RateLimiter {
-    /// Map of instance_id to their request timestamps
-    windows: Arc<Mutex<HashMap<String, Vec<Instant>>>>,
-    /// Maximum requests allowed per window
+/// Request budget for one tool
+#[derive(Debug, Clone, Copy)]
+struct Limit {
     max_requests: usize,
-    /// Time window duration
     window_duration: Duration,
 }
 
+/// Request timestamps keyed by (instance_id, tool)
+type Windows = HashMap<(String, String), Vec<Instant>>;
+
+/// Simple in-memory rate limiter for protecting against runaway processes.
+///
+/// Windows are tracked per `(instance_id, tool)`, so exhausting one tool's
+/// budget never blocks another; tools without an override share the default limit.
+#[derive(Clone)]
+pub struct RateLimiter {
+    /// Map of (instance_id, tool) to their request timestamps
+    windows: Arc<Mutex<Windows>>,
+    /// Limit applied to tools without an override
+    default_limit: Limit,
+    /// Per-tool overrides keyed by tool name
+    tool_limits: HashMap<String, Limit>,
+}
+
 impl RateLimiter {
     /// Create a new rate limiter
     ///
@@ -24,40 +38,81 @@ impl RateLimiter {
     pub fn new(max_requests: usize, window_seconds: u64) -> Self {
         Self {
             windows: Arc::new(Mutex::new(HashMap::new())),
-            max_requests,
-            window_duration: Duration::from_secs(window_seconds),
+            default_limit: Limit {
+                max_requests,
+                window_duration: Duration::from_secs(window_seconds),
+            },
+            tool_limits: HashMap::new(),
         }
     }
 
-    /// Check if an instance is allowed to make a request
+    /// Build a limiter from `config.rate_limiter`, including per-tool overrides
+    pub fn from_config(config: &RateLimiterConfig) -> Self {
+        let mut limiter = Self::new(config.max_requests as usize, config.window_seconds as u64);
+        for (tool, limit) in &config.tools {
+            limiter = limiter.with_tool_limit(
+                tool,
+                limit.max_requests as usize,
+                limit.window_seconds.unwrap_or(config.window_seconds) as u64,
+            );
+        }
+        limiter
+    }
+
+    /// Override the limit for one tool
+    pub fn with_tool_limit(mut self, tool: &str, max_requests: usize, window_seconds: u64) -> Self {
+        self.tool_limits.insert(
+            tool.to_string(),
+            Limit {
+                max_requests,
+                window_duration: Duration::from_secs(window_seconds),
+            },
+        );
+        self
+    }
+
+    fn limit_for(&self, tool: &str) -> Limit {
+        self.tool_limits
+            .get(tool)
+            .copied()
+            .unwrap_or(self.default_limit)
+    }
+
+    /// Check if an instance is allowed to make a request to `tool`
     ///
     /// # Arguments
     /// * `instance_id` - The instance identifier to check
+    /// * `tool` - The tool being called; selects the budget and window
     ///
     /// # Returns
     /// * `Ok(())` if the request is allowed
-    /// * `Err(UnifiedIntelligenceError::RateLimit)` if rate limit exceeded
-    pub async fn check_rate_limit(&self, instance_id: &str) -> Result<()> {
+    /// * `Err(UnifiedIntelligenceError::RateLimit)` if rate limit exceeded, carrying the
+    ///   seconds until the oldest request leaves the window
+    pub async fn check_rate_limit(&self, instance_id: &str, tool: &str) -> Result<()> {
+        let limit = self.limit_for(tool);
         let mut windows = self.windows.lock().await;
         let now = Instant::now();
 
-        // Get or create the window for this instance
+        // Get or create the window for this instance and tool
         let timestamps = windows
-            .entry(instance_id.to_string())
+            .entry((instance_id.to_string(), tool.to_string()))
             .or_insert_with(Vec::new);
 
         // Remove timestamps outside the window
-        timestamps.retain(|&timestamp| now.duration_since(timestamp) < self.window_duration);
+        timestamps.retain(|&timestamp| now.duration_since(timestamp) < limit.window_duration);
 
         // Check if we're at the limit
-        if timestamps.len() >= self.max_requests {
+        if timestamps.len() >= limit.max_requests {
             tracing::warn!(
-                "Rate limit exceeded for instance '{}': {} requests in {:?}",
+                "Rate limit exceeded for instance '{}' on {}: {} requests in {:?}",
                 instance_id,
+                tool,
                 timestamps.len(),
-                self.window_duration
+                limit.window_duration
             );
-            return Err(UnifiedIntelligenceError::RateLimit);
+            return Err(UnifiedIntelligenceError::RateLimit {
+                retry_after_secs: Self::retry_after_secs(limit, timestamps, now),
+            });
         }
 
         // Add the current timestamp
@@ -66,6 +121,33 @@ impl RateLimiter {
         Ok(())
     }
 
+    /// Seconds until a blocked instance may call `tool` again, or `None` if it is
+    /// under the limit.
+    ///
+    /// Does not record a request, so it is safe to call before dispatching.
+    pub async fn retry_after(&self, instance_id: &str, tool: &str) -> Option<u64> {
+        let limit = self.limit_for(tool);
+        let mut windows = self.windows.lock().await;
+        let now = Instant::now();
+        let timestamps = windows.get_mut(&(instance_id.to_string(), tool.to_string()))?;
+        timestamps.retain(|&timestamp| now.duration_since(timestamp) < limit.window_duration);
+        (timestamps.len() >= limit.max_requests)
+            .then(|| Self::retry_after_secs(limit, timestamps, now))
+    }
+
+    fn retry_after_secs(limit: Limit, timestamps: &[Instant], now: Instant) -> u64 {
+        let wait = timestamps
+            .first()
+            .map(|oldest| {
+                limit
+                    .window_duration
+                    .saturating_sub(now.duration_since(*oldest))
+            })
+            .unwrap_or(limit.window_duration);
+        // Round up so clients never retry a moment too early
+        wait.as_secs() + u64::from(wait.subsec_nanos() > 0)
+    }
+
     /// Get current usage statistics for monitoring
     #[allow(dead_code)]
     pub async fn get_usage_stats(&self) -> HashMap<String, usize> {
@@ -73,11 +155,12 @@ impl RateLimiter {
         let now = Instant::now();
         let mut stats = HashMap::new();
 
-        // Clean up old entries and collect stats
-        for (instance_id, timestamps) in windows.iter_mut() {
-            timestamps.retain(|&timestamp| now.duration_since(timestamp) < self.window_duration);
+        // Clean up old entries and collect stats, summed across tools
+        for ((instance_id, tool), timestamps) in windows.iter_mut() {
+            let window_duration = self.limit_for(tool).window_duration;
+            timestamps.retain(|&timestamp| now.duration_since(timestamp) < window_duration);
             if !timestamps.is_empty() {
-                stats.insert(instance_id.clone(), timestamps.len());
+                *stats.entry(instance_id.clone()).or_insert(0) += timestamps.len();
             }
         }
 
@@ -91,8 +174,17 @@ impl RateLimiter {
     #[allow(dead_code)]
     pub async fn clear_instance(&self, instance_id: &str) {
         let mut windows = self.windows.lock().await;
-        windows.remove(instance_id);
+        windows.retain(|(id, _), _| id != instance_id);
+    }
+}
+
+/// Tool name of a JSON-RPC `tools/call` request body, if it is one
+pub fn called_tool(body: &[u8]) -> Option<String> {
+    let request: serde_json::Value = serde_json::from_slice(body).ok()?;
+    if request.get("method")?.as_str()? != "tools/call" {
+        return None;
     }
+    Some(request.get("params")?.get("name")?.as_str()?.to_string())
 }
 
 #[cfg(test)]
@@ -106,7 +198,10 @@ mod tests {
         // Should allow 5 requests
         for i in 0..5 {
             assert!(
-                limiter.check_rate_limit("test-instance").await.is_ok(),
+                limiter
+                    .check_rate_limit("test-instance", "ui_think")
+                    .await
+                    .is_ok(),
                 "Request {} should be allowed",
                 i + 1
             );
@@ -119,14 +214,19 @@ mod tests {
 
         // Allow first 3 requests
         for _ in 0..3 {
-            assert!(limiter.check_rate_limit("test-instance").await.is_ok());
+            assert!(
+                limiter
+                    .check_rate_limit("test-instance", "ui_think")
+                    .await
+                    .is_ok()
+            );
         }
 
         // 4th request should be blocked
         assert!(
             matches!(
-                limiter.check_rate_limit("test-instance").await,
-                Err(UnifiedIntelligenceError::RateLimit)
+                limiter.check_rate_limit("test-instance", "ui_think").await,
+                Err(UnifiedIntelligenceError::RateLimit { .. })
             ),
             "4th request should be rate limited"
         );
@@ -137,14 +237,44 @@ mod tests {
         let limiter = RateLimiter::new(2, 60); // 2 requests per minute
 
         // Instance A uses its limit
-        assert!(limiter.check_rate_limit("instance-a").await.is_ok());
-        assert!(limiter.check_rate_limit("instance-a").await.is_ok());
-        assert!(limiter.check_rate_limit("instance-a").await.is_err());
+        assert!(
+            limiter
+                .check_rate_limit("instance-a", "ui_think")
+                .await
+                .is_ok()
+        );
+        assert!(
+            limiter
+                .check_rate_limit("instance-a", "ui_think")
+                .await
+                .is_ok()
+        );
+        assert!(
+            limiter
+                .check_rate_limit("instance-a", "ui_think")
+                .await
+                .is_err()
+        );
 
         // Instance B should still be allowed
-        assert!(limiter.check_rate_limit("instance-b").await.is_ok());
-        assert!(limiter.check_rate_limit("instance-b").await.is_ok());
-        assert!(limiter.check_rate_limit("instance-b").await.is_err());
+        assert!(
+            limiter
+                .check_rate_limit("instance-b", "ui_think")
+                .await
+                .is_ok()
+        );
+        assert!(
+            limiter
+                .check_rate_limit("instance-b", "ui_think")
+                .await
+                .is_ok()
+        );
+        assert!(
+            limiter
+                .check_rate_limit("instance-b", "ui_think")
+                .await
+                .is_err()
+        );
     }
 
     #[tokio::test]
@@ -152,14 +282,101 @@ mod tests {
         let limiter = RateLimiter::new(2, 1); // 2 requests per second
 
         // Use up the limit
-        assert!(limiter.check_rate_limit("test").await.is_ok());
-        assert!(limiter.check_rate_limit("test").await.is_ok());
-        assert!(limiter.check_rate_limit("test").await.is_err());
+        assert!(limiter.check_rate_limit("test", "ui_think").await.is_ok());
+        assert!(limiter.check_rate_limit("test", "ui_think").await.is_ok());
+        assert!(limiter.check_rate_limit("test", "ui_think").await.is_err());
 
         // Wait for window to pass
         tokio::time::sleep(Duration::from_millis(1100)).await;
 
         // Should be allowed again
-        assert!(limiter.check_rate_limit("test").await.is_ok());
+        assert!(limiter.check_rate_limit("test", "ui_think").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_reports_remaining_window() {
+        let limiter = RateLimiter::new(1, 60);
+
+        assert_eq!(limiter.retry_after("test", "ui_think").await, None);
+        assert!(limiter.check_rate_limit("test", "ui_think").await.is_ok());
+
+        let hint = limiter.retry_after("test", "ui_think").await;
+        assert!(matches!(hint, Some(secs) if secs > 0 && secs <= 60));
+        assert!(matches!(
+            limiter.check_rate_limit("test", "ui_think").await,
+            Err(UnifiedIntelligenceError::RateLimit { retry_after_secs }) if retry_after_secs <= 60
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_exhausting_one_tool_does_not_block_another() {
+        let limiter = RateLimiter::new(2, 60);
+
+        assert!(limiter.check_rate_limit("test", "ui_recall").await.is_ok());
+        assert!(limiter.check_rate_limit("test", "ui_recall").await.is_ok());
+        assert!(limiter.check_rate_limit("test", "ui_recall").await.is_err());
+        assert!(limiter.retry_after("test", "ui_recall").await.is_some());
+
+        // ui_think has its own window
+        assert_eq!(limiter.retry_after("test", "ui_think").await, None);
+        assert!(limiter.check_rate_limit("test", "ui_think").await.is_ok());
+        assert!(limiter.check_rate_limit("test", "ui_think").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_tool_overrides_and_default_fallback() {
+        let mut config = crate::config::Config::default().rate_limiter;
+        config.max_requests = 3;
+        config.tools.insert(
+            "ui_remember".to_string(),
+            crate::config::ToolRateLimit {
+                max_requests: 1,
+                window_seconds: None,
+            },
+        );
+        config.tools.insert(
+            "ui_recall".to_string(),
+            crate::config::ToolRateLimit {
+                max_requests: 5,
+                window_seconds: Some(10),
+            },
+        );
+        let limiter = RateLimiter::from_config(&config);
+
+        assert!(
+            limiter
+                .check_rate_limit("test", "ui_remember")
+                .await
+                .is_ok()
+        );
+        assert!(
+            limiter
+                .check_rate_limit("test", "ui_remember")
+                .await
+                .is_err()
+        );
+
+        for _ in 0..5 {
+            assert!(limiter.check_rate_limit("test", "ui_recall").await.is_ok());
+        }
+        assert!(matches!(
+            limiter.check_rate_limit("test", "ui_recall").await,
+            Err(UnifiedIntelligenceError::RateLimit { retry_after_secs }) if retry_after_secs <= 10
+        ));
+
+        // Unknown tools use the default limit
+        for _ in 0..3 {
+            assert!(limiter.check_rate_limit("test", "ui_custom").await.is_ok());
+        }
+        assert!(limiter.check_rate_limit("test", "ui_custom").await.is_err());
+    }
+
+    #[test]
+    fn test_called_tool() {
+        let call = br#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"ui_recall","arguments":{}}}"#;
+        assert_eq!(called_tool(call).as_deref(), Some("ui_recall"));
+        let list = br#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#;
+        assert_eq!(called_tool(list), None);
+        assert_eq!(called_tool(b"not json"), None);
     }
 }
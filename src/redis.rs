@@ -9,13 +9,151 @@ use sha2::{Digest, Sha256};
 use crate::error::{Result, UnifiedIntelligenceError};
 use crate::lua_scripts::{self, LoadedScripts};
 
-// TTLs are disabled: all writes persist unless explicitly deleted.
+// TTLs are disabled: all writes persist unless explicitly deleted, except
+// thoughts saved with `ttl_seconds` and `ui_memory` updates that pass one.
+
+/// Tool call responsible for the writes made on the current task
+#[derive(Debug, Clone)]
+pub struct EventOrigin {
+    pub tool: String,
+    pub request_id: String,
+}
+
+tokio::task_local! {
+    /// Set around a tool call so stream events can name the operation that
+    /// produced them without threading it through every repository call
+    pub static EVENT_ORIGIN: EventOrigin;
+}
+
+/// Origin of the tool call running on this task, if one was set
+pub fn current_event_origin() -> Option<EventOrigin> {
+    EVENT_ORIGIN.try_with(|origin| origin.clone()).ok()
+}
+
+/// Memory figures from `INFO memory`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MemoryUsage {
+    pub used: u64,
+    /// 0 when `maxmemory` is unset
+    pub max: u64,
+    /// `maxmemory-policy noeviction`: writes fail instead of evicting keys
+    pub noeviction: bool,
+}
+
+impl MemoryUsage {
+    /// `used / max`; `None` without a `maxmemory` limit
+    pub fn ratio(&self) -> Option<f64> {
+        (self.max > 0).then(|| self.used as f64 / self.max as f64)
+    }
+
+    pub fn pressure(self, warn_ratio: f64, critical_ratio: f64) -> MemoryPressure {
+        match self.ratio() {
+            Some(r) if r >= critical_ratio => MemoryPressure::Critical(self),
+            Some(r) if r >= warn_ratio => MemoryPressure::Warn(self),
+            _ => MemoryPressure::Ok,
+        }
+    }
+}
+
+/// Latest memory guard reading (`redis.memory_guard`)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MemoryPressure {
+    #[default]
+    Ok,
+    Warn(MemoryUsage),
+    Critical(MemoryUsage),
+}
+
+impl MemoryPressure {
+    /// One-line warning for `/health`; `None` when memory is fine
+    pub fn warning(&self) -> Option<String> {
+        let (level, usage) = match self {
+            MemoryPressure::Ok => return None,
+            MemoryPressure::Warn(u) => ("high", u),
+            MemoryPressure::Critical(u) => ("critical", u),
+        };
+        let mut msg = format!(
+            "Redis memory {level}: {} of {} bytes ({:.0}% of maxmemory)",
+            usage.used,
+            usage.max,
+            usage.ratio().unwrap_or_default() * 100.0
+        );
+        if matches!(self, MemoryPressure::Critical(_)) && usage.noeviction {
+            msg.push_str("; writes are refused");
+        }
+        Some(msg)
+    }
+}
+
+/// Access counter hash for a thought (`usage.track_access`)
+pub fn usage_key(instance: &str, thought_id: &str) -> String {
+    format!("{instance}:usage:{thought_id}")
+}
+
+/// Embedding cache entry for `text` as embedded by `model_id`
+/// ([`crate::embeddings::Embedder::model_id`])
+pub fn embedding_cache_key(model_id: &str, text: &str) -> String {
+    format!("embedding:{model_id}:{}", hex::encode(Sha256::digest(text)))
+}
+
+/// Parse the `used_memory`, `maxmemory` and `maxmemory_policy` lines of an
+/// `INFO memory` reply
+pub fn parse_memory_info(info: &str) -> Option<MemoryUsage> {
+    let mut used = None;
+    let mut max = 0;
+    let mut noeviction = false;
+    for line in info.lines() {
+        match line.trim().split_once(':') {
+            Some(("used_memory", v)) => used = v.parse().ok(),
+            Some(("maxmemory", v)) => max = v.parse().unwrap_or(0),
+            Some(("maxmemory_policy", v)) => noeviction = v == "noeviction",
+            _ => {}
+        }
+    }
+    Some(MemoryUsage {
+        used: used?,
+        max,
+        noeviction,
+    })
+}
 
 /// Redis connection manager
 #[derive(Clone)]
 pub struct RedisManager {
     pool: Arc<Pool>,
     scripts: Arc<tokio::sync::RwLock<LoadedScripts>>,
+    memory: Arc<std::sync::RwLock<MemoryPressure>>,
+    /// Backoff before the single retry of a failed connection acquire
+    retry: crate::config::RetryConfig,
+}
+
+/// Result of `RedisManager::health_check`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RedisHealth {
+    /// PING round trip on a pooled connection
+    pub latency_ms: f64,
+    pub pool_max_size: usize,
+    pub pool_size: usize,
+    pub pool_available: usize,
+}
+
+/// Map a pool checkout failure to pool exhaustion (every connection busy for
+/// the whole wait timeout) or Redis being unreachable (a new connection could
+/// not be opened or recycled); anything else stays a plain pool error
+pub fn classify_pool_error(
+    err: deadpool_redis::PoolError,
+    max_size: usize,
+) -> UnifiedIntelligenceError {
+    use deadpool::managed::{PoolError, TimeoutType};
+    match err {
+        PoolError::Timeout(TimeoutType::Wait) => {
+            UnifiedIntelligenceError::PoolExhausted { max_size }
+        }
+        PoolError::Timeout(TimeoutType::Create | TimeoutType::Recycle) | PoolError::Backend(_) => {
+            UnifiedIntelligenceError::RedisUnavailable(err.to_string())
+        }
+        other => UnifiedIntelligenceError::Pool(other),
+    }
 }
 
 impl RedisManager {
@@ -57,6 +195,8 @@ impl RedisManager {
         let instance = Self {
             pool: Arc::new(pool),
             scripts: Arc::new(tokio::sync::RwLock::new(LoadedScripts::new())),
+            memory: Arc::new(std::sync::RwLock::new(MemoryPressure::Ok)),
+            retry: config.retry.clone(),
         };
 
         // Load Lua scripts
@@ -65,9 +205,148 @@ impl RedisManager {
         Ok(instance)
     }
 
-    /// Get a connection from the pool
+    /// Get a connection from the pool, retrying a failed acquire once after
+    /// the `retry` backoff. Errors distinguish `PoolExhausted` from
+    /// `RedisUnavailable`.
     pub async fn get_connection(&self) -> Result<deadpool_redis::Connection> {
-        Ok(self.pool.get().await?)
+        let first = self.pool.get().await;
+        crate::metrics::global().record_redis_acquire(first.is_ok());
+        let err = match first {
+            Ok(conn) => return Ok(conn),
+            // Closed during shutdown; retrying cannot help
+            Err(deadpool::managed::PoolError::Closed) => {
+                return Err(deadpool::managed::PoolError::Closed.into());
+            }
+            Err(e) => classify_pool_error(e, self.pool.status().max_size),
+        };
+
+        let delay = crate::retry::backoff_delay(&self.retry, 1);
+        tracing::warn!(
+            "Redis connection acquire failed ({}), retrying once in {:?}",
+            err,
+            delay
+        );
+        tokio::time::sleep(delay).await;
+        let retried = self.pool.get().await;
+        crate::metrics::global().record_redis_acquire(retried.is_ok());
+        retried.map_err(|e| classify_pool_error(e, self.pool.status().max_size))
+    }
+
+    /// PING Redis on a pooled connection and report its latency with the pool
+    /// figures, for `/health`
+    pub async fn health_check(&self) -> Result<RedisHealth> {
+        let mut conn = self.get_connection().await?;
+        let start = std::time::Instant::now();
+        let _: String = redis::cmd("PING").query_async(&mut *conn).await?;
+        let latency = start.elapsed();
+        drop(conn);
+
+        let status = self.pool.status();
+        Ok(RedisHealth {
+            latency_ms: latency.as_secs_f64() * 1000.0,
+            pool_max_size: status.max_size,
+            pool_size: status.size,
+            pool_available: status.available,
+        })
+    }
+
+    /// Current pool size and idle connections, for `/metrics`
+    pub fn pool_gauge(&self) -> crate::metrics::PoolGauge {
+        let status = self.pool.status();
+        crate::metrics::PoolGauge {
+            max_size: status.max_size,
+            size: status.size,
+            available: status.available,
+        }
+    }
+
+    /// Wait until every checked-out connection is returned (in-flight commands
+    /// and scripts have finished), then close the pool. Returns `false` when
+    /// connections were still in use after `timeout`.
+    pub async fn drain(&self, timeout: std::time::Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let drained = loop {
+            let status = self.pool.status();
+            if status.size == status.available {
+                break true;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                tracing::warn!(
+                    "Redis drain timed out with {} connection(s) in use",
+                    status.size - status.available
+                );
+                break false;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        };
+        self.pool.close();
+        drained
+    }
+
+    /// Current `INFO memory` figures
+    pub async fn memory_usage(&self) -> Result<MemoryUsage> {
+        let mut conn = self.get_connection().await?;
+        let info: String = redis::cmd("INFO")
+            .arg("memory")
+            .query_async(&mut *conn)
+            .await?;
+        parse_memory_info(&info).ok_or_else(|| {
+            UnifiedIntelligenceError::Internal("INFO memory lacks used_memory".to_string())
+        })
+    }
+
+    /// Latest memory guard reading; `Ok` while the guard is off
+    pub fn memory_pressure(&self) -> MemoryPressure {
+        *self.memory.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Fail fast with `StorageFull` instead of an opaque OOM error mid-write
+    /// when the last reading was critical under `noeviction`
+    pub fn ensure_writable(&self) -> Result<()> {
+        match self.memory_pressure() {
+            MemoryPressure::Critical(usage) if usage.noeviction => {
+                Err(UnifiedIntelligenceError::StorageFull {
+                    used: usage.used,
+                    max: usage.max,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Refresh the memory reading every `check_interval_secs` when enabled,
+    /// logging each change of pressure level
+    pub fn spawn_memory_guard(&self, config: crate::config::MemoryGuardConfig) {
+        if !config.enabled {
+            return;
+        }
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let secs = config.check_interval_secs.max(1);
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(secs));
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                let usage = match manager.memory_usage().await {
+                    Ok(usage) => usage,
+                    Err(e) => {
+                        tracing::debug!("Memory guard check failed: {}", e);
+                        continue;
+                    }
+                };
+                let pressure = usage.pressure(config.warn_ratio, config.critical_ratio);
+                let previous = std::mem::replace(
+                    &mut *manager.memory.write().unwrap_or_else(|e| e.into_inner()),
+                    pressure,
+                );
+                if std::mem::discriminant(&previous) != std::mem::discriminant(&pressure) {
+                    match pressure.warning() {
+                        Some(msg) => tracing::warn!("{}", msg),
+                        None => tracing::info!("Redis memory back below the warning ratio"),
+                    }
+                }
+            }
+        });
     }
 
     /// Store a JSON object in Redis
@@ -132,8 +411,53 @@ impl RedisManager {
     // NOTE: The dangerous keys() method has been removed to prevent blocking operations.
     // Use scan_match() instead for pattern matching, which is non-blocking and production-safe.
 
+    /// Collect keys matching `pattern` with incremental SCAN.
+    ///
+    /// `count` is the per-call COUNT hint; the scan stops once `max_keys`
+    /// matching keys have been returned. The flag is true when it stopped
+    /// before the cursor completed, i.e. the keys are partial.
+    pub async fn scan_match(
+        &self,
+        pattern: &str,
+        count: usize,
+        max_keys: usize,
+    ) -> Result<(Vec<String>, bool)> {
+        let mut conn = self.get_connection().await?;
+        let count = count.max(1);
+        let mut cursor: u64 = 0;
+        let mut keys = Vec::new();
+        let mut truncated = false;
+
+        loop {
+            let (next, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(pattern)
+                .arg("COUNT")
+                .arg(count)
+                .query_async(&mut *conn)
+                .await?;
+            keys.extend(batch);
+            cursor = next;
+            if keys.len() > max_keys || (keys.len() == max_keys && cursor != 0) {
+                tracing::warn!(
+                    "scan_match('{}') stopped after {} matching keys",
+                    pattern,
+                    max_keys
+                );
+                truncated = true;
+                break;
+            }
+            if cursor == 0 {
+                break;
+            }
+        }
+
+        keys.truncate(max_keys);
+        Ok((keys, truncated))
+    }
+
     /// Add member to a set
-    #[allow(dead_code)]
     pub async fn sadd(&self, key: &str, member: &str) -> Result<()> {
         let mut conn = self.get_connection().await?;
         conn.sadd::<_, _, ()>(key, member).await?;
@@ -166,6 +490,42 @@ impl RedisManager {
 
     // Lua Script Methods
 
+    /// Cached Lua script SHAs and whether each is still in the Redis script
+    /// cache; a missing one is what triggers the NOSCRIPT reload path
+    pub async fn script_status(&self) -> Result<Vec<lua_scripts::ScriptStatus>> {
+        let entries: Vec<(&'static str, String)> = {
+            let scripts = self.scripts.read().await;
+            scripts
+                .entries()
+                .iter()
+                .map(|(name, sha)| (*name, sha.to_string()))
+                .collect()
+        };
+        let shas: Vec<&str> = entries
+            .iter()
+            .map(|(_, sha)| sha.as_str())
+            .filter(|sha| !sha.is_empty())
+            .collect();
+        let mut exists: Vec<bool> = Vec::new();
+        if !shas.is_empty() {
+            let mut conn = self.get_connection().await?;
+            exists = redis::cmd("SCRIPT")
+                .arg("EXISTS")
+                .arg(&shas)
+                .query_async(&mut *conn)
+                .await?;
+        }
+        let mut exists = exists.into_iter();
+        Ok(entries
+            .into_iter()
+            .map(|(name, sha)| lua_scripts::ScriptStatus {
+                name: name.to_string(),
+                exists: !sha.is_empty() && exists.next().unwrap_or(false),
+                sha,
+            })
+            .collect())
+    }
+
     /// Load all Lua scripts into Redis and store their SHA hashes
     pub async fn load_scripts(&self) -> Result<()> {
         let mut conn = self.get_connection().await?;
@@ -243,6 +603,18 @@ impl RedisManager {
                 ))
             })?;
 
+        // Load record access script
+        let access_script = Script::new(lua_scripts::RECORD_ACCESS_SCRIPT);
+        scripts.record_access = access_script
+            .prepare_invoke()
+            .load_async(&mut *conn)
+            .await
+            .map_err(|e| {
+                UnifiedIntelligenceError::Internal(format!(
+                    "Failed to load record access script: {e}"
+                ))
+            })?;
+
         // Update the scripts in the instance
         let mut script_store = self.scripts.write().await;
         *script_store = scripts;
@@ -264,6 +636,10 @@ impl RedisManager {
         uuid: &str,
         timestamp: i64,
         chain_id: Option<&str>,
+        content_hash_key: &str,
+        content_hash: Option<&str>,
+        chain_metadata: Option<(&str, &str, &str)>,
+        ttl_ms: Option<u64>,
     ) -> Result<bool> {
         let mut conn = self.get_connection().await?;
 
@@ -274,6 +650,9 @@ impl RedisManager {
         } else {
             keys.push(""); // Placeholder
         }
+        keys.push(content_hash_key);
+        let (meta_key, instance, updated_at) = chain_metadata.unwrap_or(("", "", ""));
+        keys.push(meta_key);
 
         // Prepare arguments
         let args = vec![
@@ -281,6 +660,10 @@ impl RedisManager {
             uuid.to_string(),
             timestamp.to_string(),
             chain_id.unwrap_or("").to_string(),
+            content_hash.unwrap_or("").to_string(),
+            instance.to_string(),
+            updated_at.to_string(),
+            ttl_ms.map(|ms| ms.to_string()).unwrap_or_default(),
         ];
 
         // Get script SHA
@@ -331,12 +714,16 @@ impl RedisManager {
         }
     }
 
-    /// Get all thoughts in a chain using Lua script
+    /// Get the thoughts at chain positions `start..=stop` (`stop` -1 for the
+    /// end) using Lua script, along with the chain length and the position of
+    /// each returned thought (ids whose thought is gone are skipped)
     pub async fn get_chain_thoughts_atomic(
         &self,
         chain_key: &str,
         instance: &str,
-    ) -> Result<Vec<String>> {
+        start: usize,
+        stop: isize,
+    ) -> Result<(usize, Vec<String>, Vec<usize>)> {
         let mut conn = self.get_connection().await?;
 
         let keys = vec![chain_key];
@@ -348,11 +735,13 @@ impl RedisManager {
         };
 
         // Try once; on NOSCRIPT, reload scripts and retry once
-        let first_attempt: Result<Vec<String>> = redis::cmd("EVALSHA")
+        let first_attempt: Result<(usize, Vec<String>, Vec<usize>)> = redis::cmd("EVALSHA")
             .arg(&script_sha)
             .arg(keys.len())
             .arg(&keys)
             .arg(instance)
+            .arg(start)
+            .arg(stop)
             .query_async(&mut *conn)
             .await
             .map_err(|e| {
@@ -362,7 +751,7 @@ impl RedisManager {
                     UnifiedIntelligenceError::Redis(e)
                 }
             });
-        let result: Vec<String> = match first_attempt {
+        let result: (usize, Vec<String>, Vec<usize>) = match first_attempt {
             Ok(v) => v,
             Err(UnifiedIntelligenceError::Internal(msg)) if msg == "NOSCRIPT" => {
                 tracing::warn!(
@@ -374,6 +763,10 @@ impl RedisManager {
                     .arg(keys.len())
                     .arg(&keys)
                     .arg(instance)
+                    .arg(start)
+                    .arg(stop)
+                    .arg(start)
+                    .arg(stop)
                     .query_async(&mut *conn)
                     .await
                     .map_err(UnifiedIntelligenceError::Redis)?
@@ -384,6 +777,63 @@ impl RedisManager {
         Ok(result)
     }
 
+    /// Bump the usage hash of each distinct thought in one Lua call
+    pub async fn record_access(&self, instance: &str, thought_ids: &[String]) -> Result<usize> {
+        let mut keys: Vec<String> = thought_ids
+            .iter()
+            .map(|id| usage_key(instance, id))
+            .collect();
+        keys.sort_unstable();
+        keys.dedup();
+        if keys.is_empty() {
+            return Ok(0);
+        }
+        let mut conn = self.get_connection().await?;
+        let script_sha = {
+            let scripts = self.scripts.read().await;
+            scripts.record_access.clone()
+        };
+        let now = chrono::Utc::now().timestamp();
+
+        // Try once; on NOSCRIPT, reload scripts and retry once
+        let first_attempt: redis::RedisResult<usize> = redis::cmd("EVALSHA")
+            .arg(&script_sha)
+            .arg(keys.len())
+            .arg(&keys)
+            .arg(now)
+            .query_async(&mut *conn)
+            .await;
+        match first_attempt {
+            Ok(n) => Ok(n),
+            Err(e) if e.to_string().contains("NOSCRIPT") => {
+                tracing::warn!("Lua script missing (record_access), reloading and retrying once");
+                self.load_scripts().await?;
+                Ok(redis::cmd("EVALSHA")
+                    .arg(&script_sha)
+                    .arg(keys.len())
+                    .arg(&keys)
+                    .arg(now)
+                    .query_async(&mut *conn)
+                    .await?)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// `record_access` in the background so the read path never waits on it
+    pub fn spawn_record_access(&self, instance: &str, thought_ids: Vec<String>) {
+        if thought_ids.is_empty() {
+            return;
+        }
+        let manager = self.clone();
+        let instance = instance.to_string();
+        tokio::spawn(async move {
+            if let Err(e) = manager.record_access(&instance, &thought_ids).await {
+                tracing::debug!("Failed to record thought access: {}", e);
+            }
+        });
+    }
+
     // Event Stream Methods
 
     /// Initialize event stream for an instance with max length
@@ -467,6 +917,10 @@ impl RedisManager {
             args.push(key);
             args.push(value);
         }
+        let origin = current_event_origin();
+        if let Some(origin) = &origin {
+            args.extend(["tool", &origin.tool, "request_id", &origin.request_id]);
+        }
 
         // Execute XADD
         let result: std::result::Result<String, _> = redis::cmd("XADD")
@@ -528,11 +982,15 @@ impl RedisManager {
         let stream_key = format!("{instance}:events");
         let event_id = "*"; // Auto-generate timestamp
 
-        let fields = vec![
+        let mut fields = vec![
             ("type", event_type.to_string()),
             ("data", data.to_string()),
             ("published_at", chrono::Utc::now().to_rfc3339()),
         ];
+        if let Some(origin) = current_event_origin() {
+            fields.push(("tool", origin.tool));
+            fields.push(("request_id", origin.request_id));
+        }
 
         let mut conn = self.get_connection().await?;
         let event_id: String = conn.xadd(&stream_key, event_id, &fields).await?;
@@ -548,9 +1006,13 @@ impl RedisManager {
 
     /// Get a cached embedding from Redis
     #[cfg_attr(not(test), allow(dead_code))]
-    pub async fn get_cached_embedding(&self, text: &str) -> Result<Option<Vec<f32>>> {
+    pub async fn get_cached_embedding(
+        &self,
+        model_id: &str,
+        text: &str,
+    ) -> Result<Option<Vec<f32>>> {
         let mut conn = self.get_connection().await?;
-        let key = format!("embedding:{}", hex::encode(Sha256::digest(text))); // Use SHA256 hash of text as key
+        let key = embedding_cache_key(model_id, text);
 
         let result: Option<Vec<u8>> = conn.get(&key).await?;
 
@@ -569,9 +1031,14 @@ impl RedisManager {
 
     /// Set a cached embedding in Redis (no TTL)
     #[cfg_attr(not(test), allow(dead_code))]
-    pub async fn set_cached_embedding(&self, text: &str, embedding: &[f32]) -> Result<()> {
+    pub async fn set_cached_embedding(
+        &self,
+        model_id: &str,
+        text: &str,
+        embedding: &[f32],
+    ) -> Result<()> {
         let mut conn = self.get_connection().await?;
-        let key = format!("embedding:{}", hex::encode(Sha256::digest(text))); // Use SHA256 hash of text as key
+        let key = embedding_cache_key(model_id, text);
 
         let bytes = bincode::serialize(embedding).map_err(|e| {
             UnifiedIntelligenceError::Internal(format!("Failed to serialize embedding: {e}"))
@@ -581,11 +1048,13 @@ impl RedisManager {
         Ok(())
     }
 
-    /// Execute RediSearch FT.SEARCH using Lua script
+    /// Execute RediSearch FT.SEARCH using Lua script; `sort_by` orders the
+    /// matches by that SORTABLE field, descending
     pub async fn search_thoughts_redisearch(
         &self,
         index_name: &str,
         query: &str,
+        sort_by: Option<&str>,
         offset: i64,
         limit: i64,
     ) -> Result<Vec<String>> {
@@ -594,7 +1063,7 @@ impl RedisManager {
         let keys = vec![index_name];
         let offset_str = offset.to_string();
         let limit_str = limit.to_string();
-        let args = vec![query, &offset_str, &limit_str];
+        let args = vec![query, &offset_str, &limit_str, sort_by.unwrap_or("")];
 
         let script_sha = {
             let scripts = self.scripts.read().await;
@@ -635,4 +1104,559 @@ impl RedisManager {
 
         Ok(result)
     }
+
+    /// KNN search returning each hit's key, distance and the requested text
+    /// fields in one round-trip (binary `vector` fields should not be requested).
+    pub async fn knn_search_with_fields(
+        &self,
+        index: &str,
+        vector: &[u8],
+        k: u32,
+        fields: &[&str],
+    ) -> Result<Vec<SearchRow>> {
+        self.knn_search_filtered(index, "*", vector, k, None, fields)
+            .await
+    }
+
+    /// `knn_search_with_fields` over the documents matching `filter` (a
+    /// RediSearch query, `*` for all), with an optional HNSW `EF_RUNTIME`
+    pub async fn knn_search_filtered(
+        &self,
+        index: &str,
+        filter: &str,
+        vector: &[u8],
+        k: u32,
+        ef_runtime: Option<u32>,
+        fields: &[&str],
+    ) -> Result<Vec<SearchRow>> {
+        let mut conn = self.get_connection().await?;
+        let mut cmd = redis::cmd("FT.SEARCH");
+        let ef = if ef_runtime.is_some() {
+            " EF_RUNTIME $ef"
+        } else {
+            ""
+        };
+        let filter = if filter == "*" {
+            filter.to_string()
+        } else {
+            format!("({filter})")
+        };
+        cmd.arg(index)
+            .arg(format!("{filter}=>[KNN $k @vector $vec{ef} AS score]"))
+            .arg("PARAMS")
+            .arg(if ef_runtime.is_some() { 6 } else { 4 })
+            .arg("k")
+            .arg(k)
+            .arg("vec")
+            .arg(vector);
+        if let Some(ef) = ef_runtime {
+            cmd.arg("ef").arg(ef);
+        }
+        cmd.arg("SORTBY")
+            .arg("score")
+            .arg("LIMIT")
+            .arg(0)
+            .arg(k)
+            .arg("RETURN")
+            .arg(fields.len() + 1)
+            .arg("score")
+            .arg(fields)
+            .arg("DIALECT")
+            .arg(2);
+        let val: redis::Value = cmd.query_async(&mut *conn).await?;
+        Ok(parse_search_rows(&val, fields).1)
+    }
+
+    /// Query search returning the total match count and, for the requested
+    /// page, each hit's key and text fields in one round-trip.
+    /// `sort` orders by a SORTABLE field: `(field, descending)`
+    pub async fn search_with_fields(
+        &self,
+        index: &str,
+        query: &str,
+        offset: usize,
+        limit: usize,
+        fields: &[&str],
+        sort: Option<(&str, bool)>,
+    ) -> Result<(usize, Vec<SearchRow>)> {
+        let mut conn = self.get_connection().await?;
+        let mut cmd = redis::cmd("FT.SEARCH");
+        cmd.arg(index).arg(query);
+        if let Some((field, desc)) = sort {
+            cmd.arg("SORTBY")
+                .arg(field)
+                .arg(if desc { "DESC" } else { "ASC" });
+        }
+        let val: redis::Value = cmd
+            .arg("LIMIT")
+            .arg(offset)
+            .arg(limit)
+            .arg("RETURN")
+            .arg(fields.len())
+            .arg(fields)
+            .query_async(&mut *conn)
+            .await?;
+        Ok(parse_search_rows(&val, fields))
+    }
+
+    /// Add a SORTABLE NUMERIC field to an existing index; `Ok(false)` when
+    /// the index already has it
+    pub async fn add_numeric_field(&self, index: &str, field: &str) -> Result<bool> {
+        let mut conn = self.get_connection().await?;
+        let res: redis::RedisResult<()> = redis::cmd("FT.ALTER")
+            .arg(index)
+            .arg("SCHEMA")
+            .arg("ADD")
+            .arg(field)
+            .arg("NUMERIC")
+            .arg("SORTABLE")
+            .query_async(&mut *conn)
+            .await;
+        match res {
+            Ok(()) => Ok(true),
+            Err(e) if e.to_string().to_lowercase().contains("duplicate") => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Create the JSON full-text index over `{prefix}*` thoughts, or add the
+    /// NUMERIC `importance`/`relevance` fields to an index created before range
+    /// filtering with `FT.ALTER`, so it stays searchable while the new fields
+    /// are indexed. `Ok(false)` when already current.
+    pub async fn ensure_thoughts_index(&self, index: &str, prefix: &str) -> Result<bool> {
+        let mut conn = self.get_connection().await?;
+        let info: redis::RedisResult<redis::Value> = redis::cmd("FT.INFO")
+            .arg(index)
+            .query_async(&mut *conn)
+            .await;
+        if let Ok(info) = info {
+            let mut altered = false;
+            for field in ["importance", "relevance"] {
+                match attribute_type_from_info(&info, field) {
+                    Some(t) if t.eq_ignore_ascii_case("NUMERIC") => {}
+                    // FT.ALTER cannot retype a field; leave the index serving searches
+                    Some(t) => tracing::warn!(
+                        "{} declares {} as {}; range filters on it need the index rebuilt",
+                        index,
+                        field,
+                        t
+                    ),
+                    None => {
+                        tracing::info!("Adding NUMERIC {} to {}", field, index);
+                        redis::cmd("FT.ALTER")
+                            .arg(index)
+                            .arg("SCHEMA")
+                            .arg("ADD")
+                            .arg(format!("$.{field}"))
+                            .arg("AS")
+                            .arg(field)
+                            .arg("NUMERIC")
+                            .arg("SORTABLE")
+                            .query_async::<()>(&mut *conn)
+                            .await?;
+                        altered = true;
+                    }
+                }
+            }
+            return Ok(altered);
+        }
+
+        redis::cmd("FT.CREATE")
+            .arg(index)
+            .arg("ON")
+            .arg("JSON")
+            .arg("PREFIX")
+            .arg(1)
+            .arg(prefix)
+            .arg("SCHEMA")
+            .arg("$.thought")
+            .arg("AS")
+            .arg("thought")
+            .arg("TEXT")
+            .arg("$.tags[*]")
+            .arg("AS")
+            .arg("tags")
+            .arg("TAG")
+            .arg("$.category")
+            .arg("AS")
+            .arg("category")
+            .arg("TAG")
+            .arg("$.chain_id")
+            .arg("AS")
+            .arg("chain_id")
+            .arg("TAG")
+            .arg("$.importance")
+            .arg("AS")
+            .arg("importance")
+            .arg("NUMERIC")
+            .arg("SORTABLE")
+            .arg("$.relevance")
+            .arg("AS")
+            .arg("relevance")
+            .arg("NUMERIC")
+            .arg("SORTABLE")
+            .query_async::<()>(&mut *conn)
+            .await?;
+        Ok(true)
+    }
+
+    /// `(index, DIM)` for every RediSearch index that declares a vector field
+    pub async fn vector_index_dims(&self) -> Result<Vec<(String, usize)>> {
+        let mut conn = self.get_connection().await?;
+        let names: Vec<String> = redis::cmd("FT._LIST").query_async(&mut *conn).await?;
+        let mut dims = Vec::new();
+        for name in names {
+            match redis::cmd("FT.INFO")
+                .arg(&name)
+                .query_async::<redis::Value>(&mut *conn)
+                .await
+            {
+                Ok(info) => {
+                    if let Some(dim) = vector_dim_from_info(&info) {
+                        dims.push((name, dim));
+                    }
+                }
+                Err(e) => tracing::debug!("FT.INFO {} failed: {}", name, e),
+            }
+        }
+        Ok(dims)
+    }
+}
+
+/// Vector dimension declared in an FT.INFO reply: the value following a
+/// `dim` key anywhere in its (nested) attribute listing
+pub fn vector_dim_from_info(val: &redis::Value) -> Option<usize> {
+    let dim_after = |key: &redis::Value, value: &redis::Value| {
+        value_as_string(key)
+            .filter(|k| k.eq_ignore_ascii_case("dim"))
+            .and_then(|_| value_as_string(value))
+            .and_then(|v| v.parse().ok())
+    };
+    match val {
+        redis::Value::Array(items) => items
+            .windows(2)
+            .find_map(|pair| dim_after(&pair[0], &pair[1]))
+            .or_else(|| items.iter().find_map(vector_dim_from_info)),
+        redis::Value::Map(pairs) => pairs
+            .iter()
+            .find_map(|(k, v)| dim_after(k, v))
+            .or_else(|| pairs.iter().find_map(|(_, v)| vector_dim_from_info(v))),
+        _ => None,
+    }
+}
+
+/// Type (`TEXT`, `NUMERIC`, ...) of `attribute` in an FT.INFO reply, taken
+/// from the attribute entry whose `attribute` key names it
+pub fn attribute_type_from_info(val: &redis::Value, attribute: &str) -> Option<String> {
+    let lookup = |pairs: Vec<(Option<String>, Option<String>)>| {
+        let names_it = pairs.iter().any(|(k, v)| {
+            k.as_deref()
+                .is_some_and(|k| k.eq_ignore_ascii_case("attribute"))
+                && v.as_deref() == Some(attribute)
+        });
+        names_it
+            .then(|| {
+                pairs
+                    .into_iter()
+                    .find_map(|(k, v)| k.filter(|k| k.eq_ignore_ascii_case("type")).and(v))
+            })
+            .flatten()
+    };
+    match val {
+        redis::Value::Array(items) => lookup(
+            items
+                .chunks(2)
+                .filter(|pair| pair.len() == 2)
+                .map(|pair| (value_as_string(&pair[0]), value_as_string(&pair[1])))
+                .collect(),
+        )
+        .or_else(|| {
+            items
+                .iter()
+                .find_map(|item| attribute_type_from_info(item, attribute))
+        }),
+        redis::Value::Map(pairs) => lookup(
+            pairs
+                .iter()
+                .map(|(k, v)| (value_as_string(k), value_as_string(v)))
+                .collect(),
+        )
+        .or_else(|| {
+            pairs
+                .iter()
+                .find_map(|(_, v)| attribute_type_from_info(v, attribute))
+        }),
+        _ => None,
+    }
+}
+
+/// One FT.SEARCH hit
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchRow {
+    pub key: String,
+    /// KNN distance when the query yields a `score` field
+    pub score: Option<f64>,
+    /// Values for the requested fields, in request order (None when absent)
+    pub fields: Vec<Option<String>>,
+}
+
+fn value_as_string(val: &redis::Value) -> Option<String> {
+    match val {
+        redis::Value::BulkString(bytes) => std::str::from_utf8(bytes).ok().map(str::to_string),
+        redis::Value::SimpleString(s) => Some(s.clone()),
+        redis::Value::Int(i) => Some(i.to_string()),
+        redis::Value::Double(d) => Some(d.to_string()),
+        _ => None,
+    }
+}
+
+/// Parse an FT.SEARCH reply into `(total, rows)`.
+///
+/// Handles both `[total, id, [field, value, ...], ...]` and the NOCONTENT form
+/// `[total, id, id, ...]`; `fields` selects which returned values are kept.
+pub fn parse_search_rows(val: &redis::Value, fields: &[&str]) -> (usize, Vec<SearchRow>) {
+    let redis::Value::Array(items) = val else {
+        return (0, Vec::new());
+    };
+    let total = match items.first() {
+        Some(redis::Value::Int(n)) => usize::try_from(*n).unwrap_or(0),
+        _ => 0,
+    };
+    let mut rows = Vec::new();
+    let mut i = 1usize;
+    while i < items.len() {
+        let key = value_as_string(&items[i]);
+        i += 1;
+        let mut row = SearchRow {
+            key: String::new(),
+            score: None,
+            fields: vec![None; fields.len()],
+        };
+        if let Some(redis::Value::Array(pairs)) = items.get(i) {
+            for pair in pairs.chunks_exact(2) {
+                let Some(name) = value_as_string(&pair[0]) else {
+                    continue;
+                };
+                if name == "score" {
+                    row.score = value_as_string(&pair[1]).and_then(|s| s.parse().ok());
+                }
+                if let Some(pos) = fields.iter().position(|f| *f == name) {
+                    row.fields[pos] = value_as_string(&pair[1]);
+                }
+            }
+            i += 1;
+        }
+        if let Some(key) = key {
+            row.key = key;
+            rows.push(row);
+        }
+    }
+    (total, rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_search_rows_with_scores_and_fields() {
+        let val = redis::Value::Array(vec![
+            redis::Value::Int(2),
+            redis::Value::BulkString(b"doc:1".to_vec()),
+            redis::Value::Array(vec![
+                redis::Value::BulkString(b"score".to_vec()),
+                redis::Value::BulkString(b"0.123".to_vec()),
+                redis::Value::BulkString(b"content".to_vec()),
+                redis::Value::BulkString(b"hello".to_vec()),
+            ]),
+            redis::Value::BulkString(b"doc:2".to_vec()),
+            redis::Value::Array(vec![
+                redis::Value::BulkString(b"score".to_vec()),
+                redis::Value::BulkString(b"0.456".to_vec()),
+            ]),
+        ]);
+
+        let (total, rows) = parse_search_rows(&val, &["content", "ts"]);
+        assert_eq!(total, 2);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].key, "doc:1");
+        assert!((rows[0].score.unwrap() - 0.123).abs() < 1e-9);
+        assert_eq!(rows[0].fields, vec![Some("hello".to_string()), None]);
+        assert_eq!(rows[1].key, "doc:2");
+        assert!((rows[1].score.unwrap() - 0.456).abs() < 1e-9);
+        assert_eq!(rows[1].fields, vec![None, None]);
+    }
+
+    #[test]
+    fn test_parse_memory_info_and_pressure() {
+        let info = "# Memory\r\nused_memory:960\r\nused_memory_human:960B\r\nmaxmemory:1000\r\nmaxmemory_policy:noeviction\r\n";
+        let usage = parse_memory_info(info).unwrap();
+        assert_eq!(
+            usage,
+            MemoryUsage {
+                used: 960,
+                max: 1000,
+                noeviction: true
+            }
+        );
+        assert_eq!(usage.pressure(0.85, 0.95), MemoryPressure::Critical(usage));
+        assert!(
+            usage
+                .pressure(0.85, 0.95)
+                .warning()
+                .unwrap()
+                .contains("refused")
+        );
+        assert_eq!(usage.pressure(0.9, 0.99), MemoryPressure::Warn(usage));
+
+        // No maxmemory: never under pressure
+        let unlimited =
+            parse_memory_info("used_memory:5\nmaxmemory:0\nmaxmemory_policy:allkeys-lru").unwrap();
+        assert_eq!(unlimited.ratio(), None);
+        assert_eq!(unlimited.pressure(0.0, 0.0), MemoryPressure::Ok);
+        assert!(parse_memory_info("maxmemory:0").is_none());
+    }
+
+    #[test]
+    fn test_embedding_cache_key_is_scoped_by_model() {
+        let small = embedding_cache_key("openai/text-embedding-3-small/1536", "hello");
+        let large = embedding_cache_key("openai/text-embedding-3-large/1536", "hello");
+        assert_ne!(small, large);
+        assert!(small.starts_with("embedding:openai/text-embedding-3-small/1536:"));
+        assert_eq!(
+            small,
+            embedding_cache_key("openai/text-embedding-3-small/1536", "hello")
+        );
+    }
+
+    #[test]
+    fn test_vector_dim_from_info() {
+        let bulk = |s: &str| redis::Value::BulkString(s.as_bytes().to_vec());
+        let info = redis::Value::Array(vec![
+            bulk("index_name"),
+            bulk("idx:CC:thought"),
+            bulk("attributes"),
+            redis::Value::Array(vec![
+                redis::Value::Array(vec![
+                    bulk("identifier"),
+                    bulk("content"),
+                    bulk("type"),
+                    bulk("TEXT"),
+                ]),
+                redis::Value::Array(vec![
+                    bulk("identifier"),
+                    bulk("vector"),
+                    bulk("type"),
+                    bulk("VECTOR"),
+                    bulk("dim"),
+                    redis::Value::Int(1536),
+                ]),
+            ]),
+        ]);
+        assert_eq!(vector_dim_from_info(&info), Some(1536));
+        assert_eq!(
+            vector_dim_from_info(&redis::Value::Array(vec![
+                bulk("index_name"),
+                bulk("idx:text")
+            ])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_classify_pool_error() {
+        use deadpool::managed::{PoolError, TimeoutType};
+        assert!(matches!(
+            classify_pool_error(PoolError::Timeout(TimeoutType::Wait), 16),
+            UnifiedIntelligenceError::PoolExhausted { max_size: 16 }
+        ));
+        assert!(matches!(
+            classify_pool_error(PoolError::Timeout(TimeoutType::Create), 16),
+            UnifiedIntelligenceError::RedisUnavailable(_)
+        ));
+        let refused = redis::RedisError::from(std::io::Error::new(
+            std::io::ErrorKind::ConnectionRefused,
+            "refused",
+        ));
+        assert!(matches!(
+            classify_pool_error(PoolError::Backend(refused), 16),
+            UnifiedIntelligenceError::RedisUnavailable(_)
+        ));
+        assert!(matches!(
+            classify_pool_error(PoolError::Closed, 16),
+            UnifiedIntelligenceError::Pool(PoolError::Closed)
+        ));
+    }
+
+    #[test]
+    fn test_attribute_type_from_info() {
+        let bulk = |s: &str| redis::Value::BulkString(s.as_bytes().to_vec());
+        let info = redis::Value::Array(vec![
+            bulk("index_name"),
+            bulk("CC:thoughts_idx"),
+            bulk("attributes"),
+            redis::Value::Array(vec![
+                redis::Value::Array(vec![
+                    bulk("identifier"),
+                    bulk("$.thought"),
+                    bulk("attribute"),
+                    bulk("thought"),
+                    bulk("type"),
+                    bulk("TEXT"),
+                ]),
+                redis::Value::Array(vec![
+                    bulk("identifier"),
+                    bulk("$.importance"),
+                    bulk("attribute"),
+                    bulk("importance"),
+                    bulk("type"),
+                    bulk("NUMERIC"),
+                    bulk("SORTABLE"),
+                ]),
+            ]),
+        ]);
+        assert_eq!(
+            attribute_type_from_info(&info, "importance").as_deref(),
+            Some("NUMERIC")
+        );
+        assert_eq!(
+            attribute_type_from_info(&info, "thought").as_deref(),
+            Some("TEXT")
+        );
+        assert_eq!(attribute_type_from_info(&info, "relevance"), None);
+    }
+
+    #[test]
+    fn test_parse_search_rows_nocontent() {
+        let val = redis::Value::Array(vec![
+            redis::Value::Int(2),
+            redis::Value::BulkString(b"doc:1".to_vec()),
+            redis::Value::BulkString(b"doc:2".to_vec()),
+        ]);
+        let (total, rows) = parse_search_rows(&val, &[]);
+        assert_eq!(total, 2);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].key, "doc:1");
+        assert!(rows[0].score.is_none());
+        assert_eq!(rows[1].key, "doc:2");
+        assert!(rows[1].score.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_event_origin_is_scoped_to_task() {
+        assert!(current_event_origin().is_none());
+        let origin = EventOrigin {
+            tool: "ui_think".to_string(),
+            request_id: "req-1".to_string(),
+        };
+        let seen = EVENT_ORIGIN
+            .scope(origin, async { current_event_origin() })
+            .await
+            .unwrap();
+        assert_eq!(
+            (seen.tool.as_str(), seen.request_id.as_str()),
+            ("ui_think", "req-1")
+        );
+        assert!(current_event_origin().is_none());
+    }
 }
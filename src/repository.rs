@@ -3,14 +3,16 @@ use std::sync::Arc;
 
 use crate::config::Config;
 use crate::error::Result;
-use crate::models::{ChainMetadata, ThoughtRecord};
+use crate::frameworks::{StuckTracker, ThinkingMode};
+use crate::models::{
+    ChainMetadata, ChainPruneReport, PrunedChain, ThoughtRecord, ThoughtSearchFilter,
+};
 use crate::redis::RedisManager;
 use crate::repository_traits::ThoughtRepository;
 
 /// Redis implementation of ThoughtRepository
 pub struct RedisThoughtRepository {
     redis: Arc<RedisManager>,
-    #[allow(dead_code)]
     config: Arc<Config>,
     #[allow(dead_code)]
     instance_id: String, // Keep instance_id for namespacing
@@ -36,11 +38,164 @@ impl RedisThoughtRepository {
     fn redi_search_index_name(&self, instance: &str) -> String {
         format!("{instance}:thoughts_idx")
     }
+
+    /// Create (or migrate) the full-text index behind `search_thoughts`
+    pub async fn ensure_search_index(&self, instance: &str) -> Result<bool> {
+        let prefix = self.thought_key(instance, "");
+        self.redis
+            .ensure_thoughts_index(&self.redi_search_index_name(instance), &prefix)
+            .await
+    }
+
+    fn stuck_tracker_key(&self, instance: &str, chain_id: &str) -> String {
+        format!("{instance}:stuck:{chain_id}")
+    }
+
+    /// Drop ids of expired (TTL) thoughts from a chain list and return the
+    /// live length, so chain counts match the thoughts that still exist
+    async fn sweep_expired_chain_ids(
+        &self,
+        conn: &mut deadpool_redis::Connection,
+        instance: &str,
+        chain_key: &str,
+    ) -> Result<usize> {
+        let ids: Vec<String> = redis::cmd("LRANGE")
+            .arg(chain_key)
+            .arg(0)
+            .arg(-1)
+            .query_async(&mut **conn)
+            .await?;
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let mut pipe = redis::pipe();
+        for id in &ids {
+            pipe.exists(self.thought_key(instance, id));
+        }
+        let exists: Vec<bool> = pipe.query_async(&mut **conn).await?;
+        let mut live = ids.len();
+        let mut sweep = redis::pipe();
+        for (id, _) in ids.iter().zip(&exists).filter(|(_, e)| !**e) {
+            sweep.lrem(chain_key, 0, id).ignore();
+            live -= 1;
+        }
+        if live < ids.len() {
+            sweep.query_async::<()>(&mut **conn).await?;
+        }
+        Ok(live)
+    }
+
+    /// Hash recorded in `{instance}:content_hashes` for duplicate detection
+    fn content_hash(&self, content: &str) -> String {
+        self.config.bloom_filter.normalization.hash(content)
+    }
+
+    /// `prune_chains` for one chain: report it when it has at most
+    /// `max_thoughts` thoughts and no activity since `idle_since`, and with
+    /// `apply` delete it in a MULTI/EXEC under the caller's WATCH. `None` when
+    /// the chain is not eligible or the transaction was aborted.
+    async fn prune_chain_if_idle(
+        &self,
+        conn: &mut deadpool_redis::Connection,
+        instance: &str,
+        chain_id: &str,
+        max_thoughts: usize,
+        idle_since: chrono::DateTime<chrono::Utc>,
+        apply: bool,
+    ) -> Result<Option<PrunedChain>> {
+        let key = format!("{instance}:chains:{chain_id}");
+        let ids: Vec<String> = redis::cmd("LRANGE")
+            .arg(&key)
+            .arg(0)
+            .arg(max_thoughts as isize)
+            .query_async(&mut **conn)
+            .await?;
+        if ids.len() > max_thoughts {
+            return Ok(None);
+        }
+
+        // Last activity: the newest of the thought timestamps and the metadata
+        // `updated_at`. Thought timestamps can be backdated on import, while
+        // metadata records ingestion time, so a fresh import is never idle.
+        let thoughts = self.get_chain_thoughts(instance, chain_id).await?;
+        let metadata_key = self.chain_metadata_key(chain_id);
+        let metadata = self
+            .redis
+            .json_get::<ChainMetadata>(&metadata_key, "$")
+            .await?;
+        let parse = |ts: &str| {
+            chrono::DateTime::parse_from_rfc3339(ts)
+                .ok()
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+        };
+        let last_activity = thoughts
+            .iter()
+            .filter_map(|t| parse(&t.timestamp))
+            .chain(
+                metadata
+                    .as_ref()
+                    .and_then(|m| parse(m.updated_at.as_deref().unwrap_or(&m.created_at))),
+            )
+            .max();
+        if last_activity.is_some_and(|t| t > idle_since) {
+            return Ok(None);
+        }
+
+        if apply {
+            let content_hash_key = format!("{instance}:content_hashes");
+            let mut pipe = redis::pipe();
+            pipe.atomic()
+                .del(&key)
+                .ignore()
+                .del(self.stuck_tracker_key(instance, chain_id))
+                .ignore()
+                .del(legacy_stuck_tracker_key(instance, chain_id))
+                .ignore();
+            // Metadata is global; leave it when another instance owns it
+            if metadata.as_ref().is_some_and(|m| m.instance == instance) {
+                pipe.del(&metadata_key).ignore();
+            }
+            for id in &ids {
+                pipe.del(self.thought_key(instance, id))
+                    .ignore()
+                    .del(format!("{instance}:embeddings:thought:{id}"))
+                    .ignore()
+                    .del(crate::redis::usage_key(instance, id))
+                    .ignore();
+            }
+            for hash in thoughts.iter().filter_map(|t| t.registered_content_hash()) {
+                pipe.srem(&content_hash_key, hash).ignore();
+            }
+            let executed: Option<()> = pipe.query_async(&mut **conn).await?;
+            if executed.is_none() {
+                tracing::info!(
+                    "prune_chains({}): chain {} changed, kept",
+                    instance,
+                    chain_id
+                );
+                return Ok(None);
+            }
+        }
+        Ok(Some(PrunedChain {
+            chain_id: chain_id.to_string(),
+            thought_count: ids.len(),
+            last_activity: last_activity.map(|t| t.to_rfc3339()),
+        }))
+    }
 }
 
 #[async_trait]
 impl ThoughtRepository for RedisThoughtRepository {
     async fn save_thought(&self, thought: &ThoughtRecord) -> Result<()> {
+        self.save_thought_with_options(thought, false).await
+    }
+
+    async fn save_thought_with_options(
+        &self,
+        thought: &ThoughtRecord,
+        skip_event: bool,
+    ) -> Result<()> {
+        self.redis.ensure_writable()?;
         let thought_key = self.thought_key(&thought.instance, &thought.id);
         let bloom_key = format!("{}:bloom:thoughts", thought.instance);
         let ts_key = format!("{}:metrics:thought_count", thought.instance);
@@ -48,21 +203,45 @@ impl ThoughtRepository for RedisThoughtRepository {
             .chain_id
             .as_ref()
             .map(|id| format!("{}:chains:{}", thought.instance, id));
+        let content_hash_key = format!("{}:content_hashes", thought.instance);
+
+        let ttl_ms = thought
+            .ttl_seconds
+            .filter(|&ttl| ttl > 0)
+            .map(|ttl| ttl.saturating_mul(1000));
+
+        // Content dedup is opt-in; ephemeral turns (skip_event) are exempt
+        // unless `bloom_filter.dedup_ephemeral`
+        let dedup = &self.config.bloom_filter;
+        let hash = self.content_hash(&thought.thought);
+        let content_hash = (dedup.normalization.dedups() && (!skip_event || dedup.dedup_ephemeral))
+            .then(|| hash.clone());
+
+        // Record the hash on the stored document for client-side change detection,
+        // and whether it was registered so removal only releases its own entry.
+        // The store script checks but never registers the hash of an expiring thought.
+        let mut thought = thought.clone();
+        if dedup.store_content_hash {
+            thought.content_hash = Some(hash);
+        }
+        thought.content_hash_registered = Some(content_hash.is_some() && ttl_ms.is_none());
+        let thought = &thought;
 
         // Serialize thought to JSON
         let thought_json =
             serde_json::to_string(thought).map_err(crate::error::UnifiedIntelligenceError::Json)?;
 
-        // Parse timestamp from ISO string to epoch seconds
-        let _timestamp = chrono::DateTime::parse_from_rfc3339(&thought.timestamp)
-            .map(|dt| dt.timestamp())
-            .unwrap_or_else(|_| {
-                // Fallback to current time if parsing fails
-                std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap_or_else(|_| std::time::Duration::from_secs(0))
-                    .as_secs() as i64
-            });
+        // Bookkeeping uses ingestion time, even for an imported thought with an
+        // explicit timestamp: TS.INCRBY rejects a backdated sample once newer
+        // ones exist, and the chain list and metadata record append order
+        let _timestamp = chrono::Utc::now().timestamp();
+
+        // (metadata key, append time) when the script should refresh chain metadata
+        let chain_metadata = thought
+            .chain_id
+            .as_deref()
+            .filter(|_| self.config.chains.auto_update_metadata)
+            .map(|id| (self.chain_metadata_key(id), chrono::Utc::now().to_rfc3339()));
 
         // Use atomic script for all operations
         let success = self
@@ -76,6 +255,12 @@ impl ThoughtRepository for RedisThoughtRepository {
                 &thought.id,
                 _timestamp,
                 thought.chain_id.as_deref(),
+                &content_hash_key,
+                content_hash.as_deref(),
+                chain_metadata
+                    .as_ref()
+                    .map(|(key, now)| (key.as_str(), thought.instance.as_str(), now.as_str())),
+                ttl_ms,
             )
             .await?;
 
@@ -85,43 +270,60 @@ impl ThoughtRepository for RedisThoughtRepository {
                 instance: thought.instance.clone(),
                 preview,
             });
+        }
+
+        // Publish to Redis Streams for background service; ephemeral saves skip it
+        // so the embedding worker ignores them
+        if skip_event {
+            tracing::debug!(
+                "Skipping thought_created event for ephemeral thought {}",
+                thought.id
+            );
         } else {
-            // Publish to Redis Streams for background service
-            let event_data = serde_json::to_value(thought)
+            let mut event_data = serde_json::to_value(thought)
                 .map_err(crate::error::UnifiedIntelligenceError::Json)?;
+            event_data["ttl_applied"] = serde_json::json!(ttl_ms.is_some());
 
             if let Err(e) = self
                 .redis
                 .publish_stream_event(&thought.instance, "thought_created", &event_data)
                 .await
             {
-                tracing::debug!(
-                    "Failed to publish thought_created event: {}. Background processing may not be triggered.",
+                tracing::warn!(
+                    "Failed to publish thought_created event for {}: {}. Background processing may not be triggered.",
+                    thought.id,
                     e
                 );
+                if self.config.event_stream.dlq_on_publish_failure {
+                    let dlq_key = format!("{}:events:dlq", thought.instance);
+                    if let Err(e) = self.redis.sadd(&dlq_key, &thought.id).await {
+                        tracing::warn!("Failed to record {} in {}: {}", thought.id, dlq_key, e);
+                    }
+                }
             }
-
-            // Log thought created event
-            let thought_preview = thought.thought.chars().take(100).collect::<String>();
-            let _ = self
-                .redis
-                .log_thought_event(
-                    &thought.instance,
-                    "thought_created",
-                    &thought.id,
-                    thought.chain_id.as_deref(),
-                    Some(vec![
-                        ("thought_preview", &thought_preview),
-                        ("thought_number", &thought.thought_number.to_string()),
-                    ]),
-                )
-                .await;
         }
 
+        // Log thought created event
+        let thought_preview = thought.thought.chars().take(100).collect::<String>();
+        let _ = self
+            .redis
+            .log_thought_event(
+                &thought.instance,
+                "thought_created",
+                &thought.id,
+                thought.chain_id.as_deref(),
+                Some(vec![
+                    ("thought_preview", &thought_preview),
+                    ("thought_number", &thought.thought_number.to_string()),
+                ]),
+            )
+            .await;
+
         Ok(())
     }
 
     async fn save_chain_metadata(&self, metadata: &ChainMetadata) -> Result<()> {
+        self.redis.ensure_writable()?;
         let key = self.chain_metadata_key(&metadata.chain_id);
 
         // Check if chain already exists
@@ -157,6 +359,89 @@ impl ThoughtRepository for RedisThoughtRepository {
         self.redis.exists(&key).await
     }
 
+    async fn chain_belongs_to(&self, instance: &str, chain_id: &str) -> Result<bool> {
+        if self
+            .redis
+            .exists(&format!("{instance}:chains:{chain_id}"))
+            .await?
+        {
+            return Ok(true);
+        }
+        let metadata = self
+            .redis
+            .json_get::<ChainMetadata>(&self.chain_metadata_key(chain_id), "$")
+            .await?;
+        Ok(metadata.is_some_and(|m| m.instance == instance))
+    }
+
+    async fn prune_chains(
+        &self,
+        instance: &str,
+        max_thoughts: usize,
+        idle_since: chrono::DateTime<chrono::Utc>,
+        apply: bool,
+    ) -> Result<ChainPruneReport> {
+        let chain_prefix = format!("{instance}:chains:");
+        let (keys, truncated) = self
+            .redis
+            .scan_match(
+                &format!("{chain_prefix}*"),
+                self.config.redis.scan_count,
+                self.config.redis.scan_max_keys,
+            )
+            .await?;
+        let mut report = ChainPruneReport {
+            scanned_chains: keys.len(),
+            truncated,
+            applied: apply,
+            ..Default::default()
+        };
+
+        let mut conn = self.redis.get_connection().await?;
+        for key in keys {
+            let Some(chain_id) = key.strip_prefix(&chain_prefix) else {
+                continue;
+            };
+            // A thought appended between the idleness check and the delete
+            // touches the chain list and aborts the transaction
+            if apply {
+                let _: () = redis::cmd("WATCH")
+                    .arg(&key)
+                    .arg(self.chain_metadata_key(chain_id))
+                    .query_async(&mut *conn)
+                    .await?;
+            }
+            let pruned = self
+                .prune_chain_if_idle(
+                    &mut conn,
+                    instance,
+                    chain_id,
+                    max_thoughts,
+                    idle_since,
+                    apply,
+                )
+                .await;
+            if let Ok(Some(chain)) = pruned {
+                report.pruned.push(chain);
+                continue;
+            }
+            // Skipped or failed: release the WATCH before the next chain
+            if apply {
+                let _: redis::RedisResult<()> = redis::cmd("UNWATCH").query_async(&mut *conn).await;
+            }
+            pruned?;
+        }
+
+        tracing::info!(
+            "prune_chains({}): {} of {} chains {}",
+            instance,
+            report.pruned.len(),
+            report.scanned_chains,
+            if apply { "deleted" } else { "eligible" }
+        );
+        Ok(report)
+    }
+
     async fn get_thought(&self, instance: &str, thought_id: &str) -> Result<Option<ThoughtRecord>> {
         let thought_key = self.thought_key(instance, thought_id);
         self.redis
@@ -164,15 +449,95 @@ impl ThoughtRepository for RedisThoughtRepository {
             .await
     }
 
+    async fn delete_thought(
+        &self,
+        instance: &str,
+        thought_id: &str,
+    ) -> Result<Option<ThoughtRecord>> {
+        let Some(thought) = self.get_thought(instance, thought_id).await? else {
+            return Ok(None);
+        };
+
+        // The bloom filter cannot forget the content hash, but a bloom hit is
+        // only confirmed against content_hashes, so dropping the hash there
+        // lets the same content be saved again
+        let hash = thought.registered_content_hash();
+        let chain_key = thought
+            .chain_id
+            .as_ref()
+            .map(|id| format!("{instance}:chains:{id}"));
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .del(self.thought_key(instance, thought_id))
+            .ignore()
+            .del(format!("{instance}:embeddings:thought:{thought_id}"))
+            .ignore()
+            .del(crate::redis::usage_key(instance, thought_id))
+            .ignore();
+        if let Some(hash) = &hash {
+            pipe.srem(format!("{instance}:content_hashes"), hash)
+                .ignore();
+        }
+        if let Some(chain_key) = &chain_key {
+            pipe.lrem(chain_key, 0, thought_id).ignore();
+        }
+        let mut conn = self.redis.get_connection().await?;
+        pipe.query_async::<()>(&mut *conn).await?;
+
+        // Keep chain metadata counts truthful, as saves do
+        if let (Some(chain_key), Some(chain_id)) = (&chain_key, thought.chain_id.as_deref())
+            && self.config.chains.auto_update_metadata
+        {
+            let metadata_key = self.chain_metadata_key(chain_id);
+            if self.redis.exists(&metadata_key).await? {
+                let len = self
+                    .sweep_expired_chain_ids(&mut conn, instance, chain_key)
+                    .await?;
+                self.redis
+                    .json_set(&metadata_key, "$.thought_count", &len)
+                    .await?;
+            }
+        }
+
+        let event_data = serde_json::json!({
+            "id": thought.id,
+            "instance": instance,
+            "chain_id": thought.chain_id,
+        });
+        if let Err(e) = self
+            .redis
+            .publish_stream_event(instance, "thought_deleted", &event_data)
+            .await
+        {
+            tracing::warn!(
+                "Failed to publish thought_deleted event for {}: {}",
+                thought_id,
+                e
+            );
+        }
+        let _ = self
+            .redis
+            .log_thought_event(
+                instance,
+                "thought_deleted",
+                thought_id,
+                thought.chain_id.as_deref(),
+                None,
+            )
+            .await;
+
+        Ok(Some(thought))
+    }
+
     async fn get_chain_thoughts(
         &self,
         instance: &str,
         chain_id: &str,
     ) -> Result<Vec<ThoughtRecord>> {
         let chain_key = format!("{instance}:chains:{chain_id}");
-        let thought_jsons = self
+        let (_, thought_jsons, _) = self
             .redis
-            .get_chain_thoughts_atomic(&chain_key, instance)
+            .get_chain_thoughts_atomic(&chain_key, instance, 0, -1)
             .await?;
         let mut thoughts = Vec::new();
         for json_str in thought_jsons {
@@ -183,45 +548,381 @@ impl ThoughtRepository for RedisThoughtRepository {
         Ok(thoughts)
     }
 
+    fn track_access(&self, instance: &str, thought_ids: Vec<String>) {
+        self.redis.spawn_record_access(instance, thought_ids);
+    }
+
+    async fn get_chain_thoughts_since(
+        &self,
+        instance: &str,
+        chain_id: &str,
+        start: usize,
+        limit: Option<usize>,
+    ) -> Result<(Vec<ThoughtRecord>, usize)> {
+        let (page, tail) = self
+            .get_chain_page(instance, chain_id, start, limit)
+            .await?;
+        Ok((page.into_iter().map(|(_, thought)| thought).collect(), tail))
+    }
+
+    async fn get_chain_page(
+        &self,
+        instance: &str,
+        chain_id: &str,
+        start: usize,
+        limit: Option<usize>,
+    ) -> Result<(Vec<(usize, ThoughtRecord)>, usize)> {
+        let stop = limit.map_or(-1, |n| (start + n.max(1)) as isize - 1);
+        let chain_key = format!("{instance}:chains:{chain_id}");
+        // Length and slice from the same script so the tail matches the delta
+        let (tail, thought_jsons, positions) = self
+            .redis
+            .get_chain_thoughts_atomic(&chain_key, instance, start, stop)
+            .await?;
+        let mut page = Vec::new();
+        for (json_str, position) in thought_jsons.into_iter().zip(positions) {
+            let thought: ThoughtRecord = serde_json::from_str(&json_str)
+                .map_err(crate::error::UnifiedIntelligenceError::Json)?;
+            page.push((position, thought));
+        }
+        Ok((page, tail))
+    }
+
     async fn search_thoughts(
         &self,
         instance: &str,
         query: &str,
+        filter: ThoughtSearchFilter,
         offset: i64,
         limit: i64,
-    ) -> Result<Vec<ThoughtRecord>> {
+    ) -> Result<(Vec<ThoughtRecord>, usize)> {
         let index_name = self.redi_search_index_name(instance);
-        let thought_jsons = self
+        let reply = self
             .redis
-            .search_thoughts_redisearch(&index_name, query, offset, limit)
+            .search_thoughts_redisearch(
+                &index_name,
+                &filter.apply(query),
+                filter.sort_by(),
+                offset,
+                limit,
+            )
             .await?;
+        parse_search_reply(reply)
+    }
 
-        let mut thoughts = Vec::new();
-        // The first element of the result is the total count, so skip it
-        for json_str in thought_jsons.into_iter().skip(1) {
-            let thought: ThoughtRecord = serde_json::from_str(&json_str)
-                .map_err(crate::error::UnifiedIntelligenceError::Json)?;
-            thoughts.push(thought);
+    async fn load_stuck_tracker(
+        &self,
+        instance: &str,
+        chain_id: &str,
+    ) -> Result<Option<StuckTracker>> {
+        let key = self.stuck_tracker_key(instance, chain_id);
+        let legacy_key = legacy_stuck_tracker_key(instance, chain_id);
+        let mut conn = self.redis.get_connection().await?;
+        read_stuck_tracker(&mut conn, &key, &legacy_key).await
+    }
+
+    async fn save_stuck_tracker(&self, instance: &str, tracker: &StuckTracker) -> Result<()> {
+        let key = self.stuck_tracker_key(instance, &tracker.chain_id);
+        let json =
+            serde_json::to_string(tracker).map_err(crate::error::UnifiedIntelligenceError::Json)?;
+        let mut conn = self.redis.get_connection().await?;
+        // Writing the current key retires the legacy one
+        let _: () = redis::pipe()
+            .atomic()
+            .set(&key, json)
+            .ignore()
+            .del(legacy_stuck_tracker_key(instance, &tracker.chain_id))
+            .ignore()
+            .query_async(&mut *conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn advance_stuck_tracker(
+        &self,
+        instance: &str,
+        chain_id: &str,
+    ) -> Result<(ThinkingMode, StuckTracker)> {
+        let key = self.stuck_tracker_key(instance, chain_id);
+        let legacy_key = legacy_stuck_tracker_key(instance, chain_id);
+        let attempts = self.config.stuck_tracker.max_retries.max(1);
+        let mut conn = self.redis.get_connection().await?;
+
+        // Optimistic lock: WATCH the key, advance locally, and commit with
+        // MULTI/EXEC. EXEC returns nil if another call modified the key first.
+        for _ in 0..attempts {
+            let _: () = redis::cmd("WATCH")
+                .arg(&key)
+                .arg(&legacy_key)
+                .query_async(&mut *conn)
+                .await?;
+            let planned = match read_stuck_tracker(&mut conn, &key, &legacy_key).await {
+                Ok(tracker) => {
+                    let mut tracker =
+                        tracker.unwrap_or_else(|| StuckTracker::new(chain_id.to_string()));
+                    let mode = tracker.next_approach();
+                    serde_json::to_string(&tracker)
+                        .map(|json| (mode, tracker, json))
+                        .map_err(crate::error::UnifiedIntelligenceError::Json)
+                }
+                Err(e) => Err(e),
+            };
+            let (mode, tracker, json) = match planned {
+                Ok(planned) => planned,
+                Err(e) => {
+                    let _: redis::RedisResult<()> =
+                        redis::cmd("UNWATCH").query_async(&mut *conn).await;
+                    return Err(e);
+                }
+            };
+
+            // Writing the current key retires the legacy one
+            let committed: Option<(redis::Value,)> = redis::pipe()
+                .atomic()
+                .set(&key, json)
+                .del(&legacy_key)
+                .ignore()
+                .query_async(&mut *conn)
+                .await?;
+            if committed.is_some() {
+                return Ok((mode, tracker));
+            }
+            tracing::debug!(
+                "StuckTracker update for chain {} contended, retrying",
+                chain_id
+            );
         }
-        Ok(thoughts)
+
+        Err(crate::error::UnifiedIntelligenceError::Internal(format!(
+            "StuckTracker update for chain {chain_id} failed after {attempts} attempts"
+        )))
+    }
+}
+
+/// Trackers used to live in RedisJSON under `{instance}:stuck:chain:{chain_id}`;
+/// they are read from there until the next advance rewrites them
+fn legacy_stuck_tracker_key(instance: &str, chain_id: &str) -> String {
+    format!("{instance}:stuck:chain:{chain_id}")
+}
+
+/// Current tracker for a chain, falling back to the legacy JSON document;
+/// `None` when neither exists or parses
+async fn read_stuck_tracker(
+    conn: &mut deadpool_redis::Connection,
+    key: &str,
+    legacy_key: &str,
+) -> Result<Option<StuckTracker>> {
+    let raw: Option<String> = redis::cmd("GET").arg(key).query_async(&mut **conn).await?;
+    if let Some(tracker) = raw.and_then(|json| serde_json::from_str::<StuckTracker>(&json).ok()) {
+        return Ok(Some(tracker));
     }
+    let legacy: Option<String> = redis::cmd("JSON.GET")
+        .arg(legacy_key)
+        .arg("$")
+        .query_async(&mut **conn)
+        .await?;
+    Ok(legacy
+        .and_then(|json| serde_json::from_str::<Vec<StuckTracker>>(&json).ok())
+        .and_then(|trackers| trackers.into_iter().next()))
 }
 
 // ========== KNOWLEDGE GRAPH REPOSITORY IMPLEMENTATION ==========
 
-use crate::models::{EntityType, KnowledgeNode, KnowledgeRelation, KnowledgeScope};
+use crate::config::EntityVector;
+use crate::models::{
+    EntityType, KnowledgeNode, KnowledgeRelation, KnowledgeScope, NameIndexReport,
+};
 use crate::repository_traits::KnowledgeRepository;
 use redis::{RedisError, Script};
 
+/// Attempts at a merge before giving up when relations keep changing under it
+const MERGE_ATTEMPTS: usize = 5;
+use std::collections::HashMap;
+
+/// Subpaths needed to evaluate a search filter and, under
+/// `knowledge.projected_search`, to answer list/search without loading the
+/// whole entity
+const SEARCH_NODE_PATHS: &[&str] = &[
+    "$.id",
+    "$.name",
+    "$.display_name",
+    "$.tags",
+    "$.entity_type",
+    "$.archived",
+    "$.archived_at",
+    "$.scope",
+    "$.created_at",
+    "$.updated_at",
+    "$.created_by",
+];
+
+/// Search result built from the projected `SEARCH_NODE_PATHS`: attributes,
+/// thought ids, embedding and metadata come back empty. `None` when a
+/// required field is missing.
+fn projected_node(fields: &HashMap<String, serde_json::Value>) -> Option<KnowledgeNode> {
+    let mut doc = serde_json::json!({
+        "attributes": {},
+        "thought_ids": [],
+        "embedding": null,
+        "metadata": {
+            "auto_extracted": false,
+            "extraction_source": null,
+            "extraction_timestamp": null,
+        },
+    });
+    for (path, value) in fields {
+        if let Some(field) = path.strip_prefix("$.") {
+            doc[field] = value.clone();
+        }
+    }
+    serde_json::from_value(doc).ok()
+}
+
+/// Split the search script's reply into the thoughts of the page and the
+/// total match count (its first element)
+pub fn parse_search_reply(reply: Vec<String>) -> Result<(Vec<ThoughtRecord>, usize)> {
+    let mut reply = reply.into_iter();
+    let total = reply
+        .next()
+        .and_then(|total| total.parse().ok())
+        .unwrap_or(0);
+    let thoughts = reply
+        .map(|json_str| {
+            serde_json::from_str(&json_str).map_err(crate::error::UnifiedIntelligenceError::Json)
+        })
+        .collect::<Result<Vec<ThoughtRecord>>>()?;
+    Ok((thoughts, total))
+}
+
+/// Fold `from` into `to` for an entity merge: tags and thought ids are unioned
+/// keeping `to`'s order, and `from`'s attributes only fill keys `to` lacks
+pub fn merge_entity_fields(mut to: KnowledgeNode, from: &KnowledgeNode) -> KnowledgeNode {
+    for tag in &from.tags {
+        if !to.tags.contains(tag) {
+            to.tags.push(tag.clone());
+        }
+    }
+    for thought_id in &from.thought_ids {
+        if !to.thought_ids.contains(thought_id) {
+            to.thought_ids.push(thought_id.clone());
+        }
+    }
+    for (key, value) in &from.attributes {
+        to.attributes
+            .entry(key.clone())
+            .or_insert_with(|| value.clone());
+    }
+    to.updated_at = chrono::Utc::now();
+    to
+}
+
+/// Repoint the ends of a relation at `from_id` to `to_id`; `None` when the
+/// result would relate `to_id` to itself (the relation linked the merged pair)
+pub fn repoint_relation(
+    mut relation: KnowledgeRelation,
+    from_id: &str,
+    to_id: &str,
+) -> Option<KnowledgeRelation> {
+    if relation.from_entity_id == from_id {
+        relation.from_entity_id = to_id.to_string();
+    }
+    if relation.to_entity_id == from_id {
+        relation.to_entity_id = to_id.to_string();
+    }
+    (relation.from_entity_id != relation.to_entity_id).then_some(relation)
+}
+
+/// Text relevance of an entity to a search query in [0, 1.1]: exact name match
+/// beats prefix beats substring, name beats display name beats tags. Shorter
+/// names covering more of the query rank higher within a tier.
+pub fn text_relevance(node: &KnowledgeNode, query: &str) -> f64 {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return 0.0;
+    }
+    let name = node.name.to_lowercase();
+    let display = node.display_name.to_lowercase();
+    let tier = if name == query || display == query {
+        1.0
+    } else if name.starts_with(&query) {
+        0.8
+    } else if display.starts_with(&query) {
+        0.7
+    } else if name.contains(&query) {
+        0.6
+    } else if display.contains(&query) {
+        0.5
+    } else if node.tags.iter().any(|t| t.to_lowercase() == query) {
+        0.4
+    } else if node.tags.iter().any(|t| t.to_lowercase().contains(&query)) {
+        0.3
+    } else {
+        return 0.0;
+    };
+    let coverage = (query.len() as f64 / name.len().max(1) as f64).min(1.0);
+    tier + 0.1 * coverage
+}
+
+fn same_entity_type(a: &EntityType, b: &EntityType) -> bool {
+    match (a, b) {
+        (EntityType::Custom(a), EntityType::Custom(b)) => a == b,
+        _ => std::mem::discriminant(a) == std::mem::discriminant(b),
+    }
+}
+
+/// `JSON.GET key path...` returning the first match for each path.
+///
+/// RedisJSON answers a single path with a bare array and several paths with an
+/// object keyed by path; both are normalized here. Paths with no match are omitted.
+async fn fetch_entity_fields(
+    conn: &mut deadpool_redis::Connection,
+    key: &str,
+    paths: &[&str],
+) -> Result<HashMap<String, serde_json::Value>> {
+    let json_str: Option<String> = redis::cmd("JSON.GET")
+        .arg(key)
+        .arg(paths)
+        .query_async(&mut **conn)
+        .await
+        .map_err(crate::error::UnifiedIntelligenceError::Redis)?;
+    let json_str = json_str
+        .ok_or_else(|| crate::error::UnifiedIntelligenceError::NotFound(key.to_string()))?;
+    let raw: serde_json::Value =
+        serde_json::from_str(&json_str).map_err(crate::error::UnifiedIntelligenceError::Json)?;
+
+    let first = |v: serde_json::Value| match v {
+        serde_json::Value::Array(mut items) if !items.is_empty() => Some(items.swap_remove(0)),
+        _ => None,
+    };
+    let mut fields = HashMap::new();
+    match (paths, raw) {
+        ([path], value) => {
+            if let Some(v) = first(value) {
+                fields.insert(path.to_string(), v);
+            }
+        }
+        (_, serde_json::Value::Object(map)) => {
+            for (path, value) in map {
+                if let Some(v) = first(value) {
+                    fields.insert(path, v);
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(fields)
+}
+
 pub struct RedisKnowledgeRepository {
     redis_manager: Arc<RedisManager>,
+    config: Arc<Config>,
     // Atomic script for entity creation + index update
     create_entity_script: Script,
     instance_id: String,
 }
 
 impl RedisKnowledgeRepository {
-    pub fn new(redis_manager: Arc<RedisManager>, instance_id: String) -> Self {
+    pub fn new(redis_manager: Arc<RedisManager>, config: Arc<Config>, instance_id: String) -> Self {
         // Lua script for atomic entity creation + index update
         let create_entity_script = Script::new(
             r#"
@@ -243,6 +944,7 @@ impl RedisKnowledgeRepository {
 
         Self {
             redis_manager,
+            config,
             create_entity_script,
             instance_id,
         }
@@ -282,6 +984,95 @@ impl RedisKnowledgeRepository {
         format!("{prefix}:KG:index:entity_relations:{entity_id}")
     }
 
+    /// Read both entities and build the MULTI that folds `from` into `to`;
+    /// returns it with the `from` entity and the merged result
+    async fn merge_pipeline(
+        &self,
+        from_id: &str,
+        to_id: &str,
+        scope: &KnowledgeScope,
+    ) -> Result<(redis::Pipeline, KnowledgeNode, KnowledgeNode)> {
+        let from = self.get_entity(from_id, scope).await?;
+        let to = self.get_entity(to_id, scope).await?;
+        let relations = self.get_relations(from_id, scope).await?;
+        let merged = merge_entity_fields(to, &from);
+        let merged_json =
+            serde_json::to_string(&merged).map_err(crate::error::UnifiedIntelligenceError::Json)?;
+
+        // One MULTI so a failure leaves either both entities or only the merged
+        // one, never relations or index entries pointing at a deleted entity
+        let to_index = self.get_relation_index_key(to_id, scope);
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        let now = chrono::Utc::now().to_rfc3339();
+        for relation in relations {
+            let relation_id = relation.id.clone();
+            let relation_key = self.get_relation_key(&relation_id, scope);
+            match repoint_relation(relation, from_id, to_id) {
+                Some(relation) => {
+                    let json_str = serde_json::to_string(&relation)
+                        .map_err(crate::error::UnifiedIntelligenceError::Json)?;
+                    let direction = if relation.from_entity_id == to_id {
+                        "outgoing"
+                    } else {
+                        "incoming"
+                    };
+                    pipe.cmd("JSON.SET")
+                        .arg(&relation_key)
+                        .arg("$")
+                        .arg(json_str)
+                        .ignore()
+                        .hset(&to_index, format!("{direction}:{relation_id}"), &now)
+                        .ignore();
+                }
+                // Relations between the merged pair would become self-loops
+                None => {
+                    pipe.del(&relation_key)
+                        .ignore()
+                        .hdel(&to_index, format!("outgoing:{relation_id}"))
+                        .ignore()
+                        .hdel(&to_index, format!("incoming:{relation_id}"))
+                        .ignore();
+                }
+            }
+        }
+        pipe.cmd("JSON.SET")
+            .arg(self.get_entity_key(to_id, scope))
+            .arg("$")
+            .arg(merged_json)
+            .ignore()
+            .del(self.get_relation_index_key(from_id, scope))
+            .ignore();
+        for vector in [EntityVector::Def, EntityVector::Context] {
+            pipe.del(format!(
+                "{}:embeddings:{}:{from_id}",
+                self.instance_id,
+                vector.kind()
+            ))
+            .ignore();
+        }
+        // The name index must end up on the surviving id, including when both
+        // entities share a name and it pointed at `from`
+        let name_index = self.get_index_key(scope);
+        if !merged.archived {
+            pipe.hset(&name_index, &merged.name, to_id).ignore();
+        }
+        if from.name != merged.name {
+            let mut conn = self.redis_manager.get_connection().await?;
+            let indexed: Option<String> =
+                redis::AsyncCommands::hget(&mut conn, &name_index, &from.name)
+                    .await
+                    .map_err(|e: RedisError| crate::error::UnifiedIntelligenceError::Redis(e))?;
+            if indexed.as_deref() == Some(from_id) {
+                pipe.hdel(&name_index, &from.name).ignore();
+            }
+        }
+        // Left unignored: EXEC yields nil for it when the WATCH aborted
+        pipe.del(self.get_entity_key(from_id, scope));
+
+        Ok((pipe, from, merged))
+    }
+
     // SCAN-based search as fallback (non-blocking)
     async fn search_entities_with_scan(
         &self,
@@ -289,76 +1080,77 @@ impl RedisKnowledgeRepository {
         scope: &KnowledgeScope,
         entity_type: Option<&EntityType>,
         limit: usize,
-    ) -> Result<Vec<KnowledgeNode>> {
-        let mut conn = self.redis_manager.get_connection().await?;
+        include_archived: bool,
+    ) -> Result<(Vec<KnowledgeNode>, usize, bool)> {
         let prefix = match scope {
             KnowledgeScope::Personal => &self.instance_id,
             _ => &scope.to_string(),
         };
         let pattern = format!("{prefix}:KG:entity:*");
-        let mut cursor = 0u64;
-        let mut results = Vec::new();
-
-        // Use SCAN instead of KEYS to avoid blocking
-        loop {
-            let (new_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
-                .arg(cursor)
-                .arg("MATCH")
-                .arg(&pattern)
-                .arg("COUNT")
-                .arg(100) // Process in batches of 100
-                .query_async(&mut conn)
-                .await
-                .map_err(|e: RedisError| crate::error::UnifiedIntelligenceError::Redis(e))?;
 
-            for key in keys {
-                if let Ok(json_str) = redis::cmd("JSON.GET")
-                    .arg(&key)
-                    .arg("$")
-                    .query_async::<String>(&mut conn)
-                    .await
-                {
-                    if let Ok(nodes) = serde_json::from_str::<Vec<KnowledgeNode>>(&json_str) {
-                        for node in nodes {
-                            // Filter by query (check name, tags, and display_name)
-                            let matches_query =
-                                node.name.to_lowercase().contains(&query.to_lowercase())
-                                    || node
-                                        .display_name
-                                        .to_lowercase()
-                                        .contains(&query.to_lowercase())
-                                    || node.tags.iter().any(|tag| {
-                                        tag.to_lowercase().contains(&query.to_lowercase())
-                                    });
-
-                            // Filter by entity type if specified
-                            let matches_type =
-                                entity_type.is_none_or(|et| match (&node.entity_type, et) {
-                                    (EntityType::Custom(a), EntityType::Custom(b)) => a == b,
-                                    _ => {
-                                        std::mem::discriminant(&node.entity_type)
-                                            == std::mem::discriminant(et)
-                                    }
-                                });
-
-                            if matches_query && matches_type {
-                                results.push(node);
-                                if results.len() >= limit {
-                                    return Ok(results);
-                                }
-                            }
-                        }
-                    }
-                }
+        // Bounded SCAN instead of KEYS to avoid blocking
+        let (keys, truncated) = self
+            .redis_manager
+            .scan_match(
+                &pattern,
+                self.config.redis.scan_count,
+                self.config.redis.scan_max_keys,
+            )
+            .await?;
+        let mut conn = self.redis_manager.get_connection().await?;
+        let query = query.to_lowercase();
+
+        // Match, count and rank on the small projected fields; only the page
+        // is loaded in full (unless `projected_search` returns projections)
+        let mut matches: Vec<(String, KnowledgeNode, f64)> = Vec::new();
+        for key in keys {
+            let Ok(fields) = fetch_entity_fields(&mut conn, &key, SEARCH_NODE_PATHS).await else {
+                continue;
+            };
+            let Some(node) = projected_node(&fields) else {
+                continue;
+            };
+            if node.archived && !include_archived {
+                continue;
             }
-
-            cursor = new_cursor;
-            if cursor == 0 {
-                break; // Scan complete
+            // Filter by query (check name, tags, and display_name)
+            let matches_query = node.name.to_lowercase().contains(&query)
+                || node.display_name.to_lowercase().contains(&query)
+                || node
+                    .tags
+                    .iter()
+                    .any(|tag| tag.to_lowercase().contains(&query));
+            // Filter by entity type if specified
+            let matches_type = entity_type.is_none_or(|et| same_entity_type(&node.entity_type, et));
+            if matches_query && matches_type {
+                let score = text_relevance(&node, &query);
+                matches.push((key, node, score));
             }
         }
-
-        Ok(results)
+        let total = matches.len();
+        // Stable: equal scores (and an empty query) keep scan order
+        matches.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
+        matches.truncate(limit);
+
+        if self.config.knowledge.projected_search {
+            return Ok((
+                matches.into_iter().map(|(_, node, _)| node).collect(),
+                total,
+                truncated,
+            ));
+        }
+        let mut pipe = redis::pipe();
+        for (key, _, _) in &matches {
+            pipe.cmd("JSON.GET").arg(key).arg("$");
+        }
+        let docs: Vec<Option<String>> = pipe.query_async(&mut *conn).await?;
+        let results = docs
+            .into_iter()
+            .flatten()
+            .filter_map(|json_str| serde_json::from_str::<Vec<KnowledgeNode>>(&json_str).ok())
+            .filter_map(|mut nodes| nodes.pop())
+            .collect();
+        Ok((results, total, truncated))
     }
 
     // Missing relation index implementation
@@ -390,6 +1182,7 @@ impl RedisKnowledgeRepository {
 #[async_trait]
 impl KnowledgeRepository for RedisKnowledgeRepository {
     async fn create_entity(&self, node: KnowledgeNode) -> Result<()> {
+        self.redis_manager.ensure_writable()?;
         let mut conn = self.redis_manager.get_connection().await?;
         let entity_key = self.get_entity_key(&node.id, &node.scope);
         let index_key = self.get_index_key(&node.scope);
@@ -441,6 +1234,32 @@ impl KnowledgeRepository for RedisKnowledgeRepository {
         })
     }
 
+    async fn get_entity_fields(
+        &self,
+        id: &str,
+        scope: &KnowledgeScope,
+        paths: &[&str],
+    ) -> Result<HashMap<String, serde_json::Value>> {
+        if paths.is_empty() {
+            return Err(crate::error::UnifiedIntelligenceError::Validation {
+                field: "paths".to_string(),
+                reason: "at least one JSON path is required".to_string(),
+            });
+        }
+        let mut conn = self.redis_manager.get_connection().await?;
+        let key = self.get_entity_key(id, scope);
+        fetch_entity_fields(&mut conn, &key, paths)
+            .await
+            .map_err(|e| match e {
+                crate::error::UnifiedIntelligenceError::NotFound(_) => {
+                    crate::error::UnifiedIntelligenceError::NotFound(format!(
+                        "Entity {id} not found"
+                    ))
+                }
+                other => other,
+            })
+    }
+
     async fn get_entity_by_name(
         &self,
         name: &str,
@@ -464,6 +1283,7 @@ impl KnowledgeRepository for RedisKnowledgeRepository {
     }
 
     async fn update_entity(&self, node: KnowledgeNode) -> Result<()> {
+        self.redis_manager.ensure_writable()?;
         let mut conn = self.redis_manager.get_connection().await?;
         let entity_key = self.get_entity_key(&node.id, &node.scope);
 
@@ -511,24 +1331,106 @@ impl KnowledgeRepository for RedisKnowledgeRepository {
         Ok(())
     }
 
+    async fn archive_entity(&self, id: &str, scope: &KnowledgeScope) -> Result<KnowledgeNode> {
+        let mut entity = self.get_entity(id, scope).await?;
+        if !entity.set_archived(true, chrono::Utc::now()) {
+            return Ok(entity);
+        }
+        let entity_key = self.get_entity_key(id, scope);
+        let index_key = self.get_index_key(scope);
+        let json_str =
+            serde_json::to_string(&entity).map_err(crate::error::UnifiedIntelligenceError::Json)?;
+
+        let mut conn = self.redis_manager.get_connection().await?;
+        // Leave the name alone if it already points at another entity
+        let indexed: Option<String> =
+            redis::AsyncCommands::hget(&mut conn, &index_key, &entity.name)
+                .await
+                .map_err(|e: RedisError| crate::error::UnifiedIntelligenceError::Redis(e))?;
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .cmd("JSON.SET")
+            .arg(&entity_key)
+            .arg("$")
+            .arg(&json_str)
+            .ignore();
+        if indexed.as_deref() == Some(id) {
+            pipe.hdel(&index_key, &entity.name).ignore();
+        }
+        pipe.query_async::<()>(&mut *conn)
+            .await
+            .map_err(crate::error::UnifiedIntelligenceError::Redis)?;
+
+        tracing::info!(
+            "Archived knowledge entity '{}' in {} scope",
+            entity.name,
+            scope
+        );
+        Ok(entity)
+    }
+
+    async fn unarchive_entity(&self, id: &str, scope: &KnowledgeScope) -> Result<KnowledgeNode> {
+        let mut entity = self.get_entity(id, scope).await?;
+        if !entity.set_archived(false, chrono::Utc::now()) {
+            return Ok(entity);
+        }
+        let entity_key = self.get_entity_key(id, scope);
+        let index_key = self.get_index_key(scope);
+        let json_str =
+            serde_json::to_string(&entity).map_err(crate::error::UnifiedIntelligenceError::Json)?;
+
+        let mut conn = self.redis_manager.get_connection().await?;
+        let indexed: Option<String> =
+            redis::AsyncCommands::hget(&mut conn, &index_key, &entity.name)
+                .await
+                .map_err(|e: RedisError| crate::error::UnifiedIntelligenceError::Redis(e))?;
+        if let Some(other) = indexed.filter(|other| other != id) {
+            return Err(crate::error::UnifiedIntelligenceError::Validation {
+                field: "entity_id".to_string(),
+                reason: format!(
+                    "name '{}' now belongs to entity {other}; rename or merge before unarchiving",
+                    entity.name
+                ),
+            });
+        }
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .cmd("JSON.SET")
+            .arg(&entity_key)
+            .arg("$")
+            .arg(&json_str)
+            .ignore()
+            .hset(&index_key, &entity.name, id)
+            .ignore();
+        pipe.query_async::<()>(&mut *conn)
+            .await
+            .map_err(crate::error::UnifiedIntelligenceError::Redis)?;
+
+        tracing::info!(
+            "Unarchived knowledge entity '{}' in {} scope",
+            entity.name,
+            scope
+        );
+        Ok(entity)
+    }
+
     async fn search_entities(
         &self,
         query: &str,
         scope: &KnowledgeScope,
         entity_type: Option<&EntityType>,
         limit: usize,
-    ) -> Result<Vec<KnowledgeNode>> {
+        include_archived: bool,
+    ) -> Result<(Vec<KnowledgeNode>, usize, bool)> {
         tracing::info!("Searching for '{}' in {} scope", query, scope);
 
         // Use SCAN-based search (production-safe)
-        let entities = self
-            .search_entities_with_scan(query, scope, entity_type, limit)
-            .await?;
-
-        Ok(entities)
+        self.search_entities_with_scan(query, scope, entity_type, limit, include_archived)
+            .await
     }
 
     async fn create_relation(&self, relation: KnowledgeRelation) -> Result<()> {
+        self.redis_manager.ensure_writable()?;
         let mut conn = self.redis_manager.get_connection().await?;
         let relation_key = self.get_relation_key(&relation.id, &relation.scope);
         let json_str = serde_json::to_string(&relation)
@@ -568,6 +1470,122 @@ impl KnowledgeRepository for RedisKnowledgeRepository {
         Ok(())
     }
 
+    async fn delete_relation(
+        &self,
+        relation_id: &str,
+        scope: &KnowledgeScope,
+    ) -> Result<KnowledgeRelation> {
+        let mut conn = self.redis_manager.get_connection().await?;
+        let relation_key = self.get_relation_key(relation_id, scope);
+        let not_found = || {
+            crate::error::UnifiedIntelligenceError::NotFound(format!(
+                "Relation {relation_id} not found"
+            ))
+        };
+
+        // Load first: the endpoints name the index hashes to clean up
+        let json_str: Option<String> = redis::cmd("JSON.GET")
+            .arg(&relation_key)
+            .arg("$")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e: RedisError| crate::error::UnifiedIntelligenceError::Redis(e))?;
+        let json_str = json_str.ok_or_else(not_found)?;
+        let relation = serde_json::from_str::<Vec<KnowledgeRelation>>(&json_str)
+            .map_err(crate::error::UnifiedIntelligenceError::Json)?
+            .into_iter()
+            .next()
+            .ok_or_else(not_found)?;
+
+        let _: () = redis::pipe()
+            .atomic()
+            .del(&relation_key)
+            .ignore()
+            .hdel(
+                self.get_relation_index_key(&relation.from_entity_id, scope),
+                format!("outgoing:{relation_id}"),
+            )
+            .ignore()
+            .hdel(
+                self.get_relation_index_key(&relation.to_entity_id, scope),
+                format!("incoming:{relation_id}"),
+            )
+            .ignore()
+            .query_async(&mut conn)
+            .await
+            .map_err(|e: RedisError| crate::error::UnifiedIntelligenceError::Redis(e))?;
+
+        tracing::info!(
+            "Deleted relation '{}' from {} to {}",
+            relation.relationship_type,
+            relation.from_entity_id,
+            relation.to_entity_id
+        );
+        Ok(relation)
+    }
+
+    async fn merge_entities(
+        &self,
+        from_id: &str,
+        to_id: &str,
+        scope: &KnowledgeScope,
+    ) -> Result<KnowledgeNode> {
+        self.redis_manager.ensure_writable()?;
+        if from_id == to_id {
+            return Err(crate::error::UnifiedIntelligenceError::Validation {
+                field: "to_entity_id".to_string(),
+                reason: "cannot merge an entity into itself".to_string(),
+            });
+        }
+        let mut conn = self.redis_manager.get_connection().await?;
+        let watched = [
+            self.get_entity_key(from_id, scope),
+            self.get_entity_key(to_id, scope),
+            self.get_relation_index_key(from_id, scope),
+            self.get_relation_index_key(to_id, scope),
+        ];
+
+        // Optimistic lock: an edit to either entity, or a relation created or
+        // deleted on either, between the reads and EXEC aborts the merge,
+        // which is retried on fresh reads
+        for _ in 0..MERGE_ATTEMPTS {
+            let _: () = redis::cmd("WATCH")
+                .arg(&watched)
+                .query_async(&mut conn)
+                .await?;
+            let (pipe, from, merged) = match self.merge_pipeline(from_id, to_id, scope).await {
+                Ok(planned) => planned,
+                Err(e) => {
+                    let _: redis::RedisResult<()> =
+                        redis::cmd("UNWATCH").query_async(&mut conn).await;
+                    return Err(e);
+                }
+            };
+            let committed: Option<(redis::Value,)> = pipe
+                .query_async(&mut conn)
+                .await
+                .map_err(|e: RedisError| crate::error::UnifiedIntelligenceError::Redis(e))?;
+            if committed.is_some() {
+                tracing::info!(
+                    "Merged knowledge entity '{}' into '{}' in {} scope",
+                    from.name,
+                    merged.name,
+                    scope
+                );
+                return Ok(merged);
+            }
+            tracing::debug!(
+                "Merge of entity {} into {} contended, retrying",
+                from_id,
+                to_id
+            );
+        }
+
+        Err(crate::error::UnifiedIntelligenceError::Internal(format!(
+            "Merging entity {from_id} into {to_id} failed after {MERGE_ATTEMPTS} attempts"
+        )))
+    }
+
     async fn get_relations(
         &self,
         entity_id: &str,
@@ -615,6 +1633,114 @@ impl KnowledgeRepository for RedisKnowledgeRepository {
         Ok(())
     }
 
+    async fn repair_name_index(
+        &self,
+        scope: &KnowledgeScope,
+        apply: bool,
+    ) -> Result<NameIndexReport> {
+        let prefix = match scope {
+            KnowledgeScope::Personal => &self.instance_id,
+            _ => &scope.to_string(),
+        };
+        let entity_prefix = format!("{prefix}:KG:entity:");
+        let (keys, truncated) = self
+            .redis_manager
+            .scan_match(
+                &format!("{entity_prefix}*"),
+                self.config.redis.scan_count,
+                self.config.redis.scan_max_keys,
+            )
+            .await?;
+
+        let mut conn = self.redis_manager.get_connection().await?;
+
+        // Authoritative name -> id mapping from the entity documents
+        // Archived entities are deliberately absent from the index
+        let mut pipe = redis::pipe();
+        for key in &keys {
+            pipe.cmd("JSON.GET")
+                .arg(key)
+                .arg("$.name")
+                .arg("$.archived");
+        }
+        let names: Vec<Option<String>> = if keys.is_empty() {
+            Vec::new()
+        } else {
+            pipe.query_async(&mut *conn)
+                .await
+                .map_err(crate::error::UnifiedIntelligenceError::Redis)?
+        };
+        let mut expected: HashMap<String, String> = HashMap::new();
+        for (key, raw) in keys.iter().zip(names) {
+            let fields = raw
+                .and_then(|r| {
+                    serde_json::from_str::<HashMap<String, Vec<serde_json::Value>>>(&r).ok()
+                })
+                .unwrap_or_default();
+            let first = |path: &str| fields.get(path).and_then(|v| v.first());
+            if first("$.archived").and_then(|v| v.as_bool()) == Some(true) {
+                continue;
+            }
+            let name = first("$.name").and_then(|v| v.as_str()).map(str::to_string);
+            if let (Some(name), Some(id)) = (name, key.strip_prefix(&entity_prefix)) {
+                expected.insert(name, id.to_string());
+            }
+        }
+
+        let index_key = self.get_index_key(scope);
+        let current: HashMap<String, String> = redis::AsyncCommands::hgetall(&mut conn, &index_key)
+            .await
+            .map_err(|e: RedisError| crate::error::UnifiedIntelligenceError::Redis(e))?;
+
+        let mut report = NameIndexReport {
+            scanned_entities: keys.len(),
+            truncated,
+            applied: apply,
+            ..Default::default()
+        };
+        for (name, id) in &expected {
+            match current.get(name) {
+                None => report.missing.push(name.clone()),
+                Some(indexed) if indexed != id => report.stale.push(name.clone()),
+                Some(_) => {}
+            }
+        }
+        for name in current.keys() {
+            if !expected.contains_key(name) {
+                report.orphaned.push(name.clone());
+            }
+        }
+        report.missing.sort();
+        report.stale.sort();
+        report.orphaned.sort();
+
+        // A truncated scan can't prove an entry is orphaned
+        if truncated && !report.orphaned.is_empty() {
+            tracing::warn!(
+                "repair_name_index: scan hit scan_max_keys; orphan removal skipped for {} entries",
+                report.orphaned.len()
+            );
+            report.orphaned.clear();
+        }
+
+        if apply {
+            let mut pipe = redis::pipe();
+            pipe.atomic();
+            for name in report.missing.iter().chain(report.stale.iter()) {
+                pipe.hset(&index_key, name, &expected[name]);
+            }
+            if !report.orphaned.is_empty() {
+                pipe.hdel(&index_key, &report.orphaned);
+            }
+            let _: () = pipe
+                .query_async(&mut *conn)
+                .await
+                .map_err(crate::error::UnifiedIntelligenceError::Redis)?;
+        }
+
+        Ok(report)
+    }
+
     async fn set_active_entity(
         &self,
         session_key: &str,
@@ -640,12 +1766,64 @@ impl KnowledgeRepository for RedisKnowledgeRepository {
         Ok(())
     }
 
+    async fn get_active_entity(
+        &self,
+        session_key: &str,
+    ) -> Result<Option<(String, KnowledgeScope)>> {
+        let mut conn = self.redis_manager.get_connection().await?;
+        let raw: Option<String> = redis::AsyncCommands::get(&mut conn, session_key)
+            .await
+            .map_err(|e: RedisError| crate::error::UnifiedIntelligenceError::Redis(e))?;
+        let Some(raw) = raw else {
+            return Ok(None);
+        };
+
+        let value: serde_json::Value =
+            serde_json::from_str(&raw).map_err(crate::error::UnifiedIntelligenceError::Json)?;
+        let entity_id = value
+            .get("entity_id")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        let scope = value
+            .get("scope")
+            .cloned()
+            .and_then(|v| serde_json::from_value::<KnowledgeScope>(v).ok())
+            .unwrap_or_default();
+        Ok(entity_id.map(|id| (id, scope)))
+    }
+
+    async fn clear_active_entity(&self, session_key: &str) -> Result<bool> {
+        let mut conn = self.redis_manager.get_connection().await?;
+        let removed: i64 = redis::AsyncCommands::del(&mut conn, session_key)
+            .await
+            .map_err(|e: RedisError| crate::error::UnifiedIntelligenceError::Redis(e))?;
+        Ok(removed > 0)
+    }
+
+    async fn get_relation_counts(
+        &self,
+        entity_ids: &[String],
+        scope: &KnowledgeScope,
+    ) -> Result<Vec<usize>> {
+        let mut pipe = redis::pipe();
+        for id in entity_ids {
+            pipe.cmd("HLEN").arg(self.get_relation_index_key(id, scope));
+        }
+        let mut conn = self.redis_manager.get_connection().await?;
+        let counts: Vec<usize> = pipe
+            .query_async(&mut *conn)
+            .await
+            .map_err(crate::error::UnifiedIntelligenceError::Redis)?;
+        Ok(counts)
+    }
+
     async fn add_thought_to_entity(
         &self,
         entity_name: &str,
         thought_id: &str,
         scope: &KnowledgeScope,
     ) -> Result<()> {
+        self.redis_manager.ensure_writable()?;
         // Get entity by name first
         let entity = self.get_entity_by_name(entity_name, scope).await?;
         let entity_key = self.get_entity_key(&entity.id, scope);
@@ -675,14 +1853,18 @@ pub struct CombinedRedisRepository {
 impl CombinedRedisRepository {
     pub fn new(redis_manager: Arc<RedisManager>, config: Arc<Config>, instance_id: String) -> Self {
         let thought_repo =
-            RedisThoughtRepository::new(redis_manager.clone(), config, instance_id.clone());
-        let knowledge_repo = RedisKnowledgeRepository::new(redis_manager, instance_id);
+            RedisThoughtRepository::new(redis_manager.clone(), config.clone(), instance_id.clone());
+        let knowledge_repo = RedisKnowledgeRepository::new(redis_manager, config, instance_id);
 
         Self {
             thought_repo,
             knowledge_repo,
         }
     }
+
+    pub async fn ensure_search_index(&self, instance: &str) -> Result<bool> {
+        self.thought_repo.ensure_search_index(instance).await
+    }
 }
 
 #[async_trait]
@@ -691,6 +1873,16 @@ impl ThoughtRepository for CombinedRedisRepository {
         self.thought_repo.save_thought(thought).await
     }
 
+    async fn save_thought_with_options(
+        &self,
+        thought: &ThoughtRecord,
+        skip_event: bool,
+    ) -> Result<()> {
+        self.thought_repo
+            .save_thought_with_options(thought, skip_event)
+            .await
+    }
+
     async fn save_chain_metadata(&self, metadata: &ChainMetadata) -> Result<()> {
         self.thought_repo.save_chain_metadata(metadata).await
     }
@@ -699,10 +1891,34 @@ impl ThoughtRepository for CombinedRedisRepository {
         self.thought_repo.chain_exists(chain_id).await
     }
 
+    async fn chain_belongs_to(&self, instance: &str, chain_id: &str) -> Result<bool> {
+        self.thought_repo.chain_belongs_to(instance, chain_id).await
+    }
+
+    async fn prune_chains(
+        &self,
+        instance: &str,
+        max_thoughts: usize,
+        idle_since: chrono::DateTime<chrono::Utc>,
+        apply: bool,
+    ) -> Result<ChainPruneReport> {
+        self.thought_repo
+            .prune_chains(instance, max_thoughts, idle_since, apply)
+            .await
+    }
+
     async fn get_thought(&self, instance: &str, thought_id: &str) -> Result<Option<ThoughtRecord>> {
         self.thought_repo.get_thought(instance, thought_id).await
     }
 
+    async fn delete_thought(
+        &self,
+        instance: &str,
+        thought_id: &str,
+    ) -> Result<Option<ThoughtRecord>> {
+        self.thought_repo.delete_thought(instance, thought_id).await
+    }
+
     async fn get_chain_thoughts(
         &self,
         instance: &str,
@@ -713,15 +1929,70 @@ impl ThoughtRepository for CombinedRedisRepository {
             .await
     }
 
+    fn track_access(&self, instance: &str, thought_ids: Vec<String>) {
+        self.thought_repo.track_access(instance, thought_ids)
+    }
+
+    async fn get_chain_thoughts_since(
+        &self,
+        instance: &str,
+        chain_id: &str,
+        start: usize,
+        limit: Option<usize>,
+    ) -> Result<(Vec<ThoughtRecord>, usize)> {
+        self.thought_repo
+            .get_chain_thoughts_since(instance, chain_id, start, limit)
+            .await
+    }
+
+    async fn get_chain_page(
+        &self,
+        instance: &str,
+        chain_id: &str,
+        start: usize,
+        limit: Option<usize>,
+    ) -> Result<(Vec<(usize, ThoughtRecord)>, usize)> {
+        self.thought_repo
+            .get_chain_page(instance, chain_id, start, limit)
+            .await
+    }
+
     async fn search_thoughts(
         &self,
         instance: &str,
         query: &str,
+        filter: ThoughtSearchFilter,
         offset: i64,
         limit: i64,
-    ) -> Result<Vec<ThoughtRecord>> {
+    ) -> Result<(Vec<ThoughtRecord>, usize)> {
+        self.thought_repo
+            .search_thoughts(instance, query, filter, offset, limit)
+            .await
+    }
+
+    async fn load_stuck_tracker(
+        &self,
+        instance: &str,
+        chain_id: &str,
+    ) -> Result<Option<StuckTracker>> {
+        self.thought_repo
+            .load_stuck_tracker(instance, chain_id)
+            .await
+    }
+
+    async fn save_stuck_tracker(&self, instance: &str, tracker: &StuckTracker) -> Result<()> {
         self.thought_repo
-            .search_thoughts(instance, query, offset, limit)
+            .save_stuck_tracker(instance, tracker)
+            .await
+    }
+
+    async fn advance_stuck_tracker(
+        &self,
+        instance: &str,
+        chain_id: &str,
+    ) -> Result<(ThinkingMode, StuckTracker)> {
+        self.thought_repo
+            .advance_stuck_tracker(instance, chain_id)
             .await
     }
 }
@@ -736,6 +2007,17 @@ impl KnowledgeRepository for CombinedRedisRepository {
         self.knowledge_repo.get_entity(id, scope).await
     }
 
+    async fn get_entity_fields(
+        &self,
+        id: &str,
+        scope: &KnowledgeScope,
+        paths: &[&str],
+    ) -> Result<HashMap<String, serde_json::Value>> {
+        self.knowledge_repo
+            .get_entity_fields(id, scope, paths)
+            .await
+    }
+
     async fn get_entity_by_name(
         &self,
         name: &str,
@@ -752,15 +2034,24 @@ impl KnowledgeRepository for CombinedRedisRepository {
         self.knowledge_repo.delete_entity(id, scope).await
     }
 
+    async fn archive_entity(&self, id: &str, scope: &KnowledgeScope) -> Result<KnowledgeNode> {
+        self.knowledge_repo.archive_entity(id, scope).await
+    }
+
+    async fn unarchive_entity(&self, id: &str, scope: &KnowledgeScope) -> Result<KnowledgeNode> {
+        self.knowledge_repo.unarchive_entity(id, scope).await
+    }
+
     async fn search_entities(
         &self,
         query: &str,
         scope: &KnowledgeScope,
         entity_type: Option<&EntityType>,
         limit: usize,
-    ) -> Result<Vec<KnowledgeNode>> {
+        include_archived: bool,
+    ) -> Result<(Vec<KnowledgeNode>, usize, bool)> {
         self.knowledge_repo
-            .search_entities(query, scope, entity_type, limit)
+            .search_entities(query, scope, entity_type, limit, include_archived)
             .await
     }
 
@@ -768,6 +2059,27 @@ impl KnowledgeRepository for CombinedRedisRepository {
         self.knowledge_repo.create_relation(relation).await
     }
 
+    async fn delete_relation(
+        &self,
+        relation_id: &str,
+        scope: &KnowledgeScope,
+    ) -> Result<KnowledgeRelation> {
+        self.knowledge_repo
+            .delete_relation(relation_id, scope)
+            .await
+    }
+
+    async fn merge_entities(
+        &self,
+        from_id: &str,
+        to_id: &str,
+        scope: &KnowledgeScope,
+    ) -> Result<KnowledgeNode> {
+        self.knowledge_repo
+            .merge_entities(from_id, to_id, scope)
+            .await
+    }
+
     async fn get_relations(
         &self,
         entity_id: &str,
@@ -780,6 +2092,14 @@ impl KnowledgeRepository for CombinedRedisRepository {
         self.knowledge_repo.update_name_index(name, id, scope).await
     }
 
+    async fn repair_name_index(
+        &self,
+        scope: &KnowledgeScope,
+        apply: bool,
+    ) -> Result<NameIndexReport> {
+        self.knowledge_repo.repair_name_index(scope, apply).await
+    }
+
     async fn set_active_entity(
         &self,
         session_key: &str,
@@ -791,6 +2111,27 @@ impl KnowledgeRepository for CombinedRedisRepository {
             .await
     }
 
+    async fn get_active_entity(
+        &self,
+        session_key: &str,
+    ) -> Result<Option<(String, KnowledgeScope)>> {
+        self.knowledge_repo.get_active_entity(session_key).await
+    }
+
+    async fn clear_active_entity(&self, session_key: &str) -> Result<bool> {
+        self.knowledge_repo.clear_active_entity(session_key).await
+    }
+
+    async fn get_relation_counts(
+        &self,
+        entity_ids: &[String],
+        scope: &KnowledgeScope,
+    ) -> Result<Vec<usize>> {
+        self.knowledge_repo
+            .get_relation_counts(entity_ids, scope)
+            .await
+    }
+
     async fn add_thought_to_entity(
         &self,
         entity_name: &str,
@@ -825,9 +2166,98 @@ mod tests {
             relevance: None,
             tags: None,
             category: None,
+            framework_prompts: None,
+            content_hash: None,
+            content_hash_registered: None,
+            parent_thought_id: None,
+            sentiment: None,
+            ttl_seconds: None,
         }
     }
 
+    #[test]
+    fn test_registered_content_hash_only_releases_own_entry() {
+        // Legacy record: no stored hash, so nothing is released
+        let mut thought = create_test_thought("t1", "same  text", "test");
+        assert_eq!(thought.registered_content_hash(), None);
+        thought.content_hash = Some("stored".to_string());
+        assert_eq!(thought.registered_content_hash().as_deref(), Some("stored"));
+        thought.content_hash_registered = Some(true);
+        assert_eq!(thought.registered_content_hash().as_deref(), Some("stored"));
+        thought.content_hash_registered = Some(false);
+        assert_eq!(thought.registered_content_hash(), None);
+    }
+
+    #[test]
+    fn test_projected_node_leaves_heavy_fields_empty() {
+        let fields: HashMap<String, serde_json::Value> = [
+            ("$.id", serde_json::json!("e1")),
+            ("$.name", serde_json::json!("redis")),
+            ("$.display_name", serde_json::json!("Redis")),
+            ("$.entity_type", serde_json::json!("tool")),
+            ("$.scope", serde_json::json!("personal")),
+            ("$.tags", serde_json::json!(["db"])),
+            ("$.created_at", serde_json::json!("2024-01-01T00:00:00Z")),
+            ("$.updated_at", serde_json::json!("2024-01-02T00:00:00Z")),
+            ("$.created_by", serde_json::json!("CC")),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+        let node = projected_node(&fields).unwrap();
+        assert_eq!(node.id, "e1");
+        assert_eq!(node.tags, vec!["db".to_string()]);
+        assert!(node.attributes.is_empty() && node.thought_ids.is_empty());
+        assert!(!node.archived);
+
+        let mut missing = fields.clone();
+        missing.remove("$.name");
+        assert!(projected_node(&missing).is_none());
+    }
+
+    #[test]
+    fn test_parse_search_reply_reads_total() {
+        let thought = create_test_thought("t1", "caching layer", "test");
+        let reply = vec![
+            "42".to_string(),
+            serde_json::to_string(&thought).expect("serializable"),
+        ];
+        let (thoughts, total) = parse_search_reply(reply).expect("valid reply");
+        assert_eq!(total, 42);
+        assert_eq!(thoughts.len(), 1);
+        assert_eq!(thoughts[0].id, "t1");
+
+        assert_eq!(parse_search_reply(Vec::new()).expect("empty").1, 0);
+        assert!(parse_search_reply(vec!["1".into(), "not json".into()]).is_err());
+    }
+
+    #[test]
+    fn test_search_filter_query_and_sort() {
+        let none = ThoughtSearchFilter::default();
+        assert_eq!(none.apply("redis cache"), "redis cache");
+        assert_eq!(none.sort_by(), None);
+
+        let filter = ThoughtSearchFilter {
+            min_importance: Some(8),
+            min_relevance: None,
+        };
+        assert_eq!(
+            filter.apply("redis cache"),
+            "(redis cache) @importance:[8 +inf]"
+        );
+        assert_eq!(filter.sort_by(), Some("importance"));
+
+        let both = ThoughtSearchFilter {
+            min_importance: Some(8),
+            min_relevance: Some(5),
+        };
+        assert_eq!(
+            both.apply(" * "),
+            "@importance:[8 +inf] @relevance:[5 +inf]"
+        );
+        assert_eq!(none.apply(""), "*");
+    }
+
     #[tokio::test]
     async fn test_fallback_search_basic() {
         // This test would require a mock RedisManager which is complex
@@ -1,6 +1,8 @@
 use crate::error::Result;
+use crate::frameworks::{StuckTracker, ThinkingMode};
 use crate::models::{
-    ChainMetadata, EntityType, KnowledgeNode, KnowledgeRelation, KnowledgeScope, ThoughtRecord,
+    ChainMetadata, ChainPruneReport, EntityType, KnowledgeNode, KnowledgeRelation, KnowledgeScope,
+    NameIndexReport, ThoughtRecord, ThoughtSearchFilter,
 };
 use async_trait::async_trait;
 
@@ -11,21 +13,92 @@ use mockall::automock;
 #[cfg_attr(test, automock)]
 pub trait ThoughtRepository: Send + Sync + 'static {
     async fn save_thought(&self, thought: &ThoughtRecord) -> Result<()>;
+    /// Save a thought, optionally without publishing the `thought_created`
+    /// stream event that triggers background embedding.
+    async fn save_thought_with_options(
+        &self,
+        thought: &ThoughtRecord,
+        skip_event: bool,
+    ) -> Result<()>;
     async fn save_chain_metadata(&self, metadata: &ChainMetadata) -> Result<()>;
     async fn chain_exists(&self, chain_id: &str) -> Result<bool>;
+    /// Whether `chain_id` belongs to `instance`: its thought list exists under
+    /// the instance namespace or its metadata names the instance
+    async fn chain_belongs_to(&self, instance: &str, chain_id: &str) -> Result<bool>;
+    /// Find chains with at most `max_thoughts` thoughts and no activity since
+    /// `idle_since`; when `apply` is set, delete them with their thoughts,
+    /// thought embeddings, metadata and stuck tracker
+    async fn prune_chains(
+        &self,
+        instance: &str,
+        max_thoughts: usize,
+        idle_since: chrono::DateTime<chrono::Utc>,
+        apply: bool,
+    ) -> Result<ChainPruneReport>;
     async fn get_thought(&self, instance: &str, thought_id: &str) -> Result<Option<ThoughtRecord>>;
+    /// Delete a thought with its embedding, usage counters, content hash and
+    /// chain entry, then publish `thought_deleted`; returns the removed record
+    /// (`None` when it did not exist)
+    async fn delete_thought(
+        &self,
+        instance: &str,
+        thought_id: &str,
+    ) -> Result<Option<ThoughtRecord>>;
     async fn get_chain_thoughts(
         &self,
         instance: &str,
         chain_id: &str,
     ) -> Result<Vec<ThoughtRecord>>;
+    /// Count a read of these thoughts in the background (`usage.track_access`)
+    fn track_access(&self, instance: &str, thought_ids: Vec<String>);
+    /// Thoughts from chain position `start` (0-based) onward, at most `limit`
+    /// when set, with the chain length at read time as the tail marker for the
+    /// next call
+    async fn get_chain_thoughts_since(
+        &self,
+        instance: &str,
+        chain_id: &str,
+        start: usize,
+        limit: Option<usize>,
+    ) -> Result<(Vec<ThoughtRecord>, usize)>;
+    /// `get_chain_thoughts_since` with each thought's chain position, so a
+    /// pager can resume after ids whose thoughts are gone
+    async fn get_chain_page(
+        &self,
+        instance: &str,
+        chain_id: &str,
+        start: usize,
+        limit: Option<usize>,
+    ) -> Result<(Vec<(usize, ThoughtRecord)>, usize)>;
+    /// RediSearch full-text query over the instance's thoughts; returns one
+    /// page and the total number of matches. A non-empty `filter` narrows by
+    /// importance/relevance and orders by importance descending.
     async fn search_thoughts(
         &self,
         instance: &str,
         query: &str,
+        filter: ThoughtSearchFilter,
         offset: i64,
         limit: i64,
-    ) -> Result<Vec<ThoughtRecord>>;
+    ) -> Result<(Vec<ThoughtRecord>, usize)>;
+    /// A chain's tracker, if one was saved (including legacy JSON trackers)
+    #[allow(dead_code)]
+    async fn load_stuck_tracker(
+        &self,
+        instance: &str,
+        chain_id: &str,
+    ) -> Result<Option<StuckTracker>>;
+    /// Overwrite a chain's tracker; not safe against concurrent `stuck` calls,
+    /// which go through `advance_stuck_tracker`
+    #[allow(dead_code)]
+    async fn save_stuck_tracker(&self, instance: &str, tracker: &StuckTracker) -> Result<()>;
+    /// Atomically load (or initialize) a chain's tracker, pick the next
+    /// approach, and persist it; safe under concurrent `stuck` calls.
+    async fn advance_stuck_tracker(
+        &self,
+        instance: &str,
+        chain_id: &str,
+    ) -> Result<(ThinkingMode, StuckTracker)>;
 }
 
 #[async_trait]
@@ -33,30 +106,83 @@ pub trait ThoughtRepository: Send + Sync + 'static {
 pub trait KnowledgeRepository: Send + Sync + 'static {
     async fn create_entity(&self, node: KnowledgeNode) -> Result<()>;
     async fn get_entity(&self, id: &str, scope: &KnowledgeScope) -> Result<KnowledgeNode>;
+    /// Fetch selected JSON subpaths (e.g. `$.name`) of an entity without the full
+    /// document; returns path -> first match, omitting paths that don't match.
+    async fn get_entity_fields(
+        &self,
+        id: &str,
+        scope: &KnowledgeScope,
+        paths: &[&str],
+    ) -> Result<std::collections::HashMap<String, serde_json::Value>>;
     async fn get_entity_by_name(&self, name: &str, scope: &KnowledgeScope)
     -> Result<KnowledgeNode>;
     async fn update_entity(&self, node: KnowledgeNode) -> Result<()>;
     async fn delete_entity(&self, id: &str, scope: &KnowledgeScope) -> Result<()>;
+    /// Soft-delete: mark the entity archived and drop it from the name index,
+    /// keeping the document and its relations; returns the updated entity
+    async fn archive_entity(&self, id: &str, scope: &KnowledgeScope) -> Result<KnowledgeNode>;
+    /// Reverse `archive_entity`, restoring the name index entry; a validation
+    /// error when another entity has since taken the name
+    async fn unarchive_entity(&self, id: &str, scope: &KnowledgeScope) -> Result<KnowledgeNode>;
+    /// Archived entities are skipped unless `include_archived` is set.
+    /// Returns the page, the total matches, and whether the key scan hit
+    /// `scan_max_keys` (so `total` may be low)
     async fn search_entities(
         &self,
         query: &str,
         scope: &KnowledgeScope,
         entity_type: Option<&EntityType>,
         limit: usize,
-    ) -> Result<Vec<KnowledgeNode>>;
+        include_archived: bool,
+    ) -> Result<(Vec<KnowledgeNode>, usize, bool)>;
     async fn create_relation(&self, relation: KnowledgeRelation) -> Result<()>;
+    /// Delete a relation and its entries in both entities' relation indices,
+    /// returning the removed relation; `NotFound` when it does not exist
+    async fn delete_relation(
+        &self,
+        relation_id: &str,
+        scope: &KnowledgeScope,
+    ) -> Result<KnowledgeRelation>;
+    /// Fold `from_id` into `to_id` in one transaction: `from`'s relations are
+    /// repointed at `to` (relations between the two are dropped), tags, thought
+    /// ids and attributes are unioned with `to` winning attribute conflicts, and
+    /// `from` is deleted with its name index entry. Returns the merged entity.
+    async fn merge_entities(
+        &self,
+        from_id: &str,
+        to_id: &str,
+        scope: &KnowledgeScope,
+    ) -> Result<KnowledgeNode>;
     async fn get_relations(
         &self,
         entity_id: &str,
         scope: &KnowledgeScope,
     ) -> Result<Vec<KnowledgeRelation>>;
+    /// Relation index cardinality for each entity, in input order
+    async fn get_relation_counts(
+        &self,
+        entity_ids: &[String],
+        scope: &KnowledgeScope,
+    ) -> Result<Vec<usize>>;
     async fn update_name_index(&self, name: &str, id: &str, scope: &KnowledgeScope) -> Result<()>;
+    /// Compare the name index against entity keys (bounded SCAN); when `apply`
+    /// is set, add/fix entries from entity names and remove orphans.
+    async fn repair_name_index(
+        &self,
+        scope: &KnowledgeScope,
+        apply: bool,
+    ) -> Result<NameIndexReport>;
     async fn set_active_entity(
         &self,
         session_key: &str,
         entity_id: &str,
         scope: &KnowledgeScope,
     ) -> Result<()>;
+    async fn get_active_entity(
+        &self,
+        session_key: &str,
+    ) -> Result<Option<(String, KnowledgeScope)>>;
+    async fn clear_active_entity(&self, session_key: &str) -> Result<bool>;
     async fn add_thought_to_entity(
         &self,
         entity_name: &str,
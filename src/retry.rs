@@ -1,3 +1,10 @@
+use std::future::Future;
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::error::{Result, UnifiedIntelligenceError};
+
 /// Retry policy configuration
 #[derive(Debug, Clone, Default)]
 pub struct RetryConfig {}
@@ -15,5 +22,124 @@ impl Default for RetryPolicy {
     }
 }
 
+/// Whether an error is worth retrying: dropped/refused connections, timeouts,
+/// pool exhaustion and Redis "try later" replies. Everything else is persistent.
+pub fn is_transient(err: &UnifiedIntelligenceError) -> bool {
+    match err {
+        UnifiedIntelligenceError::Redis(e) => {
+            e.is_io_error()
+                || e.is_timeout()
+                || e.is_connection_dropped()
+                || e.is_connection_refusal()
+                || matches!(
+                    e.kind(),
+                    redis::ErrorKind::BusyLoadingError
+                        | redis::ErrorKind::TryAgain
+                        | redis::ErrorKind::ClusterDown
+                        | redis::ErrorKind::MasterDown
+                )
+        }
+        UnifiedIntelligenceError::Pool(_)
+        | UnifiedIntelligenceError::PoolExhausted { .. }
+        | UnifiedIntelligenceError::RedisUnavailable(_) => true,
+        _ => false,
+    }
+}
+
+/// Delay before retry number `attempt` (1-based), without jitter:
+/// `initial_delay_ms * backoff_base^(attempt - 1)`, capped at `max_delay_ms`
+pub fn backoff_delay(config: &crate::config::RetryConfig, attempt: u32) -> Duration {
+    let exp = attempt.saturating_sub(1).min(32) as i32;
+    let ms = config.initial_delay_ms as f64 * config.backoff_base.max(1.0).powi(exp);
+    Duration::from_millis(ms.min(config.max_delay_ms as f64) as u64)
+}
+
+/// Run `op`, retrying transient errors with exponential backoff per the global
+/// `retry` config. Persistent errors, and the last transient one, are returned.
+pub async fn retry_transient<T, F, Fut>(
+    config: &crate::config::RetryConfig,
+    label: &str,
+    mut op: F,
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let attempts = config.max_attempts.max(1);
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < attempts && is_transient(&e) => {
+                let base = backoff_delay(config, attempt);
+                let jitter = config.jitter_factor.clamp(0.0, 1.0);
+                let delay = if jitter > 0.0 {
+                    base.mul_f64(1.0 + rand::thread_rng().gen_range(-jitter..=jitter))
+                } else {
+                    base
+                };
+                tracing::warn!(
+                    "{} failed (attempt {}/{}), retrying in {:?}: {}",
+                    label,
+                    attempt,
+                    attempts,
+                    delay,
+                    e
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    fn test_config(max_attempts: u32) -> crate::config::RetryConfig {
+        crate::config::RetryConfig {
+            max_attempts,
+            initial_delay_ms: 1,
+            max_delay_ms: 4,
+            backoff_base: 2.0,
+            jitter_factor: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_backoff_delay_grows_and_caps() {
+        let cfg = test_config(5);
+        assert_eq!(backoff_delay(&cfg, 1), Duration::from_millis(1));
+        assert_eq!(backoff_delay(&cfg, 2), Duration::from_millis(2));
+        assert_eq!(backoff_delay(&cfg, 3), Duration::from_millis(4));
+        assert_eq!(backoff_delay(&cfg, 10), Duration::from_millis(4));
+    }
+
+    #[tokio::test]
+    async fn test_retry_transient_retries_only_transient_errors() {
+        let cfg = test_config(3);
+        let mut calls = 0;
+        let result: Result<()> = retry_transient(&cfg, "test", || {
+            calls += 1;
+            async {
+                Err(UnifiedIntelligenceError::Redis(redis::RedisError::from(
+                    std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset"),
+                )))
+            }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+
+        let mut calls = 0;
+        let result: Result<()> = retry_transient(&cfg, "test", || {
+            calls += 1;
+            async { Err(UnifiedIntelligenceError::Internal("bad".to_string())) }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+}
@@ -0,0 +1,183 @@
+use std::sync::Arc;
+
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::models::{ChatMessage, GroqRequest};
+use crate::transport::Transport;
+
+const POSITIVE: &[&str] = &[
+    "good",
+    "great",
+    "excellent",
+    "awesome",
+    "nice",
+    "love",
+    "like",
+    "happy",
+    "glad",
+    "excited",
+    "clean",
+    "elegant",
+    "solid",
+    "works",
+    "working",
+    "fixed",
+    "solved",
+    "success",
+    "successful",
+    "win",
+    "progress",
+    "promising",
+    "confident",
+    "easy",
+    "fast",
+    "thanks",
+    "helpful",
+    "better",
+    "best",
+    "improved",
+];
+
+const NEGATIVE: &[&str] = &[
+    "bad",
+    "terrible",
+    "awful",
+    "hate",
+    "sad",
+    "angry",
+    "annoying",
+    "frustrated",
+    "frustrating",
+    "confused",
+    "confusing",
+    "stuck",
+    "broken",
+    "fails",
+    "failed",
+    "failing",
+    "failure",
+    "error",
+    "bug",
+    "wrong",
+    "worse",
+    "worst",
+    "slow",
+    "painful",
+    "messy",
+    "ugly",
+    "problem",
+    "issue",
+    "crash",
+    "blocked",
+];
+
+const NEGATIONS: &[&str] = &[
+    "not", "no", "never", "don't", "doesn't", "didn't", "isn't", "wasn't", "aren't", "can't",
+    "won't", "without",
+];
+
+/// Lexicon sentiment in [-1, 1]: the mean polarity of known words, flipped
+/// when one of the two preceding words is a negation. 0 when no word is known.
+pub fn lexicon_score(text: &str) -> f32 {
+    let words: Vec<String> = text
+        .split(|c: char| !(c.is_alphanumeric() || c == '\''))
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    let mut sum = 0i32;
+    let mut polar = 0i32;
+    for (i, word) in words.iter().enumerate() {
+        let polarity = if POSITIVE.contains(&word.as_str()) {
+            1
+        } else if NEGATIVE.contains(&word.as_str()) {
+            -1
+        } else {
+            continue;
+        };
+        let negated = words[i.saturating_sub(2)..i]
+            .iter()
+            .any(|w| NEGATIONS.contains(&w.as_str()));
+        sum += if negated { -polarity } else { polarity };
+        polar += 1;
+    }
+    if polar == 0 {
+        0.0
+    } else {
+        sum as f32 / polar as f32
+    }
+}
+
+/// Parse a model's numeric answer, clamped to [-1, 1]
+pub fn parse_score(raw: &str) -> Option<f32> {
+    let token = raw
+        .split_whitespace()
+        .next()?
+        .trim_matches(|c: char| c == '"' || c == '\'' || c == ',');
+    let score: f32 = token.trim_end_matches('.').parse().ok()?;
+    score.is_finite().then(|| score.clamp(-1.0, 1.0))
+}
+
+/// Scores thought sentiment using a cheap Groq model
+pub struct GroqSentiment {
+    tx: Arc<dyn Transport>,
+    model: String,
+}
+
+impl GroqSentiment {
+    pub fn new(tx: Arc<dyn Transport>, model: String) -> Self {
+        Self { tx, model }
+    }
+
+    /// Returns `Ok(None)` when the model does not answer with a number
+    pub async fn score(&self, thought: &str) -> Result<Option<f32>> {
+        let system_message = ChatMessage {
+            role: "system".to_string(),
+            content: "Rate the emotional tone of the user's text from -1 (very negative) \
+                      to 1 (very positive), 0 being neutral. Respond with the number only."
+                .to_string(),
+        };
+        let user_message = ChatMessage {
+            role: "user".to_string(),
+            content: thought.to_string(),
+        };
+
+        let request = GroqRequest {
+            model: self.model.clone(),
+            messages: vec![system_message, user_message],
+            temperature: 0.0,
+            max_tokens: 8,
+            response_format: None,
+        };
+
+        let response = self.tx.chat(&request).await?;
+        let choice = response.choices.first().ok_or_else(|| {
+            UnifiedIntelligenceError::Internal(
+                "Groq API returned empty choices for sentiment".to_string(),
+            )
+        })?;
+        Ok(parse_score(&choice.message.content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lexicon_score_polarity_and_negation() {
+        assert_eq!(lexicon_score("The refactor is clean and works great"), 1.0);
+        assert_eq!(lexicon_score("Still stuck, the build is broken"), -1.0);
+        assert_eq!(lexicon_score("This is not good"), -1.0);
+        assert_eq!(lexicon_score("Meeting at noon"), 0.0);
+        let mixed = lexicon_score("Fixed the bug but the tests are slow");
+        assert!(mixed < 0.0 && mixed > -1.0);
+    }
+
+    #[test]
+    fn test_parse_score() {
+        assert_eq!(parse_score("0.5"), Some(0.5));
+        assert_eq!(parse_score(" -0.8\n"), Some(-0.8));
+        assert_eq!(parse_score("3"), Some(1.0));
+        assert_eq!(parse_score("positive"), None);
+        assert_eq!(parse_score("NaN"), None);
+    }
+}
@@ -3,29 +3,41 @@ use rmcp::{
     handler::server::{router::tool::ToolRouter, tool::Parameters},
     model::{CallToolResult, Content, ErrorData, ServerCapabilities, ServerInfo},
 };
-use rmcp_macros::{tool, tool_handler, tool_router};
+use rmcp_macros::{tool, tool_router};
+use serde::Serialize;
 use std::future::Future;
 use std::sync::Arc;
 
-use crate::config::Config;
-use crate::embeddings::generate_openai_embedding;
+use crate::config::{AssistantContext, Config, IndexDimCheck};
+use crate::embeddings::{build_embedder, generate_embedding};
 use crate::error::UnifiedIntelligenceError;
 use crate::handlers::ToolHandlers;
 use crate::handlers::help::{HelpHandlerTrait, UiHelpParams};
 use crate::handlers::knowledge::KnowledgeHandler;
 use crate::handlers::recall::UiRecallParams;
 use crate::handlers::thoughts::ThoughtsHandler;
+use crate::models::ChatMessage;
+use crate::models::ThoughtSearchFilter;
 use crate::models::UiKnowledgeParams;
 use crate::models::UiThinkParams;
 use crate::rate_limit::RateLimiter;
-use crate::redis::RedisManager;
+use crate::redis::{EVENT_ORIGIN, EventOrigin, RedisManager};
 use crate::repository::CombinedRedisRepository;
 use crate::repository_traits::ThoughtRepository;
 use crate::synth::Synthesizer;
 use crate::tools::ui_memory::{UiMemoryParams, ui_memory_impl};
 use crate::tools::ui_remember::{UiRememberParams, UiRememberResult};
 use crate::validation::InputValidator;
-use bytemuck::cast_slice;
+
+/// URI prefix for tool results returned as MCP resources
+pub const RESULT_URI_PREFIX: &str = "ui://results/";
+
+/// Version of the tool response envelope; bump when a response shape changes
+/// incompatibly. Version 2: `ui_recall` chain mode may return an object
+/// (`{thoughts, framework_summary, ...}`) instead of an array, large results
+/// may come back as resource references, and new optional fields appeared
+/// (see CHANGELOG).
+pub const RESPONSE_SCHEMA_VERSION: u32 = 2;
 
 /// Main service struct for UnifiedIntelligence MCP server
 #[derive(Clone)]
@@ -64,6 +76,19 @@ impl UnifiedIntelligenceService {
         redis_manager.init_event_stream(&instance_id).await?;
         tracing::info!("Service::new() - Event stream initialized");
 
+        // Catch embedding_dimensions drift before any index is written to
+        check_index_dims(&redis_manager, &config).await?;
+
+        // Thought indexes created before sentiment scoring lack the field
+        if config.ui_think.sentiment != crate::config::SentimentMode::Off {
+            let index = format!("idx:{instance_id}:thought");
+            match redis_manager.add_numeric_field(&index, "sentiment").await {
+                Ok(true) => tracing::info!("Added sentiment field to {}", index),
+                Ok(false) => {}
+                Err(e) => tracing::debug!("Sentiment field not added to {}: {}", index, e),
+            }
+        }
+
         // Create repository with config and instance_id
         tracing::info!("Service::new() - Creating CombinedRedisRepository");
         let repository = Arc::new(CombinedRedisRepository::new(
@@ -73,6 +98,16 @@ impl UnifiedIntelligenceService {
         ));
         tracing::info!("Service::new() - CombinedRedisRepository created");
 
+        // Full-text index for ui_recall search; migrates pre-range-filter schemas
+        match repository.ensure_search_index(&instance_id).await {
+            Ok(true) => tracing::info!(
+                "Thought search index created or extended for {}",
+                instance_id
+            ),
+            Ok(false) => {}
+            Err(e) => tracing::warn!("Thought search index unavailable: {}", e),
+        }
+
         // Create validator
         tracing::info!("Service::new() - Creating InputValidator");
         let validator = Arc::new(InputValidator::new());
@@ -80,12 +115,39 @@ impl UnifiedIntelligenceService {
 
         // Create rate limiter with configured values
         tracing::info!("Service::new() - Creating RateLimiter");
-        let rate_limiter = Arc::new(RateLimiter::new(
-            config.rate_limiter.max_requests as usize,
-            config.rate_limiter.window_seconds as u64,
-        ));
+        let rate_limiter = Arc::new(RateLimiter::from_config(&config.rate_limiter));
         tracing::info!("Service::new() - RateLimiter created");
 
+        // Warn once about reduced retrieval rather than on every ui_remember call
+        let embedder = match build_embedder(&config) {
+            Ok(embedder) => Some(embedder),
+            Err(e) => {
+                tracing::warn!(
+                    "Embedding provider unavailable ({}); ui_remember will run without KNN vector search (reported as degraded: [\"embeddings\"])",
+                    e
+                );
+                None
+            }
+        };
+
+        // Background embedding of items queued while inline embedding is off
+        match embedder.clone() {
+            Some(embedder) => crate::backfill::spawn(
+                config.clone(),
+                repository.clone(),
+                redis_manager.clone(),
+                embedder,
+                instance_id.clone(),
+            ),
+            None if config.embeddings.backfill_interval_secs > 0 => {
+                tracing::warn!("Embedding backfill disabled: no embedding provider")
+            }
+            None => {}
+        }
+
+        // Turn maxmemory OOM write failures into StorageFull errors
+        redis_manager.spawn_memory_guard(config.redis.memory_guard.clone());
+
         // Create handlers
         tracing::info!("Service::new() - Creating ToolHandlers");
         let handlers = Arc::new(ToolHandlers::new(
@@ -93,18 +155,409 @@ impl UnifiedIntelligenceService {
             instance_id.clone(),
             validator,
             redis_manager.clone(), // Pass redis_manager
+            embedder,
         ));
         tracing::info!("Service::new() - ToolHandlers created");
 
+        // Disabled tools are dropped from the router so they are never advertised
+        let mut tool_router = Self::tool_router();
+        for tool in tool_router.list_all() {
+            if !config.tools.is_enabled(&tool.name) {
+                tracing::info!("Tool {} disabled by configuration", tool.name);
+                tool_router.remove_route::<(), ()>(&tool.name);
+            }
+        }
+
         tracing::info!("Service::new() - Service initialization complete");
         Ok(Self {
-            tool_router: Self::tool_router(),
+            tool_router,
             handlers,
             rate_limiter,
             instance_id,
             config,
         })
     }
+
+    /// Shared rate limiter, used by the HTTP layer to emit `Retry-After`
+    pub fn rate_limiter(&self) -> Arc<RateLimiter> {
+        self.rate_limiter.clone()
+    }
+
+    /// Instance id this service is bound to
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    /// Dynamic server metadata advertised in `get_info` under
+    /// `capabilities.experimental.unified_intelligence`.
+    fn server_metadata(&self) -> rmcp::model::ExperimentalCapabilities {
+        let tools: Vec<String> = self
+            .tool_router
+            .list_all()
+            .into_iter()
+            .map(|t| t.name.to_string())
+            .collect();
+        let meta = serde_json::json!({
+            "instance_id": self.instance_id,
+            "tools": tools,
+            "response_envelope": self.config.server.response_envelope,
+            "response_schema_version": RESPONSE_SCHEMA_VERSION,
+            "max_page_size": self.config.server.max_page_size,
+            "tool_timeouts_secs": self.tool_timeouts(),
+            "resource_results": self.config.server.resource_results.enabled,
+        });
+        let mut caps = rmcp::model::ExperimentalCapabilities::new();
+        if let serde_json::Value::Object(obj) = meta {
+            caps.insert("unified_intelligence".to_string(), obj);
+        }
+        caps
+    }
+
+    /// Build a successful tool result. When `server.resource_results` is enabled
+    /// and the encoded payload reaches `min_bytes`, the payload is stored under
+    /// `{instance}:results:{id}` and only a `ui://results/{id}` reference is returned.
+    async fn tool_result<T: Serialize>(
+        &self,
+        tool: &str,
+        data: T,
+    ) -> Result<CallToolResult, ErrorData> {
+        let content = self.json_content(tool, data)?;
+        let cfg = &self.config.server.resource_results;
+        let text = match content.raw.as_text() {
+            Some(t) if cfg.enabled && t.text.len() >= cfg.min_bytes => &t.text,
+            _ => return Ok(CallToolResult::success(vec![content])),
+        };
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let key = format!("{}:results:{}", self.instance_id, id);
+        let stored = async {
+            let mut con = self.handlers.redis_manager.get_connection().await?;
+            redis::AsyncCommands::set_ex::<_, _, ()>(&mut *con, &key, text, cfg.ttl_seconds)
+                .await?;
+            Ok::<_, UnifiedIntelligenceError>(())
+        }
+        .await;
+        if let Err(e) = stored {
+            // Fall back to the inline payload rather than failing the call
+            tracing::warn!("{} result not stored as resource: {}", tool, e);
+            return Ok(CallToolResult::success(vec![content]));
+        }
+
+        let reference = Content::json(serde_json::json!({
+            "resource": {
+                "uri": format!("{RESULT_URI_PREFIX}{id}"),
+                "mime_type": "application/json",
+                "size_bytes": text.len(),
+                "expires_in_secs": cfg.ttl_seconds,
+                "tool": tool,
+            }
+        }))?;
+        Ok(CallToolResult::success(vec![reference]))
+    }
+
+    /// Record the implicit follow-up signal on the previous assistant turn.
+    ///
+    /// The first follow-up sets the turn's signals. Further follow-ups within
+    /// `ui_remember.feedback_merge_window_secs` of it are merged (see
+    /// [`merge_followup`]) instead of overwriting them; later ones are ignored.
+    /// The hash (and chain aggregate) is WATCHed so concurrent or retried
+    /// queries cannot double-count. Returns `false` when the follow-up was
+    /// ignored. Transient Redis errors are retried per `retry`.
+    async fn apply_followup_feedback(
+        &self,
+        chain_id: &str,
+        assistant_id: &str,
+        delta: i64,
+        user_text: &str,
+    ) -> crate::error::Result<bool> {
+        let (score, abandoned, continued, corrected) = compute_feedback_scoring(delta, user_text);
+        let key = format!("voice:feedback:{assistant_id}");
+        let agg_key = format!("voice:feedback:chain:{chain_id}");
+        let (key, agg_key) = (&key, &agg_key);
+        let aggregate = self.config.ui_remember.aggregate_feedback;
+        let window = self.config.ui_remember.feedback_merge_window_secs;
+        let attempts = self.config.retry.max_attempts.max(1);
+        let redis_manager = &self.handlers.redis_manager;
+
+        crate::retry::retry_transient(
+            &self.config.retry,
+            "ui_remember follow-up feedback",
+            || async move {
+                let mut con = redis_manager.get_connection().await?;
+                for _ in 0..attempts {
+                    let now = chrono::Utc::now().timestamp();
+                    let _: () = redis::cmd("WATCH")
+                        .arg(key)
+                        .arg(agg_key)
+                        .query_async(&mut *con)
+                        .await?;
+                    // Every early exit below must release the WATCH, or the next
+                    // command on this pooled connection would run under it
+                    let attempt: crate::error::Result<Option<bool>> = async {
+                        let (seeded, llm_feedback, applied, prev_corrected): (
+                            Option<String>,
+                            Option<String>,
+                            Option<i64>,
+                            Option<String>,
+                        ) = redis::cmd("HMGET")
+                            .arg(key)
+                            .arg("feedback_score")
+                            .arg("llm_feedback")
+                            .arg("followup_applied")
+                            .arg("corrected")
+                            .query_async(&mut *con)
+                            .await?;
+
+                        let mut pipe = redis::pipe();
+                        pipe.atomic();
+                        if aggregate && seeded.is_some() {
+                            // Fold the finalized turn into the chain aggregate and drop it
+                            pipe.hincr(agg_key, "turns", 1)
+                                .hincr(agg_key, "feedback_score_sum", score)
+                                .hincr(agg_key, "continued", continued)
+                                .hincr(agg_key, "abandoned", abandoned)
+                                .hincr(agg_key, "corrected", i32::from(corrected))
+                                .hincr(agg_key, "time_to_next_sum", delta)
+                                .hincr(agg_key, "llm_feedback", i32::from(llm_feedback.is_some()))
+                                .hset(agg_key, "last_turn_id", assistant_id)
+                                .hset(agg_key, "last_turn_at", now)
+                                .hset(agg_key, "last_turn_score", score)
+                                .hset(agg_key, "last_turn_corrected", i32::from(corrected))
+                                .del(key);
+                        } else if aggregate {
+                            // Already folded: merge into the aggregate while the turn
+                            // is the chain's latest and still within the window
+                            let (last_id, last_at, last_score, last_corrected): (
+                                Option<String>,
+                                Option<i64>,
+                                Option<f64>,
+                                Option<i32>,
+                            ) = redis::cmd("HMGET")
+                                .arg(agg_key)
+                                .arg("last_turn_id")
+                                .arg("last_turn_at")
+                                .arg("last_turn_score")
+                                .arg("last_turn_corrected")
+                                .query_async(&mut *con)
+                                .await?;
+                            let mergeable = last_id.as_deref() == Some(assistant_id)
+                                && last_at.is_some_and(|at| within_merge_window(at, now, window));
+                            let (Some(last_score), true) = (last_score, mergeable) else {
+                                let _: () = redis::cmd("UNWATCH").query_async(&mut *con).await?;
+                                return Ok(Some(false));
+                            };
+                            let was_corrected = last_corrected.unwrap_or(0) != 0;
+                            let (merged_score, merged_corrected) =
+                                merge_followup(last_score, was_corrected, score, corrected);
+                            pipe.hincr(agg_key, "feedback_score_sum", merged_score - last_score)
+                                .hincr(
+                                    agg_key,
+                                    "corrected",
+                                    i32::from(merged_corrected && !was_corrected),
+                                )
+                                .hincr(agg_key, "merged_followups", 1)
+                                .hset(agg_key, "last_turn_score", merged_score)
+                                .hset(agg_key, "last_turn_corrected", i32::from(merged_corrected));
+                        } else if let Some(applied_at) = applied {
+                            if !within_merge_window(applied_at, now, window) {
+                                let _: () = redis::cmd("UNWATCH").query_async(&mut *con).await?;
+                                return Ok(Some(false));
+                            }
+                            // Keep time_to_next/continued from the first follow-up
+                            let prev_score = seeded.and_then(|s| s.parse().ok()).unwrap_or(score);
+                            let was_corrected = prev_corrected.is_some_and(|c| !c.is_empty());
+                            let (merged_score, merged_corrected) =
+                                merge_followup(prev_score, was_corrected, score, corrected);
+                            pipe.hset(key, "synthesis_quality", merged_score)
+                                .hset(key, "feedback_score", merged_score)
+                                .hincr(key, "followups", 1);
+                            if merged_corrected && !was_corrected {
+                                pipe.hset(key, "corrected", user_text);
+                            }
+                        } else {
+                            let corrected_text = if corrected { user_text } else { "" };
+                            pipe.hset(key, "continued", continued)
+                                .hset(key, "time_to_next", delta)
+                                .hset(key, "corrected", corrected_text)
+                                .hset(key, "synthesis_quality", score)
+                                .hset(key, "feedback_score", score)
+                                .hset(key, "abandoned", abandoned)
+                                .hset(key, "followups", 1)
+                                .hset(key, "followup_applied", now);
+                        }
+                        // EXEC returns nil if a concurrent follow-up touched the hash first;
+                        // re-read so the branches above see its write
+                        let committed: Option<Vec<redis::Value>> =
+                            pipe.query_async(&mut *con).await?;
+                        Ok(committed.is_some().then_some(true))
+                    }
+                    .await;
+                    match attempt {
+                        Ok(Some(applied)) => return Ok(applied),
+                        Ok(None) => {}
+                        Err(e) => {
+                            let _: redis::RedisResult<()> =
+                                redis::cmd("UNWATCH").query_async(&mut *con).await;
+                            return Err(e);
+                        }
+                    }
+                }
+                Err(UnifiedIntelligenceError::Internal(format!(
+                    "follow-up feedback for {assistant_id} contended after {attempts} attempts"
+                )))
+            },
+        )
+        .await
+    }
+
+    /// Attach explicit feedback (`action="feedback"`) to an assistant turn.
+    ///
+    /// Plain HSETs on the turn's hash, so resubmitting the same feedback leaves
+    /// it unchanged. Under `aggregate_feedback` a turn already folded into the
+    /// chain aggregate has no hash left; the aggregate's `llm_feedback` and
+    /// `continue_next` counters are bumped instead, once per turn, so the
+    /// per-turn hash is never recreated. Transient Redis errors are retried.
+    async fn write_llm_feedback(
+        &self,
+        chain_id: &str,
+        assistant_id: &str,
+        text: &str,
+        continue_next: bool,
+    ) -> crate::error::Result<()> {
+        let key = format!("voice:feedback:{assistant_id}");
+        let agg_key = format!("voice:feedback:chain:{chain_id}");
+        let (key, agg_key) = (&key, &agg_key);
+        let aggregate = self.config.ui_remember.aggregate_feedback;
+        let attempts = self.config.retry.max_attempts.max(1);
+        let redis_manager = &self.handlers.redis_manager;
+
+        crate::retry::retry_transient(
+            &self.config.retry,
+            "ui_remember feedback write",
+            || async move {
+                let mut con = redis_manager.get_connection().await?;
+                if !aggregate {
+                    let _: () = redis::pipe()
+                        .hset(key, "llm_feedback", text)
+                        .hset(key, "continue_next", i32::from(continue_next))
+                        .query_async(&mut *con)
+                        .await?;
+                    return Ok(());
+                }
+                for _ in 0..attempts {
+                    // WATCH so a concurrent fold cannot delete the hash between
+                    // the check and the write
+                    let _: () = redis::cmd("WATCH")
+                        .arg(key)
+                        .arg(agg_key)
+                        .query_async(&mut *con)
+                        .await?;
+                    let attempt: crate::error::Result<Option<()>> = async {
+                        let exists: bool =
+                            redis::cmd("EXISTS").arg(key).query_async(&mut *con).await?;
+                        let mut pipe = redis::pipe();
+                        pipe.atomic();
+                        if exists {
+                            pipe.hset(key, "llm_feedback", text).hset(
+                                key,
+                                "continue_next",
+                                i32::from(continue_next),
+                            );
+                        } else {
+                            let counted: Option<String> = redis::cmd("HGET")
+                                .arg(agg_key)
+                                .arg("last_feedback_turn_id")
+                                .query_async(&mut *con)
+                                .await?;
+                            if counted.as_deref() == Some(assistant_id) {
+                                let _: () = redis::cmd("UNWATCH").query_async(&mut *con).await?;
+                                return Ok(Some(()));
+                            }
+                            pipe.hincr(agg_key, "llm_feedback", 1)
+                                .hincr(agg_key, "continue_next", i32::from(continue_next))
+                                .hset(agg_key, "last_feedback_turn_id", assistant_id);
+                        }
+                        let committed: Option<Vec<redis::Value>> =
+                            pipe.query_async(&mut *con).await?;
+                        Ok(committed.map(|_| ()))
+                    }
+                    .await;
+                    match attempt {
+                        Ok(Some(())) => return Ok(()),
+                        Ok(None) => {}
+                        Err(e) => {
+                            let _: redis::RedisResult<()> =
+                                redis::cmd("UNWATCH").query_async(&mut *con).await;
+                            return Err(e);
+                        }
+                    }
+                }
+                Err(UnifiedIntelligenceError::Internal(format!(
+                    "feedback for {assistant_id} contended after {attempts} attempts"
+                )))
+            },
+        )
+        .await
+    }
+
+    /// Configured server-side budgets (seconds) for the registered tools
+    fn tool_timeouts(&self) -> serde_json::Map<String, serde_json::Value> {
+        self.tool_router
+            .list_all()
+            .into_iter()
+            .filter_map(|t| {
+                let budget = self.config.server.tool_timeout(&t.name)?;
+                Some((t.name.to_string(), serde_json::json!(budget.as_secs())))
+            })
+            .collect()
+    }
+
+    /// Parse a `ui_remember` query's intent with the configured confidence
+    /// floor. Parsing is best-effort: when Groq is unavailable the raw query
+    /// is used with no temporal filter and the default style.
+    async fn parse_intent(&self, query: &str) -> crate::models::QueryIntent {
+        use crate::intent::IntentParser;
+
+        let parsed = match crate::circuit_breaker::groq_transport(&self.config) {
+            Ok(tx) => {
+                crate::intent::GroqIntent::new(tx, self.config.groq.intent_model.clone())
+                    .with_min_confidence(self.config.groq.intent_min_confidence)
+                    .parse(query)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        parsed.unwrap_or_else(|e| {
+            tracing::warn!("ui_remember: intent parsing failed, using defaults: {}", e);
+            crate::models::QueryIntent {
+                original_query: query.to_string(),
+                temporal_filter: None,
+                synthesis_style: None,
+                confidence: None,
+                low_confidence: false,
+            }
+        })
+    }
+
+    /// Encode a tool response as JSON content, wrapped in the versioned
+    /// envelope when `server.response_envelope` is enabled.
+    fn json_content<T: Serialize>(&self, tool: &str, data: T) -> Result<Content, ErrorData> {
+        let encoded = if self.config.server.response_envelope {
+            let data = serde_json::to_value(data).map_err(|e| {
+                ErrorData::internal_error(format!("Failed to create JSON content: {e}"), None)
+            })?;
+            Content::json(serde_json::json!({
+                "schema_version": RESPONSE_SCHEMA_VERSION,
+                "tool": tool,
+                "data": data,
+            }))
+        } else {
+            Content::json(data)
+        };
+        encoded.map_err(|e| {
+            ErrorData::internal_error(format!("Failed to create JSON content: {e}"), None)
+        })
+    }
 }
 
 #[tool_router]
@@ -114,28 +567,30 @@ impl UnifiedIntelligenceService {
         &self,
         params: Parameters<UiThinkParams>,
     ) -> std::result::Result<CallToolResult, ErrorData> {
+        crate::metrics::global().record_tool_call("ui_think");
         // Check rate limit
-        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id).await {
+        if let Err(e) = self
+            .rate_limiter
+            .check_rate_limit(&self.instance_id, "ui_think")
+            .await
+        {
             tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
-            return Err(ErrorData::invalid_params(
-                "Rate limit exceeded. Please slow down your requests.".to_string(),
-                None,
-            ));
+            return Err(rate_limit_error(&e));
         }
 
         match self.handlers.ui_think(params.0).await {
             Ok(response) => {
-                let content = Content::json(response).map_err(|e| {
-                    ErrorData::internal_error(format!("Failed to create JSON content: {e}"), None)
-                })?;
+                let content = self.json_content("ui_think", response)?;
                 Ok(CallToolResult::success(vec![content]))
             }
             Err(e) => match &e {
                 UnifiedIntelligenceError::DuplicateThought { .. } => {
+                    crate::metrics::global().record_error(&e);
                     tracing::warn!("Duplicate thought attempted: {}", e);
                     Err(ErrorData::invalid_params(e.to_string(), None))
                 }
                 _ => {
+                    crate::metrics::global().record_error(&e);
                     tracing::error!("ui_think error: {}", e);
                     Err(ErrorData::internal_error(e.to_string(), None))
                 }
@@ -143,30 +598,49 @@ impl UnifiedIntelligenceService {
         }
     }
 
-    #[tool(description = "Retrieve thoughts and memories by ID or chain ID.")]
+    #[tool(
+        description = "Retrieve thoughts and memories by ID or chain ID, search them by content, or delete a thought."
+    )]
     pub async fn ui_recall(
         &self,
         params: Parameters<UiRecallParams>,
     ) -> std::result::Result<CallToolResult, ErrorData> {
+        crate::metrics::global().record_tool_call("ui_recall");
         // Check rate limit
-        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id).await {
+        if let Err(e) = self
+            .rate_limiter
+            .check_rate_limit(&self.instance_id, "ui_recall")
+            .await
+        {
             tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
-            return Err(ErrorData::invalid_params(
-                "Rate limit exceeded. Please slow down your requests.".to_string(),
-                None,
-            ));
+            return Err(rate_limit_error(&e));
         }
 
         if params.0.mode == "help" {
             let help = serde_json::json!({
                 "tool": "ui_recall",
                 "usage": {
-                    "mode": "thought|chain|help",
-                    "id": "string (thought_id or chain_id)"
+                    "mode": "thought|chain|export|search|delete|help",
+                    "id": "string (thought_id or chain_id; unused by search)",
+                    "query": "string? (search mode; RediSearch full-text query)",
+                    "min_importance": "integer? (search mode; importance >= N, sorted by importance desc)",
+                    "min_relevance": "integer? (search mode; relevance >= N)",
+                    "include_framework_summary": "bool? (chain mode)",
+                    "as_tree": "bool? (chain mode; nest by parent_thought_id)",
+                    "since": "integer? (chain mode; only thoughts after the first N, returns {thoughts, since, tail})",
+                    "offset": "integer? (chain mode; first position of a page, returns {thoughts, offset, total, next_offset}; search mode: matches to skip)",
+                    "limit": "integer? (chain and search modes; page size)",
+                    "cursor": "integer? (export mode; continue from the previous page's cursor until done)",
+                    "confirm": "bool? (delete mode; must be true)",
+                    "admin_token": "string? (delete mode; must match server.admin_token)"
                 },
                 "examples": [
                     {"mode": "thought", "id": "<thought_id>"},
                     {"mode": "chain", "id": "<chain_id>"},
+                    {"mode": "export", "id": "<chain_id>", "cursor": 0},
+                    {"mode": "search", "query": "caching", "limit": 20},
+                    {"mode": "search", "query": "caching", "min_importance": 8},
+                    {"mode": "delete", "id": "<thought_id>", "confirm": true},
                     {"mode": "help", "id": "ignored"}
                 ],
                 "troubleshooting": [
@@ -174,20 +648,14 @@ impl UnifiedIntelligenceService {
                     "Use ui_help for a list of tools and high-level guidance"
                 ]
             });
-            let content = Content::json(help).map_err(|e| {
-                ErrorData::internal_error(format!("Failed to create JSON content: {e}"), None)
-            })?;
+            let content = self.json_content("ui_recall", help)?;
             return Ok(CallToolResult::success(vec![content]));
         }
 
         match self.handlers.recall.recall(params.0).await {
-            Ok(response) => {
-                let content = Content::json(response).map_err(|e| {
-                    ErrorData::internal_error(format!("Failed to create JSON content: {e}"), None)
-                })?;
-                Ok(CallToolResult::success(vec![content]))
-            }
+            Ok(response) => self.tool_result("ui_recall", response).await,
             Err(e) => {
+                crate::metrics::global().record_error_kind("internal");
                 tracing::error!("ui_recall error: {}", e);
                 Err(ErrorData::internal_error(
                     format!("Error recalling thought: {e}"),
@@ -202,15 +670,15 @@ impl UnifiedIntelligenceService {
         &self,
         params: Parameters<UiHelpParams>,
     ) -> std::result::Result<CallToolResult, ErrorData> {
+        crate::metrics::global().record_tool_call("ui_help");
         // No rate limit for help requests
         match self.handlers.ui_help(params.0).await {
             Ok(response) => {
-                let content = Content::json(response).map_err(|e| {
-                    ErrorData::internal_error(format!("Failed to create JSON content: {e}"), None)
-                })?;
+                let content = self.json_content("ui_help", response)?;
                 Ok(CallToolResult::success(vec![content]))
             }
             Err(e) => {
+                crate::metrics::global().record_error(&e);
                 tracing::error!("ui_help error: {}", e);
                 Err(ErrorData::internal_error(
                     format!("Error generating help: {e}"),
@@ -225,62 +693,76 @@ impl UnifiedIntelligenceService {
         &self,
         params: Parameters<UiKnowledgeParams>,
     ) -> std::result::Result<CallToolResult, ErrorData> {
+        crate::metrics::global().record_tool_call("ui_knowledge");
         // Check rate limit
-        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id).await {
+        if let Err(e) = self
+            .rate_limiter
+            .check_rate_limit(&self.instance_id, "ui_knowledge")
+            .await
+        {
             tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
-            return Err(ErrorData::invalid_params(
-                "Rate limit exceeded. Please slow down your requests.".to_string(),
-                None,
-            ));
+            return Err(rate_limit_error(&e));
         }
 
         if params.0.mode == "help" {
             let help = serde_json::json!({
                 "tool": "ui_knowledge",
                 "usage": {
-                    "mode": "create|search|set_active|get_entity|create_relation|get_relations|update_entity|delete_entity|help",
+                    "mode": "create|search|set_active|get_entity|create_relation|get_relations|delete_relation|subgraph|traverse|update_entity|delete_entity|archive|unarchive|merge_entities|infer_relations|entities_near_thought|get_active|clear_active|repair_name_index|help",
                     "common": ["entity_id?", "scope?"],
                     "create/update": ["name?", "display_name?", "entity_type?", "attributes?", "tags?"],
-                    "search": ["query?", "limit?"],
+                    "search": ["query?", "limit?", "include_relation_counts?", "vector?", "search_type? (text|semantic)", "explain?", "include_archived?"],
                     "relations": ["from_entity_id?", "to_entity_id?", "relationship_type?", "bidirectional?", "weight?"],
+                    "get_relations": ["entity_id", "scope?", "include_attributes?"],
+                    "delete_relation": ["relation_id", "scope?"],
+                    "merge_entities": ["from_entity_id", "to_entity_id", "scope?"],
+                    "archive/unarchive": ["entity_id", "scope?"],
+                    "subgraph": ["entity_id", "scope?", "depth? (default 1, capped by knowledge.max_traversal_depth)", "include_attributes?"],
+                    "traverse": ["entity_id", "scope?", "depth? (default 1, at most knowledge.max_traversal_depth)", "relationship_type?", "include_attributes?"],
+                    "infer_relations": ["entity_id", "scope?", "limit?", "apply?", "include_attributes?"],
+                    "entities_near_thought": ["thought_id", "scope?", "limit?", "vector?", "entity_type?"],
+                    "repair_name_index": ["admin_token", "scope?", "apply?"],
                 },
                 "troubleshooting": [
                     "Use scope Federation or Personal appropriately",
                     "Ensure entity names are unique within scope"
                 ]
             });
-            let content = Content::json(help).map_err(|e| {
-                ErrorData::internal_error(format!("Failed to create JSON content: {e}"), None)
-            })?;
+            let content = self.json_content("ui_knowledge", help)?;
             return Ok(CallToolResult::success(vec![content]));
         }
 
         match self.handlers.ui_knowledge(params.0).await {
-            Ok(response) => {
-                let content = Content::json(response).map_err(|e| {
-                    ErrorData::internal_error(format!("Failed to create JSON content: {e}"), None)
-                })?;
-                Ok(CallToolResult::success(vec![content]))
+            Ok(response) => self.tool_result("ui_knowledge", response).await,
+            // Rejected like the other admin-only actions (ui_recall delete, ui_memory prune_chains)
+            Err(e @ UnifiedIntelligenceError::Unauthorized) => {
+                crate::metrics::global().record_error(&e);
+                Err(ErrorData::invalid_request(
+                    "repair_name_index requires a valid admin_token".to_string(),
+                    None,
+                ))
             }
             Err(e) => {
+                crate::metrics::global().record_error(&e);
                 tracing::error!("ui_knowledge error: {}", e);
                 Err(ErrorData::internal_error(e.to_string(), None))
             }
         }
     }
 
-
     #[tool(description = "Search/read/update/delete memory across embeddings with simple filters")]
     pub async fn ui_memory(
         &self,
         params: Parameters<UiMemoryParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id).await {
+        crate::metrics::global().record_tool_call("ui_memory");
+        if let Err(e) = self
+            .rate_limiter
+            .check_rate_limit(&self.instance_id, "ui_memory")
+            .await
+        {
             tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
-            return Err(ErrorData::invalid_params(
-                "Rate limit exceeded. Please slow down your requests.".to_string(),
-                None,
-            ));
+            return Err(rate_limit_error(&e));
         }
 
         // Standardized help for ui_memory
@@ -288,40 +770,118 @@ impl UnifiedIntelligenceService {
             let help = serde_json::json!({
                 "tool": "ui_memory",
                 "usage": {
-                    "action": "search|read|update|delete|help",
+                    "action": "search|read|update|delete|prune_chains|script_status|help",
                     "query?": "string",
-                    "scope?": "all|session-summaries|important|federation",
-                    "filters?": {"tags?": "string[]", "importance?": "string", "chain_id?": "string", "thought_id?": "string"},
-                    "options?": {"limit?": "number", "offset?": "number", "k?": "number", "search_type?": "string"},
+                    "scope?": "all|session-summaries|important|federation|thoughts",
+                    "filters?": {"tags?": "string[]", "importance?": "string", "chain_id?": "string", "thought_id?": "string", "sentiment_min?": "number (scope thoughts)", "sentiment_max?": "number (scope thoughts)"},
+                    "options?": {"limit?": "number", "offset?": "number", "k?": "number", "search_type?": "keyword|vector|hybrid (default hybrid)", "min_score?": "number (vector similarity)", "ef_runtime?": "number", "sort_by?": "sentiment|ts", "sort_desc?": "boolean (default true)"},
                     "targets?": {"keys?": "string[]"},
-                    "update?": {"content?": "string", "tags?": "string[]", "importance?": "string", "chain_id?": "string", "thought_id?": "string"}
+                    "update?": {"content?": "string", "tags?": "string[]", "importance?": "string", "chain_id?": "string", "thought_id?": "string"},
+                    "dry_run?": "boolean (prune_chains; default true)",
+                    "admin_token?": "string (prune_chains with dry_run=false; script_status)"
                 },
                 "examples": [
                     {"action": "search", "query": "vector db", "scope": "all"},
                     {"action": "search", "query": "session summary", "scope": "session-summaries"},
+                    {"action": "search", "scope": "thoughts", "filters": {"sentiment_max": -0.3}, "options": {"sort_by": "sentiment", "sort_desc": false}},
                     {"action": "read", "targets": {"keys": ["CC:embeddings:important:abc123"]}},
+                    {"action": "prune_chains"},
                     {"action": "help"}
                 ],
                 "troubleshooting": [
-                    "UTF-8 errors: avoids binary 'vector' field using HMGET for text fields",
+                    "UTF-8 errors: only text fields are returned; the binary 'vector' field is never fetched",
                     "Empty results: confirm indices and scope",
-                    "Set OPENAI_API_KEY for re-embedding on update"
+                    "Re-embedding on update needs an embedding provider (OPENAI_API_KEY by default)"
                 ]
             });
-            let content = Content::json(help).map_err(|e| {
-                ErrorData::internal_error(format!("Failed to create JSON content: {e}"), None)
-            })?;
+            let content = self.json_content("ui_memory", help)?;
             return Ok(CallToolResult::success(vec![content]));
         }
 
-        match ui_memory_impl(&self.config, &self.handlers.redis_manager, params.0).await {
-            Ok(response) => {
-                let content = Content::json(response).map_err(|e| {
-                    ErrorData::internal_error(format!("Failed to create JSON content: {e}"), None)
-                })?;
-                Ok(CallToolResult::success(vec![content]))
+        // Lua script cache diagnostics for NOSCRIPT issues (admin only)
+        if params.0.action.eq_ignore_ascii_case("script_status") {
+            if !self.config.server.is_admin(params.0.admin_token.as_deref()) {
+                tracing::warn!("script_status rejected: missing or invalid admin token");
+                return Err(ErrorData::invalid_request(
+                    "script_status requires a valid admin_token".to_string(),
+                    None,
+                ));
+            }
+            let scripts = self
+                .handlers
+                .redis_manager
+                .script_status()
+                .await
+                .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+            let missing = scripts.iter().filter(|s| !s.exists).count();
+            let result = crate::tools::ui_memory::UiMemoryResult {
+                message: Some(format!(
+                    "{} of {} scripts missing from the Redis script cache",
+                    missing,
+                    scripts.len()
+                )),
+                scripts: Some(scripts),
+                ..Default::default()
+            };
+            return self.tool_result("ui_memory", result).await;
+        }
+
+        // Chain pruning works on thought chains through the repository
+        if params.0.action.eq_ignore_ascii_case("prune_chains") {
+            let dry_run = params.0.dry_run.unwrap_or(true);
+            if !dry_run && !self.config.server.is_admin(params.0.admin_token.as_deref()) {
+                tracing::warn!("prune_chains rejected: missing or invalid admin token");
+                return Err(ErrorData::invalid_request(
+                    "prune_chains with dry_run=false requires a valid admin_token".to_string(),
+                    None,
+                ));
             }
+            let chains = &self.config.chains;
+            let idle_since =
+                chrono::Utc::now() - chrono::Duration::hours(chains.prune_min_age_hours as i64);
+            let report = self
+                .handlers
+                .repository
+                .prune_chains(
+                    &self.instance_id,
+                    chains.prune_max_thoughts,
+                    idle_since,
+                    !dry_run,
+                )
+                .await
+                .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+            let message = if dry_run {
+                format!(
+                    "{} of {} chains eligible for pruning; pass dry_run=false to delete them",
+                    report.pruned.len(),
+                    report.scanned_chains
+                )
+            } else {
+                format!(
+                    "Pruned {} of {} chains",
+                    report.pruned.len(),
+                    report.scanned_chains
+                )
+            };
+            let result = crate::tools::ui_memory::UiMemoryResult {
+                message: Some(message),
+                prune_report: Some(report),
+                ..Default::default()
+            };
+            return self.tool_result("ui_memory", result).await;
+        }
+
+        match ui_memory_impl(
+            &self.config,
+            &self.handlers.redis_manager,
+            self.handlers.embedder.as_deref(),
+            params.0,
+        )
+        .await
+        {
+            Ok(response) => self.tool_result("ui_memory", response).await,
             Err(e) => {
+                crate::metrics::global().record_error_kind("other");
                 tracing::error!("ui_memory error: {}", e);
                 Err(ErrorData::internal_error(e.to_string(), None))
             }
@@ -334,13 +894,16 @@ impl UnifiedIntelligenceService {
     pub async fn ui_remember(
         &self,
         params: Parameters<UiRememberParams>,
+        context: rmcp::service::RequestContext<rmcp::RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id).await {
+        crate::metrics::global().record_tool_call("ui_remember");
+        if let Err(e) = self
+            .rate_limiter
+            .check_rate_limit(&self.instance_id, "ui_remember")
+            .await
+        {
             tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
-            return Err(ErrorData::invalid_params(
-                "Rate limit exceeded. Please slow down your requests.".to_string(),
-                None,
-            ));
+            return Err(rate_limit_error(&e));
         }
 
         // 0) Help mode
@@ -349,14 +912,18 @@ impl UnifiedIntelligenceService {
             let help = serde_json::json!({
                 "tool": "ui_remember",
                 "usage": {
-                    "action?": "help|query|feedback (aliases: fb, critique, review)",
+                    "action?": "help|query|feedback (aliases: fb, critique, review)|prompt",
                     "thought": "string",
                     "thought_number": "integer (auto-assigned; client value ignored)",
                     "total_thoughts": "integer (auto-assigned; client value ignored)",
                     "chain_id?": "string (required for feedback; minted on first query)",
                     "style?": "string (e.g., deep|chronological)",
                     "tags?": "string[]",
-                    "search_all_instances?": "boolean (default false; search all instances' indices)"
+                    "search_all_instances?": "boolean (default false; search all instances' indices)",
+                    "explain?": "boolean (default false; include per-candidate scoring_debug)",
+                    "weights?": "{semantic, text, recency} each 0..=1 (overrides config for this call)",
+                    "highlights?": "integer (context sentences closest to the answer; overrides ui_remember.highlights, 0 disables)",
+                    "thought_id?": "string (prompt: assistant thought id; needs ui_remember.persist_prompts)"
                 },
                 "flow": "T1 user thought -> T2 synthesized assistant -> T3 feedback (and feedback hash)",
                 "troubleshooting": [
@@ -364,9 +931,7 @@ impl UnifiedIntelligenceService {
                     "Set OPENAI_API_KEY and GROQ_API_KEY"
                 ]
             });
-            let content = Content::json(help).map_err(|e| {
-                ErrorData::internal_error(format!("Failed to create JSON content: {e}"), None)
-            })?;
+            let content = self.json_content("ui_remember", help)?;
             return Ok(CallToolResult::success(vec![content]));
         }
 
@@ -379,16 +944,58 @@ impl UnifiedIntelligenceService {
             let norm: String = a.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
             match norm.as_str() {
                 "feedback" | "fb" | "critique" | "review" => "feedback",
+                "prompt" => "prompt",
                 _ => "query",
             }
         }
         let action = parse_action(&p.action, p.feedback.is_some());
 
+        // Debug: return the synthesis prompt persisted for an assistant thought
+        if action == "prompt" {
+            let thought_id = p.thought_id.clone().ok_or_else(|| {
+                ErrorData::invalid_params("thought_id is required for prompt".to_string(), None)
+            })?;
+            let key = synthesis_prompt_key(&self.instance_id, &thought_id);
+            let stored: Option<String> = match self.handlers.redis_manager.get_connection().await {
+                Ok(mut con) => redis::cmd("GET")
+                    .arg(&key)
+                    .query_async(&mut *con)
+                    .await
+                    .map_err(|e| ErrorData::internal_error(e.to_string(), None))?,
+                Err(e) => return Err(ErrorData::internal_error(e.to_string(), None)),
+            };
+            let Some(stored) = stored else {
+                return Err(ErrorData::resource_not_found(
+                    format!(
+                        "No stored prompt for thought {thought_id} (is ui_remember.persist_prompts enabled?)"
+                    ),
+                    None,
+                ));
+            };
+            let record: serde_json::Value = serde_json::from_str(&stored)
+                .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+            let content = self.json_content("ui_remember", record)?;
+            return Ok(CallToolResult::success(vec![content]));
+        }
+
         // If this is an explicit feedback call, store Thought 3 (feedback) and return
         if action == "feedback" {
             let chain_id = p.chain_id.clone().ok_or_else(|| {
                 ErrorData::invalid_params("chain_id is required for feedback".to_string(), None)
             })?;
+            if self.config.chains.verify_instance
+                && !self
+                    .handlers
+                    .repository
+                    .chain_belongs_to(&self.instance_id, &chain_id)
+                    .await
+                    .map_err(|e| ErrorData::internal_error(e.to_string(), None))?
+            {
+                return Err(ErrorData::resource_not_found(
+                    format!("Chain {chain_id} not found."),
+                    Some(serde_json::json!({ "chain_id": chain_id })),
+                ));
+            }
             // Find latest assistant thought in the chain to attach feedback to
             let latest_assistant = self
                 .handlers
@@ -419,32 +1026,44 @@ impl UnifiedIntelligenceService {
                 Some("ui_remember:feedback".to_string()),
             );
             let thought3_id = t3.id.clone();
-            if let Err(e) = self.handlers.repository.save_thought(&t3).await {
+            if let Err(e) = self
+                .handlers
+                .repository
+                .save_thought_with_options(&t3, self.config.ui_remember.ephemeral_thoughts)
+                .await
+            {
+                crate::metrics::global().record_error(&e);
                 tracing::error!("ui_remember: failed to save feedback T3: {}", e);
                 return Err(ErrorData::internal_error(e.to_string(), None));
             }
 
-            // Update feedback hash for latest assistant if available
-            if let (Some(assistant), Ok(mut con)) = (
-                latest_assistant,
-                self.handlers.redis_manager.get_connection().await,
-            ) {
-                let key = format!("voice:feedback:{}", assistant.id);
-                let _: () = redis::pipe()
-                    .hset(&key, "llm_feedback", &t3.thought)
-                    .hset(
-                        &key,
-                        "continue_next",
-                        i32::from(p.continue_next.unwrap_or(false)),
-                    )
-                    .query_async(&mut *con)
-                    .await
-                    .unwrap_or(());
-            }
+            // Update feedback for the latest assistant turn if available
+            let feedback_written = match latest_assistant {
+                Some(assistant) => {
+                    let written = self
+                        .write_llm_feedback(
+                            &chain_id,
+                            &assistant.id,
+                            &t3.thought,
+                            p.continue_next.unwrap_or(false),
+                        )
+                        .await;
+                    if let Err(e) = &written {
+                        tracing::error!(
+                            "ui_remember: failed to write feedback for {}: {}",
+                            assistant.id,
+                            e
+                        );
+                    }
+                    Some(written.is_ok())
+                }
+                None => None,
+            };
 
             let result = UiRememberResult {
                 status: "feedback_saved".to_string(),
                 thought3_id: Some(thought3_id),
+                feedback_written,
                 next_action: if p.continue_next.unwrap_or(false) {
                     Some(crate::tools::ui_remember::NextAction {
                         tool: "ui_remember".to_string(),
@@ -457,15 +1076,27 @@ impl UnifiedIntelligenceService {
                 },
                 ..Default::default()
             };
-            let ack = Content::text(
-                "Feedback stored. Set continue_next=true to proceed with another query.",
-            );
-            let content = Content::json(result)
-                .map_err(|e| ErrorData::internal_error(format!("JSON encode error: {e}"), None))?;
+            let ack = Content::text(if result.feedback_written == Some(false) {
+                "Feedback thought stored, but updating the feedback hash failed; see feedback_written."
+            } else {
+                "Feedback stored. Set continue_next=true to proceed with another query."
+            });
+            let content = self.json_content("ui_remember", result)?;
             return Ok(CallToolResult::success(vec![ack, content]));
         }
 
+        // Per-request weight override, validated before anything is written
+        let weights = match p.weights {
+            Some(w) => {
+                w.validate()
+                    .map_err(|reason| ErrorData::invalid_params(reason, None))?;
+                w
+            }
+            None => self.config.ui_remember.hybrid_weights,
+        };
+
         // 1) If there is a prior assistant synthesis in this chain, update its feedback from current user behavior
+        let mut feedback_written = None;
         if let Some(ref chain_id) = p.chain_id {
             if let Ok(chain_thoughts) = self
                 .handlers
@@ -484,22 +1115,27 @@ impl UnifiedIntelligenceService {
                     let now = chrono::Utc::now();
                     let delta = (now - prev_ts).num_seconds().max(0);
 
-                    let (score, abandoned, continued, corrected) =
-                        compute_feedback_scoring(delta, &p.thought);
-
-                    if let Ok(mut con) = self.handlers.redis_manager.get_connection().await {
-                        let key = format!("voice:feedback:{}", prev_assistant.id);
-                        let corrected_text = if corrected { &p.thought } else { "" };
-                        let _: () = redis::pipe()
-                            .hset(&key, "continued", continued)
-                            .hset(&key, "time_to_next", delta)
-                            .hset(&key, "corrected", corrected_text)
-                            .hset(&key, "synthesis_quality", score)
-                            .hset(&key, "feedback_score", score)
-                            .hset(&key, "abandoned", abandoned)
-                            .query_async(&mut *con)
-                            .await
-                            .unwrap_or(());
+                    match self
+                        .apply_followup_feedback(chain_id, &prev_assistant.id, delta, &p.thought)
+                        .await
+                    {
+                        Ok(applied) => {
+                            if !applied {
+                                tracing::debug!(
+                                    "ui_remember: follow-up feedback for {} ignored (outside merge window)",
+                                    prev_assistant.id
+                                );
+                            }
+                            feedback_written = Some(true);
+                        }
+                        Err(e) => {
+                            tracing::error!(
+                                "ui_remember: failed to record follow-up feedback for {}: {}",
+                                prev_assistant.id,
+                                e
+                            );
+                            feedback_written = Some(false);
+                        }
                     }
                 }
             }
@@ -535,39 +1171,104 @@ impl UnifiedIntelligenceService {
             Some("ui_remember:user".to_string()),
         );
         let thought1_id = t1.id.clone();
-        if let Err(e) = self.handlers.repository.save_thought(&t1).await {
+        if let Err(e) = self
+            .handlers
+            .repository
+            .save_thought_with_options(&t1, self.config.ui_remember.ephemeral_thoughts)
+            .await
+        {
+            crate::metrics::global().record_error(&e);
             tracing::error!("ui_remember: failed to save T1: {}", e);
             return Err(ErrorData::internal_error(e.to_string(), None));
         }
 
+        // Intent parsing is opt-in (one extra Groq call). It runs before
+        // retrieval so a parsed temporal filter narrows the candidates; below
+        // `groq.intent_min_confidence` it falls back to defaults and is flagged
+        let mut intent = if self.config.ui_remember.parse_intent {
+            self.parse_intent(&p.thought).await
+        } else {
+            crate::models::QueryIntent {
+                original_query: p.thought.clone(),
+                ..Default::default()
+            }
+        };
+        let time_range = intent
+            .temporal_filter
+            .as_ref()
+            .and_then(|f| temporal_range(f, chrono::Utc::now()));
+
         // 3) Retrieval: text search over thoughts + embedding KNN over memory indices
+        // Subsystems unavailable for this call, surfaced to the client as `degraded`
+        let mut degraded: Vec<String> = Vec::new();
+        let assistant_context = self.config.ui_remember.assistant_context;
+        let exclude_assistant = assistant_context == AssistantContext::Exclude;
+        // Over-fetch when hits are dropped so other hits fill their slots
+        let text_limit = if exclude_assistant || time_range.is_some() {
+            10
+        } else {
+            5
+        };
         let retrieved = match self
             .handlers
             .repository
-            .search_thoughts(&self.instance_id, &p.thought, 0, 5)
+            .search_thoughts(
+                &self.instance_id,
+                &p.thought,
+                ThoughtSearchFilter::default(),
+                0,
+                text_limit,
+            )
             .await
         {
-            Ok(v) => v,
+            Ok((mut v, _)) => {
+                if exclude_assistant {
+                    v.retain(|t| !is_assistant_synthesis(t.category.as_deref()));
+                }
+                // The text index has no numeric timestamp, so the window is
+                // applied to the hits
+                if let Some((start, end)) = time_range {
+                    v.retain(|t| {
+                        chrono::DateTime::parse_from_rfc3339(&t.timestamp)
+                            .is_ok_and(|dt| (start..=end).contains(&dt.timestamp()))
+                    });
+                }
+                v.truncate(5);
+                v
+            }
             Err(e) => {
                 tracing::warn!(
                     "ui_remember: retrieval failed, continuing without context: {}",
                     e
                 );
+                degraded.push("search".to_string());
                 Vec::new()
             }
         };
 
         // Embedding KNN across memory indexes (thoughts + kg_entity)
         // (key, optional_distance_score, content, ts)
-        let mut knn_items: Vec<(String, Option<f64>, String, i64)> = Vec::new();
-        if let Ok(openai_key) = self.config.openai.api_key() {
-            if let Ok(embedding) =
-                generate_openai_embedding(&p.thought, &openai_key, &self.handlers.redis_manager)
-                    .await
+        let mut knn_items: Vec<(String, Option<f64>, String, i64, String)> = Vec::new();
+        // KNN keys of prior syntheses, for `assistant_context: downweight`
+        let mut assistant_keys: std::collections::HashSet<String> =
+            std::collections::HashSet::new();
+        if let Some(embedder) = self.handlers.embedder.as_deref() {
+            if let Ok(embedding) = generate_embedding(
+                &p.thought,
+                embedder,
+                &self.handlers.redis_manager,
+                self.config.openai.embedding_max_input_chars,
+            )
+            .await
             {
-                let dims = self.config.openai.embedding_dimensions;
-                if embedding.len() == dims {
-                    let vec_bytes: Vec<u8> = cast_slice(&embedding).to_vec();
+                let dims_ok = self
+                    .config
+                    .openai
+                    .check_embedding_dims(embedding.len())
+                    .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+                if dims_ok {
+                    let vec_bytes: Vec<u8> =
+                        self.config.redis_search.vector_type.encode(&embedding);
 
                     // Build index list based on scope
                     let mut indexes: Vec<String> = Vec::new();
@@ -604,70 +1305,76 @@ impl UnifiedIntelligenceService {
                         }
                     }
 
+                    let neighbors = self.config.ui_remember.summary_chunk_neighbors;
                     for iid in instances {
-indexes.push(format!("idx:{iid}:thought"));
+                        indexes.push(format!("idx:{iid}:thought"));
                         indexes.push(format!("idx:{iid}:kg_entity"));
+                        if neighbors > 0 {
+                            indexes.push(format!("idx:{iid}:session-summaries"));
+                        }
                     }
 
-                    if let Ok(mut con) = self.handlers.redis_manager.get_connection().await {
-                        for idx in indexes {
-                            let val: redis::Value = redis::cmd("FT.SEARCH")
-                                .arg(&idx)
-                                .arg("*=>[KNN $k @vector $vec AS score]")
-                                .arg("PARAMS")
-                                .arg(4)
-                                .arg("k")
-                                .arg(5)
-                                .arg("vec")
-                                .arg(vec_bytes.as_slice())
-                                .arg("SORTBY")
-                                .arg("score")
-                                .arg("LIMIT")
-                                .arg(0)
-                                .arg(5)
-                                .arg("RETURN")
-                                .arg(1)
-                                .arg("score")
-                                .arg("DIALECT")
-                                .arg(2)
-                                .query_async(&mut *con)
-                                .await
-                                .unwrap_or(redis::Value::Nil);
-                            let keys_scores = extract_doc_ids_and_scores(&val);
-                            if keys_scores.is_empty() {
-                                continue;
-                            }
-                            // Use HMGET to fetch only text fields, avoiding binary vector field
-                            let fields = ["content", "ts"];
-                            let mut pipe = redis::pipe();
-                            for (k, _) in &keys_scores {
-                                pipe.cmd("HMGET").arg(k).arg(&fields);
-                            }
-                            let rows: Vec<Vec<Option<String>>> =
-                                pipe.query_async(&mut *con).await.unwrap_or_default();
-                            for (i, row) in rows.into_iter().enumerate() {
-                                if i >= keys_scores.len() {
-                                    break;
-                                }
-                                let mut it = row.into_iter();
-                                let content = it.next().flatten().unwrap_or_default();
-                                let ts = it
-                                    .next()
-                                    .and_then(|s| s.and_then(|x| x.parse::<i64>().ok()))
-                                    .unwrap_or_default();
-                                if !content.is_empty() {
-                                    let (key, score_opt) = &keys_scores[i];
-                                    knn_items.push((key.clone(), *score_opt, content, ts));
+                    // Every memory index carries a NUMERIC `ts`
+                    let knn_filter = time_range
+                        .map(|(start, end)| format!("@ts:[{start} {end}]"))
+                        .unwrap_or_else(|| "*".to_string());
+                    for idx in &indexes {
+                        let k = self.config.redis_search.knn_k(idx, None);
+                        // Only text fields; the binary vector is never returned
+                        let rows = self
+                            .handlers
+                            .redis_manager
+                            .knn_search_filtered(
+                                idx,
+                                &knn_filter,
+                                &vec_bytes,
+                                k,
+                                None,
+                                &["content", "ts", "category"],
+                            )
+                            .await
+                            .unwrap_or_default();
+                        for row in rows {
+                            let mut it = row.fields.into_iter();
+                            let content = it.next().flatten().unwrap_or_default();
+                            let ts = it
+                                .next()
+                                .flatten()
+                                .and_then(|x| x.parse::<i64>().ok())
+                                .unwrap_or_default();
+                            if is_assistant_synthesis(it.next().flatten().as_deref()) {
+                                match assistant_context {
+                                    AssistantContext::Exclude => continue,
+                                    AssistantContext::Downweight => {
+                                        assistant_keys.insert(row.key.clone());
+                                    }
+                                    AssistantContext::Include => {}
                                 }
                             }
+                            if !content.is_empty() {
+                                knn_items.push((row.key, row.score, content, ts, idx.clone()));
+                            }
                         }
                     }
+                    if neighbors > 0 {
+                        expand_summary_chunks(
+                            &self.handlers.redis_manager,
+                            &self.config.redis,
+                            &mut knn_items,
+                            neighbors,
+                        )
+                        .await;
+                    }
+                } else {
+                    degraded.push("embeddings".to_string());
                 }
+            } else {
+                degraded.push("embeddings".to_string());
             }
         } else {
-            tracing::warn!(
-                "OPENAI_API_KEY not available; skipping KNN vector search for ui_remember"
-            );
+            // Missing provider is reported once at startup
+            tracing::debug!("Embedding provider not available; skipping KNN vector search");
+            degraded.push("embeddings".to_string());
         }
         let knn_count = knn_items.len();
         // Simple recency proxy: average age (seconds) of KNN items
@@ -675,7 +1382,7 @@ indexes.push(format!("idx:{iid}:thought"));
         let _avg_age_secs: i64 = if knn_count > 0 {
             let sum: i64 = knn_items
                 .iter()
-                .map(|(_, _, _, ts)| (now_ts - *ts).max(0))
+                .map(|(_, _, _, ts, _)| (now_ts - *ts).max(0))
                 .sum();
             sum / (knn_count as i64)
         } else {
@@ -685,6 +1392,18 @@ indexes.push(format!("idx:{iid}:thought"));
         // Build candidate set with simple hybrid scoring (semantic/text/recency)
         struct Cand {
             thought: crate::models::Thought,
+            // Stable id of the underlying record (thought id or embedding key)
+            source_id: String,
+            // "text" for full-text hits, "knn" for vector hits
+            origin: &'static str,
+            // RediSearch index of the KNN hit (none for text-only hits)
+            index: Option<String>,
+            // Prior ui_remember synthesis (downweighted under `assistant_context`)
+            assistant: bool,
+            // Component scores before weighting
+            semantic: f64,
+            text: f64,
+            recency: f64,
             combined: f64,
         }
         let tau_secs: f64 = 86_400.0; // 1 day decay constant for recency
@@ -695,10 +1414,10 @@ indexes.push(format!("idx:{iid}:thought"));
 
         let mut cands: Vec<Cand> = Vec::new();
 
-        // Pull weights from config
-        let w_sem = self.config.ui_remember.hybrid_weights.semantic;
-        let w_text = self.config.ui_remember.hybrid_weights.text;
-        let w_rec = self.config.ui_remember.hybrid_weights.recency;
+        // Request override, else config/preset
+        let w_sem = weights.semantic;
+        let w_text = weights.text;
+        let w_rec = weights.recency;
 
         // Text hits -> text=1.0, semantic=0.0
         for r in &retrieved {
@@ -729,11 +1448,18 @@ indexes.push(format!("idx:{iid}:thought"));
                     usage_score: None,
                     combined_score: None,
                 },
+                source_id: r.id.clone(),
+                origin: "text",
+                index: None,
+                assistant: is_assistant_synthesis(r.category.as_deref()),
+                semantic: semantic_score,
+                text: text_score,
+                recency: rec,
                 combined,
             });
         }
         // KNN items -> semantic based on distance score, text=0.0
-        for (_key, score_opt, content, ts) in &knn_items {
+        for (key, score_opt, content, ts, index) in &knn_items {
             let id = uuid::Uuid::new_v4();
             let tsdt = chrono::DateTime::from_timestamp(*ts, 0).unwrap_or_else(chrono::Utc::now);
             let text_score = 0.0f64;
@@ -757,48 +1483,276 @@ indexes.push(format!("idx:{iid}:thought"));
                     usage_score: None,
                     combined_score: None,
                 },
+                source_id: key.clone(),
+                origin: "knn",
+                index: Some(index.clone()),
+                assistant: assistant_keys.contains(key),
+                semantic: semantic_score,
+                text: text_score,
+                recency: rec,
                 combined,
             });
         }
 
+        // The same memory can arrive as a text hit and a KNN hit; give it one slot
+        if self.config.ui_remember.dedup_candidates {
+            let normalization = self.config.bloom_filter.normalization;
+            let merged = dedup_candidates(
+                &mut cands,
+                |c| normalization.hash(&c.thought.content),
+                |c| c.combined,
+                |kept, other| {
+                    kept.semantic = kept.semantic.max(other.semantic);
+                    kept.text = kept.text.max(other.text);
+                    kept.recency = kept.recency.max(other.recency);
+                    kept.combined =
+                        w_sem * kept.semantic + w_text * kept.text + w_rec * kept.recency;
+                    if kept.origin != other.origin {
+                        kept.origin = "text+knn";
+                    }
+                    if kept.index.is_none() {
+                        kept.index = other.index.clone();
+                    }
+                    kept.assistant |= other.assistant;
+                },
+            );
+            if merged > 0 {
+                tracing::debug!("ui_remember: merged {} duplicate candidates", merged);
+            }
+        }
+        if assistant_context == AssistantContext::Downweight {
+            let factor = self.config.ui_remember.assistant_weight;
+            for c in cands.iter_mut().filter(|c| c.assistant) {
+                c.combined *= factor;
+            }
+        }
+
+        // Client fields win over the preset's synthesis profile
+        let profile = &self.config.ui_remember.profile;
+        let synthesis_style = p.style.clone().or_else(|| profile.style.clone());
         // Sort candidates and cap to top_k (default 5)
-        let top_k_used: usize = p.top_k.map(|v| v as usize).unwrap_or(5);
+        let top_k_used: usize = p.top_k.or(profile.top_k).map(|v| v as usize).unwrap_or(5);
         cands.sort_by(|a, b| {
             b.combined
                 .partial_cmp(&a.combined)
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
-        let ctx_thoughts: Vec<crate::models::Thought> = cands
-            .into_iter()
-            .take(top_k_used)
-            .map(|c| c.thought)
+        // Bound the synthesis context by item count and total content size
+        let content_chars: Vec<usize> = cands
+            .iter()
+            .map(|c| c.thought.content.chars().count())
             .collect();
+        let (context_len, dropped_score, dropped_size) = fit_context(
+            &content_chars,
+            top_k_used.min(self.config.ui_remember.max_context_items),
+            self.config.ui_remember.max_context_chars,
+        );
+        let scoring_debug = p.explain.unwrap_or(false).then(|| {
+            cands
+                .iter()
+                .enumerate()
+                .map(|(rank, c)| crate::tools::ui_remember::ScoringDebug {
+                    id: c.source_id.clone(),
+                    origin: c.origin.to_string(),
+                    index: c.index.clone(),
+                    semantic: c.semantic,
+                    text: c.text,
+                    recency: c.recency,
+                    weights,
+                    combined: c.combined,
+                    selected: rank < context_len,
+                })
+                .collect::<Vec<_>>()
+        });
+        if self.config.ui_remember.log_retrieval {
+            let candidates: Vec<serde_json::Value> = cands
+                .iter()
+                .enumerate()
+                .map(|(rank, c)| {
+                    serde_json::json!({
+                        "id": c.source_id,
+                        "origin": c.origin,
+                        "index": c.index,
+                        "score": c.combined,
+                        "selected": rank < context_len,
+                    })
+                })
+                .collect();
+            log_retrieval(
+                &self.handlers.redis_manager,
+                &format!("{}:eval:retrieval", self.instance_id),
+                self.config.ui_remember.log_retrieval_max_len,
+                &[
+                    ("query", p.thought.clone()),
+                    ("chain_id", chain_id.clone()),
+                    ("thought1_id", thought1_id.clone()),
+                    ("top_k", top_k_used.to_string()),
+                    ("ts", chrono::Utc::now().timestamp().to_string()),
+                    (
+                        "candidates",
+                        serde_json::Value::from(candidates).to_string(),
+                    ),
+                ],
+            )
+            .await;
+        }
+        let sources = self.config.ui_remember.include_sources.then(|| {
+            cands
+                .iter()
+                .take(context_len)
+                .map(|c| crate::tools::ui_remember::ContextSource {
+                    id: c.source_id.clone(),
+                    origin: c.origin.to_string(),
+                    index: c.index.clone(),
+                })
+                .collect::<Vec<_>>()
+        });
+        let (ctx_ids, ctx_thoughts): (Vec<String>, Vec<crate::models::Thought>) = cands
+            .into_iter()
+            .take(context_len)
+            .map(|c| (c.source_id, c.thought))
+            .unzip();
+        if self.config.usage.track_access {
+            self.handlers.repository.track_access(
+                &self.instance_id,
+                ctx_ids
+                    .iter()
+                    .filter_map(|id| context_thought_id(id))
+                    .map(str::to_string)
+                    .collect(),
+            );
+        }
 
-        // 3) Build intent and synthesize via Groq
-        let intent = crate::models::QueryIntent {
-            original_query: p.thought.clone(),
-            temporal_filter: None,
-            synthesis_style: p.style.clone(),
-        };
+        // 3) Synthesize; an explicit style wins over the parsed one
+        if synthesis_style.is_some() {
+            intent.synthesis_style = synthesis_style;
+        }
+        let synthesis_style = intent.synthesis_style.clone();
 
-        let groq_api_key = self.config.groq.api_key.clone();
-        let tx = match crate::transport::GroqTransport::new(groq_api_key) {
-            Ok(v) => std::sync::Arc::new(v) as std::sync::Arc<dyn crate::transport::Transport>,
+        let tx = match crate::circuit_breaker::synth_transport(&self.config) {
+            Ok(v) => v,
             Err(e) => return Err(ErrorData::internal_error(e.to_string(), None)),
         };
-        let synth = crate::synth::GroqSynth::new(
-            tx,
-            self.config.groq.model_fast.clone(),
-            self.config.groq.model_deep.clone(),
+        let (model_fast, model_deep) = self.config.synth.models(&self.config.groq);
+        let (model_fast, model_deep) = profile.model_pair(model_fast, model_deep);
+        let synth =
+            crate::synth::GroqSynth::new(tx, model_fast.to_string(), model_deep.to_string())
+                .with_deep_token_threshold(self.config.groq.deep_token_threshold)
+                .with_temperature(profile.temperature);
+
+        // Optional synthesis cache keyed by query + ordered context ids + model
+        // + style + temperature + provider
+        let cache_cfg = &self.config.ui_remember;
+        let style = synthesis_style.clone().unwrap_or_default();
+        let model = crate::synth::select_model(
+            synthesis_style.as_deref(),
+            &p.thought,
+            &ctx_thoughts,
+            model_fast,
+            model_deep,
+            (self.config.groq.deep_token_threshold > 0)
+                .then_some(self.config.groq.deep_token_threshold),
+        );
+        let cache_key = synthesis_cache_key(
+            &self.instance_id,
+            &p.thought,
+            &ctx_ids,
+            model,
+            &style,
+            profile.temperature,
+            self.config.synth.provider,
         );
+        let mut cached = false;
+        let cached_synth = if cache_cfg.synthesis_cache_enabled {
+            load_cached_synthesis(&self.handlers.redis_manager, &cache_key).await
+        } else {
+            None
+        };
 
         let start = std::time::Instant::now();
-        let synthesized = match synth.synth(&intent, &ctx_thoughts).await {
-            Ok(s) => s,
-            Err(e) => return Err(ErrorData::internal_error(e.to_string(), None)),
+        let synthesized = match cached_synth {
+            Some(hit) => {
+                cached = true;
+                hit
+            }
+            None => match synthesize(
+                &synth,
+                &intent,
+                &ctx_thoughts,
+                model,
+                &self.config.groq,
+                &context,
+            )
+            .await
+            {
+                Ok(s) => {
+                    // A partial answer is returned once but never reused
+                    if cache_cfg.synthesis_cache_enabled && !s.partial {
+                        store_cached_synthesis(
+                            &self.handlers.redis_manager,
+                            &cache_key,
+                            &s,
+                            cache_cfg.synthesis_cache_ttl_seconds,
+                        )
+                        .await;
+                    }
+                    s
+                }
+                // Groq is known to be down: keep T1 and report the skipped synthesis
+                Err(e @ UnifiedIntelligenceError::CircuitOpen { .. }) => {
+                    tracing::warn!("ui_remember: skipping synthesis: {}", e);
+                    degraded.push("synthesis".to_string());
+                    let result = UiRememberResult {
+                        status: "degraded".to_string(),
+                        thought1_id,
+                        retrieved_text_count: Some(retrieved.len()),
+                        retrieved_embedding_count: Some(knn_count),
+                        intent: Some(intent),
+                        degraded: Some(degraded),
+                        feedback_written,
+                        sources,
+                        ..Default::default()
+                    };
+                    let text_part = Content::text(format!(
+                        "Your message was stored, but synthesis was skipped: {e}"
+                    ));
+                    let json_part = self.json_content("ui_remember", result)?;
+                    return Ok(CallToolResult::success(vec![text_part, json_part]));
+                }
+                Err(e @ UnifiedIntelligenceError::Timeout { .. }) => {
+                    tracing::warn!("ui_remember: {}", e);
+                    return Err(timeout_error(&e));
+                }
+                Err(e) => return Err(ErrorData::internal_error(e.to_string(), None)),
+            },
         };
         let _latency_ms = start.elapsed().as_millis() as i64;
 
+        // Extractive grounding: context sentences closest to the answer
+        let highlight_count = p.highlights.unwrap_or(self.config.ui_remember.highlights);
+        let highlights = match self.handlers.embedder.as_deref() {
+            Some(embedder) if highlight_count > 0 => {
+                match extract_highlights(
+                    embedder,
+                    &synthesized.text,
+                    &ctx_ids,
+                    &ctx_thoughts,
+                    highlight_count,
+                    self.config.embeddings.max_calls_per_request,
+                    self.config.openai.embedding_max_input_chars,
+                )
+                .await
+                {
+                    Ok(h) => Some(h),
+                    Err(e) => {
+                        tracing::warn!("ui_remember: highlight extraction failed: {}", e);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
         // 4) Store Thought 2 (assistant synthesis)
         let t2 = crate::models::ThoughtRecord::new(
             self.instance_id.clone(),
@@ -814,22 +1768,59 @@ indexes.push(format!("idx:{iid}:thought"));
             Some("ui_remember:assistant".to_string()),
         );
         let thought2_id = t2.id.clone();
-        if let Err(e) = self.handlers.repository.save_thought(&t2).await {
+        if let Err(e) = self
+            .handlers
+            .repository
+            .save_thought_with_options(&t2, self.config.ui_remember.ephemeral_thoughts)
+            .await
+        {
+            crate::metrics::global().record_error(&e);
             tracing::error!("ui_remember: failed to save T2: {}", e);
             return Err(ErrorData::internal_error(e.to_string(), None));
         }
 
+        if self.config.ui_remember.persist_prompts {
+            let request = synth.render_request(&intent, &ctx_thoughts);
+            let record = serde_json::json!({
+                "thought_id": thought2_id,
+                "chain_id": chain_id,
+                "model": request.model,
+                "temperature": request.temperature,
+                "max_tokens": request.max_tokens,
+                "messages": request.messages,
+                "context_ids": ctx_ids,
+                "prompt_hash": prompt_hash(&request.messages),
+                "cached": cached,
+                "created_at": chrono::Utc::now().to_rfc3339(),
+            });
+            store_synthesis_prompt(
+                &self.handlers.redis_manager,
+                &synthesis_prompt_key(&self.instance_id, &thought2_id),
+                &record,
+            )
+            .await;
+        }
+
         // 5) Prompt for LLM feedback (no metrics thought here). Seed feedback hash for T2.
-        if let Ok(mut con) = self.handlers.redis_manager.get_connection().await {
-            let key = format!("voice:feedback:{thought2_id}");
-            let mut pipe = redis::pipe();
-            pipe.hset(&key, "synthesis_quality", 0.0f32)
+        // The next follow-up only scores (and aggregates) a seeded hash, so a
+        // failed seed loses that turn's signal; log it rather than fail the query
+        let key = format!("voice:feedback:{thought2_id}");
+        let seeded = match self.handlers.redis_manager.get_connection().await {
+            Ok(mut con) => redis::pipe()
+                .atomic()
+                .hset(&key, "synthesis_quality", 0.0f32)
                 .hset(&key, "continued", 0)
                 .hset(&key, "abandoned", 0)
                 .hset(&key, "corrected", "")
                 .hset(&key, "time_to_next", -1)
-                .hset(&key, "feedback_score", 0.0f32);
-            let _: () = pipe.query_async(&mut *con).await.unwrap_or(());
+                .hset(&key, "feedback_score", 0.0f32)
+                .query_async::<()>(&mut *con)
+                .await
+                .map_err(UnifiedIntelligenceError::Redis),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = seeded {
+            tracing::warn!("ui_remember: failed to seed feedback hash {}: {}", key, e);
         }
 
         let result = UiRememberResult {
@@ -842,6 +1833,20 @@ indexes.push(format!("idx:{iid}:thought"));
             assistant_text: Some(synthesized.text.clone()),
             retrieved_text_count: Some(retrieved.len()),
             retrieved_embedding_count: Some(knn_count),
+            cached: cached.then_some(true),
+            partial: synthesized.partial.then_some(true),
+            finish_reason: synthesized.partial.then(|| "timeout".to_string()),
+            intent: Some(intent),
+            degraded: (!degraded.is_empty()).then_some(degraded),
+            scoring_debug,
+            weights: Some(weights),
+            context_dropped: Some(crate::tools::ui_remember::ContextDropped {
+                score: dropped_score,
+                size: dropped_size,
+            }),
+            feedback_written,
+            highlights,
+            sources,
             next_action: Some(crate::tools::ui_remember::NextAction {
                 tool: "ui_remember".to_string(),
                 action: "feedback".to_string(),
@@ -855,12 +1860,483 @@ indexes.push(format!("idx:{iid}:thought"));
         let prompt = Content::text(
             "Provide feedback via ui_remember {action:\"feedback\", chain_id, feedback, continue_next?}.",
         );
-        let json_part = Content::json(result)
-            .map_err(|e| ErrorData::internal_error(format!("JSON encode error: {e}"), None))?;
+        let json_part = self.json_content("ui_remember", result)?;
         Ok(CallToolResult::success(vec![text_part, prompt, json_part]))
     }
 }
 
+// MCP error for a tool that ran past its budget, with the budget in `data`
+fn timeout_error(e: &UnifiedIntelligenceError) -> ErrorData {
+    crate::metrics::global().record_error(e);
+    let data = match e {
+        UnifiedIntelligenceError::Timeout { tool, budget_secs } => {
+            Some(serde_json::json!({ "tool": tool, "timeout_secs": budget_secs }))
+        }
+        _ => None,
+    };
+    ErrorData::internal_error(e.to_string(), data)
+}
+
+// MCP error for a rate-limit rejection, with a Retry-After equivalent in `data`
+fn rate_limit_error(e: &UnifiedIntelligenceError) -> ErrorData {
+    crate::metrics::global().record_error(e);
+    let data = match e {
+        UnifiedIntelligenceError::RateLimit { retry_after_secs } => {
+            Some(serde_json::json!({ "retry_after_secs": retry_after_secs }))
+        }
+        _ => None,
+    };
+    ErrorData::invalid_params(
+        "Rate limit exceeded. Please slow down your requests.".to_string(),
+        data,
+    )
+}
+
+/// Synthesize an answer, streaming it to clients that sent a progress token:
+/// each chunk is forwarded as a `notifications/progress` message. Streamed
+/// answers carry no token usage. A stream running past
+/// `groq.stream_timeout_secs` fails with a timeout, or with
+/// `groq.return_partial_on_timeout` returns the text received so far as
+/// `partial`.
+async fn synthesize(
+    synth: &crate::synth::GroqSynth,
+    intent: &crate::models::QueryIntent,
+    ctx: &[crate::models::Thought],
+    model: &str,
+    groq: &crate::config::GroqConfig,
+    context: &rmcp::service::RequestContext<rmcp::RoleServer>,
+) -> crate::error::Result<crate::synth::SynthResult> {
+    use futures::StreamExt;
+
+    let Some(progress_token) = context.meta.get_progress_token() else {
+        return synth.synth(intent, ctx).await;
+    };
+    let deadline = (groq.stream_timeout_secs > 0).then(|| {
+        tokio::time::Instant::now() + std::time::Duration::from_secs(groq.stream_timeout_secs)
+    });
+    let mut stream = synth.synth_stream(intent, ctx).await?;
+    let mut text = String::new();
+    let mut chunks = 0u32;
+    loop {
+        let next = match deadline {
+            Some(deadline) => match tokio::time::timeout_at(deadline, stream.next()).await {
+                Ok(next) => next,
+                Err(_) if groq.return_partial_on_timeout && !text.is_empty() => {
+                    tracing::warn!(
+                        "ui_remember: synthesis stream timed out after {}s; keeping {} chunks",
+                        groq.stream_timeout_secs,
+                        chunks
+                    );
+                    return Ok(crate::synth::SynthResult {
+                        text,
+                        usage: None,
+                        model_used: model.to_string(),
+                        partial: true,
+                    });
+                }
+                Err(_) => {
+                    return Err(UnifiedIntelligenceError::Timeout {
+                        tool: "ui_remember synthesis".to_string(),
+                        budget_secs: groq.stream_timeout_secs,
+                    });
+                }
+            },
+            None => stream.next().await,
+        };
+        let Some(chunk) = next else {
+            break;
+        };
+        let chunk = chunk?;
+        chunks += 1;
+        let progress = rmcp::model::ProgressNotificationParam {
+            progress_token: progress_token.clone(),
+            progress: f64::from(chunks),
+            total: None,
+            message: Some(chunk.clone()),
+        };
+        if let Err(e) = context.peer.notify_progress(progress).await {
+            tracing::debug!("ui_remember: failed to forward synthesis chunk: {}", e);
+        }
+        text.push_str(&chunk);
+    }
+    Ok(crate::synth::SynthResult {
+        text,
+        usage: None,
+        model_used: model.to_string(),
+        partial: false,
+    })
+}
+
+// Cache key for a synthesis over a given query and ordered context
+fn synthesis_cache_key(
+    instance_id: &str,
+    query: &str,
+    ctx_ids: &[String],
+    model: &str,
+    style: &str,
+    temperature: Option<f32>,
+    provider: crate::config::SynthProviderKind,
+) -> String {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    // Unset temperature hashes as "" so it never collides with an explicit one
+    let temperature = temperature.map(|t| t.to_string()).unwrap_or_default();
+    let provider = format!("{provider:?}");
+    for part in [query, model, style, &temperature, &provider] {
+        hasher.update(part.as_bytes());
+        hasher.update([0u8]);
+    }
+    for id in ctx_ids {
+        hasher.update(id.as_bytes());
+        hasher.update([0u8]);
+    }
+    let digest = hex::encode(hasher.finalize());
+    format!("{instance_id}:synth_cache:{digest}")
+}
+
+async fn load_cached_synthesis(
+    redis_manager: &RedisManager,
+    key: &str,
+) -> Option<crate::synth::SynthResult> {
+    let mut con = redis_manager.get_connection().await.ok()?;
+    let raw: Option<String> = redis::cmd("GET")
+        .arg(key)
+        .query_async(&mut *con)
+        .await
+        .ok()?;
+    let v: serde_json::Value = serde_json::from_str(&raw?).ok()?;
+    Some(crate::synth::SynthResult {
+        text: v.get("text")?.as_str()?.to_string(),
+        usage: None,
+        model_used: v.get("model_used")?.as_str()?.to_string(),
+        partial: false,
+    })
+}
+
+async fn store_cached_synthesis(
+    redis_manager: &RedisManager,
+    key: &str,
+    synthesized: &crate::synth::SynthResult,
+    ttl_seconds: u64,
+) {
+    let payload = serde_json::json!({
+        "text": synthesized.text,
+        "model_used": synthesized.model_used,
+    })
+    .to_string();
+    let res = match redis_manager.get_connection().await {
+        Ok(mut con) => redis::cmd("SET")
+            .arg(key)
+            .arg(payload)
+            .arg("EX")
+            .arg(ttl_seconds.max(1))
+            .query_async::<()>(&mut *con)
+            .await
+            .map_err(UnifiedIntelligenceError::from),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = res {
+        tracing::warn!("ui_remember: failed to cache synthesis: {}", e);
+    }
+}
+
+/// Compare existing vector index dimensions with `openai.embedding_dimensions`.
+/// A mismatch makes vector writes skip and KNN return nothing, so it is logged
+/// as an error (or fails startup under `index_dim_check: strict`).
+async fn check_index_dims(
+    redis_manager: &RedisManager,
+    config: &Config,
+) -> Result<(), UnifiedIntelligenceError> {
+    let mode = config.redis_search.index_dim_check;
+    if mode == IndexDimCheck::Off {
+        return Ok(());
+    }
+    let indexes = match redis_manager.vector_index_dims().await {
+        Ok(indexes) => indexes,
+        Err(e) => {
+            tracing::warn!("Skipping index dimension check: {}", e);
+            return Ok(());
+        }
+    };
+    let expected = config.openai.embedding_dimensions;
+    for (index, got) in indexes {
+        if got == expected {
+            continue;
+        }
+        tracing::error!(
+            "Index {} has DIM {} but openai.embedding_dimensions is {}; vector writes to it are skipped and KNN returns nothing. Restore the previous dimensions or reindex (FT.DROPINDEX {}, then re-embed with backfill_embeddings).",
+            index,
+            got,
+            expected,
+            index
+        );
+        if mode == IndexDimCheck::Strict {
+            return Err(UnifiedIntelligenceError::EmbeddingDimMismatch { expected, got });
+        }
+    }
+    Ok(())
+}
+
+/// Key of the persisted synthesis prompt for assistant thought `thought_id`
+fn synthesis_prompt_key(instance: &str, thought_id: &str) -> String {
+    format!("{instance}:synth_prompt:{thought_id}")
+}
+
+/// Stable hash of the rendered chat messages, for spotting prompt drift
+fn prompt_hash(messages: &[ChatMessage]) -> String {
+    use sha2::{Digest, Sha256};
+    let serialized = serde_json::to_string(messages).unwrap_or_default();
+    hex::encode(Sha256::digest(serialized.as_bytes()))
+}
+
+/// Persist a rendered synthesis prompt record (best-effort; no TTL)
+async fn store_synthesis_prompt(
+    redis_manager: &RedisManager,
+    key: &str,
+    record: &serde_json::Value,
+) {
+    let res = match redis_manager.get_connection().await {
+        Ok(mut con) => redis::cmd("SET")
+            .arg(key)
+            .arg(record.to_string())
+            .query_async::<()>(&mut *con)
+            .await
+            .map_err(UnifiedIntelligenceError::from),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = res {
+        tracing::warn!("ui_remember: failed to persist synthesis prompt: {}", e);
+    }
+}
+
+/// Append a ui_remember retrieval record to the eval stream (best-effort)
+async fn log_retrieval(
+    redis_manager: &RedisManager,
+    stream_key: &str,
+    max_len: usize,
+    fields: &[(&str, String)],
+) {
+    let res = match redis_manager.get_connection().await {
+        Ok(mut con) => redis::cmd("XADD")
+            .arg(stream_key)
+            .arg("MAXLEN")
+            .arg("~")
+            .arg(max_len.max(1))
+            .arg("*")
+            .arg(fields)
+            .query_async::<String>(&mut *con)
+            .await
+            .map_err(UnifiedIntelligenceError::from),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = res {
+        tracing::warn!("ui_remember: failed to log retrieval: {}", e);
+    }
+}
+
+/// Split a session summary chunk key
+/// (`{instance}:embeddings:session-summaries:{chain}:{offset}`) into its
+/// chain prefix and byte offset.
+fn summary_chunk_offset(key: &str) -> Option<(&str, usize)> {
+    let (prefix, offset) = key.rsplit_once(':')?;
+    let offset = offset.parse().ok()?;
+    match prefix.split(':').collect::<Vec<_>>().as_slice() {
+        [_, "embeddings", "session-summaries", _] => Some((prefix, offset)),
+        _ => None,
+    }
+}
+
+/// Collapse items sharing a key, keeping the higher-`score` instance and
+/// folding the other into it with `merge`. First-seen order is otherwise
+/// preserved; returns how many items were merged away.
+fn dedup_candidates<T>(
+    items: &mut Vec<T>,
+    key: impl Fn(&T) -> String,
+    score: impl Fn(&T) -> f64,
+    merge: impl Fn(&mut T, &T),
+) -> usize {
+    use std::collections::hash_map::Entry;
+    let before = items.len();
+    let mut slots: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    let mut kept: Vec<T> = Vec::with_capacity(before);
+    for item in items.drain(..) {
+        match slots.entry(key(&item)) {
+            Entry::Occupied(slot) => {
+                let existing = &mut kept[*slot.get()];
+                if score(&item) > score(existing) {
+                    let weaker = std::mem::replace(existing, item);
+                    merge(existing, &weaker);
+                } else {
+                    merge(existing, &item);
+                }
+            }
+            Entry::Vacant(slot) => {
+                slot.insert(kept.len());
+                kept.push(item);
+            }
+        }
+    }
+    *items = kept;
+    before - items.len()
+}
+
+/// Length of the score-ordered prefix used as synthesis context, with the
+/// number of candidates dropped for rank (beyond `max_items`) and for size
+/// (total characters over `max_chars`). The top candidate is always kept.
+/// Unix-second window `[start, end]` for a parsed temporal filter, relative
+/// to `now`. Explicit `YYYY-MM-DD` dates win over `relative_timeframe`
+/// ("today", "yesterday", "last week", "past 3 days", ...); `None` when
+/// nothing in the filter is recognised.
+fn temporal_range(
+    filter: &crate::models::TemporalFilter,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<(i64, i64)> {
+    const DAY: i64 = 86_400;
+    let date = |s: &str| chrono::NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").ok();
+    let start = filter.start_date.as_deref().and_then(date);
+    let end = filter.end_date.as_deref().and_then(date);
+    if start.is_some() || end.is_some() {
+        let start = start.map_or(0, |d| {
+            d.and_time(chrono::NaiveTime::MIN).and_utc().timestamp()
+        });
+        let end = end.map_or(now.timestamp(), |d| {
+            d.and_time(chrono::NaiveTime::MIN).and_utc().timestamp() + DAY - 1
+        });
+        return (start <= end).then_some((start, end));
+    }
+
+    let timeframe = filter
+        .relative_timeframe
+        .as_deref()?
+        .trim()
+        .to_ascii_lowercase();
+    let midnight = now
+        .date_naive()
+        .and_time(chrono::NaiveTime::MIN)
+        .and_utc()
+        .timestamp();
+    match timeframe.as_str() {
+        "today" => return Some((midnight, now.timestamp())),
+        "yesterday" => return Some((midnight - DAY, midnight - 1)),
+        _ => {}
+    }
+    let rest = timeframe
+        .strip_prefix("last ")
+        .or_else(|| timeframe.strip_prefix("past "))?;
+    let (count, unit) = match rest.split_once(' ') {
+        Some((n, unit)) => (n.parse::<i64>().ok()?, unit),
+        None => (1, rest),
+    };
+    let unit_secs = match unit.trim_end_matches('s') {
+        "hour" => 3_600,
+        "day" => DAY,
+        "week" => 7 * DAY,
+        "month" => 30 * DAY,
+        "year" => 365 * DAY,
+        _ => return None,
+    };
+    (count > 0).then(|| (now.timestamp() - count * unit_secs, now.timestamp()))
+}
+
+fn fit_context(chars: &[usize], max_items: usize, max_chars: usize) -> (usize, usize, usize) {
+    let by_rank = chars.len().min(max_items.max(1));
+    let mut len = by_rank;
+    let mut total: usize = chars[..len].iter().sum();
+    while len > 1 && total > max_chars {
+        len -= 1;
+        total -= chars[len];
+    }
+    (len, chars.len() - by_rank, by_rank - len)
+}
+
+/// Offsets within `n` positions of `hit` in the sorted chunk offsets of a chain
+fn neighbor_offsets(sorted: &[usize], hit: usize, n: usize) -> Vec<usize> {
+    let Ok(pos) = sorted.binary_search(&hit) else {
+        return Vec::new();
+    };
+    let lo = pos.saturating_sub(n);
+    let hi = (pos + n + 1).min(sorted.len());
+    sorted[lo..hi]
+        .iter()
+        .copied()
+        .filter(|o| *o != hit)
+        .collect()
+}
+
+/// Append up to `n` neighbouring chunks on each side of every session summary
+/// chunk hit (best-effort). Neighbours inherit the hit's score so they rank
+/// beside it; chunks already present are not repeated.
+async fn expand_summary_chunks(
+    redis_manager: &RedisManager,
+    redis_config: &crate::config::RedisConfig,
+    knn_items: &mut Vec<(String, Option<f64>, String, i64, String)>,
+    n: usize,
+) {
+    let mut seen: std::collections::HashSet<String> =
+        knn_items.iter().map(|(key, ..)| key.clone()).collect();
+    let mut chain_offsets: std::collections::HashMap<String, Vec<usize>> =
+        std::collections::HashMap::new();
+    // Neighbours inherit the score and index of the chunk that matched
+    let mut extra: Vec<(String, Option<f64>, String)> = Vec::new();
+    for (key, score, _, _, index) in knn_items.iter() {
+        let Some((prefix, offset)) = summary_chunk_offset(key) else {
+            continue;
+        };
+        if !chain_offsets.contains_key(prefix) {
+            let keys = redis_manager
+                .scan_match(
+                    &format!("{prefix}:*"),
+                    redis_config.scan_count,
+                    redis_config.scan_max_keys,
+                )
+                .await
+                .map(|(keys, _)| keys)
+                .unwrap_or_default();
+            let mut offsets: Vec<usize> = keys
+                .iter()
+                .filter_map(|k| summary_chunk_offset(k).map(|(_, o)| o))
+                .collect();
+            offsets.sort_unstable();
+            chain_offsets.insert(prefix.to_string(), offsets);
+        }
+        for neighbor in neighbor_offsets(&chain_offsets[prefix], offset, n) {
+            let neighbor_key = format!("{prefix}:{neighbor}");
+            if seen.insert(neighbor_key.clone()) {
+                extra.push((neighbor_key, *score, index.clone()));
+            }
+        }
+    }
+    if extra.is_empty() {
+        return;
+    }
+
+    let mut pipe = redis::pipe();
+    for (key, ..) in &extra {
+        pipe.cmd("HMGET").arg(key).arg("content").arg("ts");
+    }
+    let rows: Vec<(Option<String>, Option<String>)> = match redis_manager.get_connection().await {
+        Ok(mut con) => match pipe.query_async(&mut *con).await {
+            Ok(rows) => rows,
+            Err(e) => {
+                tracing::warn!("ui_remember: failed to fetch summary neighbours: {}", e);
+                return;
+            }
+        },
+        Err(e) => {
+            tracing::warn!("ui_remember: failed to fetch summary neighbours: {}", e);
+            return;
+        }
+    };
+    for ((key, score, index), (content, ts)) in extra.into_iter().zip(rows) {
+        let content = content.unwrap_or_default();
+        if content.is_empty() {
+            continue;
+        }
+        let ts = ts.and_then(|t| t.parse::<i64>().ok()).unwrap_or_default();
+        knn_items.push((key, score, content, ts, index));
+    }
+}
+
 // Ensure an HNSW RediSearch index exists for HASH prefixes
 #[allow(dead_code)]
 async fn ensure_index_hash_hnsw(
@@ -870,6 +2346,7 @@ async fn ensure_index_hash_hnsw(
     dims: usize,
     m: u32,
     ef_construction: u32,
+    vector_type: crate::config::VectorType,
 ) -> std::result::Result<bool, redis::RedisError> {
     let mut con = redis_manager.get_connection().await.map_err(|e| match e {
         crate::error::UnifiedIntelligenceError::Redis(e) => e,
@@ -914,7 +2391,7 @@ async fn ensure_index_hash_hnsw(
         .arg("HNSW")
         .arg(10)
         .arg("TYPE")
-        .arg("FLOAT32")
+        .arg(vector_type.index_type())
         .arg("DIM")
         .arg(dims)
         .arg("DISTANCE_METRIC")
@@ -939,70 +2416,153 @@ async fn ensure_index_hash_hnsw(
     }
 }
 
-// Extract doc ids and optional score from FT.SEARCH responses
-fn extract_doc_ids_and_scores(val: &redis::Value) -> Vec<(String, Option<f64>)> {
-    let mut out = Vec::new();
-    match val {
-        redis::Value::Array(items) if !items.is_empty() => {
-            let mut i = 1usize; // skip total count
-            while i < items.len() {
-                // Expect id first
-                let id_opt = match &items[i] {
-                    redis::Value::BulkString(bytes) => {
-                        std::str::from_utf8(bytes).ok().map(|s| s.to_string())
-                    }
-                    redis::Value::SimpleString(s) => Some(s.clone()),
-                    _ => None,
-                };
-                i += 1;
-                if id_opt.is_none() {
-                    continue;
-                }
-                let mut score_opt: Option<f64> = None;
-                // Next may be an array of field-value pairs
-                if i < items.len() {
-                    if let redis::Value::Array(fields) = &items[i] {
-                        let mut j = 0usize;
-                        while j + 1 < fields.len() {
-                            let k = &fields[j];
-                            let v = &fields[j + 1];
-                            let key_is_score = match k {
-                                redis::Value::BulkString(b) => std::str::from_utf8(b)
-                                    .ok()
-                                    .map(|s| s == "score")
-                                    .unwrap_or(false),
-                                redis::Value::SimpleString(s) => s == "score",
-                                _ => false,
-                            };
-                            if key_is_score {
-                                if let Some(num) = match v {
-                                    redis::Value::BulkString(b) => std::str::from_utf8(b)
-                                        .ok()
-                                        .and_then(|s| s.parse::<f64>().ok()),
-                                    redis::Value::SimpleString(s) => s.parse::<f64>().ok(),
-                                    redis::Value::Int(i) => Some(*i as f64),
-                                    _ => None,
-                                } {
-                                    score_opt = Some(num);
-                                    break;
-                                }
-                            }
-                            j += 2;
-                        }
-                        i += 1;
-                    }
-                }
-                if let Some(id) = id_opt {
-                    out.push((id, score_opt));
-                }
+// Helper for computing feedback metrics heuristics
+/// Thought id behind a context candidate: text hits carry the id itself,
+/// thought KNN hits their `{instance}:embeddings:thought:{id}` key
+fn context_thought_id(source_id: &str) -> Option<&str> {
+    match source_id.split_once(":embeddings:thought:") {
+        Some((_, id)) => Some(id),
+        None => uuid::Uuid::parse_str(source_id)
+            .is_ok()
+            .then_some(source_id),
+    }
+}
+
+/// Whether a thought category marks a stored ui_remember synthesis (T2)
+fn is_assistant_synthesis(category: Option<&str>) -> bool {
+    category == Some("ui_remember:assistant")
+}
+
+/// Sentences of `text` worth quoting: split after `.`, `!`, `?` or a line
+/// break, trimmed, keeping those with at least four words
+fn split_sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let end = match c {
+            '\n' => Some(i),
+            '.' | '!' | '?' if chars.peek().is_none_or(|(_, n)| n.is_whitespace()) => {
+                Some(i + c.len_utf8())
             }
+            _ => None,
+        };
+        if let Some(end) = end {
+            sentences.push(&text[start..end]);
+            start = end;
         }
-        _ => {}
     }
-    out
+    sentences.push(&text[start..]);
+    sentences
+        .into_iter()
+        .map(str::trim)
+        .filter(|s| s.split_whitespace().count() >= 4)
+        .collect()
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    let (mut dot, mut na, mut nb) = (0f64, 0f64, 0f64);
+    for (x, y) in a.iter().zip(b) {
+        let (x, y) = (f64::from(*x), f64::from(*y));
+        dot += x * y;
+        na += x * x;
+        nb += y * y;
+    }
+    if na == 0.0 || nb == 0.0 {
+        0.0
+    } else {
+        dot / (na.sqrt() * nb.sqrt())
+    }
+}
+
+/// Embed the answer with the context's sentences in one batch and return the
+/// `limit` sentences most similar to it. Sentences are taken in context rank
+/// order and capped so the batch (plus the query embedding) stays within
+/// `embeddings.max_calls_per_request`.
+async fn extract_highlights(
+    embedder: &dyn crate::embeddings::Embedder,
+    answer: &str,
+    ctx_ids: &[String],
+    ctx_thoughts: &[crate::models::Thought],
+    limit: usize,
+    max_calls: usize,
+    max_input_chars: usize,
+) -> Result<Vec<crate::tools::ui_remember::Highlight>, UnifiedIntelligenceError> {
+    let budget = if max_calls == 0 {
+        usize::MAX
+    } else {
+        max_calls.saturating_sub(2)
+    };
+    let sentences: Vec<(&str, &str)> = ctx_ids
+        .iter()
+        .zip(ctx_thoughts)
+        .flat_map(|(id, t)| {
+            split_sentences(&t.content)
+                .into_iter()
+                .map(move |s| (id.as_str(), s))
+        })
+        .take(budget)
+        .collect();
+    if sentences.is_empty() {
+        return Ok(Vec::new());
+    }
+    let texts: Vec<String> = std::iter::once(answer)
+        .chain(sentences.iter().map(|(_, s)| *s))
+        .map(|t| crate::embeddings::truncate_for_embedding(t, max_input_chars).to_string())
+        .collect();
+    let vectors = embedder.embed_batch(&texts).await?;
+    let Some((answer_vec, sentence_vecs)) = vectors.split_first() else {
+        return Ok(Vec::new());
+    };
+    Ok(top_highlights(
+        sentences
+            .iter()
+            .zip(sentence_vecs)
+            .map(|((id, s), v)| (*id, *s, cosine_similarity(answer_vec, v))),
+        limit,
+    ))
+}
+
+/// Highest-similarity sentences, best first, skipping repeated sentences
+fn top_highlights<'a>(
+    scored: impl IntoIterator<Item = (&'a str, &'a str, f64)>,
+    limit: usize,
+) -> Vec<crate::tools::ui_remember::Highlight> {
+    let mut scored: Vec<_> = scored.into_iter().collect();
+    scored.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
+    let mut seen = std::collections::HashSet::new();
+    scored
+        .into_iter()
+        .filter(|(_, sentence, _)| seen.insert(*sentence))
+        .take(limit)
+        .map(
+            |(source_id, sentence, similarity)| crate::tools::ui_remember::Highlight {
+                source_id: source_id.to_string(),
+                sentence: sentence.to_string(),
+                similarity,
+            },
+        )
+        .collect()
+}
+
+/// Whether a follow-up at `now` still merges into a turn first applied at
+/// `applied_at`; a zero window applies each turn's first follow-up only
+fn within_merge_window(applied_at: i64, now: i64, window_secs: u64) -> bool {
+    window_secs > 0 && now.saturating_sub(applied_at) <= window_secs as i64
+}
+
+/// Combine a further follow-up with the turn's recorded signal: the lowest
+/// score wins and any correction sticks, so a quick "thanks" cannot erase an
+/// earlier "that's wrong"
+fn merge_followup(
+    prev_score: f64,
+    prev_corrected: bool,
+    score: f64,
+    corrected: bool,
+) -> (f64, bool) {
+    (prev_score.min(score), prev_corrected || corrected)
 }
 
-// Helper for computing feedback metrics heuristics
 fn compute_feedback_scoring(delta_secs: i64, user_text: &str) -> (f64, i32, i32, bool) {
     let lc = user_text.to_lowercase();
     let corrected = [
@@ -1053,8 +2613,108 @@ fn compute_feedback_scoring(delta_secs: i64, user_text: &str) -> (f64, i32, i32,
     (score, abandoned, continued, corrected)
 }
 
-#[tool_handler]
+// call_tool is written out (rather than generated by #[tool_handler]) so every
+// tool runs under its configured server-side timeout
 impl ServerHandler for UnifiedIntelligenceService {
+    async fn call_tool(
+        &self,
+        request: rmcp::model::CallToolRequestParam,
+        context: rmcp::service::RequestContext<rmcp::RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let tool = request.name.to_string();
+        if !self.config.tools.is_enabled(&tool) {
+            return Err(ErrorData::invalid_request(
+                format!("Tool {tool} is disabled on this server"),
+                Some(serde_json::json!({ "tool": tool, "disabled": true })),
+            ));
+        }
+        let budget = self.config.server.tool_timeout(&tool);
+        // Clients may supply `_meta.request_id`; otherwise one is generated
+        let origin = self
+            .config
+            .event_stream
+            .include_origin
+            .then(|| EventOrigin {
+                tool: tool.clone(),
+                request_id: context
+                    .meta
+                    .0
+                    .get("request_id")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+                    .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            });
+        let tcc = rmcp::handler::server::tool::ToolCallContext::new(self, request, context);
+        let call = async {
+            match budget {
+                Some(budget) => tokio::time::timeout(budget, self.tool_router.call(tcc)).await,
+                None => Ok(self.tool_router.call(tcc).await),
+            }
+        };
+        let result = match origin {
+            Some(origin) => {
+                tracing::debug!("{} request_id={}", tool, origin.request_id);
+                EVENT_ORIGIN.scope(origin, call).await
+            }
+            None => call.await,
+        };
+        match result {
+            Ok(result) => result,
+            Err(_) => {
+                let e = UnifiedIntelligenceError::Timeout {
+                    tool,
+                    budget_secs: budget.map_or(0, |b| b.as_secs()),
+                };
+                tracing::warn!("{}", e);
+                Err(timeout_error(&e))
+            }
+        }
+    }
+
+    async fn read_resource(
+        &self,
+        request: rmcp::model::ReadResourceRequestParam,
+        _context: rmcp::service::RequestContext<rmcp::RoleServer>,
+    ) -> Result<rmcp::model::ReadResourceResult, ErrorData> {
+        let uri = request.uri;
+        let Some(id) = uri.strip_prefix(RESULT_URI_PREFIX) else {
+            return Err(ErrorData::resource_not_found(
+                format!("Unknown resource: {uri}"),
+                None,
+            ));
+        };
+        let key = format!("{}:results:{}", self.instance_id, id);
+        let mut con = self
+            .handlers
+            .redis_manager
+            .get_connection()
+            .await
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        let text: Option<String> = redis::AsyncCommands::get(&mut *con, &key)
+            .await
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        let text = text.ok_or_else(|| {
+            ErrorData::resource_not_found(format!("Resource {uri} not found or expired"), None)
+        })?;
+        Ok(rmcp::model::ReadResourceResult {
+            contents: vec![rmcp::model::ResourceContents::TextResourceContents {
+                uri,
+                mime_type: Some("application/json".to_string()),
+                text,
+            }],
+        })
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<rmcp::model::PaginatedRequestParam>,
+        _context: rmcp::service::RequestContext<rmcp::RoleServer>,
+    ) -> Result<rmcp::model::ListToolsResult, ErrorData> {
+        Ok(rmcp::model::ListToolsResult::with_all_items(
+            self.tool_router.list_all(),
+        ))
+    }
+
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: rmcp::model::ProtocolVersion::V_2024_11_05,
@@ -1064,11 +2724,16 @@ impl ServerHandler for UnifiedIntelligenceService {
             },
             capabilities: ServerCapabilities {
                 tools: Some(Default::default()),
+                resources: self
+                    .config
+                    .server
+                    .resource_results
+                    .enabled
+                    .then(Default::default),
+                experimental: Some(self.server_metadata()),
                 ..Default::default()
             },
-            instructions: Some(
-                "UnifiedIntelligence MCP Server for Redis-backed thought storage".into(),
-            ),
+            instructions: Some(self.config.server.resolve_instructions()),
         }
     }
 }
@@ -1078,42 +2743,171 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_extract_doc_ids_and_scores_with_scores() {
-        let val = redis::Value::Array(vec![
-            redis::Value::Int(2),
-            redis::Value::BulkString(b"doc:1".to_vec()),
-            redis::Value::Array(vec![
-                redis::Value::BulkString(b"score".to_vec()),
-                redis::Value::BulkString(b"0.123".to_vec()),
-            ]),
-            redis::Value::BulkString(b"doc:2".to_vec()),
-            redis::Value::Array(vec![
-                redis::Value::BulkString(b"score".to_vec()),
-                redis::Value::BulkString(b"0.456".to_vec()),
-            ]),
-        ]);
-
-        let out = extract_doc_ids_and_scores(&val);
-        assert_eq!(out.len(), 2);
-        assert_eq!(out[0].0, "doc:1");
-        assert!((out[0].1.unwrap() - 0.123).abs() < 1e-9);
-        assert_eq!(out[1].0, "doc:2");
-        assert!((out[1].1.unwrap() - 0.456).abs() < 1e-9);
+    fn test_synthesis_cache_key_depends_on_context_order() {
+        let ids = vec!["a".to_string(), "b".to_string()];
+        let rev = vec!["b".to_string(), "a".to_string()];
+        let groq = crate::config::SynthProviderKind::Groq;
+        let key = |ids: &[String], style: &str, temperature: Option<f32>, provider| {
+            synthesis_cache_key("CC", "q", ids, "m", style, temperature, provider)
+        };
+        let k1 = key(&ids, "", None, groq);
+        assert_eq!(k1, key(&ids, "", None, groq));
+        assert!(k1.starts_with("CC:synth_cache:"));
+        assert_ne!(k1, key(&rev, "", None, groq));
+        assert_ne!(k1, key(&ids, "deep", None, groq));
+        assert_ne!(k1, key(&ids, "", Some(0.2), groq));
+        assert_ne!(
+            k1,
+            key(&ids, "", None, crate::config::SynthProviderKind::Anthropic)
+        );
+    }
+
+    #[test]
+    fn test_summary_chunk_neighbors() {
+        assert_eq!(
+            summary_chunk_offset("CC:embeddings:session-summaries:chain-1:2048"),
+            Some(("CC:embeddings:session-summaries:chain-1", 2048))
+        );
+        assert_eq!(summary_chunk_offset("CC:embeddings:thought:abc"), None);
+        assert_eq!(summary_chunk_offset("CC:embeddings:important:abc:12"), None);
+
+        let offsets = [0, 1000, 2000, 3000, 4000];
+        assert_eq!(neighbor_offsets(&offsets, 2000, 1), vec![1000, 3000]);
+        assert_eq!(neighbor_offsets(&offsets, 0, 2), vec![1000, 2000]);
+        assert_eq!(neighbor_offsets(&offsets, 4000, 1), vec![3000]);
+        assert!(neighbor_offsets(&offsets, 1500, 1).is_empty());
+    }
+
+    #[test]
+    fn test_dedup_candidates_merges_overlapping_hit() {
+        // (content, origin, semantic, text); the KNN hit duplicates the first text hit
+        let mut cands = vec![
+            ("alpha", "text", 0.0, 1.0),
+            ("beta", "text", 0.0, 1.0),
+            ("alpha", "knn", 0.9, 0.0),
+        ];
+        let score = |c: &(&str, &str, f64, f64)| 0.6 * c.2 + 0.3 * c.3;
+        let merged = dedup_candidates(
+            &mut cands,
+            |c| c.0.to_string(),
+            score,
+            |kept, other| {
+                kept.2 = kept.2.max(other.2);
+                kept.3 = kept.3.max(other.3);
+            },
+        );
+        assert_eq!(merged, 1);
+        assert_eq!(cands.len(), 2);
+        // The higher-scored KNN instance is kept, carrying the text signal too
+        assert_eq!(cands[0], ("alpha", "knn", 0.9, 1.0));
+        assert_eq!(cands[1].0, "beta");
+    }
+
+    #[test]
+    fn test_fit_context_drops_by_rank_then_size() {
+        // Rank cap only
+        assert_eq!(fit_context(&[10, 10, 10, 10], 3, 1000), (3, 1, 0));
+        // Size cap drops the lowest-scored selected candidates
+        assert_eq!(fit_context(&[400, 300, 200, 100], 4, 750), (2, 0, 2));
+        // Both, and the best candidate survives even when oversized
+        assert_eq!(fit_context(&[5000, 10, 10], 2, 100), (1, 1, 1));
+        assert_eq!(fit_context(&[], 5, 100), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_temporal_range() {
+        use crate::models::TemporalFilter;
+        let now = chrono::DateTime::parse_from_rfc3339("2024-03-10T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let midnight = now.timestamp() - 12 * 3_600;
+        let relative = |s: &str| TemporalFilter {
+            relative_timeframe: Some(s.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            temporal_range(&relative("yesterday"), now),
+            Some((midnight - 86_400, midnight - 1))
+        );
+        assert_eq!(
+            temporal_range(&relative("Past 3 days"), now),
+            Some((now.timestamp() - 3 * 86_400, now.timestamp()))
+        );
+        assert_eq!(
+            temporal_range(&relative("last week"), now),
+            Some((now.timestamp() - 7 * 86_400, now.timestamp()))
+        );
+        assert_eq!(temporal_range(&relative("sometime"), now), None);
+
+        // Explicit dates cover whole days and win over the timeframe
+        let day = TemporalFilter {
+            start_date: Some("2024-03-01".to_string()),
+            end_date: Some("2024-03-01".to_string()),
+            relative_timeframe: Some("yesterday".to_string()),
+        };
+        let start = midnight - 9 * 86_400;
+        assert_eq!(temporal_range(&day, now), Some((start, start + 86_399)));
+        assert_eq!(temporal_range(&TemporalFilter::default(), now), None);
+    }
+
+    #[test]
+    fn test_context_thought_id() {
+        let id = "96331831-0fa7-4da0-8445-7d3b0a0fdf44";
+        assert_eq!(context_thought_id(id), Some(id));
+        assert_eq!(
+            context_thought_id(&format!("CC:embeddings:thought:{id}")),
+            Some(id)
+        );
+        assert_eq!(context_thought_id("CC:embeddings:kg_entity:e1"), None);
+        assert_eq!(
+            context_thought_id("CC:embeddings:session-summaries:c:0"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_split_sentences_and_top_highlights() {
+        let text = "Redis keeps the vectors in hashes. Short one. Is v1.2 still used in prod?\nThe index is rebuilt nightly";
+        assert_eq!(
+            split_sentences(text),
+            vec![
+                "Redis keeps the vectors in hashes.",
+                "Is v1.2 still used in prod?",
+                "The index is rebuilt nightly"
+            ]
+        );
+
+        assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-9);
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 0.0]), 0.0);
+
+        let top = top_highlights(
+            [
+                ("a", "low match here", 0.1),
+                ("b", "best match here", 0.9),
+                ("c", "best match here", 0.8),
+                ("a", "mid match here", 0.5),
+            ],
+            2,
+        );
+        let got: Vec<(&str, &str)> = top
+            .iter()
+            .map(|h| (h.source_id.as_str(), h.sentence.as_str()))
+            .collect();
+        assert_eq!(got, vec![("b", "best match here"), ("a", "mid match here")]);
     }
 
     #[test]
-    fn test_extract_doc_ids_and_scores_nocontent() {
-        let val = redis::Value::Array(vec![
-            redis::Value::Int(2),
-            redis::Value::BulkString(b"doc:1".to_vec()),
-            redis::Value::BulkString(b"doc:2".to_vec()),
-        ]);
-        let out = extract_doc_ids_and_scores(&val);
-        assert_eq!(out.len(), 2);
-        assert_eq!(out[0].0, "doc:1");
-        assert!(out[0].1.is_none());
-        assert_eq!(out[1].0, "doc:2");
-        assert!(out[1].1.is_none());
+    fn test_followup_merge_window_and_signals() {
+        assert!(within_merge_window(100, 130, 60));
+        assert!(within_merge_window(100, 160, 60));
+        assert!(!within_merge_window(100, 161, 60));
+        assert!(!within_merge_window(100, 100, 0));
+        // Legacy `followup_applied = 1` markers are long expired
+        assert!(!within_merge_window(1, 1_700_000_000, 60));
+
+        assert_eq!(merge_followup(0.9, false, 0.3, true), (0.3, true));
+        assert_eq!(merge_followup(0.3, true, 1.0, false), (0.3, true));
+        assert_eq!(merge_followup(0.7, false, 0.9, false), (0.7, false));
     }
 
     #[test]
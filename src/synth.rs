@@ -3,19 +3,23 @@ use std::sync::Arc;
 
 use crate::error::{Result, UnifiedIntelligenceError};
 use crate::models::{ChatMessage, GroqRequest, GroqUsage, QueryIntent, Thought};
-use crate::transport::Transport;
+use crate::transport::{ChatStream, Transport};
 
 #[derive(Debug, Clone)]
 pub struct SynthResult {
     pub text: String,
     pub usage: Option<GroqUsage>,
     pub model_used: String,
+    /// Streamed text cut off by `groq.stream_timeout_secs`
+    pub partial: bool,
 }
 
 pub struct GroqSynth {
     tx: Arc<dyn Transport>,
     model_fast: String,
     model_deep: String,
+    deep_token_threshold: Option<usize>,
+    temperature: Option<f32>,
 }
 
 impl GroqSynth {
@@ -25,33 +29,36 @@ impl GroqSynth {
             tx,
             model_fast,
             model_deep,
+            deep_token_threshold: None,
+            temperature: None,
         }
     }
-}
 
-#[async_trait]
-pub trait Synthesizer: Send + Sync {
-    #[cfg_attr(not(test), allow(dead_code))]
-    async fn synth(&self, intent: &QueryIntent, ctx: &[Thought]) -> Result<SynthResult>;
-}
+    /// Override the default synthesis temperature (0.3).
+    pub fn with_temperature(mut self, temperature: Option<f32>) -> Self {
+        self.temperature = temperature;
+        self
+    }
 
-#[async_trait]
-impl Synthesizer for GroqSynth {
-    async fn synth(&self, intent: &QueryIntent, ctx: &[Thought]) -> Result<SynthResult> {
-        tracing::info!(
-            "Synthesizing response with Groq for query: {}",
-            intent.original_query
-        );
+    /// Escalate to the deep model when query + context exceed `threshold`
+    /// tokens; 0 leaves escalation off.
+    pub fn with_deep_token_threshold(mut self, threshold: usize) -> Self {
+        self.deep_token_threshold = (threshold > 0).then_some(threshold);
+        self
+    }
 
-        // Determine model based on synthesis style or default to fast
-        let model = if let Some(style) = &intent.synthesis_style {
-            match style.as_str() {
-                "deep" => self.model_deep.clone(),
-                _ => self.model_fast.clone(),
-            }
-        } else {
-            self.model_fast.clone()
-        };
+    /// Render the exact Groq request `synth` sends for this intent and context
+    pub fn render_request(&self, intent: &QueryIntent, ctx: &[Thought]) -> GroqRequest {
+        // Determine model from synthesis style and request size
+        let model = select_model(
+            intent.synthesis_style.as_deref(),
+            &intent.original_query,
+            ctx,
+            &self.model_fast,
+            &self.model_deep,
+            self.deep_token_threshold,
+        )
+        .to_string();
 
         // Sort memories chronologically by default
         let mut sorted_memories = ctx.to_vec();
@@ -105,18 +112,65 @@ impl Synthesizer for GroqSynth {
             ),
         };
 
-        let request = GroqRequest {
+        GroqRequest {
             model,
             messages: vec![system_message, user_message],
-            temperature: 0.3,
+            temperature: self.temperature.unwrap_or(0.3),
             max_tokens: if intent.synthesis_style == Some("deep".to_string()) {
                 2000 // More tokens for comprehensive summaries
             } else {
                 1500 // Default for regular synthesis
             },
             response_format: None, // No specific format needed for synthesis
-        };
+        }
+    }
+}
+
+/// Pick the model for a synthesis request.
+///
+/// The deep model is used when the style is `deep` or, with a threshold set,
+/// when the query plus context exceeds it (approx. 4 characters per token);
+/// otherwise the fast model is used.
+pub fn select_model<'a>(
+    style: Option<&str>,
+    query: &str,
+    ctx: &[Thought],
+    model_fast: &'a str,
+    model_deep: &'a str,
+    deep_token_threshold: Option<usize>,
+) -> &'a str {
+    if style == Some("deep") {
+        return model_deep;
+    }
+    if let Some(threshold) = deep_token_threshold {
+        let chars = query.len() + ctx.iter().map(|t| t.content.len()).sum::<usize>();
+        if chars / 4 > threshold {
+            return model_deep;
+        }
+    }
+    model_fast
+}
+
+#[async_trait]
+pub trait Synthesizer: Send + Sync {
+    #[cfg_attr(not(test), allow(dead_code))]
+    async fn synth(&self, intent: &QueryIntent, ctx: &[Thought]) -> Result<SynthResult>;
 
+    /// Stream the synthesized answer as token chunks. A failure after the
+    /// first chunk is yielded as an error item rather than ending the stream.
+    /// Streamed completions carry no token usage.
+    async fn synth_stream(&self, intent: &QueryIntent, ctx: &[Thought]) -> Result<ChatStream>;
+}
+
+#[async_trait]
+impl Synthesizer for GroqSynth {
+    async fn synth(&self, intent: &QueryIntent, ctx: &[Thought]) -> Result<SynthResult> {
+        tracing::info!(
+            "Synthesizing response with Groq for query: {}",
+            intent.original_query
+        );
+
+        let request = self.render_request(intent, ctx);
         let groq_response = self.tx.chat(&request).await?;
 
         if let Some(choice) = groq_response.choices.first() {
@@ -124,6 +178,7 @@ impl Synthesizer for GroqSynth {
                 text: choice.message.content.clone(),
                 usage: groq_response.usage.clone(),
                 model_used: request.model,
+                partial: false,
             })
         } else {
             Err(UnifiedIntelligenceError::Internal(
@@ -131,6 +186,15 @@ impl Synthesizer for GroqSynth {
             ))
         }
     }
+
+    async fn synth_stream(&self, intent: &QueryIntent, ctx: &[Thought]) -> Result<ChatStream> {
+        tracing::info!(
+            "Streaming synthesis with Groq for query: {}",
+            intent.original_query
+        );
+
+        self.tx.chat_stream(&self.render_request(intent, ctx)).await
+    }
 }
 
 #[cfg(test)]
@@ -140,6 +204,7 @@ mod tests {
     use crate::transport::Transport;
     use async_trait::async_trait;
     use chrono::{Duration, Utc};
+    use futures::TryStreamExt;
     use std::sync::Mutex;
     use uuid::Uuid;
 
@@ -173,6 +238,32 @@ mod tests {
         }
     }
 
+    // Streams fixed chunks, optionally failing after them
+    struct StreamingTransport {
+        chunks: Vec<&'static str>,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl Transport for StreamingTransport {
+        async fn chat(&self, _req: &GroqRequest) -> Result<GroqResponse> {
+            unreachable!("synthesis streams")
+        }
+
+        async fn chat_stream(&self, _req: &GroqRequest) -> Result<ChatStream> {
+            use futures::StreamExt;
+
+            let mut items: Vec<Result<String>> =
+                self.chunks.iter().map(|c| Ok(c.to_string())).collect();
+            if self.fail {
+                items.push(Err(UnifiedIntelligenceError::Internal(
+                    "Groq stream interrupted".to_string(),
+                )));
+            }
+            Ok(futures::stream::iter(items).boxed())
+        }
+    }
+
     fn create_mock_thought(content: &str, days_ago: i64) -> Thought {
         Thought {
             id: Uuid::new_v4(),
@@ -200,7 +291,12 @@ mod tests {
                     content: "Synthesized response content.".to_string(),
                 },
             }],
-            usage: None,
+            // Shape AnthropicTransport maps its usage into
+            usage: Some(GroqUsage {
+                prompt_tokens: Some(12),
+                completion_tokens: Some(5),
+                total_tokens: Some(17),
+            }),
         };
         let mock_transport = MockTransport::new(vec![mock_response]);
         let groq_synth = GroqSynth::new(
@@ -213,6 +309,8 @@ mod tests {
             original_query: "Test query.".to_string(),
             temporal_filter: None,
             synthesis_style: None,
+            confidence: None,
+            low_confidence: false,
         };
         let thoughts = vec![
             create_mock_thought("Thought 1", 1),
@@ -224,7 +322,7 @@ mod tests {
             .await
             .expect("Synthesis should succeed in test");
         assert_eq!(result.text, "Synthesized response content.");
-        assert!(result.usage.is_none());
+        assert_eq!(result.usage.and_then(|u| u.total_tokens), Some(17));
         assert!(!result.model_used.is_empty());
     }
 
@@ -250,6 +348,8 @@ mod tests {
             original_query: "Test query.".to_string(),
             temporal_filter: None,
             synthesis_style: None,
+            confidence: None,
+            low_confidence: false,
         };
 
         // Create many thoughts to exceed token limit
@@ -291,6 +391,8 @@ mod tests {
             original_query: "Test query.".to_string(),
             temporal_filter: None,
             synthesis_style: Some("deep".to_string()),
+            confidence: None,
+            low_confidence: false,
         };
         let thoughts = vec![create_mock_thought("Thought 1", 1)];
 
@@ -301,4 +403,91 @@ mod tests {
         assert_eq!(result.text, "Deep synthesized response content.");
         assert_eq!(result.model_used, "deep-model");
     }
+
+    #[tokio::test]
+    async fn test_groq_synth_streams_chunks() {
+        let intent = QueryIntent {
+            original_query: "Test query.".to_string(),
+            temporal_filter: None,
+            synthesis_style: None,
+            confidence: None,
+            low_confidence: false,
+        };
+        let thoughts = vec![create_mock_thought("Thought 1", 1)];
+        let synth = |fail| {
+            GroqSynth::new(
+                Arc::new(StreamingTransport {
+                    chunks: vec!["Stream", "ed ", "answer"],
+                    fail,
+                }),
+                "fast-model".to_string(),
+                "deep-model".to_string(),
+            )
+        };
+
+        let chunks: Vec<String> = synth(false)
+            .synth_stream(&intent, &thoughts)
+            .await
+            .expect("stream should open")
+            .try_collect()
+            .await
+            .expect("stream should complete");
+        assert_eq!(chunks, vec!["Stream", "ed ", "answer"]);
+
+        // A mid-stream failure surfaces as an error item instead of truncating
+        let collected: Result<Vec<String>> = synth(true)
+            .synth_stream(&intent, &thoughts)
+            .await
+            .expect("stream should open")
+            .try_collect()
+            .await;
+        assert!(collected.is_err());
+    }
+
+    #[test]
+    fn test_render_request_includes_query_and_context() {
+        let groq_synth = GroqSynth::new(
+            Arc::new(MockTransport::new(vec![])),
+            "fast-model".to_string(),
+            "deep-model".to_string(),
+        );
+        let intent = QueryIntent {
+            original_query: "What did we decide?".to_string(),
+            temporal_filter: None,
+            synthesis_style: None,
+            confidence: None,
+            low_confidence: false,
+        };
+        let thoughts = vec![create_mock_thought("We picked Redis streams.", 1)];
+
+        let request = groq_synth.render_request(&intent, &thoughts);
+        assert_eq!(request.model, "fast-model");
+        assert_eq!(request.messages.len(), 2);
+        assert_eq!(request.messages[0].role, "system");
+        let user = &request.messages[1].content;
+        assert!(user.contains("What did we decide?"));
+        assert!(user.contains("We picked Redis streams."));
+    }
+
+    #[test]
+    fn test_select_model_threshold_and_style() {
+        let small = vec![create_mock_thought("short note", 0)];
+        let large = vec![create_mock_thought(&"x".repeat(4000), 0)];
+        assert_eq!(
+            select_model(None, "q", &small, "fast", "deep", Some(500)),
+            "fast"
+        );
+        assert_eq!(
+            select_model(None, "q", &large, "fast", "deep", Some(500)),
+            "deep"
+        );
+        assert_eq!(
+            select_model(None, "q", &large, "fast", "deep", None),
+            "fast"
+        );
+        assert_eq!(
+            select_model(Some("deep"), "q", &small, "fast", "deep", Some(500)),
+            "deep"
+        );
+    }
 }
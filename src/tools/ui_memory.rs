@@ -1,12 +1,9 @@
 use crate::config::Config;
+use crate::embeddings::Embedder;
+use crate::error::UnifiedIntelligenceError;
+use crate::models::{ChainPruneReport, ThoughtRecord};
 use crate::redis::RedisManager;
 use anyhow::{Context, Result, anyhow};
-use async_openai::{
-    Client,
-    config::OpenAIConfig,
-    types::{CreateEmbeddingRequestArgs, EmbeddingInput},
-};
-use bytemuck;
 use chrono::Utc;
 use redis::AsyncCommands;
 use schemars::JsonSchema;
@@ -29,6 +26,12 @@ pub struct UiMemoryParams {
     pub targets: Option<MemoryTargets>,
     #[serde(default)]
     pub update: Option<MemoryUpdate>,
+    /// `prune_chains`: report without deleting (default true)
+    #[serde(default)]
+    pub dry_run: Option<bool>,
+    /// `prune_chains` (when `dry_run` is false) and `script_status`: must match `UI_ADMIN_TOKEN`
+    #[serde(default)]
+    pub admin_token: Option<String>,
 }
 
 fn default_scope() -> Option<String> {
@@ -43,6 +46,30 @@ pub struct MemoryFilters {
     pub chain_id: Option<String>,
     pub thought_id: Option<String>,
     pub time_range: Option<MemoryTimeRange>,
+    /// Inclusive sentiment bounds in [-1, 1] (scope `thoughts` only)
+    #[serde(default)]
+    pub sentiment_min: Option<f32>,
+    #[serde(default)]
+    pub sentiment_max: Option<f32>,
+}
+
+impl MemoryFilters {
+    fn has_sentiment(&self) -> bool {
+        self.sentiment_min.is_some() || self.sentiment_max.is_some()
+    }
+}
+
+/// `@sentiment:[min max]` clause, open-ended where a bound is missing
+fn sentiment_clause(min: Option<f32>, max: Option<f32>) -> Option<String> {
+    if min.is_none() && max.is_none() {
+        return None;
+    }
+    let bound = |b: Option<f32>, open: &str| b.map_or_else(|| open.to_string(), |v| v.to_string());
+    Some(format!(
+        "@sentiment:[{} {}]",
+        bound(min, "-inf"),
+        bound(max, "+inf")
+    ))
 }
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug, Default)]
@@ -63,6 +90,15 @@ pub struct MemoryOptions {
     pub search_type: String,
     pub min_score: Option<f32>,
     pub ef_runtime: Option<u32>,
+    /// Truncate search result `content` to this many chars (overrides `ui_memory.snippet_length`)
+    #[serde(default)]
+    pub snippet_length: Option<usize>,
+    /// Order search results by `sentiment` (scope `thoughts` only) or `ts`
+    #[serde(default)]
+    pub sort_by: Option<String>,
+    /// Descending order for `sort_by` (default true)
+    #[serde(default)]
+    pub sort_desc: Option<bool>,
 }
 
 impl Default for MemoryOptions {
@@ -74,6 +110,9 @@ impl Default for MemoryOptions {
             search_type: default_search_type(),
             min_score: None,
             ef_runtime: None,
+            snippet_length: None,
+            sort_by: None,
+            sort_desc: None,
         }
     }
 }
@@ -102,7 +141,7 @@ pub struct MemoryUpdate {
     pub chain_id: Option<String>,
     pub thought_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub ttl_seconds: Option<u64>, // Ignored; TTLs disabled
+    pub ttl_seconds: Option<u64>, // Expire the updated item; absent or 0 persists
 }
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
@@ -115,6 +154,11 @@ pub struct MemoryItem {
     pub thought_id: String,
     pub ts: i64,
     pub score: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sentiment: Option<f32>,
+    /// Set when `content` is a snippet; `read` returns the full text
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub content_truncated: bool,
 }
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug, Default)]
@@ -127,6 +171,16 @@ pub struct UiMemoryResult {
     pub updated: Option<Vec<(String, String)>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
+    /// Total matches across indexes before paging (search only): keyword
+    /// matches plus KNN hits not among them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
+    /// Chains selected (and, unless dry run, deleted) by `prune_chains`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prune_report: Option<ChainPruneReport>,
+    /// Cached Lua script SHAs and whether Redis still has them (`script_status`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scripts: Option<Vec<crate::lua_scripts::ScriptStatus>>,
 }
 
 fn short_hash(s: &str) -> String {
@@ -136,19 +190,281 @@ fn short_hash(s: &str) -> String {
     hex::encode(&result[..8])
 }
 
-async fn openai_embed(cfg: &Config, text: &str) -> Result<Vec<f32>> {
-    let config = OpenAIConfig::new().with_api_key(cfg.openai.api_key()?);
-    let client = Client::with_config(config);
-    let req = CreateEmbeddingRequestArgs::default()
-        .model(cfg.openai.embedding_model.clone())
-        .input(EmbeddingInput::String(text.to_owned()))
-        .build()?;
-    let resp = client.embeddings().create(req).await?;
-    let first = resp
-        .data
-        .first()
-        .ok_or_else(|| anyhow!("no embedding returned"))?;
-    Ok(first.embedding.clone())
+/// The configured embedder, or an error for operations that must re-embed
+fn require_embedder(embedder: Option<&dyn Embedder>) -> Result<&dyn Embedder> {
+    embedder.ok_or_else(|| anyhow!("no embedding provider configured (set OPENAI_API_KEY)"))
+}
+
+/// Resolve `(instance, chain_id)` for a whole session summary key:
+/// `{instance}:ui_start:summary:{chain}`.
+fn summary_chain(key: &str) -> Option<(String, String)> {
+    let parts: Vec<&str> = key.split(':').collect();
+    match parts.as_slice() {
+        [instance, "ui_start", "summary", chain] => Some((instance.to_string(), chain.to_string())),
+        _ => None,
+    }
+}
+
+/// Resolve `(instance, chain_id, offset)` for one chunk of a chain's session
+/// summary embeddings: `{instance}:embeddings:session-summaries:{chain}:{offset}`.
+fn summary_chunk(key: &str) -> Option<(String, String, usize)> {
+    let parts: Vec<&str> = key.split(':').collect();
+    match parts.as_slice() {
+        [instance, "embeddings", "session-summaries", chain, offset] => Some((
+            instance.to_string(),
+            chain.to_string(),
+            offset.parse().ok()?,
+        )),
+        _ => None,
+    }
+}
+
+/// Split text into chunks of at most `size` bytes on char boundaries,
+/// returning each chunk with its starting byte offset.
+fn chunk_text(text: &str, size: usize) -> Vec<(usize, String)> {
+    let size = size.max(1);
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < text.len() {
+        let mut end = std::cmp::min(start + size, text.len());
+        while end > start && !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        if end == start {
+            // A single char wider than `size`; take it whole
+            end = start + text[start..].chars().next().map_or(1, char::len_utf8);
+        }
+        chunks.push((start, text[start..end].to_string()));
+        start = end;
+    }
+    chunks
+}
+
+/// Cut `text` to at most `max_chars` chars plus an ellipsis; `None` if it already fits.
+fn truncate_snippet(text: &str, max_chars: usize) -> Option<String> {
+    let (cut, _) = text.char_indices().nth(max_chars)?;
+    Some(format!("{}…", &text[..cut]))
+}
+
+/// Embed summary chunks with one batch request. When the batch fails, each
+/// chunk is retried on its own so a partial outage only leaves the failing
+/// chunks without a vector (`None`); errors only when no chunk could be embedded.
+async fn embed_chunks(embedder: &dyn Embedder, texts: &[String]) -> Result<Vec<Option<Vec<f32>>>> {
+    if texts.is_empty() {
+        return Ok(Vec::new());
+    }
+    let batch_err = match embedder.embed_batch(texts).await {
+        Ok(vectors) => return Ok(vectors.into_iter().map(Some).collect()),
+        Err(e) => e,
+    };
+    tracing::warn!(
+        "Batch embedding of {} summary chunks failed ({}); embedding them one at a time",
+        texts.len(),
+        batch_err
+    );
+    let mut vectors = Vec::with_capacity(texts.len());
+    let mut last_err = batch_err;
+    for text in texts {
+        match embedder.embed(text).await {
+            Ok(vector) => vectors.push(Some(vector)),
+            Err(e) => {
+                vectors.push(None);
+                last_err = e;
+            }
+        }
+    }
+    if vectors.iter().all(Option::is_none) {
+        return Err(last_err.into());
+    }
+    Ok(vectors)
+}
+
+/// Embed `texts` within the request's `embeddings.max_calls_per_request`
+/// budget (`embed_calls` counts texts embedded so far), each cut to
+/// `openai.embedding_max_input_chars`. Texts past the
+/// budget, failed ones and vectors skipped under `warn_on_dim_mismatch`
+/// come back as `None`; any other dimension mismatch is an error.
+async fn embed_within_budget(
+    config: &Config,
+    embedder: &dyn Embedder,
+    chain_id: &str,
+    texts: &[String],
+    embed_calls: &mut usize,
+) -> Result<Vec<Option<Vec<f32>>>> {
+    let max_calls = config.embeddings.max_calls_per_request;
+    let embed_count = if max_calls == 0 {
+        texts.len()
+    } else {
+        texts.len().min(max_calls.saturating_sub(*embed_calls))
+    };
+    if embed_count < texts.len() {
+        tracing::warn!(
+            "ui_memory update: summary for chain {} has {} chunks to embed; embedding {} now (max {} per request), queuing the rest for backfill",
+            chain_id,
+            texts.len(),
+            embed_count,
+            max_calls
+        );
+    }
+    let max_chars = config.openai.embedding_max_input_chars;
+    let inputs: Vec<String> = texts[..embed_count]
+        .iter()
+        .map(|t| crate::embeddings::truncate_for_embedding(t, max_chars).to_string())
+        .collect();
+    let mut vectors = embed_chunks(embedder, &inputs).await?;
+    *embed_calls += embed_count;
+    for slot in &mut vectors {
+        if let Some(vector) = slot
+            && !config.openai.check_embedding_dims(vector.len())?
+        {
+            *slot = None;
+        }
+    }
+    vectors.resize(texts.len(), None);
+    Ok(vectors)
+}
+
+/// Queue the writes for one summary chunk hash: its content, and its vector
+/// or, without one, the removal of the stale vector and a backfill entry;
+/// then the update's tags, importance and TTL
+#[allow(clippy::too_many_arguments)]
+fn write_summary_chunk(
+    pipe: &mut redis::Pipeline,
+    config: &Config,
+    instance: &str,
+    chain_id: &str,
+    offset: usize,
+    content: &str,
+    vector: Option<&[f32]>,
+    update: &MemoryUpdate,
+) -> String {
+    let key = format!("{instance}:embeddings:session-summaries:{chain_id}:{offset}");
+    pipe.hset(&key, "content", content)
+        .hset(&key, "chain_id", chain_id);
+    match vector {
+        Some(vector) => {
+            pipe.hset(
+                &key,
+                "vector",
+                config.redis_search.vector_type.encode(vector),
+            );
+        }
+        None => {
+            pipe.hdel(&key, "vector").sadd(
+                crate::embeddings::pending_key(instance),
+                crate::embeddings::summary_chunk_member(chain_id, offset),
+            );
+        }
+    }
+    if let Some(tags) = &update.tags {
+        pipe.hset(&key, "tags", tags.join(","));
+    }
+    if let Some(imp) = &update.importance {
+        pipe.hset(&key, "importance", imp);
+    }
+    if let Some(ttl) = update.ttl_seconds.filter(|&ttl| ttl > 0) {
+        pipe.pexpire(&key, ttl.saturating_mul(1000) as i64);
+    }
+    key
+}
+
+/// Replace a chain's session summary chunk embeddings with fresh ones for `content`.
+/// The stored `ui_start` summary JSON is updated too, only once embedding succeeded.
+///
+/// Chunks are overwritten in place and only offsets the new text no longer
+/// reaches are deleted, in one MULTI/EXEC. Fields the update leaves unset
+/// (`tags`, `importance`, `ts`) carry over from the existing chunks.
+/// Chunks past `embeddings.max_calls_per_request` or whose embedding failed
+/// are stored without a vector and queued for the backfill scheduler.
+#[allow(clippy::too_many_arguments)]
+async fn reembed_summary(
+    config: &Config,
+    redis_manager: &RedisManager,
+    embedder: &dyn Embedder,
+    con: &mut deadpool_redis::Connection,
+    instance: &str,
+    chain_id: &str,
+    content: &str,
+    update: &MemoryUpdate,
+    embed_calls: &mut usize,
+) -> Result<Vec<String>> {
+    // Embed first so a failed request leaves the old chunks searchable
+    let chunks = chunk_text(content, config.ui_memory.summary_chunk_size);
+    let texts: Vec<String> = chunks.iter().map(|(_, c)| c.clone()).collect();
+    let vectors = embed_within_budget(config, embedder, chain_id, &texts, embed_calls).await?;
+
+    let pattern = format!("{instance}:embeddings:session-summaries:{chain_id}:*");
+    let (old_keys, _) = redis_manager
+        .scan_match(
+            &pattern,
+            config.redis.scan_count,
+            config.redis.scan_max_keys,
+        )
+        .await?;
+    // Chunks of one summary share these fields; keep them unless overridden
+    let (tags, importance, ts): (Option<String>, Option<String>, Option<i64>) =
+        match old_keys.first() {
+            Some(first) => {
+                redis::cmd("HMGET")
+                    .arg(first)
+                    .arg("tags")
+                    .arg("importance")
+                    .arg("ts")
+                    .query_async(&mut **con)
+                    .await?
+            }
+            None => (None, None, None),
+        };
+    let ts = ts.unwrap_or_else(|| Utc::now().timestamp());
+
+    let summary_key = format!("{instance}:ui_start:summary:{chain_id}");
+    let exists: bool = con.exists(&summary_key).await?;
+    let mut pipe = redis::pipe();
+    pipe.atomic();
+    if exists {
+        pipe.cmd("JSON.SET")
+            .arg(&summary_key)
+            .arg("$.summary")
+            .arg(serde_json::to_string(content)?);
+    }
+    let mut new_keys = Vec::with_capacity(chunks.len());
+    for ((start, chunk), vector) in chunks.iter().zip(&vectors) {
+        let key = format!("{instance}:embeddings:session-summaries:{chain_id}:{start}");
+        pipe.hset(&key, "ts", ts);
+        if update.tags.is_none()
+            && let Some(tags) = &tags
+        {
+            pipe.hset(&key, "tags", tags);
+        }
+        if update.importance.is_none()
+            && let Some(imp) = &importance
+        {
+            pipe.hset(&key, "importance", imp);
+        }
+        write_summary_chunk(
+            &mut pipe,
+            config,
+            instance,
+            chain_id,
+            *start,
+            chunk,
+            vector.as_deref(),
+            update,
+        );
+        new_keys.push(key);
+    }
+    let stale: Vec<&String> = old_keys.iter().filter(|k| !new_keys.contains(k)).collect();
+    if !stale.is_empty() {
+        pipe.del(&stale);
+    }
+    let _: () = pipe.query_async(&mut **con).await?;
+    tracing::info!(
+        "Re-embedded session summary for chain {} ({} chunks, {} stale removed)",
+        chain_id,
+        new_keys.len(),
+        stale.len()
+    );
+    Ok(new_keys)
 }
 
 #[allow(dead_code)]
@@ -158,6 +474,7 @@ fn determine_indexes(instance_id: &str, scope: &str) -> Vec<String> {
         "session-summaries" => indexes.push(format!("idx:{instance_id}:session-summaries")),
         "important" => indexes.push(format!("idx:{instance_id}:important")),
         "federation" => indexes.push("idx:Federation:embeddings".to_string()),
+        "thoughts" => indexes.push(format!("idx:{instance_id}:thought")),
         "all" => {
             indexes.push(format!("idx:{instance_id}:session-summaries"));
             indexes.push(format!("idx:{instance_id}:important"));
@@ -168,6 +485,114 @@ fn determine_indexes(instance_id: &str, scope: &str) -> Vec<String> {
     indexes
 }
 
+/// Thought ids behind memory items (embedding hashes derived from a thought)
+fn thought_ids_of(items: &[MemoryItem]) -> Vec<String> {
+    items
+        .iter()
+        .filter(|item| !item.thought_id.is_empty())
+        .map(|item| item.thought_id.clone())
+        .collect()
+}
+
+/// Text fields read for memory items; never the binary `vector`
+const MEMORY_FIELDS: [&str; 7] = [
+    "content",
+    "tags",
+    "importance",
+    "chain_id",
+    "thought_id",
+    "ts",
+    "sentiment",
+];
+
+/// Memory item from `MEMORY_FIELDS` values, in order
+fn memory_item(key: String, fields: Vec<Option<String>>) -> MemoryItem {
+    let mut it = fields.into_iter();
+    let content = it.next().flatten().unwrap_or_default();
+    let tags = it
+        .next()
+        .flatten()
+        .map(|s| s.split(',').map(String::from).collect())
+        .unwrap_or_default();
+    let importance = it.next().flatten().unwrap_or_default();
+    let chain_id = it.next().flatten().unwrap_or_default();
+    let thought_id = it.next().flatten().unwrap_or_default();
+    let ts = it
+        .next()
+        .flatten()
+        .and_then(|x| x.parse::<i64>().ok())
+        .unwrap_or_default();
+    let sentiment = it.next().flatten().and_then(|s| s.parse().ok());
+    MemoryItem {
+        key,
+        content,
+        tags,
+        importance,
+        chain_id,
+        thought_id,
+        ts,
+        score: None,
+        sentiment,
+        content_truncated: false,
+    }
+}
+
+/// Retrieval paths run by `search` (`options.search_type`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SearchMode {
+    Keyword,
+    Vector,
+    Hybrid,
+}
+
+impl SearchMode {
+    fn parse(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "keyword" | "text" => Ok(SearchMode::Keyword),
+            "vector" => Ok(SearchMode::Vector),
+            "hybrid" => Ok(SearchMode::Hybrid),
+            other => Err(anyhow!(
+                "Unsupported search_type '{other}' (use keyword|vector|hybrid)"
+            )),
+        }
+    }
+}
+
+/// Merge KNN hits into keyword hits by key, the vector score winning;
+/// returns how many hits were not already present
+fn merge_vector_hits(items: &mut Vec<MemoryItem>, hits: Vec<MemoryItem>) -> usize {
+    let mut added = 0;
+    for hit in hits {
+        match items.iter_mut().find(|item| item.key == hit.key) {
+            Some(existing) => existing.score = hit.score,
+            None => {
+                items.push(hit);
+                added += 1;
+            }
+        }
+    }
+    added
+}
+
+/// Order merged results by `sort` when given, else by score (unscored last)
+fn sort_items(items: &mut [MemoryItem], sort: Option<(&str, bool)>) {
+    let key = |item: &MemoryItem| -> Option<f64> {
+        match sort {
+            Some(("ts", _)) => Some(item.ts as f64),
+            Some(("sentiment", _)) => item.sentiment.map(f64::from),
+            _ => item.score.map(f64::from),
+        }
+    };
+    let ascending = matches!(sort, Some((_, false)));
+    items.sort_by(|a, b| match (key(a), key(b)) {
+        (Some(x), Some(y)) if ascending => x.total_cmp(&y),
+        (Some(x), Some(y)) => y.total_cmp(&x),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+}
+
 fn parse_key_scope(key: &str) -> (String, String) {
     let parts: Vec<&str> = key.split(':').collect();
     if parts.len() > 2 {
@@ -180,6 +605,7 @@ fn parse_key_scope(key: &str) -> (String, String) {
 pub async fn ui_memory_impl(
     config: &Config,
     redis_manager: &RedisManager,
+    embedder: Option<&dyn Embedder>,
     params: UiMemoryParams,
 ) -> Result<UiMemoryResult> {
     let mut con = redis_manager.get_connection().await?;
@@ -188,25 +614,32 @@ pub async fn ui_memory_impl(
         "help" => {
             let help = r#"ui_memory tool
 Actions:
-  - search: keyword search with optional filters
+  - search: keyword, vector (KNN over the query embedding) or hybrid search with optional filters
   - read: read exact keys
-  - update: update fields, optionally re-embed on content change
+  - update: update fields, optionally re-embed on content change (session summaries are re-chunked per chain)
   - delete: delete exact keys
+  - prune_chains: find short, idle thought chains (chains.prune_max_thoughts / prune_min_age_hours);
+    dry_run defaults to true, deleting requires dry_run=false plus admin_token
+  - script_status: cached Lua script SHAs and whether Redis still has each (SCRIPT EXISTS); requires admin_token
 
 Params shape:
   {
-    action: "search|read|update|delete|help",
+    action: "search|read|update|delete|prune_chains|script_status|help",
     query?: string,
-    scope?: "all|session-summaries|important|federation" (default: all),
-    filters?: { tags?: string[], importance?: string, chain_id?: string, thought_id?: string },
-    options?: { limit?: number, offset?: number, k?: number, search_type?: string },
+    scope?: "all|session-summaries|important|federation|thoughts" (default: all),
+    filters?: { tags?: string[], importance?: string, chain_id?: string, thought_id?: string, sentiment_min?: number, sentiment_max?: number },
+    options?: { limit?: number, offset?: number, k?: number, search_type?: "keyword|vector|hybrid" (default hybrid), min_score?: number, ef_runtime?: number, snippet_length?: number, sort_by?: "sentiment|ts", sort_desc?: boolean },
     targets?: { keys?: string[] },
-    update?: { content?: string, tags?: string[], importance?: string, chain_id?: string, thought_id?: string, ttl_seconds?: number }
+    update?: { content?: string, tags?: string[], importance?: string, chain_id?: string, thought_id?: string, ttl_seconds?: number },
+    dry_run?: boolean,
+    admin_token?: string
   }
 
 Troubleshooting:
   - UTF-8 errors: The tool now avoids fetching binary fields like 'vector'. Use read/update routes which HMGET only text fields.
-  - Empty results: Ensure the RediSearch indices exist and scope is correct. Supported indices: idx:{instance}:session-summaries, idx:{instance}:important, idx:Federation:embeddings.
+  - Empty results: Ensure the RediSearch indices exist and scope is correct. Supported indices: idx:{instance}:session-summaries, idx:{instance}:important, idx:Federation:embeddings, idx:{instance}:thought.
+  - Vector/hybrid scores are cosine similarity (higher is closer); min_score drops KNN hits below it. Hybrid without an embedding provider, or when embedding the query fails, returns keyword hits only; `message` says why.
+  - Sentiment filters/sorting need scope "thoughts" and ui_think.sentiment enabled when the thoughts were saved.
 "#;
             Ok(UiMemoryResult {
                 message: Some(help.to_string()),
@@ -214,109 +647,198 @@ Troubleshooting:
             })
         }
         "search" => {
-            // Keyword-only search MVP with filters. Uses RediSearch to get ids, then HGET fields.
+            // Keyword (FT.SEARCH), vector (KNN over the query embedding) or
+            // hybrid (both, merged by key). Only text fields are returned.
             let instance_id = std::env::var("INSTANCE_ID")
                 .unwrap_or_else(|_| config.server.default_instance_id.clone());
             let scope = params.scope.as_deref().unwrap_or("all");
             let indexes = determine_indexes(&instance_id, scope);
             let options = params.options.clone().unwrap_or_default();
-
-            let mut query = String::new();
-            if let Some(q) = params.query.as_deref() {
-                if !q.trim().is_empty() {
-                    query.push_str(q.trim());
+            let limit = config.server.clamp_limit(options.limit as usize);
+            let mode = SearchMode::parse(&options.search_type)?;
+            let sort = match options.sort_by.as_deref() {
+                None => None,
+                Some(field @ ("sentiment" | "ts")) => {
+                    Some((field, options.sort_desc.unwrap_or(true)))
                 }
+                Some(other) => anyhow::bail!("Unsupported sort_by '{other}' (use sentiment|ts)"),
+            };
+            let wants_sentiment = params.filters.as_ref().is_some_and(|f| f.has_sentiment())
+                || matches!(sort, Some(("sentiment", _)));
+            if wants_sentiment && scope != "thoughts" {
+                anyhow::bail!("Sentiment filters and sorting require scope \"thoughts\"");
             }
+
+            let text = params
+                .query
+                .as_deref()
+                .map(str::trim)
+                .filter(|q| !q.is_empty());
+            let mut clauses: Vec<String> = Vec::new();
             if let Some(f) = params.filters.as_ref() {
                 if !f.tags.is_empty() {
-                    if !query.is_empty() {
-                        query.push(' ');
-                    }
                     let tags = f.tags.join("|");
-                    query.push_str(&format!("@tags:{{{tags}}}"));
+                    clauses.push(format!("@tags:{{{tags}}}"));
                 }
                 if let Some(imp) = &f.importance {
-                    if !query.is_empty() {
-                        query.push(' ');
-                    }
-                    query.push_str(&format!("@importance:{imp}"));
+                    clauses.push(format!("@importance:{imp}"));
                 }
                 if let Some(cid) = &f.chain_id {
-                    if !query.is_empty() {
-                        query.push(' ');
-                    }
-                    query.push_str(&format!("@chain_id:{cid}"));
+                    clauses.push(format!("@chain_id:{cid}"));
                 }
                 if let Some(tid) = &f.thought_id {
-                    if !query.is_empty() {
-                        query.push(' ');
-                    }
-                    query.push_str(&format!("@thought_id:{tid}"));
+                    clauses.push(format!("@thought_id:{tid}"));
+                }
+                if let Some(clause) = sentiment_clause(f.sentiment_min, f.sentiment_max) {
+                    clauses.push(clause);
                 }
             }
-            if query.is_empty() {
-                query.push('*');
-            }
+            let filter = if clauses.is_empty() {
+                "*".to_string()
+            } else {
+                clauses.join(" ")
+            };
+            let query = match text {
+                Some(q) if clauses.is_empty() => q.to_string(),
+                Some(q) => format!("{q} {filter}"),
+                None => filter.clone(),
+            };
 
+            // Vector and hybrid embed the query; hybrid falls back to keyword
+            // only and reports why in `message`
+            let mut degraded: Option<String> = None;
+            let query_vector = match (mode, text, embedder) {
+                (SearchMode::Keyword, ..) => None,
+                (_, Some(q), Some(embedder)) => {
+                    let embedded: Result<Option<Vec<u8>>> = async {
+                        let embedding = crate::embeddings::generate_embedding(
+                            q,
+                            embedder,
+                            redis_manager,
+                            config.openai.embedding_max_input_chars,
+                        )
+                        .await?;
+                        Ok(config
+                            .openai
+                            .check_embedding_dims(embedding.len())?
+                            .then(|| config.redis_search.vector_type.encode(&embedding)))
+                    }
+                    .await;
+                    match embedded {
+                        Ok(Some(vector)) => Some(vector),
+                        Ok(None) if mode == SearchMode::Hybrid => {
+                            degraded = Some(
+                                "query embedding dimension mismatch; keyword results only"
+                                    .to_string(),
+                            );
+                            None
+                        }
+                        Ok(None) => None,
+                        Err(e) if mode == SearchMode::Hybrid => {
+                            tracing::warn!(
+                                "ui_memory search: query embedding failed, keyword only: {}",
+                                e
+                            );
+                            degraded = Some(format!(
+                                "query embedding failed ({e}); keyword results only"
+                            ));
+                            None
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+                (SearchMode::Vector, None, _) => {
+                    anyhow::bail!("search_type \"vector\" needs a query")
+                }
+                (SearchMode::Vector, Some(_), None) => {
+                    require_embedder(None)?;
+                    None
+                }
+                (SearchMode::Hybrid, ..) => {
+                    tracing::debug!("ui_memory search: no query embedding; keyword results only");
+                    None
+                }
+            };
+
+            // Results merged across indexes or with KNN hits are paged after
+            // merging, so each source contributes its top `offset + limit` rows
+            let offset = options.offset as usize;
+            let page_merged = query_vector.is_some() || indexes.len() > 1;
+            let (fetch_offset, fetch_limit) = if page_merged {
+                (0, offset.saturating_add(limit))
+            } else {
+                (offset, limit)
+            };
             let mut all_items: Vec<MemoryItem> = Vec::new();
+            let mut vector_items: Vec<MemoryItem> = Vec::new();
+            let mut total = 0usize;
             for idx in indexes {
-                let res: redis::Value = redis::cmd("FT.SEARCH")
-                    .arg(&idx)
-                    .arg(&query)
-                    .arg("NOCONTENT")
-                    .arg("LIMIT")
-                    .arg(options.offset)
-                    .arg(options.limit)
-                    .query_async(&mut *con)
-                    .await?;
-                let keys = extract_doc_ids(&res);
-                if keys.is_empty() {
-                    continue;
+                if mode != SearchMode::Vector {
+                    let (matched, rows) = redis_manager
+                        .search_with_fields(
+                            &idx,
+                            &query,
+                            fetch_offset,
+                            fetch_limit,
+                            &MEMORY_FIELDS,
+                            sort,
+                        )
+                        .await?;
+                    total += matched;
+                    all_items.extend(rows.into_iter().map(|row| memory_item(row.key, row.fields)));
                 }
-
-                // Fetch only text fields to avoid UTF-8 issues with binary 'vector'
-                let fields = [
-                    "content",
-                    "tags",
-                    "importance",
-                    "chain_id",
-                    "thought_id",
-                    "ts",
-                ];
-                let mut pipe = redis::pipe();
-                for k in &keys {
-                    pipe.cmd("HMGET").arg(k).arg(&fields);
+                if let Some(vector) = &query_vector {
+                    let k = config
+                        .redis_search
+                        .knn_k(&idx, Some(options.k.saturating_add(options.offset)));
+                    let rows = redis_manager
+                        .knn_search_filtered(
+                            &idx,
+                            &filter,
+                            vector,
+                            k,
+                            options.ef_runtime,
+                            &MEMORY_FIELDS,
+                        )
+                        .await
+                        .unwrap_or_else(|e| {
+                            tracing::warn!("ui_memory search: KNN on {} failed: {}", idx, e);
+                            degraded = Some(format!("KNN on {idx} failed ({e})"));
+                            Vec::new()
+                        });
+                    vector_items.extend(rows.into_iter().map(|row| {
+                        let mut item = memory_item(row.key, row.fields);
+                        // Cosine distance -> similarity
+                        item.score = row.score.map(|d| (1.0 - d) as f32);
+                        item
+                    }));
                 }
-                let rows: Vec<Vec<Option<String>>> = pipe.query_async(&mut *con).await?;
-                for (i, row) in rows.into_iter().enumerate() {
-                    let mut it = row.into_iter();
-                    let content = it.next().flatten().unwrap_or_default();
-                    let tags = it
-                        .next()
-                        .flatten()
-                        .map(|s| s.split(',').map(|x| x.to_string()).collect())
-                        .unwrap_or_else(Vec::new);
-                    let importance = it.next().flatten().unwrap_or_default();
-                    let chain_id = it.next().flatten().unwrap_or_default();
-                    let thought_id = it.next().flatten().unwrap_or_default();
-                    let ts = it
-                        .next()
-                        .and_then(|s| s.and_then(|x| x.parse::<i64>().ok()))
-                        .unwrap_or_default();
-                    all_items.push(MemoryItem {
-                        key: keys[i].clone(),
-                        content,
-                        tags,
-                        importance,
-                        chain_id,
-                        thought_id,
-                        ts,
-                        score: None,
-                    });
+            }
+            if query_vector.is_some() {
+                if let Some(min) = options.min_score {
+                    vector_items.retain(|item| item.score.is_some_and(|s| s >= min));
+                }
+                total += merge_vector_hits(&mut all_items, vector_items);
+            }
+            if page_merged {
+                sort_items(&mut all_items, sort);
+                all_items = all_items.into_iter().skip(offset).take(limit).collect();
+            }
+            if config.usage.track_access {
+                redis_manager.spawn_record_access(&instance_id, thought_ids_of(&all_items));
+            }
+            if let Some(max_chars) = options.snippet_length.or(config.ui_memory.snippet_length) {
+                for item in &mut all_items {
+                    if let Some(snippet) = truncate_snippet(&item.content, max_chars) {
+                        item.content = snippet;
+                        item.content_truncated = true;
+                    }
                 }
             }
             Ok(UiMemoryResult {
                 results: Some(all_items),
+                total: Some(total),
+                message: degraded,
                 ..Default::default()
             })
         }
@@ -329,46 +851,21 @@ Troubleshooting:
                 });
             }
 
-            let fields = [
-                "content",
-                "tags",
-                "importance",
-                "chain_id",
-                "thought_id",
-                "ts",
-            ];
             let mut pipe = redis::pipe();
             for key in &keys {
-                pipe.cmd("HMGET").arg(key).arg(&fields);
+                pipe.cmd("HMGET").arg(key).arg(&MEMORY_FIELDS);
             }
             let rows: Vec<Vec<Option<String>>> = pipe.query_async(&mut *con).await?;
 
-            let mut memory_items = Vec::new();
-            for (i, row) in rows.into_iter().enumerate() {
-                let mut it = row.into_iter();
-                let content = it.next().flatten().unwrap_or_default();
-                let tags: Vec<String> = it
-                    .next()
-                    .flatten()
-                    .map(|s| s.split(',').map(String::from).collect())
-                    .unwrap_or_default();
-                let importance = it.next().flatten().unwrap_or_default();
-                let chain_id = it.next().flatten().unwrap_or_default();
-                let thought_id = it.next().flatten().unwrap_or_default();
-                let ts = it
-                    .next()
-                    .and_then(|s| s.and_then(|x| x.parse::<i64>().ok()))
-                    .unwrap_or_default();
-                memory_items.push(MemoryItem {
-                    key: keys[i].clone(),
-                    content,
-                    tags,
-                    importance,
-                    chain_id,
-                    thought_id,
-                    ts,
-                    score: None,
-                });
+            let memory_items: Vec<MemoryItem> = keys
+                .iter()
+                .zip(rows)
+                .map(|(key, row)| memory_item(key.clone(), row))
+                .collect();
+            if config.usage.track_access {
+                let instance_id = std::env::var("INSTANCE_ID")
+                    .unwrap_or_else(|_| config.server.default_instance_id.clone());
+                redis_manager.spawn_record_access(&instance_id, thought_ids_of(&memory_items));
             }
             Ok(UiMemoryResult {
                 results: Some(memory_items),
@@ -383,6 +880,31 @@ Troubleshooting:
                     ..Default::default()
                 });
             }
+            // Deleted thoughts release their dedup hash so the content can be saved again
+            let mut release = redis::pipe();
+            for key in &keys {
+                let Some((instance, _)) = key.split_once(":Thoughts:") else {
+                    continue;
+                };
+                let raw: Option<String> = redis::cmd("JSON.GET")
+                    .arg(key)
+                    .arg("$")
+                    .query_async(&mut *con)
+                    .await
+                    .unwrap_or(None);
+                let hash = raw
+                    .and_then(|r| serde_json::from_str::<Vec<ThoughtRecord>>(&r).ok())
+                    .and_then(|mut v| v.pop())
+                    .and_then(|t| t.registered_content_hash());
+                if let Some(hash) = hash {
+                    release
+                        .srem(format!("{instance}:content_hashes"), hash)
+                        .ignore();
+                }
+            }
+            if let Err(e) = release.query_async::<()>(&mut *con).await {
+                tracing::warn!("ui_memory delete: failed to release content hashes: {}", e);
+            }
             let count: usize = con.del(&keys).await?;
             Ok(UiMemoryResult {
                 deleted: Some(count),
@@ -390,11 +912,65 @@ Troubleshooting:
             })
         }
         "update" => {
+            redis_manager.ensure_writable()?;
             let keys = params.targets.context("Missing targets for update")?.keys;
             let update_data = params.update.context("Missing update data")?;
             let mut updated_pairs = Vec::new();
+            let mut reembedded_chains = std::collections::HashSet::new();
+            let mut embed_calls = 0;
 
             for key in &keys {
+                // Edits to the summary itself regenerate the whole chain's chunk embeddings
+                if let Some(content) = &update_data.content
+                    && config.ui_memory.reembed_summaries_on_update
+                    && let Some((instance, chain_id)) = summary_chain(key)
+                {
+                    if reembedded_chains.insert((instance.clone(), chain_id.clone())) {
+                        let new_keys = reembed_summary(
+                            config,
+                            redis_manager,
+                            require_embedder(embedder)?,
+                            &mut con,
+                            &instance,
+                            &chain_id,
+                            content,
+                            &update_data,
+                            &mut embed_calls,
+                        )
+                        .await?;
+                        updated_pairs.extend(new_keys.into_iter().map(|k| (key.clone(), k)));
+                    }
+                    continue;
+                }
+                // A single chunk is re-embedded in place under the same budget and
+                // dimension checks; its siblings and its `ts` are untouched
+                if let Some(content) = &update_data.content
+                    && config.ui_memory.reembed_summaries_on_update
+                    && let Some((instance, chain_id, offset)) = summary_chunk(key)
+                {
+                    let vectors = embed_within_budget(
+                        config,
+                        require_embedder(embedder)?,
+                        &chain_id,
+                        std::slice::from_ref(content),
+                        &mut embed_calls,
+                    )
+                    .await?;
+                    let mut pipe = redis::pipe();
+                    write_summary_chunk(
+                        &mut pipe,
+                        config,
+                        &instance,
+                        &chain_id,
+                        offset,
+                        content,
+                        vectors[0].as_deref(),
+                        &update_data,
+                    );
+                    let _: () = pipe.query_async(&mut *con).await?;
+                    updated_pairs.push((key.clone(), key.clone()));
+                    continue;
+                }
                 if let Some(content) = &update_data.content {
                     let (instance, scope) = parse_key_scope(key);
                     let new_hash = short_hash(content);
@@ -406,12 +982,27 @@ Troubleshooting:
                     };
 
                     // Re-embed
-                    let vector_f32 = openai_embed(config, content).await?;
+                    let vector_f32 = crate::embeddings::generate_embedding(
+                        content,
+                        require_embedder(embedder)?,
+                        redis_manager,
+                        config.openai.embedding_max_input_chars,
+                    )
+                    .await?;
                     let dims = config.openai.embedding_dimensions;
                     if vector_f32.len() != dims {
-                        return Err(anyhow!("embedding dims mismatch"));
+                        tracing::error!(
+                            "ui_memory update: embedding dimension mismatch (expected {}, got {})",
+                            dims,
+                            vector_f32.len()
+                        );
+                        return Err(UnifiedIntelligenceError::EmbeddingDimMismatch {
+                            expected: dims,
+                            got: vector_f32.len(),
+                        }
+                        .into());
                     }
-                    let vector_bytes: Vec<u8> = bytemuck::cast_slice(&vector_f32).to_vec();
+                    let vector_bytes: Vec<u8> = config.redis_search.vector_type.encode(&vector_f32);
                     let ts = Utc::now().timestamp();
 
                     let mut pipe = redis::pipe();
@@ -430,7 +1021,9 @@ Troubleshooting:
                     if let Some(tid) = &update_data.thought_id {
                         pipe.hset(&new_key, "thought_id", tid);
                     }
-                    // TTLs disabled; do not set expiration
+                    if let Some(ttl) = update_data.ttl_seconds.filter(|&ttl| ttl > 0) {
+                        pipe.pexpire(&new_key, ttl.saturating_mul(1000) as i64);
+                    }
                     pipe.del(key);
                     let _: () = pipe.query_async(&mut *con).await?;
                     updated_pairs.push((key.clone(), new_key));
@@ -446,10 +1039,13 @@ Troubleshooting:
                         has_update = true;
                     }
                     // ... other fields
+                    if let Some(ttl) = update_data.ttl_seconds.filter(|&ttl| ttl > 0) {
+                        pipe.pexpire(key, ttl.saturating_mul(1000) as i64);
+                        has_update = true;
+                    }
                     if has_update {
                         let _: () = pipe.query_async(&mut *con).await?;
                     }
-                    // TTLs disabled; do not set expiration
                     updated_pairs.push((key.clone(), key.clone()));
                 }
             }
@@ -465,24 +1061,160 @@ Troubleshooting:
     }
 }
 
-fn extract_doc_ids(val: &redis::Value) -> Vec<String> {
-    // RediSearch NOCONTENT response (RESP3): [total(Int), id1(BulkString), id2(BulkString), ...]
-    let mut out = Vec::new();
-    match val {
-        redis::Value::Array(items) if !items.is_empty() => {
-            for item in items.iter().skip(1) {
-                match item {
-                    redis::Value::BulkString(bytes) => {
-                        if let Ok(s) = std::str::from_utf8(bytes) {
-                            out.push(s.to_string());
-                        }
-                    }
-                    redis::Value::SimpleString(s) => out.push(s.clone()),
-                    _ => {}
-                }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rejects batches and any text containing "bad"
+    struct FlakyEmbedder;
+
+    #[async_trait::async_trait]
+    impl Embedder for FlakyEmbedder {
+        async fn embed(&self, text: &str) -> crate::error::Result<Vec<f32>> {
+            if text.contains("bad") {
+                return Err(UnifiedIntelligenceError::Internal("provider down".into()));
             }
+            Ok(vec![text.len() as f32])
         }
-        _ => {}
+
+        async fn embed_batch(&self, _texts: &[String]) -> crate::error::Result<Vec<Vec<f32>>> {
+            Err(UnifiedIntelligenceError::Internal("batch rejected".into()))
+        }
+
+        fn dimensions(&self) -> usize {
+            1
+        }
+
+        fn model_id(&self) -> String {
+            "test/flaky/1".to_string()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_embed_chunks_falls_back_per_chunk() {
+        let texts = vec!["ok".to_string(), "bad".to_string(), "fine".to_string()];
+        let vectors = embed_chunks(&FlakyEmbedder, &texts).await.unwrap();
+        assert_eq!(vectors, vec![Some(vec![2.0]), None, Some(vec![4.0])]);
+
+        let all_bad = vec!["bad".to_string(), "bad too".to_string()];
+        assert!(embed_chunks(&FlakyEmbedder, &all_bad).await.is_err());
+        assert!(embed_chunks(&FlakyEmbedder, &[]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_embed_within_budget_caps_calls_and_skips_mismatches() {
+        let mut config = Config::default();
+        config.openai.embedding_dimensions = 1;
+        config.embeddings.max_calls_per_request = 3;
+        let texts = vec!["ok".to_string(), "fine".to_string()];
+        let mut calls = 2;
+        let vectors = embed_within_budget(&config, &FlakyEmbedder, "c", &texts, &mut calls)
+            .await
+            .unwrap();
+        assert_eq!(vectors, vec![Some(vec![2.0]), None]);
+        assert_eq!(calls, 3);
+
+        // Mismatched vectors are skipped under warn_on_dim_mismatch, else rejected
+        config.openai.embedding_dimensions = 8;
+        config.openai.warn_on_dim_mismatch = true;
+        let mut calls = 0;
+        let vectors = embed_within_budget(&config, &FlakyEmbedder, "c", &texts, &mut calls)
+            .await
+            .unwrap();
+        assert_eq!(vectors, vec![None, None]);
+        config.openai.warn_on_dim_mismatch = false;
+        assert!(
+            embed_within_budget(&config, &FlakyEmbedder, "c", &texts, &mut calls)
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_summary_chain_matches_summary_keys() {
+        assert_eq!(
+            summary_chain("CC:ui_start:summary:abc"),
+            Some(("CC".to_string(), "abc".to_string()))
+        );
+        assert_eq!(
+            summary_chain("CC:embeddings:session-summaries:abc:2000"),
+            None
+        );
+        assert_eq!(
+            summary_chunk("CC:embeddings:session-summaries:abc:2000"),
+            Some(("CC".to_string(), "abc".to_string(), 2000))
+        );
+        assert_eq!(
+            summary_chunk("CC:embeddings:session-summaries:deadbeef"),
+            None
+        );
+        assert_eq!(summary_chunk("CC:embeddings:important:abc:0"), None);
+    }
+
+    #[test]
+    fn test_sentiment_clause_bounds() {
+        assert_eq!(sentiment_clause(None, None), None);
+        assert_eq!(
+            sentiment_clause(Some(-0.5), Some(0.5)).as_deref(),
+            Some("@sentiment:[-0.5 0.5]")
+        );
+        assert_eq!(
+            sentiment_clause(None, Some(0.0)).as_deref(),
+            Some("@sentiment:[-inf 0]")
+        );
+        assert_eq!(
+            sentiment_clause(Some(0.3), None).as_deref(),
+            Some("@sentiment:[0.3 +inf]")
+        );
+        assert_eq!(
+            determine_indexes("CC", "thoughts"),
+            vec!["idx:CC:thought".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_chunk_text_respects_char_boundaries() {
+        let text = "ab\u{e9}cd";
+        let chunks = chunk_text(text, 3);
+        assert_eq!(
+            chunks,
+            vec![
+                (0, "ab".to_string()),
+                (2, "\u{e9}c".to_string()),
+                (5, "d".to_string())
+            ]
+        );
+        let joined: String = chunks.into_iter().map(|(_, c)| c).collect();
+        assert_eq!(joined, text);
+    }
+
+    #[test]
+    fn test_truncate_snippet_on_char_boundary() {
+        assert_eq!(truncate_snippet("short", 10), None);
+        assert_eq!(truncate_snippet("exact", 5), None);
+        assert_eq!(
+            truncate_snippet("caf\u{e9} au lait", 4),
+            Some("caf\u{e9}…".to_string())
+        );
+    }
+
+    #[test]
+    fn test_merge_vector_hits_prefers_vector_score() {
+        let item = |key: &str, score: Option<f32>| {
+            let mut item = memory_item(key.to_string(), vec![Some(key.to_string())]);
+            item.score = score;
+            item
+        };
+        let mut items = vec![item("a", None), item("b", None)];
+        let added = merge_vector_hits(&mut items, vec![item("b", Some(0.9)), item("c", Some(0.4))]);
+        assert_eq!(added, 1);
+        sort_items(&mut items, None);
+        let order: Vec<(&str, Option<f32>)> =
+            items.iter().map(|i| (i.key.as_str(), i.score)).collect();
+        assert_eq!(order, vec![("b", Some(0.9)), ("c", Some(0.4)), ("a", None)]);
+
+        assert_eq!(SearchMode::parse("Hybrid").unwrap(), SearchMode::Hybrid);
+        assert_eq!(SearchMode::parse("text").unwrap(), SearchMode::Keyword);
+        assert!(SearchMode::parse("fuzzy").is_err());
     }
-    out
 }
@@ -64,6 +64,22 @@ pub struct UiRememberParams {
     /// Search across all instance IDs instead of only the current one
     #[serde(default)]
     pub search_all_instances: Option<bool>,
+
+    /// Include a per-candidate scoring breakdown (`scoring_debug`) in the result
+    #[serde(default)]
+    pub explain: Option<bool>,
+
+    /// Hybrid weights for this call only, each in 0..=1 (overrides config/preset)
+    #[serde(default)]
+    pub weights: Option<crate::config::HybridWeights>,
+
+    /// Highlight count for this call (overrides `ui_remember.highlights`; 0 disables)
+    #[serde(default)]
+    pub highlights: Option<usize>,
+
+    /// Assistant thought (T2) whose stored synthesis prompt to return (action="prompt")
+    #[serde(default)]
+    pub thought_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -83,10 +99,87 @@ pub struct UiRememberResult {
     pub retrieved_text_count: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub retrieved_embedding_count: Option<usize>,
+    /// Set when the synthesis was served from the synthesis cache
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cached: Option<bool>,
+    /// Set when a streamed synthesis timed out and the text received so far
+    /// was kept (`groq.return_partial_on_timeout`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partial: Option<bool>,
+    /// Why a partial synthesis stopped ("timeout")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<String>,
+    /// Intent that drove synthesis (query, temporal filter, style, confidence)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intent: Option<crate::models::QueryIntent>,
+    /// Subsystems unavailable for this call ("embeddings", "search", "synthesis")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub degraded: Option<Vec<String>>,
+    /// Per-candidate hybrid scoring breakdown, sorted by `combined` (explain=true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scoring_debug: Option<Vec<ScoringDebug>>,
+    /// Hybrid weights used to rank candidates (request override or config)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weights: Option<crate::config::HybridWeights>,
+    /// Candidates left out of the synthesis context, by reason
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_dropped: Option<ContextDropped>,
+    /// Whether the feedback hash write succeeded: the explicit feedback on
+    /// `action="feedback"`, or the follow-up signal recorded on the previous turn
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feedback_written: Option<bool>,
+    /// Context sentences closest to the answer embedding, best first
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub highlights: Option<Vec<Highlight>>,
+    /// Where each synthesis context item came from (`ui_remember.include_sources`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sources: Option<Vec<ContextSource>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub next_action: Option<NextAction>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ContextDropped {
+    /// Ranked below `top_k` / `max_context_items`
+    pub score: usize,
+    /// Removed to keep total content within `max_context_chars`
+    pub size: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Highlight {
+    /// Context candidate the sentence was taken from
+    pub source_id: String,
+    pub sentence: String,
+    /// Cosine similarity to the answer embedding
+    pub similarity: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ContextSource {
+    pub id: String,
+    /// "text", "knn" or "text+knn"
+    pub origin: String,
+    /// RediSearch index of the KNN hit; absent for text-only hits
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScoringDebug {
+    pub id: String,
+    pub origin: String,
+    /// RediSearch index of the KNN hit; absent for text-only hits
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
+    pub semantic: f64,
+    pub text: f64,
+    pub recency: f64,
+    pub weights: crate::config::HybridWeights,
+    pub combined: f64,
+    pub selected: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct NextAction {
     pub tool: String,
@@ -1,19 +1,183 @@
 use async_trait::async_trait;
+use futures::StreamExt;
+use futures::stream::BoxStream;
 use rand::Rng;
 use reqwest::Client;
+use std::collections::VecDeque;
 use std::time::{Duration, Instant};
 use tokio::time::sleep;
 
 use crate::error::{Result, UnifiedIntelligenceError};
-use crate::models::{GroqRequest, GroqResponse};
+use crate::models::{ChatMessage, Choice, GroqRequest, GroqResponse, GroqUsage};
 
 const GROQ_API_URL: &str = "https://api.groq.com/openai/v1/chat/completions";
+const ANTHROPIC_API_URL: &str = "https://api.anthropic.com/v1/messages";
 const MAX_RETRIES: u8 = 5;
 const MAX_RETRY_DURATION: Duration = Duration::from_secs(300); // 5 minutes max
 
+/// Content deltas of a streamed chat completion; a transport or provider
+/// failure mid-stream is yielded as an error instead of ending the stream early
+pub type ChatStream = BoxStream<'static, Result<String>>;
+
 #[async_trait]
 pub trait Transport: Send + Sync {
     async fn chat(&self, req: &GroqRequest) -> Result<GroqResponse>;
+
+    /// Stream the completion as content deltas. The default makes a regular
+    /// request and yields its whole content as a single chunk.
+    async fn chat_stream(&self, req: &GroqRequest) -> Result<ChatStream> {
+        let response = self.chat(req).await?;
+        let text = response
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message.content)
+            .ok_or_else(|| {
+                UnifiedIntelligenceError::Internal("Groq API returned empty choices".to_string())
+            })?;
+        Ok(futures::stream::once(async move { Ok(text) }).boxed())
+    }
+}
+
+/// Content delta of one streamed chunk (`choices[0].delta.content`); an
+/// `error` object in the event becomes an error
+fn parse_stream_data(data: &str) -> Result<Option<String>> {
+    let value: serde_json::Value = serde_json::from_str(data).map_err(|e| {
+        UnifiedIntelligenceError::Internal(format!("Malformed Groq stream event: {e}"))
+    })?;
+    if let Some(error) = value.get("error") {
+        return Err(UnifiedIntelligenceError::Internal(format!(
+            "Groq stream error: {error}"
+        )));
+    }
+    Ok(value
+        .pointer("/choices/0/delta/content")
+        .and_then(|content| content.as_str())
+        .filter(|content| !content.is_empty())
+        .map(str::to_string))
+}
+
+/// Incremental decoder for the server-sent events of a streamed completion
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    buf: Vec<u8>,
+    done: bool,
+}
+
+impl SseDecoder {
+    /// Feed raw body bytes; returns the content deltas of every complete
+    /// `data:` line. Input after the `[DONE]` sentinel is ignored.
+    pub fn push(&mut self, bytes: &[u8]) -> Result<Vec<String>> {
+        let mut deltas = Vec::new();
+        if self.done {
+            return Ok(deltas);
+        }
+        self.buf.extend_from_slice(bytes);
+        while let Some(end) = self.buf.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buf.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            // Blank separators, comments and other fields carry no content
+            let Some(data) = line.trim().strip_prefix("data:") else {
+                continue;
+            };
+            let data = data.trim();
+            if data == "[DONE]" {
+                self.done = true;
+                self.buf.clear();
+                break;
+            }
+            deltas.extend(parse_stream_data(data)?);
+        }
+        Ok(deltas)
+    }
+
+    /// Whether the `[DONE]` sentinel has been seen
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+/// Send a request built by `build`, retrying failures with exponential backoff
+/// and jitter; returns the first successful response. The total latency,
+/// retries included, is recorded in the upstream metrics.
+async fn send_with_retry<F>(service: &str, build: F) -> Result<reqwest::Response>
+where
+    F: Fn() -> reqwest::RequestBuilder,
+{
+    let start_time = Instant::now();
+    let result = send_with_retry_inner(service, build, start_time).await;
+    crate::metrics::global().observe_upstream(service, start_time.elapsed());
+    result
+}
+
+async fn send_with_retry_inner<F>(
+    service: &str,
+    build: F,
+    start_time: Instant,
+) -> Result<reqwest::Response>
+where
+    F: Fn() -> reqwest::RequestBuilder,
+{
+    let mut attempts = 0;
+
+    while attempts < MAX_RETRIES {
+        // Check if we've exceeded the maximum retry duration
+        if start_time.elapsed() > MAX_RETRY_DURATION {
+            return Err(UnifiedIntelligenceError::Internal(format!(
+                "{service} API request timed out after {} seconds (max retry duration exceeded)",
+                MAX_RETRY_DURATION.as_secs()
+            )));
+        }
+
+        attempts += 1;
+
+        match build().send().await {
+            Ok(response) => {
+                if response.status().is_success() {
+                    return Ok(response);
+                }
+
+                // For non-success responses, return error after max attempts
+                if attempts >= MAX_RETRIES {
+                    return Err(UnifiedIntelligenceError::Internal(format!(
+                        "{service} API error after {} attempts: {}",
+                        attempts,
+                        response
+                            .text()
+                            .await
+                            .unwrap_or_else(|_| "Unknown error".to_string())
+                    )));
+                }
+            }
+            Err(e) => {
+                // For network errors, return error after max attempts
+                if attempts >= MAX_RETRIES {
+                    return Err(UnifiedIntelligenceError::Internal(format!(
+                        "Failed to send request to {service} API after {attempts} attempts: {e}"
+                    )));
+                }
+            }
+        }
+
+        // Exponential backoff with jitter (only if we're going to retry)
+        if attempts < MAX_RETRIES {
+            let base_delay =
+                Duration::from_millis(200 * 2u64.pow(attempts.saturating_sub(1) as u32));
+            let jitter = rand::thread_rng().gen_range(0.8..=1.2);
+            let delay = Duration::from_millis((base_delay.as_millis() as f64 * jitter) as u64);
+
+            // Cap the delay to prevent excessive waiting
+            let max_delay = Duration::from_secs(30);
+            let final_delay = std::cmp::min(delay, max_delay);
+
+            sleep(final_delay).await;
+        }
+    }
+
+    // This should never be reached due to the loop condition, but just in case
+    Err(UnifiedIntelligenceError::Internal(format!(
+        "{service} API request failed after {MAX_RETRIES} attempts"
+    )))
 }
 
 pub struct GroqTransport {
@@ -34,88 +198,262 @@ impl GroqTransport {
 #[async_trait]
 impl Transport for GroqTransport {
     async fn chat(&self, req: &GroqRequest) -> Result<GroqResponse> {
-        let start_time = Instant::now();
-        let mut attempts = 0;
-
-        while attempts < MAX_RETRIES {
-            // Check if we've exceeded the maximum retry duration
-            if start_time.elapsed() > MAX_RETRY_DURATION {
-                return Err(UnifiedIntelligenceError::Internal(format!(
-                    "Groq API request timed out after {} seconds (max retry duration exceeded)",
-                    MAX_RETRY_DURATION.as_secs()
-                )));
-            }
+        let response = send_with_retry("Groq", || {
+            self.client
+                .post(GROQ_API_URL)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .header("Content-Type", "application/json")
+                .json(req)
+        })
+        .await?;
+        response.json().await.map_err(|e| {
+            UnifiedIntelligenceError::Internal(format!("Failed to parse Groq API response: {e}"))
+        })
+    }
 
-            attempts += 1;
+    async fn chat_stream(&self, req: &GroqRequest) -> Result<ChatStream> {
+        let mut body = serde_json::to_value(req).map_err(UnifiedIntelligenceError::Json)?;
+        body["stream"] = serde_json::Value::Bool(true);
 
-            match self
-                .client
+        // Only opening the stream is retried: a partially consumed stream
+        // cannot be replayed
+        let response = send_with_retry("Groq", || {
+            self.client
                 .post(GROQ_API_URL)
                 .header("Authorization", format!("Bearer {}", self.api_key))
                 .header("Content-Type", "application/json")
-                .json(req)
-                .send()
-                .await
-            {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        return response.json().await.map_err(|e| {
-                            UnifiedIntelligenceError::Internal(format!(
-                                "Failed to parse Groq API response: {e}"
-                            ))
-                        });
-                    }
+                .json(&body)
+        })
+        .await?;
 
-                    // For non-success responses, return error after max attempts
-                    if attempts >= MAX_RETRIES {
-                        return Err(UnifiedIntelligenceError::Internal(format!(
-                            "Groq API error after {} attempts: {}",
-                            attempts,
-                            response
-                                .text()
-                                .await
-                                .unwrap_or_else(|_| "Unknown error".to_string())
-                        )));
+        let state = (response, SseDecoder::default(), VecDeque::new());
+        Ok(futures::stream::try_unfold(
+            state,
+            |(mut response, mut decoder, mut pending)| async move {
+                loop {
+                    if let Some(delta) = pending.pop_front() {
+                        return Ok(Some((delta, (response, decoder, pending))));
                     }
-                }
-                Err(e) => {
-                    // For network errors, return error after max attempts
-                    if attempts >= MAX_RETRIES {
-                        return Err(UnifiedIntelligenceError::Internal(format!(
-                            "Failed to send request to Groq API after {attempts} attempts: {e}"
-                        )));
+                    if decoder.is_done() {
+                        return Ok(None);
+                    }
+                    let chunk = response.chunk().await.map_err(|e| {
+                        UnifiedIntelligenceError::Internal(format!("Groq stream interrupted: {e}"))
+                    })?;
+                    match chunk {
+                        Some(bytes) => pending.extend(decoder.push(&bytes)?),
+                        // A body that ends without [DONE] is a truncated answer
+                        None => {
+                            return Err(UnifiedIntelligenceError::Internal(
+                                "Groq stream ended before [DONE]".to_string(),
+                            ));
+                        }
                     }
                 }
-            }
+            },
+        )
+        .boxed())
+    }
+}
 
-            // Exponential backoff with jitter (only if we're going to retry)
-            if attempts < MAX_RETRIES {
-                let base_delay =
-                    Duration::from_millis(200 * 2u64.pow(attempts.saturating_sub(1) as u32));
-                let jitter = rand::thread_rng().gen_range(0.8..=1.2);
-                let delay = Duration::from_millis((base_delay.as_millis() as f64 * jitter) as u64);
+/// Anthropic Messages API body for a chat request: system messages move to the
+/// top-level `system` field. Anthropic has no JSON mode, so `response_format`
+/// is dropped.
+fn anthropic_body(req: &GroqRequest) -> serde_json::Value {
+    let system: Vec<&str> = req
+        .messages
+        .iter()
+        .filter(|m| m.role == "system")
+        .map(|m| m.content.as_str())
+        .collect();
+    let messages: Vec<serde_json::Value> = req
+        .messages
+        .iter()
+        .filter(|m| m.role != "system")
+        .map(|m| serde_json::json!({"role": m.role, "content": m.content}))
+        .collect();
+    let mut body = serde_json::json!({
+        "model": req.model,
+        "max_tokens": req.max_tokens,
+        "temperature": req.temperature,
+        "messages": messages,
+    });
+    if !system.is_empty() {
+        body["system"] = serde_json::Value::String(system.join("\n\n"));
+    }
+    body
+}
 
-                // Cap the delay to prevent excessive waiting
-                let max_delay = Duration::from_secs(30);
-                let final_delay = std::cmp::min(delay, max_delay);
+/// Map a Messages API response onto the chat completion shape, joining the
+/// text blocks and filling `usage` from `input_tokens`/`output_tokens`
+fn parse_anthropic_response(value: &serde_json::Value) -> Result<GroqResponse> {
+    let blocks = value
+        .get("content")
+        .and_then(|content| content.as_array())
+        .ok_or_else(|| {
+            UnifiedIntelligenceError::Internal(
+                "Anthropic API response is missing content".to_string(),
+            )
+        })?;
+    let text: String = blocks
+        .iter()
+        .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("text"))
+        .filter_map(|block| block.get("text").and_then(|t| t.as_str()))
+        .collect();
+    let tokens = |field: &str| {
+        value
+            .pointer(&format!("/usage/{field}"))
+            .and_then(|n| n.as_i64())
+            .map(|n| n as i32)
+    };
+    let (prompt_tokens, completion_tokens) = (tokens("input_tokens"), tokens("output_tokens"));
+    let total_tokens = match (prompt_tokens, completion_tokens) {
+        (None, None) => None,
+        (p, c) => Some(p.unwrap_or(0) + c.unwrap_or(0)),
+    };
+    Ok(GroqResponse {
+        choices: vec![Choice {
+            message: ChatMessage {
+                role: "assistant".to_string(),
+                content: text,
+            },
+        }],
+        usage: Some(GroqUsage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens,
+        }),
+    })
+}
 
-                sleep(final_delay).await;
-            }
-        }
+/// Anthropic Messages API transport. Streaming uses the trait default (one
+/// chunk per completion).
+pub struct AnthropicTransport {
+    client: Client,
+    api_key: String,
+    api_version: String,
+}
+
+impl AnthropicTransport {
+    pub fn new(api_key: String, api_version: String) -> Result<Self> {
+        Ok(Self {
+            client: Client::new(),
+            api_key,
+            api_version,
+        })
+    }
+}
 
-        // This should never be reached due to the loop condition, but just in case
-        Err(UnifiedIntelligenceError::Internal(format!(
-            "Groq API request failed after {MAX_RETRIES} attempts"
-        )))
+#[async_trait]
+impl Transport for AnthropicTransport {
+    async fn chat(&self, req: &GroqRequest) -> Result<GroqResponse> {
+        let body = anthropic_body(req);
+        let response = send_with_retry("Anthropic", || {
+            self.client
+                .post(ANTHROPIC_API_URL)
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", &self.api_version)
+                .header("Content-Type", "application/json")
+                .json(&body)
+        })
+        .await?;
+        let value: serde_json::Value = response.json().await.map_err(|e| {
+            UnifiedIntelligenceError::Internal(format!(
+                "Failed to parse Anthropic API response: {e}"
+            ))
+        })?;
+        parse_anthropic_response(&value)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{ChatMessage, GroqRequest};
     use tokio;
 
+    fn event(content: &str) -> String {
+        format!(
+            "data: {}\n\n",
+            serde_json::json!({"choices": [{"delta": {"content": content}}]})
+        )
+    }
+
+    #[test]
+    fn test_sse_decoder_handles_split_events_and_done() {
+        let body = format!(
+            ": keep-alive\n\n{}{}data: {{\"choices\":[{{\"delta\":{{}}}}]}}\n\ndata: [DONE]\n\n{}",
+            event("Hel"),
+            event("lo"),
+            event("ignored")
+        );
+        let (first, second) = body.as_bytes().split_at(30);
+        let mut decoder = SseDecoder::default();
+        let mut deltas = decoder.push(first).expect("valid events");
+        assert!(!decoder.is_done());
+        deltas.extend(decoder.push(second).expect("valid events"));
+        assert_eq!(deltas, vec!["Hel", "lo"]);
+        assert!(decoder.is_done());
+        assert!(decoder.push(event("late").as_bytes()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sse_decoder_surfaces_stream_errors() {
+        let mut decoder = SseDecoder::default();
+        let body = format!(
+            "{}data: {{\"error\":{{\"message\":\"overloaded\"}}}}\n\n",
+            event("partial")
+        );
+        let err = decoder.push(body.as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("overloaded"));
+        assert!(decoder.push(b"data: {not json\n").is_err());
+    }
+
+    #[test]
+    fn test_anthropic_body_moves_system_messages() {
+        let req = GroqRequest {
+            model: "claude-3-5-haiku-latest".to_string(),
+            messages: vec![
+                ChatMessage {
+                    role: "system".to_string(),
+                    content: "Be brief.".to_string(),
+                },
+                ChatMessage {
+                    role: "user".to_string(),
+                    content: "Hi".to_string(),
+                },
+            ],
+            temperature: 0.3,
+            max_tokens: 64,
+            response_format: Some(serde_json::json!({"type": "json_object"})),
+        };
+        let body = anthropic_body(&req);
+        assert_eq!(body["system"], "Be brief.");
+        assert_eq!(
+            body["messages"],
+            serde_json::json!([{"role": "user", "content": "Hi"}])
+        );
+        assert_eq!(body["max_tokens"], 64);
+        assert!(body.get("response_format").is_none());
+    }
+
+    #[test]
+    fn test_parse_anthropic_response_maps_text_and_usage() {
+        let value = serde_json::json!({
+            "content": [
+                {"type": "text", "text": "Paris"},
+                {"type": "text", "text": " is the capital."}
+            ],
+            "usage": {"input_tokens": 12, "output_tokens": 5}
+        });
+        let response = parse_anthropic_response(&value).expect("valid response");
+        assert_eq!(response.choices[0].message.content, "Paris is the capital.");
+        let usage = response.usage.expect("usage");
+        assert_eq!(usage.prompt_tokens, Some(12));
+        assert_eq!(usage.completion_tokens, Some(5));
+        assert_eq!(usage.total_tokens, Some(17));
+        assert!(parse_anthropic_response(&serde_json::json!({"type": "error"})).is_err());
+    }
+
     #[tokio::test]
     async fn test_groq_transport_chat_retry() {
         // This test is a bit tricky as it requires a mock server to simulate failures.
@@ -18,12 +18,16 @@ pub enum ValidationError {
 
     #[error("Thought content cannot be empty")]
     EmptyThought,
+
+    #[error("Too many tags: {actual} (max: {max})")]
+    TooManyTags { actual: usize, max: usize },
 }
 
 #[derive(Clone)]
 pub struct InputValidator {
     max_thought_length: usize,
     max_thoughts_per_chain: i32,
+    max_tags: usize,
 }
 
 impl InputValidator {
@@ -37,7 +41,33 @@ impl InputValidator {
                 .unwrap_or_else(|_| "1000".to_string())
                 .parse()
                 .unwrap_or(1000),
+            max_tags: env::var("MAX_TAGS")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()
+                .unwrap_or(20),
+        }
+    }
+
+    /// Trim, lowercase and dedupe tags (first occurrence wins), dropping empty
+    /// ones, then enforce the tag count limit on the result.
+    pub fn normalize_tags(
+        &self,
+        tags: &[String],
+    ) -> std::result::Result<Vec<String>, ValidationError> {
+        let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+        for tag in tags {
+            let tag = tag.trim().to_lowercase();
+            if !tag.is_empty() && !normalized.contains(&tag) {
+                normalized.push(tag);
+            }
+        }
+        if normalized.len() > self.max_tags {
+            return Err(ValidationError::TooManyTags {
+                actual: normalized.len(),
+                max: self.max_tags,
+            });
         }
+        Ok(normalized)
     }
 
     pub fn validate_thought_content(
@@ -239,4 +269,25 @@ mod tests {
             Err(ValidationError::InvalidInstanceId { .. })
         ));
     }
+
+    #[test]
+    fn test_normalize_tags() {
+        let validator = InputValidator::new();
+        let tags = vec![
+            "Database".to_string(),
+            " database ".to_string(),
+            "  ".to_string(),
+            "Redis".to_string(),
+        ];
+        assert_eq!(
+            validator.normalize_tags(&tags).unwrap(),
+            vec!["database".to_string(), "redis".to_string()]
+        );
+
+        let too_many: Vec<String> = (0..=validator.max_tags).map(|i| format!("t{i}")).collect();
+        assert!(matches!(
+            validator.normalize_tags(&too_many),
+            Err(ValidationError::TooManyTags { .. })
+        ));
+    }
 }
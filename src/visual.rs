@@ -1,4 +1,4 @@
-use crate::frameworks::WorkflowState;
+use crate::frameworks::{FrameworkVisual, WorkflowState};
 use colored::*;
 
 /// Visual output module for unified-intelligence MCP
@@ -13,6 +13,9 @@ impl VisualOutput {
 
     /// Display thought storage beginning
     pub fn thought_start(&self, thought_number: i32, total_thoughts: i32) {
+        if !FrameworkVisual::enabled() {
+            return;
+        }
         eprintln!(
             "{} {}{}{}",
             "🧠".blue(),
@@ -24,6 +27,9 @@ impl VisualOutput {
 
     /// Display thought content with indentation
     pub fn thought_content(&self, content: &str) {
+        if !FrameworkVisual::enabled() {
+            return;
+        }
         // Wrap long content lines
         let max_width = 80;
         for line in content.lines() {
@@ -56,6 +62,9 @@ impl VisualOutput {
 
     /// Display current framework state (workflow) banner
     pub fn framework_state(&self, state: WorkflowState) {
+        if !FrameworkVisual::enabled() {
+            return;
+        }
         let (icon, label, note) = match state {
             WorkflowState::Conversation => {
                 ("🗒", "conversation", Some("read-only; focus on capturing"))
@@ -79,6 +88,9 @@ impl VisualOutput {
 
     /// Display chain information
     pub fn chain_info(&self, chain_id: &str, is_new: bool) {
+        if !FrameworkVisual::enabled() {
+            return;
+        }
         if is_new {
             eprintln!(
                 "   {} {}",
@@ -96,6 +108,9 @@ impl VisualOutput {
 
     /// Display thought storage success
     pub fn thought_stored(&self, thought_id: &str) {
+        if !FrameworkVisual::enabled() {
+            return;
+        }
         eprintln!(
             "   {} {}",
             "✅".bright_green(),
@@ -106,6 +121,9 @@ impl VisualOutput {
     /// Display search results count
     #[allow(dead_code)]
     pub fn search_results(&self, count: usize, query: &str) {
+        if !FrameworkVisual::enabled() {
+            return;
+        }
         if count > 0 {
             eprintln!(
                 "{} {} {}",
@@ -124,6 +142,9 @@ impl VisualOutput {
 
     /// Display thinking completion
     pub fn thinking_complete(&self) {
+        if !FrameworkVisual::enabled() {
+            return;
+        }
         eprintln!(
             "   {} {}",
             "🎯".bright_blue(),
@@ -133,6 +154,9 @@ impl VisualOutput {
 
     /// Display next thought needed indicator
     pub fn next_thought_indicator(&self, next_needed: bool) {
+        if !FrameworkVisual::enabled() {
+            return;
+        }
         if next_needed {
             eprintln!(
                 "   {} {}",
@@ -153,6 +177,9 @@ impl VisualOutput {
 
     /// Progress bar for sequential thinking
     pub fn progress_bar(&self, current: i32, total: i32) {
+        if !FrameworkVisual::enabled() {
+            return;
+        }
         let progress = (current as f32 / total as f32 * 20.0) as usize;
         let filled = "█".repeat(progress);
         let empty = "░".repeat(20 - progress);